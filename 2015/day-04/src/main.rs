@@ -1,25 +1,28 @@
-extern crate hex;
-extern crate md5;
+extern crate aoc_utils;
 
-use hex::encode;
-use md5::{Digest, Md5};
+use aoc_utils::hash::{has_zero_prefix, search, to_hex};
 
 const KEY: &str = "bgvyzdsv";
 
+// first number from start that makes the hash of key || number start
+// with zeroes zeroes
+fn mine(key: &str, start: u64, zeroes: usize) -> (u64, String) {
+    let (n, digest) = search(key.as_bytes(), start, |d| has_zero_prefix(d, zeroes))
+        .next()
+        .unwrap();
+    (n, to_hex(&digest))
+}
+
 fn main() {
-    let mut skip5 = false;
-    for n in 1.. {
-        let mut hash = Md5::new();
-        hash.update(KEY.as_bytes());
-        hash.update(n.to_string().as_bytes());
-        let result = encode(hash.finalize());
-        if !skip5 && &result[0..5] == "00000" {
-            println!("{} || {} -> {:x?}", KEY, n, result);
-            skip5 = true;
-        }
-        if &result[0..6] == "000000" {
-            println!("{} || {} -> {}", KEY, n, result);
-            break;
-        }
-    }
+    let (n, result) = mine(KEY, 1, 5);
+    println!("{} || {} -> {}", KEY, n, result);
+    // a hash with six zeroes also has five
+    let (n, result) = mine(KEY, n, 6);
+    println!("{} || {} -> {}", KEY, n, result);
+}
+
+#[test]
+fn test_mine() {
+    assert_eq!(609043, mine("abcdef", 1, 5).0);
+    assert_eq!(1048970, mine("pqrstuv", 1, 5).0);
 }
@@ -1,67 +1,46 @@
-extern crate permutohedron;
+extern crate aoc_utils;
 extern crate regex;
 
-use std::collections::HashMap;
-use std::collections::HashSet;
 use std::io::{self, Read};
-use std::iter::FromIterator;
 use std::str::FromStr;
 
-use permutohedron::Heap;
+use aoc_utils::tour::Tour;
 use regex::Regex;
 
 struct DistanceMap {
-    distances: HashMap<(String, String), usize>,
-    destinations: HashSet<String>,
+    destinations: Vec<String>,
+    distances: Vec<Vec<Option<usize>>>,
 }
 
 impl DistanceMap {
     fn new() -> Self {
         DistanceMap{
-            distances: HashMap::new(),
-            destinations: HashSet::new(),
+            destinations: Vec::new(),
+            distances: Vec::new(),
         }
     }
 
-    fn push(&mut self, from: &str, to: &str, dist: usize) {
-        self.destinations.insert(String::from(from));
-        self.destinations.insert(String::from(to));
-        if from < to {
-            self.distances.insert((String::from(from), String::from(to)), dist);
-        } else {
-            self.distances.insert((String::from(to), String::from(from)), dist);
+    fn destination(&mut self, name: &str) -> usize {
+        if let Some(idx) = self.destinations.iter().position(|d| d == name) {
+            return idx;
         }
-    }
-
-    fn get(&self, from: &str, to: &str) -> Option<usize> {
-        if from < to {
-            self.distances.get(&(String::from(from), String::from(to))).cloned()
-        } else {
-            self.distances.get(&(String::from(to), String::from(from))).cloned()
+        self.destinations.push(String::from(name));
+        for row in &mut self.distances {
+            row.push(None);
         }
+        self.distances.push(vec![None; self.destinations.len()]);
+        self.destinations.len() - 1
     }
 
-    fn path_distance(&self, path: &Vec<&String>) -> Option<usize> {
-        let mut total = 0;
-        for segment in path.as_slice().windows(2) {
-            if let Some(dist) = self.get(&segment[0], &segment[1]) {
-                total += dist;
-            } else {
-                return None;
-            }
-        }
-        Some(total)
+    fn push(&mut self, from: &str, to: &str, dist: usize) {
+        let from = self.destination(from);
+        let to = self.destination(to);
+        self.distances[from][to] = Some(dist);
+        self.distances[to][from] = Some(dist);
     }
 
-    fn possible_paths(&self) -> Vec<Vec<&String>> {
-        let mut locations: Vec<&String> = self.destinations.iter().collect();
-        let mut possible_paths = Vec::new();
-        // will have to use unstable...
-        let heap = Heap::new(&mut locations);
-        for perm in heap {
-            possible_paths.push(Vec::from_iter(perm.iter().cloned()));
-        }
-        possible_paths
+    fn tour(&self) -> Tour {
+        Tour::new(self.distances.clone())
     }
 }
 
@@ -83,7 +62,19 @@ fn main() {
         }
     }
 
-    let possible_paths: Vec<(usize, Vec<&String>)> = distance_map.possible_paths().into_iter().map(|v| (distance_map.path_distance(&v), v)).filter(|p| p.0.is_some()).map(|p| (p.0.unwrap(), p.1)).collect();
-    println!("Minimum path: {:?}", possible_paths.iter().min());
-    println!("Maximum path: {:?}", possible_paths.iter().max());
+    let tour = distance_map.tour();
+    println!("Minimum path: {:?}", tour.shortest().map(|r| r.length));
+    println!("Maximum path: {:?}", tour.longest().map(|r| r.length));
+}
+
+#[test]
+fn test() {
+    let mut distance_map = DistanceMap::new();
+    distance_map.push("London", "Dublin", 464);
+    distance_map.push("London", "Belfast", 518);
+    distance_map.push("Dublin", "Belfast", 141);
+
+    let tour = distance_map.tour();
+    assert_eq!(Some(605), tour.shortest().map(|r| r.length));
+    assert_eq!(Some(982), tour.longest().map(|r| r.length));
 }
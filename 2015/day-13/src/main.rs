@@ -1,32 +1,33 @@
+extern crate aoc_utils;
 extern crate regex;
-extern crate permutohedron;
 
 use std::io::{self, Read};
 use std::collections::HashMap;
-use std::collections::HashSet;
 use std::str::FromStr;
 
-use permutohedron::Heap;
+use aoc_utils::tour::Tour;
 use regex::Regex;
 
 type HappinessAdjustment = HashMap<(String, String), i64>;
 
 struct Table {
-    attendees: HashSet<String>,
+    attendees: Vec<String>,
     adjustment: HappinessAdjustment,
 }
 
 impl Table {
     fn new() -> Table {
         Table {
-            attendees: HashSet::new(),
+            attendees: Vec::new(),
             adjustment: HappinessAdjustment::new(),
         }
     }
 
     fn change_happiness(&mut self, attendee: &str,
                         change: i64, neighbour: &str) {
-        self.attendees.insert(String::from(attendee));
+        if !self.attendees.iter().any(|a| a == attendee) {
+            self.attendees.push(String::from(attendee));
+        }
         self.adjustment.insert((String::from(attendee),
                                 String::from(neighbour)), change);
     }
@@ -39,30 +40,18 @@ impl Table {
         self.change_happiness(attendee, -loss, neighbour);
     }
 
-    fn happiness_change(&self, sitting: &Vec<&String>) -> i64 {
-        let mut change = 0;
-        for chunks in sitting.as_slice().windows(3) {
-            change += *self.adjustment.get(&(chunks[1].clone(), chunks[0].clone())).unwrap();
-            change += *self.adjustment.get(&(chunks[1].clone(), chunks[2].clone())).unwrap();
-        }
-
-        // do the first and last attendees
-        change += *self.adjustment.get(&(sitting[0].clone(), sitting[1].clone())).unwrap();
-        change += *self.adjustment.get(&(sitting[0].clone(), sitting[sitting.len() - 1].clone())).unwrap();
-        change += *self.adjustment.get(&(sitting[sitting.len() - 1].clone(), sitting[0].clone())).unwrap();
-        change += *self.adjustment.get(&(sitting[sitting.len() - 1].clone(), sitting[sitting.len() - 2].clone())).unwrap();
-
-        change
-    }
-
-    fn possible_sittings(&self) -> Vec<Vec<&String>> {
-        let mut attendees: Vec<&String> = self.attendees.iter().collect();
-        Heap::new(&mut attendees).collect()
+    // the change of happiness of a pair sitting next to each other
+    fn pair_change(&self, a: &str, b: &str) -> i64 {
+        self.adjustment[&(String::from(a), String::from(b))]
+            + self.adjustment[&(String::from(b), String::from(a))]
     }
 
-    fn possible_sittings_with_happiness(&self) -> Vec<(i64, Vec<&String>)> {
-        let mut attendees: Vec<&String> = self.attendees.iter().collect();
-        Heap::new(&mut attendees).map(|sitting| (self.happiness_change(&sitting), sitting)).collect()
+    // a round table is a closed tour
+    fn optimal_happiness(&self) -> i64 {
+        let changes = self.attendees.iter()
+            .map(|a| self.attendees.iter().map(|b| if a == b { 0 } else { self.pair_change(a, b) }).collect())
+            .collect();
+        Tour::from_matrix(changes).closed().longest().unwrap().length
     }
 }
 
@@ -77,7 +66,7 @@ fn main() {
     let mut table = Table::new();
 
     for line in buffer.lines() {
-        if let Some(ref caps) = re.captures(&line) {
+        if let Some(ref caps) = re.captures(line) {
             let attendee = caps.get(1).unwrap().into();
             let neighbour = caps.get(4).unwrap().into();
             let change = i64::from_str(caps.get(3).unwrap().into()).unwrap();
@@ -94,14 +83,14 @@ fn main() {
         }
     }
 
-    println!("Optimal sitting: {:?}", table.possible_sittings_with_happiness().into_iter().max());
+    println!("Optimal sitting: {}", table.optimal_happiness());
 
     for attendee in table.attendees.clone() {
         table.gain("Myself", 0, &attendee);
         table.gain(&attendee, 0, "Myself");
     }
 
-    println!("Optimal sitting with me: {:?}", table.possible_sittings_with_happiness().into_iter().max());
+    println!("Optimal sitting with me: {}", table.optimal_happiness());
 }
 
 #[test]
@@ -124,5 +113,5 @@ fn test() {
     table.gain("David", 41, "Carol");
 
 
-    assert_eq!(table.possible_sittings_with_happiness().into_iter().max().unwrap().0, 330);
+    assert_eq!(table.optimal_happiness(), 330);
 }
@@ -1,117 +1,60 @@
+extern crate aoc_utils;
+
 use std::io::{self, Read};
 use std::fmt;
 
+use aoc_utils::automaton::{Automaton, Neighbourhood, Neighbours};
+use aoc_utils::grid::{Grid, MaxDist};
+
 struct Lights {
-    lights: Vec<Vec<u8>>,
+    lights: Automaton<bool>,
+    width: usize,
+}
+
+fn animate(on: &bool, neighbours: Neighbours<bool>) -> bool {
+    let count = neighbours.filter(|&&n| n).count();
+    count == 3 || (*on && count == 2)
 }
 
 impl Lights {
     fn from_str(input: &str) -> Self {
-        let mut lights = Vec::new();
-
-        for line in input.lines() {
-            let mut row = Vec::new();
-            row.push(0);
-            for c in line.chars() {
-                match c {
-                    '#' => row.push(1),
-                    '.' => row.push(0),
-                    _ => (),
-                }
-            }
-            row.push(0);
-            if lights.is_empty() {
-                lights.push(vec![0; row.len()]);
-            }
-            lights.push(row);
+        let lines: Vec<&str> = input.lines().filter(|l| !l.is_empty()).collect();
+        let width = lines[0].len();
+        let lights: Vec<bool> = lines.iter().flat_map(|l| l.bytes()).map(|b| b == b'#').collect();
+        let grid: Grid<bool, MaxDist> = Grid::from_vec(width, lights);
+
+        Lights {
+            lights: Automaton::from_grid(&grid, Neighbourhood::Moore, false),
+            width,
         }
-
-        let len = lights[0].len();
-        lights.push(vec![0; len]);
-
-        assert!(lights.iter().all(|r| r.len() == len),
-                "not all rows have the same length");
-
-        Lights { lights: lights }
     }
 
-    fn next_state(&self) -> Self {
-        let line_len = self.lights[0].len() - 2;
-
-        let mut accum: Vec<Vec<u8>> = Vec::new();
-        for _ in 0..line_len {
-            accum.push(Vec::new());
-        }
-
-        for row in &self.lights {
-            for (col, chunk) in row.as_slice().windows(3).enumerate() {
-                accum[col].push(chunk.iter().sum());
-            }
-        }
-
-        let mut lights = Vec::new();
-
-        lights.push(vec![0; line_len + 2]);
-        for _ in 0..self.lights.len() - 2 {
-            lights.push(vec![0; 1]);
-        }
-        lights.push(vec![0; line_len + 2]);
-
-        for (col, count) in accum.into_iter().enumerate() {
-            for (row, chunk) in count.as_slice().windows(3).enumerate() {
-                let total: u8 = chunk.iter().sum();
-
-                let on = if self.lights[row + 1][col + 1] == 1 {
-                    total == 3 || total == 4
-                } else {
-                    total == 3
-                };
-                if on {
-                    lights[row + 1].push(1);
-                } else {
-                    lights[row + 1].push(0);
-                }
-            }
-        }
-
-        for row in 1..lights.len() - 1 {
-            lights[row].push(0);
-        }
-
-        assert_eq!(self.lights.len(), lights.len());
-
-        Lights { lights: lights }
+    fn next_state(mut self) -> Self {
+        self.lights.step(animate);
+        self
     }
 
     fn count_on(&self) -> usize {
-        let mut total = 0;
-        for row in &self.lights {
-            total += row.iter().map(|u| *u as usize).sum();
-        }
-        total
+        self.lights.count(|&l| l)
     }
 
     fn force_on(&mut self) {
-        let line_len = self.lights[0].len();
-        let rows = self.lights.len();
-        self.lights[1][1] = 1;
-        self.lights[1][line_len - 2] = 1;
-        self.lights[rows - 2][1] = 1;
-        self.lights[rows - 2][line_len - 2] = 1;
+        let width = self.width;
+        let cells = self.lights.cells_mut();
+        let len = cells.len();
+        for corner in &[0, width - 1, len - width, len - 1] {
+            cells[*corner] = true;
+        }
     }
 }
 
 impl fmt::Display for Lights {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        for row in &self.lights.as_slice()[1..self.lights.len() - 1] {
-            for t in &row.as_slice()[1..row.len() - 1] {
-                if *t == 0 {
-                    try!(write!(f, "."));
-                } else {
-                    try!(write!(f, "#"));
-                }
+        for row in self.lights.cells().chunks(self.width) {
+            for &t in row {
+                write!(f, "{}", if t { '#' } else { '.' })?;
             }
-            try!(write!(f, "\n"));
+            writeln!(f)?;
         }
         Ok(())
     }
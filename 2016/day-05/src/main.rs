@@ -1,80 +1,47 @@
-extern crate md5;
-extern crate hex;
+extern crate aoc_utils;
 
-use std::char;
-
-use md5::{Md5, Digest};
-use hex::encode;
+use aoc_utils::hash::{has_zero_prefix, hex_digit, nibble, search, Digest};
 
 static KEY: &'static str = "abbhdwsy";
 
-fn is_password_letter(index: u32, key: &str) -> Option<(char, char)> {
-    let mut buffer = String::new();
-    buffer.push_str(key);
-    buffer.push_str(&index.to_string());
+fn is_interesting(digest: &Digest) -> bool {
+    has_zero_prefix(digest, 5)
+}
 
-    let mut hasher = Md5::new();
-    hasher.update(buffer.as_bytes());
-    let hash = encode(hasher.finalize());
+// the sixth and seventh hex digits of an interesting hash
+fn password_letter(digest: &Digest) -> (u8, u8) {
+    (nibble(digest, 5), nibble(digest, 6))
+}
 
-    if hash.starts_with("00000") {
-        let bytes = hash.as_bytes();
-        Some((bytes[5] as char, bytes[6] as char) )
+#[cfg(test)]
+fn is_password_letter(index: u64, key: &str) -> Option<(char, char)> {
+    let digest = aoc_utils::hash::md5_salted(key.as_bytes(), index);
+    if is_interesting(&digest) {
+        let (p, c) = password_letter(&digest);
+        Some((hex_digit(p), hex_digit(c)))
     } else {
         None
     }
 }
 
-fn char_to_usize(c: char) -> usize {
-    match c {
-        '0' => 0,
-        '1' => 1,
-        '2' => 2,
-        '3' => 3,
-        '4' => 4,
-        '5' => 5,
-        '6' => 6,
-        '7' => 7,
-        '8' => 8,
-        '9' => 9,
-        'a' => 10,
-        'b' => 11,
-        'c' => 12,
-        'd' => 13,
-        'e' => 14,
-        'f' => 15,
-        _ => unreachable!()
-    }
-}
-
 fn basic_code(key: &str) -> String {
-    let mut code = String::new();
-
-    for i in 0.. {
-        if let Some((c, _)) = is_password_letter(i, key) {
-            code.push(c);
-
-            if code.len() == 8 {
-                return code
-            }
-        }
-    }
-
-    unreachable!()
+    search(key.as_bytes(), 0, is_interesting)
+        .map(|(_, digest)| hex_digit(password_letter(&digest).0))
+        .take(8)
+        .collect()
 }
 
 fn advanced_code(key: &str) -> String {
     let mut code = vec![None; 8];
 
-    for i in 0.. {
-        if let Some((p, c)) = is_password_letter(i, key) {
-            let pos = char_to_usize(p);
-            if pos > 7 || code[pos].is_some() {
-                continue
-            }
-            code[pos] = Some(c);
-            println!("code so far: {:?}", code);
+    for (_, digest) in search(key.as_bytes(), 0, is_interesting) {
+        let (pos, c) = password_letter(&digest);
+        let pos = pos as usize;
+        if pos > 7 || code[pos].is_some() {
+            continue
         }
+        code[pos] = Some(hex_digit(c));
+        println!("code so far: {:?}", code);
         if code.iter().all(Option::is_some) {
             return code.into_iter().map(Option::unwrap).collect();
         }
@@ -93,12 +60,12 @@ fn test_basic() {
     assert_eq!(Some('1'), is_password_letter(3231929, "abc").map(|t| t.0));
     assert_eq!(Some('8'), is_password_letter(5017308, "abc").map(|t| t.0));
     assert_eq!(Some('f'), is_password_letter(5278568, "abc").map(|t| t.0));
+    assert_eq!("18f47a30", basic_code("abc"));
 }
 
 #[test]
 fn test_advance() {
     assert_eq!(Some(('1', '5')), is_password_letter(3231929, "abc"));
     assert_eq!(Some(('4', 'e')), is_password_letter(5357525, "abc"));
-    // runs but too slow in debug mode; only tests in release mode
     assert_eq!("05ace8e3", advanced_code("abc"));
 }
@@ -1,3 +1,6 @@
+extern crate aoc_utils;
+
+use aoc_utils::ocr::{self, OcrError};
 use std::fmt;
 use std::io::{self, Read};
 use std::str::FromStr;
@@ -51,6 +54,12 @@ impl Panel {
         total
     }
 
+    fn code(&self) -> Result<String, OcrError> {
+        ocr::read_points(self.panel.iter().enumerate().flat_map(|(y, r)| {
+            r.iter().enumerate().filter(|&(_, c)| *c).map(move |(x, _)| (x as i64, y as i64))
+        }))
+    }
+
     fn execute_command(&mut self, command: &str) {
         if command.starts_with("rect ") {
             let size = &command[5..];
@@ -89,18 +98,66 @@ impl fmt::Display for Panel {
     }
 }
 
+fn run(input: &str) -> Panel {
+    let mut panel = Panel::new();
+
+    for command in input.lines() {
+        panel.execute_command(command);
+    }
+    panel
+}
+
 fn main() {
     let mut buffer = String::new();
     let stdin = io::stdin();
     let mut handle = stdin.lock();
     handle.read_to_string(&mut buffer).unwrap();
 
-    let mut panel = Panel::new();
+    let panel = run(&buffer);
 
-    for command in buffer.lines() {
-        panel.execute_command(command);
+    println!("Count of lights on: {}", panel.count());
+    match panel.code() {
+        Ok(code) => println!("Code: {}", code),
+        Err(e) => println!("{}\n{}", e, panel),
     }
+}
 
-    println!("Count of lights on: {}", panel.count());
-    println!("{}", panel);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static SCREEN: &str = "\
+#### #### ###  ###   ##  ###  #    #  #  ### #   #
+   # #    #  # #  # #  # #  # #    #  # #    #   #
+  #  ###  ###  #  # #  # #  # #    #  # #     # #
+ #   #    #  # ###  #### ###  #    #  #  ##    #
+#    #    #  # # #  #  # #    #    #  #    #   #
+#### #### ###  #  # #  # #    ####  ##  ###    #";
+
+    // commands drawing the screen: each column is built in column 0, one
+    // pixel at a time, then all the rows move right to make room for the
+    // next one
+    fn commands(screen: &str) -> String {
+        let rows: Vec<_> = screen.lines().map(|l| l.as_bytes()).collect();
+        let mut commands = vec![];
+        for x in (0..WIDTH).rev() {
+            for y in (0..HEIGHT).rev() {
+                commands.push("rotate column x=0 by 1".to_owned());
+                if rows[y].get(x) == Some(&b'#') {
+                    commands.push("rect 1x1".to_owned());
+                }
+            }
+            if x > 0 {
+                commands.extend((0..HEIGHT).map(|y| format!("rotate row y={} by 1", y)));
+            }
+        }
+        commands.join("\n")
+    }
+
+    #[test]
+    fn test_code() {
+        let panel = run(&commands(SCREEN));
+        assert_eq!(SCREEN.matches('#').count(), panel.count());
+        assert_eq!(Ok("ZEBRAPLUSY".to_owned()), panel.code());
+    }
 }
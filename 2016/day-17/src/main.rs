@@ -1,11 +1,9 @@
-extern crate md5;
-extern crate hex;
+extern crate aoc_utils;
 
 use std::collections::BinaryHeap;
 use std::cmp::Ordering;
 
-use md5::{Md5, Digest};
-use hex::encode;
+use aoc_utils::hash::{md5, nibble, Digest};
 
 #[derive(Copy, Clone)]
 enum Direction {
@@ -25,32 +23,19 @@ impl Direction {
         }
     }
 
-    fn from_hash(hash: &str) -> Vec<Direction> {
-        assert!(hash.len() > 4, "Hash length is not at least 4");
-        let mut iter = hash.chars();
-        let mut result = Vec::new();
-
-        if is_open(iter.next().unwrap()) {
-            result.push(Direction::U);
-        }
-        if is_open(iter.next().unwrap()) {
-            result.push(Direction::D);
-        }
-        if is_open(iter.next().unwrap()) {
-            result.push(Direction::L);
-        }
-        if is_open(iter.next().unwrap()) {
-            result.push(Direction::R);
-        }
-        result
+    // the first four hex digits of the hash are the doors
+    fn from_hash(hash: &Digest) -> Vec<Direction> {
+        [Direction::U, Direction::D, Direction::L, Direction::R]
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| is_open(nibble(hash, i)))
+            .map(|(_, d)| *d)
+            .collect()
     }
 }
 
-fn is_open(c: char) -> bool {
-    match c {
-        'b'..='f' => true,
-        _ => false,
-    }
+fn is_open(n: u8) -> bool {
+    n >= 0xb
 }
 
 #[derive(PartialEq, Eq, PartialOrd, Ord)]
@@ -106,11 +91,9 @@ impl State {
         if self.at_exit() {
             return Vec::new();
         }
-        let mut md5 = Md5::new();
-        md5.update(key.as_bytes());
-        md5.update(&self.path.as_bytes());
+        let hash = md5(format!("{}{}", key, self.path));
 
-        Direction::from_hash(&encode(md5.finalize())).into_iter()
+        Direction::from_hash(&hash).into_iter()
             .filter(|d| self.location.is_valid_direction(*d))
             .map(|d| self.move_to(d))
             .collect()
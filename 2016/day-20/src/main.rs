@@ -1,46 +1,22 @@
+extern crate aoc_utils;
+
 use std::io::{self, Read};
 use std::str::FromStr;
 
-type Intervals = Vec<(u32, u32)>;
-
-fn minimum(intervals: &Intervals) -> u32 {
-    let mut minimum = u32::min_value();
+use aoc_utils::interval::IntervalSet;
 
-    for &(l, h) in intervals {
-        if l <= minimum {
-            minimum = h + 1;
-        } else {
-            break;
-        }
-    }
+// all the IPs
+const IPS: std::ops::RangeInclusive<u32> = 0..=u32::MAX;
 
-    minimum
+fn minimum(blocked: &IntervalSet<u32>) -> u32 {
+    *blocked.gaps(IPS).next().unwrap().start()
 }
 
-fn count(intervals: &Intervals) -> u32 {
-    let mut minimum = u32::min_value();
-    let mut count = 0;
-
-    for &(l, h) in intervals {
-        if l <= minimum {
-            if minimum < h + 1 {
-                if h == u32::max_value() {
-                    return count;
-                }
-                minimum = h + 1;
-            }
-        } else {
-            count += l - minimum;
-            if h == u32::max_value() {
-                return count;
-            }
-            minimum = h+1;
-        }
-    }
-
-    count += u32::max_value() - minimum + 1;
-
-    count
+fn count(blocked: &IntervalSet<u32>) -> u64 {
+    blocked
+        .gaps(IPS)
+        .map(|g| u64::from(g.end() - g.start()) + 1)
+        .sum()
 }
 
 fn main() {
@@ -49,31 +25,33 @@ fn main() {
     let mut handle = stdin.lock();
     handle.read_to_string(&mut buffer).unwrap();
 
-    let mut intervals = Vec::new();
+    let mut blocked = IntervalSet::new();
 
     for line in buffer.lines() {
         let mut iter = line.split('-');
         let low = u32::from_str(iter.next().unwrap()).unwrap();
         let high = u32::from_str(iter.next().unwrap()).unwrap();
 
-        intervals.push((low, high));
+        blocked.insert_inclusive(low..=high);
     }
 
-    intervals.sort();
-
-    println!("Minimum available IP: {}", minimum(&intervals));
-    println!("Available IP count: {}", count(&intervals));
+    println!("Minimum available IP: {}", minimum(&blocked));
+    println!("Available IP count: {}", count(&blocked));
 }
 
 
 #[test]
 fn test() {
-    let mut intervals = Vec::new();
-    intervals.push((5, 8));
-    intervals.push((0, 2));
-    intervals.push((4, 7));
-
-    intervals.sort();
-
-    assert_eq!(3, minimum(&intervals));
+    let mut blocked = IntervalSet::new();
+    blocked.insert_inclusive(5..=8);
+    blocked.insert_inclusive(0..=2);
+    blocked.insert_inclusive(4..=7);
+
+    assert_eq!(3, minimum(&blocked));
+    assert_eq!((1 << 32) - 8, count(&blocked));
+
+    // a blacklist may end at the last IP
+    blocked.insert_inclusive(10..=u32::MAX);
+    assert_eq!(3, minimum(&blocked));
+    assert_eq!(2, count(&blocked));
 }
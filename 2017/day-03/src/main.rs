@@ -1,120 +1,57 @@
-fn main() {
-    let input = 265149;
-
-    println!("{}", manhattan(to_pos(input)));
-
-    let mut store = std::collections::HashMap::new();
-
-    store.insert(to_pos(1), 1);
+extern crate aoc_utils;
 
-    for p in 2.. {
-        let pos = to_pos(p);
-
-        let mut to_store = 0;
-
-        for n in pos.neighbours() {
-            to_store += *store.get(&n).unwrap_or(&0);
-        }
-
-        if to_store > input {
-            println!("{:?} <- {}", pos, to_store);
-            break;
-        }
-
-        store.insert(pos, to_store);
-    }
-}
+use aoc_utils::spiral;
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
-struct Position(i32, i32);
+fn main() {
+    let input = 265149;
 
-impl Position {
-    fn neighbours(&self) -> Vec<Position> {
-        let mut results = Vec::new();
+    println!("{}", manhattan(spiral::position(input)));
 
-        for i in -1..2 {
-            for j in -1..2 {
-                if i != 0 || j != 0 {
-                    results.push(Position(self.0 + i, self.1 + j));
-                }
-            }
-        }
-        results
-    }
+    // the first value larger than the input, when each square stores the
+    // sum of the values around it
+    let (pos, value) = spiral::accumulate(1, |around| around.sum())
+        .find(|&(_, value)| value > input)
+        .unwrap();
+    println!("{:?} <- {}", pos, value);
 }
 
-fn manhattan(p: Position) -> u32 {
-    p.0.abs() as u32 + p.1.abs() as u32
-}
-
-fn to_pos(i: i32) -> Position {
-    if i == 1 {
-        return Position(0, 0);
-    }
-    let mut base = ((i - 1)  as f64).sqrt() as i32;
-    if base % 2 == 0 {
-        base -= 1;
-    }
-
-    let corner = base * base;
-    let side = base + 1;
-    let mut start = corner;
-
-    // right side
-    if start < i && i <= start + side {
-        return Position(side / 2, start - i + side / 2);
-    }
-    start += side;
-
-    // top
-    if start < i && i <= start + side {
-        return Position(start - i + (side / 2), - side / 2);
-    }
-    start += side;
-
-    // left
-    if start < i && i <= start + side {
-        return Position(- side / 2, i - start - side / 2);
-    }
-    start += side;
-
-    // bottom
-    return Position(i - start - (side / 2) , side / 2);
+fn manhattan(p: (i64, i64)) -> u64 {
+    p.0.unsigned_abs() + p.1.unsigned_abs()
 }
 
 #[test]
 fn test_1() {
-    assert_eq!(to_pos(1), Position(0, 0));
-    assert_eq!(manhattan(to_pos(1)), 0);
+    assert_eq!(spiral::position(1), (0, 0));
+    assert_eq!(manhattan(spiral::position(1)), 0);
 }
 
 #[test]
 fn test_12() {
-    assert_eq!(to_pos(12), Position(2, -1));
-    assert_eq!(manhattan(to_pos(12)), 3);
+    assert_eq!(spiral::position(12), (2, -1));
+    assert_eq!(manhattan(spiral::position(12)), 3);
 }
 
 #[test]
 fn test_23() {
-    assert_eq!(to_pos(23), Position(0, 2));
-    assert_eq!(manhattan(to_pos(23)), 2);
+    assert_eq!(spiral::position(23), (0, 2));
+    assert_eq!(manhattan(spiral::position(23)), 2);
 }
 
 #[test]
 fn test_1024() {
-    assert_eq!(manhattan(to_pos(1024)), 31);
+    assert_eq!(manhattan(spiral::position(1024)), 31);
 }
 
 #[test]
 fn test() {
     // should test each side
-    assert_eq!(to_pos(1), Position(0, 0));
-    assert_eq!(to_pos(2), Position(1, 0));
-    assert_eq!(to_pos(3), Position(1, -1));
-    assert_eq!(to_pos(4), Position(0, -1));
-    assert_eq!(to_pos(5), Position(-1, -1));
-    assert_eq!(to_pos(6), Position(-1, 0));
-    assert_eq!(to_pos(7), Position(-1, 1));
-    assert_eq!(to_pos(8), Position(0, 1));
-    assert_eq!(to_pos(9), Position(1, 1));
+    assert_eq!(spiral::position(1), (0, 0));
+    assert_eq!(spiral::position(2), (1, 0));
+    assert_eq!(spiral::position(3), (1, -1));
+    assert_eq!(spiral::position(4), (0, -1));
+    assert_eq!(spiral::position(5), (-1, -1));
+    assert_eq!(spiral::position(6), (-1, 0));
+    assert_eq!(spiral::position(7), (-1, 1));
+    assert_eq!(spiral::position(8), (0, 1));
+    assert_eq!(spiral::position(9), (1, 1));
 }
@@ -1,53 +1,45 @@
+extern crate aoc_utils;
 extern crate regex;
 
-use std::collections::HashSet;
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::{BufRead,BufReader};
-use std::env::args;
+use std::fs::read_to_string;
+use aoc_utils::get_input_path;
+use aoc_utils::graph::Graph;
 use regex::Regex;
 
 fn main() {
-    assert!(args().len() > 1);
-    let path = args().nth(1).unwrap();
-    let input = File::open(path).unwrap();
-    let input = BufReader::new(input);
-
-    let mut programs = HashSet::new();
-    let mut supported = HashSet::new();
+    let input = read_to_string(get_input_path()).unwrap();
 
+    // edges from each program to the programs on its disc
+    let mut discs = Graph::directed();
     let mut program_weights = HashMap::new();
-    let mut program_discs = HashMap::new();
     let re = Regex::new(r"^(\w+) \((\d+)\)(?: -> (.*))?$").unwrap();
 
     for line in input.lines() {
-        let line = line.unwrap();
-        for cap in re.captures_iter(&line) {
+        for cap in re.captures_iter(line) {
             assert!(cap.len() > 2);
 
-            programs.insert(cap[1].to_owned());
-            program_weights.insert(cap[1].to_owned(), cap[2].parse::<u32>().unwrap());
-            let mut vec = Vec::new();
-            if cap.get(3).is_some() {
-                for prog in cap[3].split(", ") {
-                    supported.insert(prog.to_owned());
-                    vec.push(prog.to_owned());
+            let program = discs.node(cap.get(1).unwrap().as_str());
+            program_weights.insert(program, cap[2].parse::<u32>().unwrap());
+            if let Some(supported) = cap.get(3) {
+                for prog in supported.as_str().split(", ") {
+                    let prog = discs.node(prog);
+                    discs.add_edge_ids(program, prog, ());
                 }
             }
-            program_discs.insert(cap[1].to_owned(), vec);
         }
     }
 
-    for p in programs.difference(&supported) {
-        println!("root: {}", p);
+    for p in (0..discs.len()).filter(|&p| discs.in_degree(p) == 0) {
+        println!("root: {}", discs.label(p));
     }
 
     let mut unbalanced = Vec::new();
     let mut diff: i32 = 0;
-    for (p, v) in program_discs.iter() {
-        if !v.is_empty() {
-            let mut w: Vec<(&String, u32)> = v.iter().map(|p| (p, compute_weight(p, &program_weights, &program_discs))).collect();
-            w.sort_by(|&(_, w1), &(_, w2)| w1.cmp(&w2));
+    for p in 0..discs.len() {
+        if discs.degree(p) > 0 {
+            let mut w: Vec<(usize, u32)> = discs.neighbours(p).map(|p| (p, compute_weight(p, &program_weights, &discs))).collect();
+            w.sort_by_key(|&(_, w)| w);
             if w[0].1 != w[w.len() - 1].1 {
                 assert!(w.len() > 2);
                 if w[0].1 == w[1].1 {
@@ -57,16 +49,17 @@ fn main() {
                     diff = w[0].1 as i32 - w[1].1 as i32;
                     unbalanced.push(w[0]);
                 }
-                println!("balance problem on disc of {} - {:?}", p, w);
+                let named: Vec<(&str, u32)> = w.iter().map(|&(p, w)| (*discs.label(p), w)).collect();
+                println!("balance problem on disc of {} - {:?}", discs.label(p), named);
             }
         }
     }
-    unbalanced.sort_by(|&(_, w1), &(_, w2)| w1.cmp(&w2));
+    unbalanced.sort_by_key(|&(_, w)| w);
     let p = unbalanced[0];
-    let w = program_weights.get(p.0).unwrap();
-    println!("{} should have weight {}", p.0, *w as i32 + diff);
+    let w = program_weights.get(&p.0).unwrap();
+    println!("{} should have weight {}", discs.label(p.0), *w as i32 + diff);
 }
 
-fn compute_weight(p: &String, pw: &HashMap<String, u32>, pd: &HashMap<String, Vec<String>>) -> u32 {
-    *pw.get(p).unwrap() + pd.get(p).unwrap().iter().map(|p| compute_weight(p, pw, pd)).sum::<u32>()
+fn compute_weight(p: usize, pw: &HashMap<usize, u32>, discs: &Graph<&str>) -> u32 {
+    *pw.get(&p).unwrap() + discs.neighbours(p).map(|p| compute_weight(p, pw, discs)).sum::<u32>()
 }
@@ -0,0 +1,40 @@
+#[macro_use]
+extern crate criterion;
+extern crate knot_hash;
+
+use criterion::{black_box, Criterion};
+use knot_hash::{Knot, KnotHash};
+
+// the lengths of a full hash of a 2017 day 14 key
+fn lengths() -> Vec<u8> {
+    let mut v = b"flqrgnkx-0".to_vec();
+    v.extend([17, 31, 73, 47, 23].iter());
+    v
+}
+
+fn benchmark(c: &mut Criterion) {
+    let lengths = lengths();
+    let mut group = c.benchmark_group("64 rounds");
+    group.bench_function("Swapping", |b| {
+        b.iter(|| {
+            let mut knot = Knot::new(256).unwrap();
+            for _ in 0..64 {
+                knot.round_swapping(black_box(&lengths));
+            }
+            knot
+        })
+    });
+    group.bench_function("Rotating", |b| {
+        b.iter(|| {
+            let mut knot = Knot::new(256).unwrap();
+            knot.rounds(64, black_box(&lengths));
+            knot
+        })
+    });
+    group.finish();
+
+    c.bench_function("Digest", |b| b.iter(|| KnotHash::digest(black_box(b"flqrgnkx-0"))));
+}
+
+criterion_group!(benches, benchmark);
+criterion_main!(benches);
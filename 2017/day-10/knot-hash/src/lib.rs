@@ -1,5 +1,9 @@
+extern crate aoc_utils;
+
 use std::fmt::{Error, Formatter, LowerHex};
 
+use aoc_utils::grid::{Grid, Taxicab};
+
 #[derive(Debug)]
 pub struct Knot {
     v: Vec<u8>,
@@ -8,27 +12,55 @@ pub struct Knot {
 }
 
 impl Knot {
-    pub fn new(r: usize) -> Self {
-        assert!(r <= 256);
-        let mut v: Vec<u8> = (0..).take(r-1).collect();
-        v.push((r-1) as u8);
-        Knot {
-            v, 
+    // a list of r elements, 0 to r - 1; the elements are bytes, so there
+    // is no list of more than 256 of them, nor one of none at all
+    pub fn new(r: usize) -> Option<Self> {
+        if r == 0 || r > 256 {
+            return None;
+        }
+        Some(Knot {
+            v: (0..r).map(|i| i as u8).collect(),
             cur: 0,
             skip: 0,
+        })
+    }
+
+    // Rather than reversing around the end of the list, the list is
+    // rotated so that the current position is at the start: each length
+    // is a plain reverse followed by a rotation
+    pub fn round(&mut self, lengths: &[u8]) {
+        let r = self.v.len();
+        let mut cur = self.cur;
+        let mut skip = self.skip;
+
+        self.v.rotate_left(cur);
+        for &s in lengths {
+            let s = s as usize;
+            assert!(s <= r, "length {} longer than the list", s);
+            self.v[..s].reverse();
+            let step = (s + skip) % r;
+            self.v.rotate_left(step);
+            cur = (cur + step) % r;
+            skip += 1;
         }
+        self.v.rotate_right(cur);
+
+        self.cur = cur;
+        self.skip = skip;
     }
 
-    pub fn round(&mut self, v: &Vec<u8>) {
+    // the original round, swapping elements one by one; kept to compare
+    // in the benchmark
+    pub fn round_swapping(&mut self, lengths: &[u8]) {
         let r = self.v.len();
 
         let mut cur = self.cur;
         let mut skip = self.skip;
 
-        for s in v {
+        for s in lengths {
             let s = *s as usize;
-            if cur+s <= r {
-                self.v[cur..cur+s].reverse();
+            if cur + s <= r {
+                self.v[cur..cur + s].reverse();
             } else {
                 let h = s / 2;
                 for i in 0..h {
@@ -46,16 +78,16 @@ impl Knot {
         self.skip = skip;
     }
 
-    pub fn rounds(&mut self, r: u8, v: &Vec<u8>) {
+    pub fn rounds(&mut self, r: u8, lengths: &[u8]) {
         for _ in 0..r {
-            self.round(v);
+            self.round(lengths);
         }
     }
 
-    pub fn knot(r: usize, v: Vec<u8>) -> Vec<u8> {
-        let mut k = Knot::new(r);
-        k.round(&v);
-        k.v
+    pub fn knot(r: usize, lengths: &[u8]) -> Option<Vec<u8>> {
+        let mut k = Knot::new(r)?;
+        k.round(lengths);
+        Some(k.v)
     }
 
     pub fn dense(&self) -> Vec<u8> {
@@ -63,17 +95,13 @@ impl Knot {
     }
 
     pub fn from_str(s: &str) -> Self {
-        let mut knot = Knot::new(256);
-        let mut v: Vec<u8> = s.bytes().collect();
-        v.extend([17, 31, 73, 47, 23].iter());
-
-        knot.rounds(64, &v);
-
+        let mut knot = Knot::new(256).unwrap();
+        knot.rounds(64, &lengths(s.as_bytes()));
         knot
     }
 
     pub fn hash(s: &str) -> String {
-        format!("{:x}", Knot::from_str(s))
+        KnotHash::new(s.as_bytes()).to_hex()
     }
 }
 
@@ -86,9 +114,103 @@ impl LowerHex for Knot {
     }
 }
 
+// the input bytes, followed by the standard suffix
+fn lengths(input: &[u8]) -> Vec<u8> {
+    let mut v = input.to_vec();
+    v.extend([17, 31, 73, 47, 23].iter());
+    v
+}
+
+// The full knot hash: 64 rounds on a 256 elements list, reduced to 16
+// bytes
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct KnotHash(pub [u8; 16]);
+
+impl KnotHash {
+    pub fn digest(input: &[u8]) -> [u8; 16] {
+        let mut knot = Knot::new(256).unwrap();
+        knot.rounds(64, &lengths(input));
+        let mut digest = [0; 16];
+        for (d, c) in digest.iter_mut().zip(knot.v.chunks(16)) {
+            *d = c.iter().fold(0, |c, n| c ^ n);
+        }
+        digest
+    }
+
+    pub fn new(input: &[u8]) -> Self {
+        KnotHash(Self::digest(input))
+    }
+
+    pub fn to_hex(&self) -> String {
+        format!("{:x}", self)
+    }
+
+    // the 128 bits, most significant first
+    pub fn bits<'a>(&'a self) -> impl Iterator<Item = bool> + 'a {
+        self.0
+            .iter()
+            .flat_map(|b| (0..8).rev().map(move |i| b & (1 << i) != 0))
+    }
+}
+
+impl LowerHex for KnotHash {
+    fn fmt(&self, f: &mut Formatter) -> std::result::Result<(), Error> {
+        for d in self.0.iter() {
+            write!(f, "{:02x}", d)?;
+        }
+        Ok(())
+    }
+}
+
+// the 128x128 grid of 2017 day 14: row i is the hash of key-i
+pub fn disk_grid(key: &str) -> Grid<bool, Taxicab> {
+    let cells = (0..128)
+        .flat_map(|i| {
+            KnotHash::new(format!("{}-{}", key, i).as_bytes())
+                .bits()
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    Grid::from_vec(128, cells)
+}
+
 #[test]
 fn test() {
-    assert_eq!(Knot::knot(5, vec![3,4,1,5]), [3,4,2,1,0]);
+    assert_eq!(Knot::knot(5, &[3, 4, 1, 5]).unwrap(), [3, 4, 2, 1, 0]);
+
+    let mut knot = Knot::new(5).unwrap();
+    knot.round_swapping(&[3, 4, 1, 5]);
+    assert_eq!(knot.v, [3, 4, 2, 1, 0]);
+}
+
+#[test]
+fn test_sizes() {
+    assert!(Knot::new(0).is_none());
+    assert!(Knot::new(257).is_none());
+    assert!(Knot::knot(0, &[]).is_none());
+    assert_eq!(Knot::new(256).unwrap().v[255], 255);
+}
+
+#[test]
+fn test_rounds() {
+    // both rounds agree, whatever the state they start from
+    let lengths = lengths(b"AoC 2017");
+    let mut rotating = Knot::new(256).unwrap();
+    let mut swapping = Knot::new(256).unwrap();
+    for _ in 0..10 {
+        rotating.round(&lengths);
+        swapping.round_swapping(&lengths);
+        assert_eq!(rotating.v, swapping.v);
+        assert_eq!((rotating.cur, rotating.skip), (swapping.cur, swapping.skip));
+    }
+    // lengths of 0 and of the whole list, on a short list
+    let mut rotating = Knot::new(7).unwrap();
+    let mut swapping = Knot::new(7).unwrap();
+    for _ in 0..20 {
+        rotating.round(&[0, 7, 3, 6, 1, 5]);
+        swapping.round_swapping(&[0, 7, 3, 6, 1, 5]);
+        assert_eq!(rotating.v, swapping.v);
+    }
 }
 
 #[test]
@@ -97,4 +219,22 @@ fn test_hash() {
     assert_eq!(Knot::hash("AoC 2017"), "33efeb34ea91902bb2f59c9920caa6cd");
     assert_eq!(Knot::hash("1,2,3"), "3efbe78a8d82f29979031a4aa0b16a9d");
     assert_eq!(Knot::hash("1,2,4"), "63960835bcdc130f0b66d7ff4f6a5a8e");
+    assert_eq!(format!("{:x}", Knot::from_str("1,2,3")), Knot::hash("1,2,3"));
+}
+
+#[test]
+fn test_views() {
+    let hash = KnotHash::new(b"flqrgnkx-0");
+    assert_eq!(128, hash.bits().count());
+    let start: String = hash
+        .bits()
+        .take(8)
+        .map(|b| if b { '#' } else { '.' })
+        .collect();
+    assert_eq!("##.#.#..", start);
+
+    let grid = disk_grid("flqrgnkx");
+    assert_eq!((128, 128), (grid.width(), grid.height()));
+    assert_eq!(8108, grid.iter().filter(|&&b| b).count());
+    assert!(grid[(1, 1)] && !grid[(0, 2)]);
 }
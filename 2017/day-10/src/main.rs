@@ -14,8 +14,8 @@ fn main() {
 
     for line in buf.lines() {
         let line = line.unwrap();
-        let v = line.split(",").map(|w| w.parse().unwrap()).collect();
-        let r = Knot::knot(256, v);
+        let v: Vec<u8> = line.split(",").map(|w| w.parse().unwrap()).collect();
+        let r = Knot::knot(256, &v).unwrap();
         println!("result: {}", r[0] as u32 * r[1] as u32);
     }
 
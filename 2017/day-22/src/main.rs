@@ -1,4 +1,5 @@
-use std::{collections::HashMap, fmt::Display};
+use aoc_utils::grid::{Grid, SparseGrid, Taxicab};
+use std::fmt::Display;
 
 const INPUT: &str = include_str!("input.txt");
 
@@ -9,9 +10,9 @@ enum Part1Tile {
     Infected,
 }
 
-impl From<char> for Part1Tile {
-    fn from(value: char) -> Self {
-        if value == '#' {
+impl From<u8> for Part1Tile {
+    fn from(value: u8) -> Self {
+        if value == b'#' {
             Part1Tile::Infected
         } else {
             Part1Tile::Clean
@@ -54,50 +55,10 @@ impl Burster for Part1Tile {
     }
 }
 
-struct Grid<T>(HashMap<(isize, isize), T>);
-
-impl<T: From<char>> Grid<T> {
-    fn from_str(input: &str) -> Self {
-        let lines: Vec<&str> = input.lines().collect();
-        let mut map = HashMap::new();
-        let width = lines[0].len();
-        let height = lines.len();
-
-        let mid_width = (width / 2) as isize;
-        let mid_height = (height / 2) as isize;
-
-        for (row, line) in lines.into_iter().enumerate() {
-            for (col, c) in line.chars().enumerate() {
-                map.insert(
-                    (col as isize - mid_width, row as isize - mid_height),
-                    T::from(c),
-                );
-            }
-        }
-
-        Self(map)
-    }
-}
-
-impl<T: Clone + Display + Default> Display for Grid<T> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if !self.0.is_empty() {
-            let min_col = self.0.keys().map(|(c, _)| *c).min().unwrap();
-            let max_col = self.0.keys().map(|(c, _)| *c).max().unwrap();
-
-            let min_row = self.0.keys().map(|(_, r)| *r).min().unwrap();
-            let max_row = self.0.keys().map(|(_, r)| *r).max().unwrap();
-
-            for r in min_row..=max_row {
-                for c in min_col..=max_col {
-                    let t = self.0.get(&(c, r)).cloned().unwrap_or_default();
-                    write!(f, "{t}")?;
-                }
-                writeln!(f)?;
-            }
-        }
-        Ok(())
-    }
+// the map, with the virus starting in the middle at (0, 0)
+fn parse<T: From<u8>>(input: &str) -> SparseGrid<T> {
+    let grid: Grid<T, Taxicab> = Grid::try_from(input).unwrap();
+    SparseGrid::centred(grid)
 }
 
 #[derive(Eq, PartialEq, PartialOrd, Debug, Copy, Clone)]
@@ -127,7 +88,7 @@ impl Direction {
         }
     }
 
-    fn delta(&self) -> (isize, isize) {
+    fn delta(&self) -> (i64, i64) {
         match self {
             Direction::Up => (0, -1),
             Direction::Left => (-1, 0),
@@ -136,20 +97,20 @@ impl Direction {
         }
     }
 
-    fn forward(&self, pos: (isize, isize)) -> (isize, isize) {
+    fn forward(&self, pos: (i64, i64)) -> (i64, i64) {
         let d = self.delta();
         (pos.0 + d.0, pos.1 + d.1)
     }
 }
 
 struct Virus<T> {
-    grid: Grid<T>,
-    pos: (isize, isize),
+    grid: SparseGrid<T>,
+    pos: (i64, i64),
     direction: Direction,
 }
 
 impl<T> Virus<T> {
-    fn new(grid: Grid<T>) -> Self {
+    fn new(grid: SparseGrid<T>) -> Self {
         Self {
             grid,
             pos: (0, 0),
@@ -160,11 +121,11 @@ impl<T> Virus<T> {
 
 impl<T: Burster + Default + Copy> Virus<T> {
     fn burst(&mut self) -> T {
-        let t = self.grid.0.get(&self.pos).cloned().unwrap_or_default();
-        let new_t = t.burst_node();
+        let t = self.grid.entry(self.pos);
         let new_dir = t.burst_direction(self.direction);
+        *t = t.burst_node();
+        let new_t = *t;
 
-        self.grid.0.insert(self.pos, new_t);
         self.direction = new_dir;
         self.pos = new_dir.forward(self.pos);
 
@@ -181,7 +142,7 @@ impl<T: Burster + Default + Copy> Iterator for Virus<T> {
 }
 
 fn part1(input: &str) -> usize {
-    let grid: Grid<Part1Tile> = Grid::from_str(input);
+    let grid: SparseGrid<Part1Tile> = parse(input);
     Virus::new(grid)
         .take(10000)
         .filter(|&t| t == Part1Tile::Infected)
@@ -197,9 +158,9 @@ enum Part2Tile {
     Flagged,
 }
 
-impl From<char> for Part2Tile {
-    fn from(value: char) -> Self {
-        if value == '#' {
+impl From<u8> for Part2Tile {
+    fn from(value: u8) -> Self {
+        if value == b'#' {
             Part2Tile::Infected
         } else {
             Part2Tile::Clean
@@ -228,7 +189,7 @@ impl Burster for Part2Tile {
 }
 
 fn part2(input: &str) -> usize {
-    let grid: Grid<Part2Tile> = Grid::from_str(input);
+    let grid: SparseGrid<Part2Tile> = parse(input);
     Virus::new(grid)
         .take(10000000)
         .filter(|&t| t == Part2Tile::Infected)
@@ -5,41 +5,27 @@ use std::fmt;
 use std::io::BufRead;
 use std::str::FromStr;
 use aoc_utils::get_input;
+use aoc_utils::cuboid::Rect;
 use regex::Regex;
 use lazy_static::lazy_static;
 
 #[derive(Debug)]
 struct Claim {
     id: usize,
-    left: usize,
-    top: usize,
-    width: usize,
-    height: usize,
+    area: Rect,
 }
 
 impl Claim {
-    fn cover(&self, cloth: &mut HashMap<(usize, usize), usize>) {
-        for x in self.left .. self.left + self.width {
-            for y in self.top .. self.top + self.height {
+    fn cover(&self, cloth: &mut HashMap<(i64, i64), usize>) {
+        for x in self.area.min[0]..self.area.max[0] {
+            for y in self.area.min[1]..self.area.max[1] {
                 *cloth.entry((x, y)).or_default() += 1;
             }
         }
     }
 
-    fn contains(&self, point: (usize, usize)) -> bool {
-        self.left <= point.0 && point.0 <= (self.left + self.width)
-            && self.top <= point.1 && point.1 <= (self.top + self.height)
-    }
-
     fn overlap(&self, other: &Claim) -> bool {
-        for x in other.left..other.left + other.width {
-            for y in other.top..other.top + other.height {
-                if self.contains((x, y)) {
-                    return true;
-                }
-            }
-        }
-        false
+        self.area.intersects(&other.area)
     }
 }
 
@@ -85,10 +71,10 @@ impl FromStr for Claim {
         for cap in CLAIM_RE.captures_iter(s) {
             return Ok(Claim {
                     id: cap[1].parse()?,
-                    left: cap[2].parse()?,
-                    top: cap[3].parse()?,
-                    width: cap[4].parse()?,
-                    height: cap[5].parse()?,
+                    area: Rect::with_size(
+                        [cap[2].parse()?, cap[3].parse()?],
+                        [cap[4].parse()?, cap[5].parse()?],
+                    ),
                 });
         }
         Err(ParseClaimError{ message: s.into(), cause: None})
@@ -105,7 +91,7 @@ fn input() -> Result<Vec<Claim>, Box<dyn Error>> {
 }
 
 fn part_one(v: &Vec<Claim>) -> usize {
-    let mut cloth: HashMap<(usize, usize), usize> = HashMap::new();
+    let mut cloth: HashMap<(i64, i64), usize> = HashMap::new();
 
     for claim in v {
         claim.cover(&mut cloth);
@@ -135,9 +135,9 @@ fn input() -> Result<Constellation, Box<dyn Error>> {
     Ok(Constellation::new(v))
 }
 
-fn part_one() -> Result<(), Box<dyn Error>> {
+// the constellation when the stars are the closest, and the time it takes
+fn align(mut c: Constellation) -> (Constellation, usize) {
     let mut seconds = 0;
-    let mut c = input()?;
     while c.y_span() > 8 {
         let nc = c.step();
         if nc.y_span() > c.y_span() {
@@ -146,9 +146,20 @@ fn part_one() -> Result<(), Box<dyn Error>> {
         seconds += 1;
         c = nc;
     }
+    (c, seconds)
+}
+
+fn part_one() -> Result<(), Box<dyn Error>> {
+    let (c, seconds) = align(input()?);
     println!("minimum x-span: {}, y-span: {} after {} seconds", c.x_span(), c.y_span(), seconds);
 
-    c.print();
+    match ocr::read_points(c.0.iter().map(|s| s.position)) {
+        Ok(message) => println!("message: {}", message),
+        Err(e) => {
+            println!("{}", e);
+            c.print();
+        }
+    }
     Ok(())
 }
 
@@ -197,4 +208,41 @@ position=<-3,  6> velocity=< 2, -1>";
     fn test_parser() {
         INPUT.lines().map(|l| l.parse::<Star>().unwrap()).collect::<Vec<_>>();
     }
+
+    static MESSAGE: &str = r"
+ ####   #    #     ###  #    #  #    #  ######
+#    #  #    #      #   ##   #  #    #       #
+#       #    #      #   ##   #   #  #        #
+#       #    #      #   # #  #   #  #       #
+#       ######      #   # #  #    ##       #
+#  ###  #    #      #   #  # #    ##      #
+#    #  #    #      #   #  # #   #  #    #
+#    #  #    #  #   #   #   ##   #  #   #
+#   ##  #    #  #   #   #   ##  #    #  #
+ ### #  #    #   ###    #    #  #    #  ######";
+
+    // the example is too small for the font: stars that spell the message
+    // after 10_000 seconds, coming from all directions
+    #[test]
+    fn test_message() {
+        let stars = MESSAGE
+            .lines()
+            .enumerate()
+            .flat_map(|(y, l)| {
+                l.char_indices().filter(|(_, c)| *c == '#').map(move |(x, _)| (x as i64, y as i64))
+            })
+            .enumerate()
+            .map(|(i, (x, y))| {
+                let velocity = (i as i64 % 5 - 2, i as i64 / 5 % 5 - 2);
+                let position = (x - 10_000 * velocity.0, y - 10_000 * velocity.1);
+                let (p, v) = (position, velocity);
+                format!("position=<{}, {}> velocity=<{}, {}>", p.0, p.1, v.0, v.1)
+                    .parse::<Star>()
+                    .unwrap()
+            })
+            .collect();
+        let (c, seconds) = align(Constellation::new(stars));
+        assert_eq!(10_000, seconds);
+        assert_eq!(Ok("GHJNXZ".to_owned()), ocr::read_points(c.0.iter().map(|s| s.position)));
+    }
 }
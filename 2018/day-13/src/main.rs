@@ -1,6 +1,10 @@
+extern crate aoc_utils;
+
 use std::convert::TryInto;
 use std::fmt::Display;
 
+use aoc_utils::render::{Cell, Frame, Rgb, Visualizer};
+
 static INPUT: &str = include_str!("input.txt");
 
 #[derive(Debug)]
@@ -266,6 +270,32 @@ impl Map {
         Ok(())
     }
 
+    // tracks, with the carts on top and the crashes in red
+    fn frame(&self) -> Frame {
+        let width = self.lines.iter().map(|l| l.len()).max().unwrap_or(0);
+        let mut frame = Frame::new(width, self.lines.len(), Cell::new(' '));
+        for (row, line) in self.lines.iter().enumerate() {
+            for (col, b) in line.iter().enumerate() {
+                frame.draw((col, row), Cell::colored(*b as char, Rgb::GREY));
+            }
+        }
+        frame.with_overlay(self.carts.iter().map(|c| {
+            let cell = if c.is_crashed() {
+                Cell::colored('X', Rgb::RED)
+            } else {
+                Cell::colored(c.get_byte() as char, Rgb::YELLOW)
+            };
+            (c.position, cell)
+        }))
+    }
+
+    // shows the map once all the carts have moved
+    fn show(&self, visualizer: &mut Visualizer) {
+        if visualizer.is_enabled() && self.tick_work.is_empty() {
+            visualizer.show(&self.frame()).expect("cannot show map");
+        }
+    }
+
     fn count_running(&self) -> usize {
         self.carts.iter().filter(|c| !c.is_crashed()).count()
     }
@@ -302,39 +332,47 @@ impl Display for Map {
     }
 }
 
-fn run_to_crash(desc: &str) -> (usize, usize) {
+fn run_to_crash(desc: &str, visualizer: &mut Visualizer) -> (usize, usize) {
     let mut map = Map::new(desc);
     loop {
         match map.tick_once() {
-            Ok(_) => {}
+            Ok(_) => map.show(visualizer),
             Err(pos) => {
+                if visualizer.is_enabled() {
+                    visualizer.show(&map.frame()).expect("cannot show map");
+                }
                 return pos;
             }
         }
     }
 }
 
-fn run_to_one(desc: &str) -> (usize, usize) {
+fn run_to_one(desc: &str, visualizer: &mut Visualizer) -> (usize, usize) {
     let mut map = Map::new(desc);
 
     loop {
         if let Some(pos) = map.tick() {
             return pos;
         }
+        map.show(visualizer);
     }
 }
 
-fn part_1() -> (usize, usize) {
-    run_to_crash(INPUT)
+fn part_1(visualizer: &mut Visualizer) -> (usize, usize) {
+    run_to_crash(INPUT, visualizer)
 }
 
-fn part_2() -> (usize, usize) {
-    run_to_one(INPUT)
+fn part_2(visualizer: &mut Visualizer) -> (usize, usize) {
+    run_to_one(INPUT, visualizer)
 }
 
 fn main() {
-    println!("part 1: {:?}", part_1());
-    println!("part 2: {:?}", part_2());
+    let mut visualizer = Visualizer::from_args();
+    let part_1 = part_1(&mut visualizer);
+    let part_2 = part_2(&mut visualizer);
+    visualizer.finish().expect("cannot write animation");
+    println!("part 1: {:?}", part_1);
+    println!("part 2: {:?}", part_2);
 }
 
 #[cfg(test)]
@@ -350,7 +388,7 @@ mod test {
 
     #[test]
     fn test_crash_pos() {
-        assert_eq!(run_to_crash(TEST), (7, 3));
+        assert_eq!(run_to_crash(TEST, &mut Visualizer::off()), (7, 3));
     }
 
     static TEST_2: &str = r#"/>-<\
@@ -363,6 +401,6 @@ mod test {
 
     #[test]
     fn test_last_cart() {
-        assert_eq!(run_to_one(TEST_2), (6, 4));
+        assert_eq!(run_to_one(TEST_2, &mut Visualizer::off()), (6, 4));
     }
 }
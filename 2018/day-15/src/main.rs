@@ -1,5 +1,8 @@
+use aoc_utils::battle::{first_step, reading_order};
+use aoc_utils::grid::{Grid, Taxicab};
+use aoc_utils::render::{Cell, Frame, Rgb, Visualizer};
 use std::{
-    collections::{BTreeSet, HashSet},
+    collections::HashSet,
     fmt::Display,
     ops::{Index, IndexMut},
     str::FromStr,
@@ -20,8 +23,10 @@ enum Tile {
     Elf(i32),
 }
 
-impl Tile {
-    fn from_u8(byte: u8) -> anyhow::Result<Tile> {
+impl TryFrom<u8> for Tile {
+    type Error = anyhow::Error;
+
+    fn try_from(byte: u8) -> anyhow::Result<Tile> {
         match byte {
             b'#' => Ok(Tile::Wall),
             b'.' => Ok(Tile::OpenCavern),
@@ -30,7 +35,20 @@ impl Tile {
             _ => anyhow::bail!("Unknown character {}", byte),
         }
     }
+}
 
+impl Display for Tile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Tile::Wall => write!(f, "#"),
+            Tile::OpenCavern => write!(f, "."),
+            Tile::Goblin(_) => write!(f, "G"),
+            Tile::Elf(_) => write!(f, "E"),
+        }
+    }
+}
+
+impl Tile {
     fn is_unit(&self) -> bool {
         self != &Tile::Wall && self != &Tile::OpenCavern
     }
@@ -71,10 +89,9 @@ impl Tile {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 struct Map {
-    tiles: Vec<Tile>,
-    width: usize,
+    tiles: Grid<Tile, Taxicab>,
     elf_attack_power: i32,
 }
 
@@ -82,67 +99,47 @@ impl FromStr for Map {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut width = 0;
-        let tiles = s
+        let lines: Vec<_> = s
             .lines()
-            .filter(|l| !l.is_empty())
             .map(|l| l.trim())
-            .flat_map(|l| {
-                width = width.max(l.len());
-                l.bytes().map(Tile::from_u8)
-            })
-            .collect::<anyhow::Result<Vec<Tile>>>()?;
+            .filter(|l| !l.is_empty())
+            .collect();
         Ok(Self {
-            tiles,
-            width,
+            tiles: Grid::try_from(lines.join("\n").as_str())?,
             elf_attack_power: 3,
         })
     }
 }
 
-// row, col like unit_positions
+// x, y like unit_positions
 impl Index<(usize, usize)> for Map {
     type Output = Tile;
 
     fn index(&self, index: (usize, usize)) -> &Self::Output {
-        &self.tiles[index.1 + index.0 * self.width]
+        &self.tiles[index]
     }
 }
 
 impl IndexMut<(usize, usize)> for Map {
     fn index_mut(&mut self, index: (usize, usize)) -> &mut Self::Output {
-        &mut self.tiles[index.1 + index.0 * self.width]
+        &mut self.tiles[index]
     }
 }
 
 impl Display for Map {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for (idx, t) in self.tiles.iter().enumerate() {
-            if idx > 0 && idx % self.width == 0 {
-                writeln!(f)?;
-            }
-            match t {
-                Tile::Wall => write!(f, "#")?,
-                Tile::OpenCavern => write!(f, ".")?,
-                Tile::Goblin(_) => write!(f, "G")?,
-                Tile::Elf(_) => write!(f, "E")?,
-            }
-        }
-        Ok(())
+        write!(f, "{}", self.tiles)
     }
 }
 
 impl Map {
-    // by construction already in the correct order
-    // row, col to simplify further sorting if needed
+    // by construction already in reading order
     fn unit_positions(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
-        self.tiles.iter().enumerate().filter_map(|(idx, t)| {
-            if t.is_unit() {
-                Some((idx / self.width, idx % self.width))
-            } else {
-                None
-            }
-        })
+        self.tiles
+            .iter()
+            .enumerate()
+            .filter(|(_, t)| t.is_unit())
+            .map(|(idx, _)| self.tiles.idx_to_pos(idx))
     }
 
     fn adjacent_positions_matching<F>(
@@ -153,20 +150,7 @@ impl Map {
     where
         F: Fn(Tile) -> bool + 'static,
     {
-        const DELTA: [(isize, isize); 4] = [(-1, 0), (0, -1), (0, 1), (1, 0)];
-        // we have walls all around and pos will always be inside, so no need to care about getting
-        // out of index
-        DELTA.iter().filter_map(move |d| {
-            let apos = (
-                pos.0.checked_add_signed(d.0).unwrap(),
-                pos.1.checked_add_signed(d.1).unwrap(),
-            );
-            if f(self[apos]) {
-                Some(apos)
-            } else {
-                None
-            }
-        })
+        self.tiles.neighbours(pos).filter(move |&n| f(self[n]))
     }
 
     fn adjacent_open_caverns(
@@ -188,6 +172,19 @@ impl Map {
         self.unit_positions().filter(|p| self[*p].is_elf()).count()
     }
 
+    // walls in grey, elves in green and goblins in red
+    fn show(&self, visualizer: &mut Visualizer) {
+        if visualizer.is_enabled() {
+            let frame = Frame::from_grid(&self.tiles, |t| match t {
+                Tile::Wall => Cell::colored('#', Rgb::GREY),
+                Tile::OpenCavern => Cell::new(' '),
+                Tile::Goblin(_) => Cell::colored('G', Rgb::RED),
+                Tile::Elf(_) => Cell::colored('E', Rgb::GREEN),
+            });
+            visualizer.show(&frame).expect("cannot show map");
+        }
+    }
+
     fn is_fight_finished(&self) -> bool {
         let elves = self.has_any_elf();
         let goblins = self.unit_positions().any(|p| self[p].is_goblin());
@@ -217,48 +214,18 @@ impl Map {
             if targets.is_empty() {
                 return false;
             }
-            // use a temp to avoid spurious borrowing
-            // need to sort by hitpoints then position order...
-            let au = self.adjacent_units(p).find(|au| targets.contains(au));
-            // if not in range, try and move
-            if au.is_none() {
-                let target_adjacent_positions: BTreeSet<_> = targets
+            // if not in range, move toward the nearest square in range
+            if !self.adjacent_units(p).any(|au| targets.contains(&au)) {
+                let in_range: HashSet<_> = targets
                     .iter()
                     .flat_map(|t| self.adjacent_open_caverns(*t))
                     .collect();
-                // for each adjacent_open_caverns pos, compute the minimum number of steps required
-                // to reach a target or None if impossible
-                // then, move to the adjacent pos that has the minimum number of steps to a target,
-                // using pos order to break ties
-                let mut dists: Vec<_> = self
-                    .adjacent_open_caverns(p)
-                    .filter_map(|aocp| {
-                        // try and reach a target if possible
-                        let mut seen = HashSet::new();
-                        let mut boundary = HashSet::new();
-                        boundary.insert(aocp);
-                        let mut idx: usize = 0;
-                        loop {
-                            if boundary.is_empty() {
-                                return None;
-                            }
-                            for p in &boundary {
-                                if target_adjacent_positions.contains(p) {
-                                    return Some((idx, aocp));
-                                }
-                                seen.insert(*p);
-                            }
-                            boundary = boundary
-                                .into_iter()
-                                .flat_map(|p| self.adjacent_open_caverns(p))
-                                .filter(|p| !seen.contains(p))
-                                .collect();
-                            idx += 1;
-                        }
-                    })
-                    .collect();
-                dists.sort(); // sort by dist then pos order
-                if let Some(&(_, np)) = dists.first() {
+                let step = first_step(
+                    p,
+                    |q| in_range.contains(&q),
+                    |q| self.adjacent_open_caverns(q),
+                );
+                if let Some(np) = step {
                     let unit = self[p];
                     self[p] = Tile::OpenCavern;
                     self[np] = unit;
@@ -270,7 +237,7 @@ impl Map {
                 .adjacent_units(p)
                 .filter(|au| targets.contains(au))
                 .collect();
-            all_adjacent_units.sort_by_key(|&au| (self[au].hitpoints(), au));
+            all_adjacent_units.sort_by_key(|&au| (self[au].hitpoints(), reading_order(au)));
             if let Some(&au) = all_adjacent_units.first() {
                 let attack_power = if self[p].is_elf() {
                     self.elf_attack_power
@@ -286,9 +253,10 @@ impl Map {
         true
     }
 
-    fn fight(&mut self) -> usize {
+    fn fight(&mut self, visualizer: &mut Visualizer) -> usize {
         let mut r: usize = 0;
         loop {
+            self.show(visualizer);
             if self.is_fight_finished() {
                 return r;
             }
@@ -299,10 +267,11 @@ impl Map {
         }
     }
 
-    fn fight_for_elves(&mut self) -> Option<usize> {
+    fn fight_for_elves(&mut self, visualizer: &mut Visualizer) -> Option<usize> {
         let elf_count = self.elf_count();
         let mut r: usize = 0;
         loop {
+            self.show(visualizer);
             if self.is_fight_finished() {
                 return Some(r);
             }
@@ -319,8 +288,8 @@ impl Map {
         }
     }
 
-    fn fight_score(&mut self) -> usize {
-        let complete_rounds = self.fight();
+    fn fight_score(&mut self, visualizer: &mut Visualizer) -> usize {
+        let complete_rounds = self.fight(visualizer);
 
         let total_hit_points = self.total_hit_points();
 
@@ -336,19 +305,20 @@ impl Map {
     }
 }
 
-fn part_01(input: &str) -> anyhow::Result<usize> {
+fn part_01(input: &str, visualizer: &mut Visualizer) -> anyhow::Result<usize> {
     let mut map = Map::from_str(input)?;
 
-    Ok(map.fight_score())
+    Ok(map.fight_score(visualizer))
 }
 
-fn part_02(input: &str) -> anyhow::Result<usize> {
+// every attempt is shown, up to the first one the elves win
+fn part_02(input: &str, visualizer: &mut Visualizer) -> anyhow::Result<usize> {
     let map = Map::from_str(input)?;
     for attack_power in 4.. {
         let mut map = map.clone();
         map.elf_attack_power = attack_power;
 
-        if let Some(complete_rounds) = map.fight_for_elves() {
+        if let Some(complete_rounds) = map.fight_for_elves(visualizer) {
             return Ok(complete_rounds * map.total_hit_points());
         }
     }
@@ -362,14 +332,22 @@ fn _part_02_alt(input: &str) -> anyhow::Result<()> {
     for attack_power in 4..30 {
         let mut map = map.clone();
         map.elf_attack_power = attack_power;
-        println!("{}: {}", attack_power, map.fight_for_elves().is_some());
+        println!(
+            "{}: {}",
+            attack_power,
+            map.fight_for_elves(&mut Visualizer::off()).is_some()
+        );
     }
     Ok(())
 }
 
 fn main() -> anyhow::Result<()> {
-    println!("Part 1: {}", part_01(INPUT)?);
-    println!("Part 2: {}", part_02(INPUT)?);
+    let mut visualizer = Visualizer::from_args();
+    let part_01 = part_01(INPUT, &mut visualizer)?;
+    let part_02 = part_02(INPUT, &mut visualizer)?;
+    visualizer.finish()?;
+    println!("Part 1: {}", part_01);
+    println!("Part 2: {}", part_02);
     //part_02_alt(INPUT)?;
     Ok(())
 }
@@ -378,6 +356,8 @@ fn main() -> anyhow::Result<()> {
 mod tests {
     use std::str::FromStr;
 
+    use aoc_utils::render::Visualizer;
+
     use crate::{part_01, part_02, Map, INPUT};
 
     static TEST_MAP_INPUT: &str = r"#######
@@ -460,7 +440,7 @@ mod tests {
     #[test_case(TEST_INPUT_06, 18740)]
     #[test_case(INPUT, 198531)]
     fn test_part_01(input: &str, res: usize) {
-        assert_eq!(res, part_01(input).unwrap());
+        assert_eq!(res, part_01(input, &mut Visualizer::off()).unwrap());
     }
 
     #[test_case(TEST_INPUT_01, 4988, 15)]
@@ -473,7 +453,7 @@ mod tests {
         let mut map = Map::from_str(input).unwrap();
         let count_elves = map.elf_count();
         map.elf_attack_power = attack_power;
-        assert_eq!(res, map.fight_score());
+        assert_eq!(res, map.fight_score(&mut Visualizer::off()));
         assert_eq!(count_elves, map.elf_count());
     }
 
@@ -484,6 +464,6 @@ mod tests {
     #[test_case(TEST_INPUT_06, 1140)]
     #[test_case(INPUT, 90420)]
     fn test_part_02(input: &str, res: usize) {
-        assert_eq!(res, part_02(input).unwrap());
+        assert_eq!(res, part_02(input, &mut Visualizer::off()).unwrap());
     }
 }
@@ -56,9 +56,10 @@ impl Operation {
     }
 }
 
-use std::collections::BTreeSet;
 use std::fmt::Display;
 
+use aoc_utils::assignment::Assignment;
+
 use miette::GraphicalReportHandler;
 use nom_supreme::{
     error::{BaseErrorKind, ErrorTree, GenericErrorTree},
@@ -197,52 +198,22 @@ fn part_01(input: &'static str) -> usize {
 
 fn part_02(input: &'static str) -> i64 {
     let (test_cases, program) = parse(input);
-    // build a map from opcode to all possible instructions (i.e. opcode -> set of instructions)
-    // iterate over test cases, removing instructions that do not match
-    let mut mapping: Vec<BTreeSet<usize>> =
-        Vec::from_iter((0..16).map(|_| BTreeSet::from_iter(0..16)));
+    // start with every opcode mapping to every operation, then
+    // remove the operations that do not match each test case
+    let mut assignment = Assignment::new(0..16u8, 0..OPERATIONS.len());
 
     test_cases.into_iter().for_each(|tc| {
         OPERATIONS.iter().enumerate().for_each(|(idx, o)| {
             if !tc.matches(o) {
-                mapping[tc.instr.opcode as usize].remove(&idx);
+                assignment.remove(&tc.instr.opcode, &idx);
             }
         });
     });
 
-    loop {
-        if mapping.iter().all(|m| m.len() == 1) {
-            break;
-        }
-
-        let singles: Vec<usize> = mapping
-            .iter()
-            .filter_map(|m| {
-                if m.len() == 1 {
-                    Some(*m.first().unwrap())
-                } else {
-                    None
-                }
-            })
-            .collect();
-
-        if singles.is_empty() {
-            panic!("cannot simplify");
-        }
-
-        for m in &mut mapping {
-            if m.len() > 1 {
-                for s in &singles {
-                    m.remove(s);
-                }
-            }
-        }
-    }
-
-    let mapping: Vec<_> = mapping.into_iter().map(|m| *m.first().unwrap()).collect();
+    let mapping = assignment.solve().expect("cannot map opcodes");
     let mut register = [0; 4];
     for i in program.into_iter() {
-        register = i.eval(&OPERATIONS[mapping[i.opcode as usize]], &register);
+        register = i.eval(&OPERATIONS[mapping[&i.opcode]], &register);
     }
     register[0]
 }
@@ -1,187 +1,79 @@
-use std::{collections::BTreeMap, fmt::Display, ops::RangeInclusive};
-
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    ops::RangeInclusive,
+};
+
+use aoc_utils::{
+    error::final_parse,
+    gravity::{pour_water, Water},
+    parse::{axis_ranges, lines, values},
+};
 use itertools::{Itertools, MinMaxResult};
 
-use lazy_static::lazy_static;
-use regex::Regex;
-// basic solution design:
-// keep track of the water input:
-// - we start with one, we might add more when overflow
-// from each water input:
-// 1. go down, marking each spot as | running water as we go
-// 2. if reached high enough y, stop (y goes up as we go down)
-// 3. if spot below is # clay or ~ still water, search left and right for
-// first of
-//  a. a wall on the same level
-//  b. . sand on the spot below
-// if both ends have wall, fill range with ~, then redo analysis
-// from previous y
-// if or both end have open with . below, fill with |, then create
-// new source of water on the open spot(s)
+// the water falls from the spring, spreads over clay and fills basins up
+// to their lowest side, before overflowing: see gravity::pour_water
 
 static INPUT: &str = include_str!("input.txt");
 
-lazy_static! {
-    static ref INPUT_REGEX: Regex = Regex::new(r"^(.)=(\d+), (.)=(\d+)..(\d+)$").unwrap();
-}
-
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
-enum Tile {
-    Wall,
-    RunningWater,
-    StillWater,
-}
-
-fn parse_input(input: &str) -> BTreeMap<(usize, usize), Tile> {
-    BTreeMap::from_iter(
-        input
-            .lines()
-            .filter(|l| !l.trim().is_empty())
-            .flat_map(|l| {
-                let cap = INPUT_REGEX.captures(l).unwrap();
-                let x_dim = &cap[1] == "x";
-                let fdp = cap[2].parse().unwrap();
-                let sdr: RangeInclusive<usize> =
-                    RangeInclusive::new(cap[4].parse().unwrap(), cap[5].parse().unwrap());
-                sdr.into_iter().map(move |s| {
-                    if x_dim {
-                        ((fdp, s), Tile::Wall)
-                    } else {
-                        ((s, fdp), Tile::Wall)
-                    }
-                })
-            }),
-    )
+fn parse_input(input: &str) -> HashSet<(i64, i64)> {
+    let veins = final_parse(input, lines(axis_ranges)).unwrap();
+    HashSet::from_iter(veins.into_iter().flat_map(|vein| {
+        // one coordinate is a single value, the other a range
+        let (mut x, mut y) = (0..=0, 0..=0);
+        for (axis, range) in values(&vein) {
+            if axis == 'x' {
+                x = range;
+            } else {
+                y = range;
+            }
+        }
+        x.cartesian_product(y)
+    }))
 }
 
 struct Reservoir {
-    map: BTreeMap<(usize, usize), Tile>,
-    x_range: RangeInclusive<usize>,
-    y_range: RangeInclusive<usize>,
+    walls: HashSet<(i64, i64)>,
+    water: HashMap<(i64, i64), Water>,
+    x_range: RangeInclusive<i64>,
+    y_range: RangeInclusive<i64>,
 }
 
 impl Reservoir {
     fn parse(input: &str) -> Self {
-        let map = parse_input(input);
+        let walls = parse_input(input);
 
-        let MinMaxResult::MinMax(&y_min, &y_max) = map.keys().map(|(_, y)| y).minmax() else {
+        let MinMaxResult::MinMax(&y_min, &y_max) = walls.iter().map(|(_, y)| y).minmax() else {
             panic!("y_min == y_max");
         };
 
-        let MinMaxResult::MinMax(&x_min, &x_max) = map.keys().map(|(x, _)| x).minmax() else {
+        let MinMaxResult::MinMax(&x_min, &x_max) = walls.iter().map(|(x, _)| x).minmax() else {
             panic!("x_min == x_max");
         };
 
         Self {
-            map,
+            walls,
+            water: HashMap::new(),
             x_range: RangeInclusive::new(x_min - 1, x_max + 1),
             y_range: RangeInclusive::new(y_min, y_max),
         }
     }
 
-    // looking for ends:
-    // - on either side, open if lower level is empty
-    // - on either side, could be wall or |. In the latter case, it's like open but no need to
-    // process
-    fn fill_horizontal(&mut self, (x_start, y_start): (usize, usize)) {
-        let mut x_min = None;
-        let mut x_max = None;
-        let mut open_left = false;
-        let mut open_right = false;
-        let mut skip_left = false;
-        let mut skip_right = false;
-
-        // look for ends of range
-        // only two case on each side:
-        // - open below, or | -> open_end, with skip if |
-        // - wall -> closed end
-        for y in (1..=y_start).rev() {
-            for x in (*self.x_range.start()..x_start).rev() {
-                let below = self.map.get(&(x, y + 1));
-                if below.is_none() || below == Some(&Tile::RunningWater) {
-                    x_min = Some(x);
-                    open_left = true;
-                    skip_left = below.is_some();
-                    break;
-                } else if self.map.get(&(x, y)) == Some(&Tile::Wall) {
-                    x_min = Some(x + 1);
-                    open_left = false;
-                    break;
-                }
-            }
-            for x in x_start + 1..=*self.x_range.end() {
-                let below = self.map.get(&(x, y + 1));
-                if below.is_none() || below == Some(&Tile::RunningWater) {
-                    x_max = Some(x);
-                    open_right = true;
-                    skip_right = below.is_some();
-                    break;
-                } else if self.map.get(&(x, y)) == Some(&Tile::Wall) {
-                    x_max = Some(x - 1);
-                    open_right = false;
-                    break;
-                }
-            }
-
-            let (x_min, x_max) = (x_min.unwrap(), x_max.unwrap());
-
-            if open_left || open_right {
-                for x in x_min..=x_max {
-                    assert!(self.map.get(&(x, y)) != Some(&Tile::Wall));
-                    *self.map.entry((x, y)).or_insert(Tile::RunningWater) = Tile::RunningWater;
-                }
-                if open_left && !skip_left {
-                    self.fill_from((x_min, y));
-                }
-                if open_right && !skip_right {
-                    self.fill_from((x_max, y));
-                }
-                break;
-            } else {
-                for x in x_min..=x_max {
-                    assert!(self.map.get(&(x, y)) != Some(&Tile::Wall));
-                    *self.map.entry((x, y)).or_insert(Tile::StillWater) = Tile::StillWater;
-                }
-            }
-        }
-    }
-
-    fn fill_from(&mut self, (x_start, y_start): (usize, usize)) {
-        for y in y_start + 1..=*self.y_range.end() {
-            if !self.map.contains_key(&(x_start, y)) {
-                self.map.insert((x_start, y), Tile::RunningWater);
-            } else {
-                match self.map[&(x_start, y)] {
-                    Tile::RunningWater => {}
-                    Tile::Wall | Tile::StillWater => self.fill_horizontal((x_start, y - 1)),
-                }
-                break;
-            }
-        }
-        // for y in 1..self.y_max, check (x_pos, y);
-        // if empty, replace with '|'
-        // if not, switch to horizontal expand on the previous line
-        // - look for left and right edges
-        //   - edge is either an empty tile below, or a wall
-        //   - if walls on both side, fill with water, and step back to do edge again
-        //   - if one or both end open, fill with |, then use fill_from from the open ends
+    fn fill_from(&mut self, source: (i64, i64)) {
+        self.water = pour_water(&self.walls, source, *self.y_range.end());
     }
 
     fn water_count(&self) -> usize {
-        self.map
-            .iter()
-            .filter(|((x, y), t)| {
-                self.x_range.contains(x) && self.y_range.contains(y) && t != &&Tile::Wall
-            })
+        self.water
+            .keys()
+            .filter(|(_, y)| self.y_range.contains(y))
             .count()
     }
 
     fn water_at_rest_count(&self) -> usize {
-        self.map
+        self.water
             .iter()
-            .filter(|((x, y), t)| {
-                self.x_range.contains(x) && self.y_range.contains(y) && t == &&Tile::StillWater
-            })
+            .filter(|((_, y), &w)| self.y_range.contains(y) && w == Water::Still)
             .count()
     }
 }
@@ -190,14 +82,14 @@ impl Display for Reservoir {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         for y in 1..=*self.y_range.end() + 2 {
             for x in self.x_range.clone() {
-                let c = if let Some(&t) = self.map.get(&(x, y)) {
-                    match t {
-                        Tile::Wall => '#',
-                        Tile::RunningWater => '|',
-                        Tile::StillWater => '~',
-                    }
+                let c = if self.walls.contains(&(x, y)) {
+                    '#'
                 } else {
-                    '.'
+                    match self.water.get(&(x, y)) {
+                        Some(Water::Flowing) => '|',
+                        Some(Water::Still) => '~',
+                        None => '.',
+                    }
                 };
                 write!(f, "{}", c)?;
             }
@@ -1,5 +1,6 @@
 use std::{collections::BinaryHeap, cmp::Reverse};
 
+use aoc_utils::cuboid::{Cuboid, Octahedron};
 use itertools::{Itertools, MinMaxResult};
 use lazy_static::lazy_static;
 use regex::Regex;
@@ -40,6 +41,10 @@ impl Nanobot {
     fn in_range(&self, pos: Position) -> bool {
         distance(self.position, pos) <= self.range
     }
+
+    fn octahedron(&self) -> Octahedron {
+        Octahedron::new(self.position.into(), self.range)
+    }
 }
 
 fn part_01(input: &str) -> usize {
@@ -169,10 +174,8 @@ fn part_02(input: &str) -> i64 {
         } else if range < 100 {
             // ugly hack; if range is large, the approach below takes too much time
             // presumably, the cutoff could be defined as the distance between nanobots range?
-            let proj = (bot.position.0.clamp(pos.0, pos.0 + range - 1),
-            bot.position.1.clamp(pos.1, pos.1 + range - 1),
-            bot.position.2.clamp(pos.2, pos.2 + range - 1));
-            bot.in_range(proj)
+            bot.octahedron()
+                .intersects_box(&Cuboid::with_size(pos.into(), [range; 3]))
             } else {
                 (distance(pos, bot.position) - bot.range) / range <= 0
             }
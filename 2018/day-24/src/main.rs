@@ -1,5 +1,4 @@
-use log::info;
-use log::warn;
+use aoc_utils::battle::{minimal_boost, select_targets, turn_order, Replay};
 use std::{cmp::Reverse, fmt::Display};
 
 use lazy_static::lazy_static;
@@ -112,6 +111,7 @@ impl Group {
 struct Armies {
     groups: Vec<Group>,
     stuck: bool,
+    replay: Replay,
 }
 
 impl Armies {
@@ -144,6 +144,7 @@ impl Armies {
         Self {
             groups,
             stuck: false,
+            replay: Replay::off(),
         }
     }
 
@@ -154,65 +155,45 @@ impl Armies {
             .for_each(|g| g.damage += booster);
     }
 
-    fn target_selection(&self) -> Vec<(usize, usize)> {
-        let mut targets = vec![];
-
-        let mut attackers: Vec<usize> = (0..self.groups.len()).collect();
-        attackers.sort_by_key(|&i| {
-            (
-                Reverse(self.groups[i].effective_power()),
-                Reverse(self.groups[i].initiative),
-            )
+    fn target_selection(&mut self) -> Vec<(usize, usize)> {
+        let groups = &self.groups;
+        let attackers = turn_order(groups, |g| {
+            (Reverse(g.effective_power()), Reverse(g.initiative))
         });
 
-        let mut remaining_targets = Vec::from_iter(0..self.groups.len());
-
-        for a in &attackers {
-            let g = &self.groups[*a];
-            let mut possible_targets: Vec<_> = (0..self.groups.len())
-                .filter(|i| {
-                    remaining_targets.contains(i)
-                        && self.groups[*i].side != g.side
-                        && g.effective_damage(&self.groups[*i]) > 0
-                })
-                .collect();
-
-            if possible_targets.is_empty() {
-                continue;
-            }
+        let targets = select_targets(&attackers, groups.len(), |a, t| {
+            let (g, target) = (&groups[a], &groups[t]);
+            let damage = if target.side != g.side {
+                g.effective_damage(target)
+            } else {
+                0
+            };
+            (damage > 0).then_some((damage, target.effective_power(), target.initiative))
+        });
 
-            possible_targets.sort_by_key(|i| {
-                (
-                    Reverse(g.effective_damage(&self.groups[*i])),
-                    Reverse(self.groups[*i].effective_power()),
-                    Reverse(self.groups[*i].initiative),
+        for &(a, t) in &targets {
+            self.replay.record(|| {
+                format!(
+                    "{} group {} would deal defending group {} {} damage",
+                    groups[a].side,
+                    groups[a].id,
+                    groups[t].id,
+                    groups[a].effective_damage(&groups[t])
                 )
             });
-
-            info!(
-                "{} group {} would deal defending group {} {} damage",
-                g.side,
-                g.id,
-                self.groups[possible_targets[0]].id,
-                g.effective_damage(&self.groups[possible_targets[0]])
-            );
-            targets.push((*a, possible_targets[0]));
-            remaining_targets.retain(|rt| *rt != possible_targets[0]);
         }
-
         targets
     }
 
-    fn attack(&mut self, mut targets: Vec<(usize, usize)>) -> bool {
-        targets.sort_by_key(|(a, _)| Reverse(self.groups[*a].initiative));
+    fn attack(&mut self, targets: Vec<(usize, usize)>) -> bool {
+        let order = turn_order(&targets, |&(a, _)| Reverse(self.groups[a].initiative));
         let mut damage_dealt = false;
 
-        for (a, d) in targets {
+        for (a, d) in order.into_iter().map(|i| targets[i]) {
             if self.groups[a].is_dead() {
-                warn!(
-                    "{} group {} already dead",
-                    self.groups[a].side, self.groups[a].id
-                );
+                let ag = &self.groups[a];
+                self.replay
+                    .record(|| format!("{} group {} already dead", ag.side, ag.id));
             } else {
                 let (cu, ed) = {
                     let ag = &self.groups[a];
@@ -223,13 +204,15 @@ impl Armies {
                 let ag = &self.groups[a];
                 let dg = &self.groups[d];
                 damage_dealt = damage_dealt || cu > dg.unit;
-                info!(
-                    "{} group {} attacks defending group {}, killing {}",
-                    ag.side,
-                    ag.id,
-                    dg.id,
-                    cu - dg.unit
-                );
+                self.replay.record(|| {
+                    format!(
+                        "{} group {} attacks defending group {}, killing {} units",
+                        ag.side,
+                        ag.id,
+                        dg.id,
+                        cu - dg.unit
+                    )
+                });
             }
         }
 
@@ -238,20 +221,17 @@ impl Armies {
     }
 
     fn round(&mut self) -> bool {
-        info!("Immune System:");
-        self.groups
-            .iter()
-            .filter(|g| g.side == Side::ImmuneSystem)
-            .for_each(|g| info!("Group {} contains {} units", g.id, g.unit));
-        info!("Infection:");
-        self.groups
-            .iter()
-            .filter(|g| g.side == Side::Infection)
-            .for_each(|g| info!("Group {} contains {} units", g.id, g.unit));
+        self.replay.next_round();
+        for side in [Side::ImmuneSystem, Side::Infection] {
+            for g in self.groups.iter().filter(|g| g.side == side) {
+                self.replay
+                    .record(|| format!("{} group {} contains {} units", side, g.id, g.unit));
+            }
+        }
         let targets = self.target_selection();
 
         if !self.attack(targets) {
-            warn!("armies are stuck");
+            self.replay.record(|| "armies are stuck".to_owned());
             self.stuck = true;
             return true;
         }
@@ -290,52 +270,32 @@ fn part_01(input: &str) -> u64 {
     }
 }
 
-fn part_02(input: &str) -> u64 {
-    let mut min = 0;
-    let mut max = 10000000000;
-    loop {
-        if min == max {
-            panic!("cannot find booster in range");
-        }
-        let booster = min + (max - min) / 2;
+fn boosted_fight(input: &str, booster: u64) -> Armies {
+    let mut armies = Armies::from_str(input);
+    armies.boost(booster);
+    armies.fight();
+    armies
+}
 
-        let mut armies = Armies::from_str(input);
-        armies.boost(booster);
-        armies.fight();
-        if armies.stuck {
-            min += 1;
-        } else if let Some(Side::ImmuneSystem) = armies.remaining_side() {
-            // yes, I could implement binary search correctly, or, I can just
-            // scan and be done with it
-            if max - min < 5 {
-                for booster in min..=max {
-                    let mut armies = Armies::from_str(input);
-                    armies.boost(booster);
-                    armies.fight();
-                    if armies.stuck {
-                        continue;
-                    }
-                    if let Some(Side::ImmuneSystem) = armies.remaining_side() {
-                        return armies.remaining_units();
-                    }
-                }
-            }
-            max = booster + 1;
-        } else {
-            min = booster + 1;
-        }
-    }
+// stuck fights count as lost
+fn part_02(input: &str) -> u64 {
+    let booster = minimal_boost(0, |booster| {
+        let armies = boosted_fight(input, booster);
+        !armies.stuck && armies.remaining_side() == Some(Side::ImmuneSystem)
+    })
+    .expect("cannot find booster");
+    boosted_fight(input, booster).remaining_units()
 }
 
 fn main() {
-    env_logger::init();
     println!("Part 1: {}", part_01(INPUT));
     println!("Part 2: {}", part_02(INPUT));
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{part_01, part_02, INPUT};
+    use crate::{part_01, part_02, Armies, INPUT};
+    use aoc_utils::battle::Replay;
     use test_case::test_case;
 
     static TEST_INPUT: &str = r"Immune System:
@@ -352,6 +312,28 @@ Infection:
         assert_eq!(units, part_01(input));
     }
 
+    #[test]
+    fn test_replay() {
+        let mut armies = Armies::from_str(TEST_INPUT);
+        armies.replay = Replay::recording();
+        armies.fight();
+        let attacks: Vec<_> = armies
+            .replay
+            .round_events(1)
+            .filter(|e| e.contains("attacks"))
+            .collect();
+        assert_eq!(
+            vec![
+                "Infection group 2 attacks defending group 2, killing 84 units",
+                "Immune System group 2 attacks defending group 1, killing 4 units",
+                "Immune System group 1 attacks defending group 2, killing 51 units",
+                "Infection group 1 attacks defending group 1, killing 17 units",
+            ],
+            attacks
+        );
+        assert_eq!(8, armies.replay.round());
+    }
+
     #[test_case(TEST_INPUT, 51)]
     #[test_case(INPUT, 4573)]
     fn test_part_02(input: &str, units: u64) {
@@ -1,85 +1,46 @@
-use std::collections::{HashMap, HashSet, VecDeque};
+use aoc_utils::graph::Graph;
 
-fn build_orbits(desc: &'static str) -> HashMap<&'static str, HashSet<&'static str>> {
-    let mut orbits = HashMap::new();
-    for line in desc.lines() {
-        if let Some(pos) = line.find(')') {
-            let planet = &line[..pos];
-            let moon = &line[pos+1..];
-            orbits.entry(planet).or_insert(HashSet::new()).insert(moon);
-        } else {
-            panic!("cannot parse {}", line);
-        }
-    }
-    orbits
+// edges from planets to their moons
+fn build_orbits(desc: &'static str) -> Graph<&'static str> {
+    Graph::parse_pairs(desc, ")", true).unwrap()
 }
 
-fn compute_depth(orbits: &HashMap<&'static str, HashSet<&'static str>>) -> HashMap<&'static str, usize> {
-    let mut level = 0;
-    let mut levels = HashMap::new();
-    let mut moons = HashSet::new();
-
-    levels.insert(COM, level);
-
-    orbits.get(COM).map(|m| moons.extend(m.iter()));
-
-    while !moons.is_empty() {
-        level += 1;
-        let mut next_moons = HashSet::new();
-        for moon in moons  {
-            if levels.insert(moon, level).is_some() {
-                panic!("{} orbits multiple planets", moon);
+// the path from COM to each planet, by id
+fn build_paths(orbits: &Graph<&'static str>) -> Vec<Vec<usize>> {
+    let com = orbits.id(COM).expect("no COM");
+    let mut paths = vec![Vec::new(); orbits.len()];
+    for planet in orbits.reachable(com) {
+        for moon in orbits.neighbours(planet) {
+            if orbits.in_degree(moon) > 1 {
+                panic!("{} orbits multiple planets", orbits.label(moon));
             }
-            orbits.get(moon).map(|m| next_moons.extend(m.iter()));
+            let mut path = paths[planet].clone();
+            path.push(planet);
+            paths[moon] = path;
         }
-        moons = next_moons;
     }
-
-    levels
-}
-
-fn sum_orbits(levels: &HashMap<&'static str, usize>) -> usize {
-    levels.iter().map(|(_, v)| v).sum()
+    paths
 }
 
 fn part_1(desc: &'static str) -> usize {
     let orbits = build_orbits(desc);
-    let levels = compute_depth(&orbits);
-    sum_orbits(&levels)
+    build_paths(&orbits).iter().map(Vec::len).sum()
 }
 
-fn build_paths(desc: &'static str) -> HashMap<&'static str, Vec<&'static str>> {
-    let orbits = build_orbits(desc);
-    let mut paths = HashMap::new();
-
-    paths.insert(COM, Vec::new());
-    let mut planets = VecDeque::new();
-    planets.push_back(COM);
-
-    while let Some(planet) = planets.pop_front() {
-        orbits.get(planet).map(|moons| {
-            for moon in moons {
-                let mut path = paths.get(planet).expect("planet not found").clone();
-                path.push(planet);
-                paths.insert(moon, path);
-                planets.push_back(moon);
-            }
-        });
-    }
-    paths
-}
-
-fn compute_transers(from: &'static str, to: &'static str, paths: &HashMap<&'static str, Vec<&'static str>>) -> usize {
-    let from_path = paths.get(from).expect("from path");
-    let to_path = paths.get(to).expect("to path");
+fn compute_transers(from: usize, to: usize, paths: &[Vec<usize>]) -> usize {
+    let from_path = &paths[from];
+    let to_path = &paths[to];
 
     let common_prefix = from_path.iter().zip(to_path.iter()).filter(|(f, t)| f == t).count();
     from_path.len() + to_path.len() - 2 * common_prefix
 }
 
 fn part_2(desc: &'static str) -> usize {
-    let paths = build_paths(desc);
-    compute_transers("YOU", "SAN", &paths)
+    let orbits = build_orbits(desc);
+    let paths = build_paths(&orbits);
+    let from = orbits.id("YOU").expect("from path");
+    let to = orbits.id("SAN").expect("to path");
+    compute_transers(from, to, &paths)
 }
 
 static INPUT: &str = include_str!("input.txt");
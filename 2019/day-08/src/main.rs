@@ -1,3 +1,5 @@
+use aoc_utils::ocr;
+
 static INPUT: &str = include_str!("input.txt");
 static WIDTH: usize = 25;
 static HEIGHT: usize = 6;
@@ -42,10 +44,25 @@ fn display(layer: Vec<Vec<u8>>) {
     }
 }
 
+fn read(layer: &[Vec<u8>]) -> Result<String, ocr::OcrError> {
+    ocr::read_points(layer.iter().enumerate().flat_map(|(y, line)| {
+        line.iter()
+            .enumerate()
+            .filter(|(_, b)| **b == 1)
+            .map(move |(x, _)| (x as i64, y as i64))
+    }))
+}
+
 fn main() {
     println!("part 1: {}", part_1(INPUT, WIDTH, HEIGHT));
-    println!("part 2:");
-    display(part_2(INPUT, WIDTH, HEIGHT));
+    let image = part_2(INPUT, WIDTH, HEIGHT);
+    match read(&image) {
+        Ok(message) => println!("part 2: {}", message),
+        Err(e) => {
+            println!("part 2: {}", e);
+            display(image);
+        }
+    }
 }
 
 #[cfg(test)]
@@ -57,4 +74,29 @@ mod test {
     fn test_part_2() {
         assert_eq!(part_2(TEST, 2, 2), vec![vec![0,1],vec![1,0]]);
     }
+
+    static MESSAGE: &str = r"
+ ##  #   ####  #### ###
+#  # #   ##  # #    #  #
+#     # # ###  ###  #  #
+#      #  #  # #    ###
+#  #   #  #  # #    # #
+ ##    #  ###  #### #  #";
+
+    #[test]
+    fn test_message() {
+        // three layers: transparent but for a third of the pixels, the
+        // message, and noise that must stay hidden
+        let pixels: Vec<_> = MESSAGE
+            .lines()
+            .skip(1)
+            .flat_map(|l| format!("{:WIDTH$}", l).into_bytes())
+            .map(|b| if b == b'#' { b'1' } else { b'0' })
+            .collect();
+        let top = pixels.iter().enumerate().map(|(i, &p)| if i % 3 == 0 { p } else { b'2' });
+        let noise = (0..pixels.len()).map(|i| if i % 2 == 0 { b'1' } else { b'0' });
+        let image: Vec<u8> = top.chain(pixels.iter().cloned()).chain(noise).collect();
+        let image = String::from_utf8(image).unwrap();
+        assert_eq!(Ok("CYBER".to_owned()), read(&part_2(&image, WIDTH, HEIGHT)));
+    }
 }
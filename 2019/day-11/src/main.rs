@@ -1,4 +1,5 @@
-use anyhow::*;
+use anyhow::{anyhow, Result};
+use aoc_utils::ocr;
 use intcode::*;
 use std::collections::HashSet;
 
@@ -104,43 +105,110 @@ impl Robot {
     }
 }
 
-fn part_1() -> Result<usize> {
-    let mut robot = Robot::new(INPUT, false)?;
+fn paint(code: &str, init_white: bool) -> Result<Robot> {
+    let mut robot = Robot::new(code, init_white)?;
     while !robot.is_stopped() {
         robot.step();
     }
+    Ok(robot)
+}
 
-    Ok(robot.painted_panel_count())
+fn part_1(code: &str) -> Result<usize> {
+    Ok(paint(code, false)?.painted_panel_count())
 }
 
-fn part_2() -> Result<()> {
-    println!("part 2");
-    let mut robot = Robot::new(INPUT, true)?;
-    while !robot.is_stopped() {
-        robot.step();
+fn part_2(code: &str) -> Result<String> {
+    let robot = paint(code, true)?;
+
+    let white = robot.panel.iter().map(|&(x, y)| (x as i64, y as i64));
+    match ocr::read_points(white) {
+        Ok(id) => Ok(id),
+        Err(e) => {
+            let lx = robot.panel.iter().map(|c| c.0).min().unwrap();
+            let ly = robot.panel.iter().map(|c| c.1).min().unwrap();
+
+            let hx = robot.panel.iter().map(|c| c.0).max().unwrap();
+            let hy = robot.panel.iter().map(|c| c.1).max().unwrap();
+
+            let hull: Vec<String> = (ly..=hy)
+                .map(|row| {
+                    (lx..=hx)
+                        .map(|col| {
+                            if robot.panel.contains(&(col, row)) {
+                                '#'
+                            } else {
+                                ' '
+                            }
+                        })
+                        .collect()
+                })
+                .collect();
+            Err(anyhow!("{}\n{}", e, hull.join("\n")))
+        }
     }
+}
 
-    let lx = robot.panel.iter().map(|c| c.0).min().unwrap();
-    let ly = robot.panel.iter().map(|c| c.1).min().unwrap();
-
-    let hx = robot.panel.iter().map(|c| c.0).max().unwrap();
-    let hy = robot.panel.iter().map(|c| c.1).max().unwrap();
+fn main() -> Result<()> {
+    println!("part 1: {}", part_1(INPUT)?);
+    println!("part 2: {}", part_2(INPUT)?);
+    Ok(())
+}
 
-    for row in ly..=hy {
-        for col in lx..=hx {
-            if robot.panel.contains(&(col, row)) {
-                print!("#");
-            } else {
-                print!(" ");
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static HULL: &str = r"
+#  # #  #  ##   ###
+#  # #  # #  # #
+#### #  # #    #
+#  # #  # # ##  ##
+#  # #  # #  #    #
+#  #  ##   ### ###";
+
+    // a program that ignores the camera and paints the hull: the robot
+    // visits every panel of the message, turning at every step, and paints
+    // each panel it leaves with the colour of the message
+    fn painter(hull: &str) -> String {
+        let white: HashSet<(isize, isize)> = hull
+            .lines()
+            .skip(1)
+            .enumerate()
+            .flat_map(|(y, l)| {
+                l.char_indices()
+                    .filter(|(_, c)| *c == '#')
+                    .map(move |(x, _)| (x as isize, y as isize))
+            })
+            .collect();
+        let (width, height) = (20, 6);
+        let mut program = vec![];
+        let (mut position, mut delta) = ((0, 0), Direction::Up.delta());
+        let targets = (0..height).flat_map(|y| (0..width).map(move |x| (x, y)));
+        for target in targets.chain(Some((0, height))) {
+            while position != target {
+                // moves alternate between the two axes
+                let toward = |from: isize, to: isize| if to < from { -1 } else { 1 };
+                let next = if delta.0 == 0 {
+                    (toward(position.0, target.0), 0)
+                } else {
+                    (0, toward(position.1, target.1))
+                };
+                let right = next == (-delta.1, delta.0);
+                program.push(format!(
+                    "104,{},104,{}",
+                    white.contains(&position) as u8,
+                    right as u8
+                ));
+                position = (position.0 + next.0, position.1 + next.1);
+                delta = next;
             }
         }
-        println!();
+        program.push("99".to_owned());
+        program.join(",")
     }
 
-    Ok(())
-}
-
-fn main() -> Result<()> {
-    println!("part 1: {}", part_1()?);
-    part_2()
+    #[test]
+    fn test_part_2() {
+        assert_eq!("HUGS", part_2(&painter(HULL)).unwrap());
+    }
 }
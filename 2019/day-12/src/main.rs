@@ -1,8 +1,8 @@
-use anyhow::*;
+use anyhow::{anyhow, Result};
+use aoc_utils::vec3::Vec3;
 use lazy_static::lazy_static;
 use regex::Regex;
 use std::fmt::{Display, Formatter};
-use std::str::FromStr;
 static INPUT: &str = include_str!("input.txt");
 
 lazy_static! {
@@ -27,52 +27,23 @@ fn gcd(mut a: usize, mut b: usize) -> usize {
     }
 }
 
-#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
-struct Vector(Data, Data, Data);
+type Vector = Vec3<Data>;
 
-impl Default for Vector {
-    fn default() -> Self {
-        Vector(0, 0, 0)
-    }
-}
-
-impl Display for Vector {
-    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
-        write!(f, "<x={:2}, y={:2}, z={:2}>", self.0, self.1, self.2)
-    }
-}
-
-impl FromStr for Vector {
-    type Err = Error;
-
-    fn from_str(s: &str) -> Result<Self> {
-        REGEX
-            .captures(s)
-            .map(|cap| {
-                Vector(
-                    cap.get(1).unwrap().as_str().parse().unwrap(),
-                    cap.get(2).unwrap().as_str().parse().unwrap(),
-                    cap.get(3).unwrap().as_str().parse().unwrap(),
-                )
-            })
-            .ok_or(anyhow!("cannot parse {}", s))
-    }
-}
-
-fn delta(v1: &Vector, v2: &Vector) -> Vector {
-    Vector(
-        (v2.0 - v1.0).signum(),
-        (v2.1 - v1.1).signum(),
-        (v2.2 - v1.2).signum(),
-    )
-}
-
-fn add(v: &Vector, delta: &Vector) -> Vector {
-    Vector(v.0 + delta.0, v.1 + delta.1, v.2 + delta.2)
+fn parse_vector(s: &str) -> Result<Vector> {
+    REGEX
+        .captures(s)
+        .map(|cap| {
+            Vector::new(
+                cap.get(1).unwrap().as_str().parse().unwrap(),
+                cap.get(2).unwrap().as_str().parse().unwrap(),
+                cap.get(3).unwrap().as_str().parse().unwrap(),
+            )
+        })
+        .ok_or(anyhow!("cannot parse {}", s))
 }
 
 fn parse_input(desc: &str) -> Result<Vec<Vector>> {
-    desc.lines().map(|line| line.parse::<Vector>()).collect()
+    desc.lines().map(parse_vector).collect()
 }
 
 // count digits required to represent number
@@ -89,9 +60,9 @@ fn count_digits(d: Data) -> usize {
 }
 
 fn count_digit_vector(v: &[Vector]) -> (usize, usize, usize) {
-    let xc = v.iter().map(|p| count_digits(p.0)).max().unwrap();
-    let yc = v.iter().map(|p| count_digits(p.1)).max().unwrap();
-    let zc = v.iter().map(|p| count_digits(p.2)).max().unwrap();
+    let xc = v.iter().map(|p| count_digits(p.x)).max().unwrap();
+    let yc = v.iter().map(|p| count_digits(p.y)).max().unwrap();
+    let zc = v.iter().map(|p| count_digits(p.z)).max().unwrap();
     (xc, yc, zc)
 }
 
@@ -125,21 +96,21 @@ impl Planets {
             .map(|p| {
                 self.positions
                     .iter()
-                    .map(|o| delta(p, o))
-                    .fold(Vector::default(), |acc, v| add(&acc, &v))
+                    .map(|o| (*o - *p).signum())
+                    .sum()
             })
             .collect();
         let velocities: Vec<Vector> = self
             .velocities
             .iter()
             .zip(gravities.iter())
-            .map(|(v, g)| add(v, g))
+            .map(|(v, g)| *v + *g)
             .collect();
         let positions: Vec<Vector> = self
             .positions
             .iter()
             .zip(velocities.iter())
-            .map(|(p, v)| add(p, v))
+            .map(|(p, v)| *p + *v)
             .collect();
 
         self.positions.copy_from_slice(&positions);
@@ -149,11 +120,11 @@ impl Planets {
     fn total_energy(&self) -> Data {
         self.positions
             .iter()
-            .map(|p| p.0.abs() + p.1.abs() + p.2.abs())
+            .map(|p| p.manhattan())
             .zip(
                 self.velocities
                     .iter()
-                    .map(|v| v.0.abs() + v.1.abs() + v.2.abs()),
+                    .map(|v| v.manhattan()),
             )
             .map(|(p, v)| p * v)
             .sum()
@@ -176,9 +147,9 @@ impl Display for AlignedVector {
         write!(
             f,
             "<x={:xw$}, y={:yw$}, z={:zw$}>",
-            self.vector.0,
-            self.vector.1,
-            self.vector.2,
+            self.vector.x,
+            self.vector.y,
+            self.vector.z,
             xw = self.width.0 + 1,
             yw = self.width.1 + 1,
             zw = self.width.2 + 1
@@ -246,25 +217,25 @@ impl Default for Coordinate {
 fn planets_cycle(desc: &str) -> usize {
     let planets = Planets::new(desc).unwrap();
     let mut coordinate = Coordinate::default();
-    let xp: Vec<_> = planets.positions.iter().map(|&p| p.0).collect();
+    let xp: Vec<_> = planets.positions.iter().map(|&p| p.x).collect();
     coordinate.0.copy_from_slice(&xp);
-    let xv: Vec<_> = planets.velocities.iter().map(|&v| v.0).collect();
+    let xv: Vec<_> = planets.velocities.iter().map(|&v| v.x).collect();
     coordinate.1.copy_from_slice(&xv);
 
     let x_cycle = coordinate.cycle();
 
     let mut coordinate = Coordinate::default();
-    let yp: Vec<_> = planets.positions.iter().map(|&p| p.1).collect();
+    let yp: Vec<_> = planets.positions.iter().map(|&p| p.y).collect();
     coordinate.0.copy_from_slice(&yp);
-    let yv: Vec<_> = planets.velocities.iter().map(|&v| v.1).collect();
+    let yv: Vec<_> = planets.velocities.iter().map(|&v| v.y).collect();
     coordinate.1.copy_from_slice(&yv);
 
     let y_cycle = coordinate.cycle();
 
     let mut coordinate = Coordinate::default();
-    let zp: Vec<_> = planets.positions.iter().map(|&p| p.2).collect();
+    let zp: Vec<_> = planets.positions.iter().map(|&p| p.z).collect();
     coordinate.0.copy_from_slice(&zp);
-    let zv: Vec<_> = planets.velocities.iter().map(|&v| v.2).collect();
+    let zv: Vec<_> = planets.velocities.iter().map(|&v| v.z).collect();
     coordinate.1.copy_from_slice(&zv);
 
     let z_cycle = coordinate.cycle();
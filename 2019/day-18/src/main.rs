@@ -1,311 +1,110 @@
-use std::fmt;
-use std::ops::IndexMut;
-use std::{
-    cmp::Reverse,
-    collections::{BinaryHeap, HashMap, HashSet},
-    fmt::Display,
-    ops::Index,
-};
+use std::collections::VecDeque;
 
-static INPUT: &str = include_str!("input.txt");
-
-#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
-struct Keys(u32);
+use aoc_utils::grid::{Grid, Taxicab};
+use aoc_utils::tour::Tour;
 
-impl Keys {
-    fn new() -> Self {
-        Self(0)
-    }
-
-    fn has_key(&self, key: u8) -> bool {
-        assert!(key.is_ascii_lowercase());
-        self.0 & (2 << (key - b'a')) != 0
-    }
-
-    fn add_key(&self, key: u8) -> Self {
-        assert!(key.is_ascii_lowercase());
-        let mut other = *self;
-        other.0 |= 2 << (key - b'a');
-        other
-    }
-
-    fn key_count(&self) -> u32 {
-        self.0.count_ones()
-    }
-}
+static INPUT: &str = include_str!("input.txt");
 
-impl fmt::Debug for Keys {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let keys: String = (b'a'..=b'z')
-            .filter(|k| self.has_key(*k))
-            .map(|b| b as char)
-            .collect();
-        f.debug_tuple("Keys").field(&keys).finish()
-    }
+type Maze = Grid<u8, Taxicab>;
+
+fn parse(input: &str) -> Maze {
+    // the examples are indented
+    let lines: Vec<&str> = input
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .collect();
+    Grid::from_vec(
+        lines[0].len(),
+        lines.iter().flat_map(|l| l.bytes()).collect(),
+    )
 }
 
-struct Grid {
-    grid: Vec<u8>,
-    width: usize,
-    height: usize,
-    key_count: u32,
+fn find_all(maze: &Maze, what: impl Fn(u8) -> bool) -> Vec<(u8, (usize, usize))> {
+    maze.iter()
+        .enumerate()
+        .filter(|(_, &b)| what(b))
+        .map(|(idx, &b)| (b, maze.idx_to_pos(idx)))
+        .collect()
 }
 
-impl Grid {
-    fn from_str(input: &str) -> Self {
-        let mut width = 0;
-        let grid: Vec<u8> = input
-            .lines()
-            .filter(|l| !l.trim().is_empty())
-            .inspect(|l| width = l.len())
-            .flat_map(|l| l.bytes())
-            .collect();
-        let height = grid.len() / width;
-        let key_count = grid.iter().filter(|b| b.is_ascii_lowercase()).count() as u32;
-        Self {
-            grid,
-            width,
-            height,
-            key_count,
-        }
-    }
-
-    fn idx_to_pos(&self, idx: usize) -> (usize, usize) {
-        (idx % self.width, idx / self.width)
-    }
-
-    fn pos_to_idx(&self, pos: (usize, usize)) -> usize {
-        pos.0 + self.width * pos.1
-    }
-
-    fn origin(&self) -> Option<(usize, usize)> {
-        self.grid
-            .iter()
-            .position(|&b| b == b'@')
-            .map(|p| self.idx_to_pos(p))
-    }
-
-    fn all_starting_tags(&self) -> Vec<u8> {
-        self.grid
-            .iter()
-            .cloned()
-            .filter(|b| b.is_ascii_digit() || *b == b'@')
-            .collect()
-    }
-
-    fn neighbours(&self, pos: (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
-        [(-1, 0), (1, 0), (0, -1), (0, 1)]
-            .into_iter()
-            .filter_map(move |d| {
-                pos.0
-                    .checked_add_signed(d.0)
-                    .and_then(|x| pos.1.checked_add_signed(d.1).map(|y| (x, y)))
-                    .filter(|(x, y)| *x < self.width && *y < self.height && self[(*x, *y)] != b'#')
-            })
-    }
-    // precompute step: make a graph between non-wall and non-empty, where we only expand
-    // neighbourds of empty pos -> edges are between things that can be reached (from key to door,
-    // or key to key without any obstacle), and then we can reason on this graph
-
-    // return a map from significant idx (non-empty, non-wall) to directly reachable
-    // in the minimum number of steps. Directly here means not going over another key or
-    // through a door
-    fn compress_graph(&self) -> Graph {
-        let mut neighbours = HashMap::new();
-        for (idx, b) in self.grid.iter().enumerate().filter_map(|(idx, b)| {
-            // allow [a-z][A-Z] and [0-9] for multirobots
-            if b.is_ascii_alphanumeric() || *b == b'@' {
-                Some((idx, *b))
-            } else {
-                None
+// the doors on the way from start to each cell, as a bitmask of their
+// letters. The mazes are trees (apart from the open space around the
+// entrance), so there is only one way
+fn doors_on_the_way(maze: &Maze, start: (usize, usize)) -> Vec<Option<u32>> {
+    let mut doors = vec![None; maze.width() * maze.height()];
+    doors[maze.pos_to_idx(start)] = Some(0);
+    let mut fringe = VecDeque::from([start]);
+    while let Some(pos) = fringe.pop_front() {
+        let on_the_way = doors[maze.pos_to_idx(pos)].unwrap();
+        for n in maze.neighbours(pos) {
+            let idx = maze.pos_to_idx(n);
+            if maze[n] == b'#' || doors[idx].is_some() {
+                continue;
             }
-        }) {
-            neighbours.insert(b, self.find_neighbours(idx));
-        }
-        Graph {
-            graph: neighbours,
-            cache: HashMap::new(),
-        }
-    }
-
-    fn find_neighbours(&self, orig_idx: usize) -> Vec<(usize, u8)> {
-        let mut fringe = BinaryHeap::new();
-        let mut seen = HashSet::new();
-        let mut neighbours = Vec::new();
-
-        fringe.push((Reverse(0), orig_idx));
-
-        while let Some((steps, idx)) = fringe.pop() {
-            seen.insert(idx);
-            let b = self.grid[idx];
-            if orig_idx != idx && b.is_ascii_alphabetic() {
-                neighbours.push((steps.0, b));
+            doors[idx] = Some(if maze[n].is_ascii_uppercase() {
+                on_the_way | 1 << (maze[n] - b'A')
             } else {
-                for n in self.neighbours(self.idx_to_pos(idx)) {
-                    if seen.contains(&self.pos_to_idx(n)) {
-                        continue;
-                    }
-                    let new_idx = self.pos_to_idx(n);
-                    fringe.push((Reverse(steps.0 + 1), new_idx));
-                }
-            }
+                on_the_way
+            });
+            fringe.push_back(n);
         }
-
-        neighbours
     }
-
-    // search pattern for graph:
-    // - fringe as BinaryHeap with the number of steps are the primary factor (use Reverse to
-    // minimize)
-    // - best as HashMap between state and steps
-    // - maybe initialize origin as 0 if needed (not needed here as origin or start of search are
-    // always excluded from expansion by other aspect of the problem)
-    // - for state in fringe {
-    //     for neighbour in state.neighbours {
-    //       if neighbour.dist < best[neighbours] {
-    //         best[neighbour] = neighbour.dist
-    //         fringe.insert(neighbour)
-    //       }
-    //     }
-    // }
-
-    fn collect_all<const N: usize>(&self) -> Option<usize> {
-        let mut graph = self.compress_graph();
-        let mut fringe = BinaryHeap::new();
-
-        let mut best = HashMap::new();
-        let starting_tags = self.all_starting_tags();
-        let bots: [u8; N] = std::array::from_fn(|i| starting_tags[i]);
-
-        fringe.push((Reverse(0), bots, Keys::new()));
-
-        while let Some((steps, bots, keys)) = fringe.pop() {
-            if keys.key_count() == self.key_count {
-                return Some(steps.0);
-            }
-
-            if let Some(&best_so_far) = best.get(&(bots, keys)) {
-                if steps.0 > best_so_far {
-                    continue;
-                }
-            }
-
-            for (idx, bot) in bots.iter().enumerate() {
-                for (dist, b) in graph.neighbours(*bot, keys) {
-                    let keys = keys.add_key(*b);
-                    let dist = steps.0 + dist;
-                    let mut bots = bots;
-                    bots[idx] = *b;
-                    let best_entry = best.entry((bots, keys)).or_insert(usize::MAX);
-                    // need to update bots
-                    if dist < *best_entry {
-                        *best_entry = dist;
-                        fringe.push((Reverse(dist), bots, keys));
-                    }
-                }
-            }
-        }
-
-        None
-    }
-}
-
-#[derive(Debug)]
-struct Graph {
-    graph: HashMap<u8, Vec<(usize, u8)>>,
-    cache: HashMap<(u8, Keys), Vec<(usize, u8)>>,
+    doors
 }
 
-impl Graph {
-    fn neighbours(&mut self, key: u8, keys: Keys) -> &Vec<(usize, u8)> {
-        self.cache.entry((key, keys)).or_insert({
-            let mut fringe = BinaryHeap::new();
-            let mut distance = HashMap::new();
-
-            fringe.push((Reverse(0), key));
-            let mut neighbours = vec![];
-            distance.insert(key, 0);
-
-            while let Some((steps, b)) = fringe.pop() {
-                let best_dist = distance.entry(b).or_insert(usize::MAX);
-                if steps.0 > *best_dist {
-                    continue;
-                }
-                *best_dist = steps.0;
-                for (dist, b) in self.graph.get(&b).unwrap() {
-                    // if upper but no key, cannot pass
-                    if b.is_ascii_uppercase() && !keys.has_key(b.to_ascii_lowercase()) {
-                        continue;
-                    }
-                    // if lower, but no key, add it as neighbour
-                    if b.is_ascii_lowercase() && !keys.has_key(*b) {
-                        neighbours.push((steps.0 + dist, *b));
-                        continue;
-                    }
-                    let next_dist = distance.entry(*b).or_insert(usize::MAX);
-                    if steps.0 + dist < *next_dist {
-                        *next_dist = steps.0 + dist;
-
-                        fringe.push((Reverse(steps.0 + dist), *b));
-                    }
-                    // otherwise keep searching
+// compress the maze to the distances between the robots and the keys,
+// walking through doors; a key can only be picked once the keys of the
+// doors on its way have been. Then the robots take turns moving to the
+// next key
+fn collect_all(maze: &Maze) -> usize {
+    let robots = find_all(maze, |b| b == b'@');
+    let keys = find_all(maze, |b| b.is_ascii_lowercase());
+    let pois: Vec<_> = robots.iter().chain(&keys).map(|(_, pos)| *pos).collect();
+
+    let mut tour = Tour::new(maze.poi_distances(&pois, |&b| b != b'#'));
+    for (_, start) in &robots {
+        let doors = doors_on_the_way(maze, *start);
+        for (k, (_, pos)) in keys.iter().enumerate() {
+            let Some(doors) = doors[maze.pos_to_idx(*pos)] else {
+                continue;
+            };
+            for (l, (key, _)) in keys.iter().enumerate() {
+                if doors & 1 << (key - b'a') != 0 {
+                    tour = tour.requires(robots.len() + k, robots.len() + l);
                 }
             }
-            neighbours
-        })
-    }
-}
-
-impl Index<(usize, usize)> for Grid {
-    type Output = u8;
-
-    fn index(&self, pos: (usize, usize)) -> &Self::Output {
-        &self.grid[self.pos_to_idx(pos)]
-    }
-}
-
-impl IndexMut<(usize, usize)> for Grid {
-    fn index_mut(&mut self, pos: (usize, usize)) -> &mut Self::Output {
-        let idx = self.pos_to_idx(pos);
-        &mut self.grid[idx]
-    }
-}
-
-impl Display for Grid {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        writeln!(f, "{}x{}", self.width, self.height)?;
-        for (idx, c) in self.grid.iter().enumerate() {
-            if idx > 1 && idx % self.width == 0 {
-                writeln!(f)?;
-            }
-            write!(f, "{}", *c as char)?;
         }
-        Ok(())
     }
+
+    let starts: Vec<usize> = (0..robots.len()).collect();
+    tour.shortest_with_agents(&starts).unwrap()
 }
 
 fn part_01(input: &str) -> usize {
-    Grid::from_str(input).collect_all::<1>().unwrap()
+    collect_all(&parse(input))
 }
 
 fn part_02(input: &str) -> usize {
-    let mut grid = Grid::from_str(input);
+    let mut maze = parse(input);
 
-    let Some(origin) = grid.origin() else { panic!("Origin no longer present?") };
+    let [(_, origin)] = find_all(&maze, |b| b == b'@')[..] else {
+        panic!("Origin no longer present?")
+    };
 
-    let neighbours: Vec<_> = grid.neighbours(origin).collect();
+    let neighbours: Vec<_> = maze.neighbours(origin).collect();
     for n in neighbours {
-        grid[n] = b'#';
+        maze[n] = b'#';
     }
-    grid[origin] = b'#';
+    maze[origin] = b'#';
 
     // add the new robots
-    grid[(origin.0 - 1, origin.1 - 1)] = b'1';
-    grid[(origin.0 + 1, origin.1 - 1)] = b'2';
-    grid[(origin.0 - 1, origin.1 + 1)] = b'3';
-    grid[(origin.0 + 1, origin.1 + 1)] = b'4';
+    maze[(origin.0 - 1, origin.1 - 1)] = b'@';
+    maze[(origin.0 + 1, origin.1 - 1)] = b'@';
+    maze[(origin.0 - 1, origin.1 + 1)] = b'@';
+    maze[(origin.0 + 1, origin.1 + 1)] = b'@';
 
-    grid.collect_all::<4>().unwrap()
+    collect_all(&maze)
 }
 
 fn main() {
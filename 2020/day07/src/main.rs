@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 use std::collections::HashSet;
 use regex::Regex;
+use aoc_utils::graph::Graph;
 
 const INPUT: &str = include_str!("input.txt");
 
@@ -31,77 +32,55 @@ fn parse(input: &str) -> Vec<Rule> {
         .collect()
 }
 
-fn part1(rule: &Vec<Rule>) -> usize {
-    // build a map of contained bag to container
-    // lookup shiny gold
-    // check all container to shiny gold to a set
-    // diff new set with current one
-    // for each diff, loop
-    // if empty diff, count set
-
-    let mut contained = HashMap::new();
-    for rule in rule {
-        for (_, bag) in &rule.contained {
-            contained.entry(bag.to_owned()).or_insert(Vec::new())
-                .push(&rule.container)
+// edges from container to contained bags, weighted by count
+fn build_graph(rules: &[Rule]) -> Graph<&str, usize> {
+    let mut graph = Graph::directed();
+    for rule in rules {
+        graph.node(&*rule.container);
+        for (count, bag) in &rule.contained {
+            graph.add_edge(&*rule.container, &**bag, *count);
         }
     }
+    graph
+}
 
+fn part1(rules: &[Rule]) -> usize {
+    // walk up the containers of shiny gold, and of their containers...
+    let graph = build_graph(rules);
     let mut containers = HashSet::new();
-    
-    let mut new_containers = HashSet::new();
-
-    for container in contained.get("shiny gold").unwrap() {
-        new_containers.insert(container);
-    }
-
-    while !new_containers.is_empty() {
-        containers = containers.union(&new_containers).cloned().collect();
-
-        let mut tmp = HashSet::new();
+    let mut new_containers = vec![graph.id("shiny gold").unwrap()];
 
-        new_containers.iter().for_each(|&bag| {
-            if let Some(v) = contained.get(*bag) {
-                for container in v {
-                    tmp.insert(container);
-                }
+    while let Some(bag) = new_containers.pop() {
+        for container in graph.predecessors(bag) {
+            if containers.insert(container) {
+                new_containers.push(container);
             }
-        });
-
-        new_containers = tmp.difference(&containers).cloned().collect();
+        }
     }
 
     containers.len()
 }
 
-fn part2(rules: &Vec<Rule>) -> usize {
-    let mut containers = HashMap::new();
-
-    for rule in rules {
-        containers.insert(&*rule.container, &rule.contained);
-    }
+fn part2(rules: &[Rule]) -> usize {
+    let graph = build_graph(rules);
 
     // memoize
     let mut bag_count = HashMap::new();
 
-    fn rec<'a>(target: &'a str, containers: &HashMap<&'a str, &'a Vec<(usize, String)>>, bag_count: &mut HashMap<&'a str, usize>) -> usize {
-        if let Some(u) = bag_count.get(target) {
+    fn rec(target: usize, graph: &Graph<&str, usize>, bag_count: &mut HashMap<usize, usize>) -> usize {
+        if let Some(u) = bag_count.get(&target) {
             *u
         } else {
-            let count = if let Some(v) = containers.get(target) {
-                v.iter().map(|(count, ref target)| {
-                    count * rec(target, containers, bag_count)
-                }).sum::<usize>() + 1
-            } else {
-                1
-            };
+            let count = graph.edges(target).map(|(target, count)| {
+                count * rec(target, graph, bag_count)
+            }).sum::<usize>() + 1;
             bag_count.insert(target, count);
             count
         }
     }
 
     // outer shiny bag does not count
-    rec("shiny gold", &containers, &mut bag_count) - 1
+    rec(graph.id("shiny gold").unwrap(), &graph, &mut bag_count) - 1
 }
 
 fn main() {
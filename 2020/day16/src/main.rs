@@ -1,7 +1,6 @@
-use std::{
-    collections::{BTreeSet, HashMap},
-    ops::RangeInclusive,
-};
+use std::{collections::HashMap, ops::RangeInclusive};
+
+use aoc_utils::assignment::Assignment;
 
 static INPUT: &str = include_str!("input.txt");
 
@@ -116,69 +115,27 @@ fn part_1(input: &str) -> u64 {
 fn part_2(input: &str) -> u64 {
     let input = Input::parse_str(input).cleanup();
 
-    let mut mappings = HashMap::new();
-
-    for idx in 0..input.my_ticket.len() {
-        let entry = mappings.entry(idx).or_insert(BTreeSet::new());
-        for f in &input.fields {
-            entry.insert(f.name);
-        }
-    }
-
-    for (idx, v) in input.my_ticket.iter().enumerate() {
-        for f in &input.fields {
-            if !f.is_compatible(v) {
-                mappings.get_mut(&idx).unwrap().remove(f.name);
-            }
-        }
-    }
+    let mut assignment = Assignment::new(
+        0..input.my_ticket.len(),
+        input.fields.iter().map(|f| f.name),
+    );
 
-    for t in &input.nearby_tickets {
+    for t in std::iter::once(&input.my_ticket).chain(&input.nearby_tickets) {
         for (idx, v) in t.iter().enumerate() {
             for f in &input.fields {
                 if !f.is_compatible(v) {
-                    mappings.get_mut(&idx).unwrap().remove(f.name);
-                }
-            }
-        }
-    }
-
-    loop {
-        if mappings.values().all(|v| v.len() == 1) {
-            break;
-        }
-
-        // look for singles, and remove them from other mappings
-
-        let singles: Vec<_> = mappings
-            .values()
-            .filter_map(|v| {
-                if v.len() == 1 {
-                    Some(*v.iter().next().unwrap())
-                } else {
-                    None
-                }
-            })
-            .collect();
-
-        let mut removed = false;
-
-        for s in singles {
-            for v in mappings.values_mut() {
-                if v.len() > 1 {
-                    v.remove(s);
-                    removed = true;
+                    assignment.remove(&idx, &f.name);
                 }
             }
         }
-
-        if !removed {
-            panic!("will need search: {mappings:?}");
-        }
     }
 
-    let mappings: HashMap<&str, usize> =
-        HashMap::from_iter(mappings.into_iter().map(|(k, v)| (*v.first().unwrap(), k)));
+    let mappings: HashMap<&str, usize> = assignment
+        .solve()
+        .expect("cannot map fields")
+        .into_iter()
+        .map(|(k, v)| (v, k))
+        .collect();
 
     mappings
         .into_iter()
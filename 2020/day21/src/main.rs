@@ -1,3 +1,4 @@
+use aoc_utils::assignment::Assignment;
 use itertools::Itertools;
 use std::collections::{BTreeMap, BTreeSet};
 
@@ -66,32 +67,11 @@ fn part_1(input: &str) -> usize {
 }
 
 fn part_2(input: &str) -> String {
-    let mut input = Input::parse_str(input);
-
-    let mut allergens = BTreeMap::new();
-
-    while !input.allergens.is_empty() {
-        let mut single = None;
-
-        for (&a, v) in &input.allergens {
-            if let Some(&first) = v.first() {
-                if v.len() == 1 {
-                    allergens.insert(a, first);
-                    single = Some(a);
-                    break;
-                }
-            }
-        }
+    let input = Input::parse_str(input);
 
-        if let Some((a, ai)) = single.and_then(|a| allergens.get(a).map(|ai| (a, ai))) {
-            input.allergens.remove(a);
-            for v in input.allergens.values_mut() {
-                v.retain(|i| i != ai);
-            }
-        } else {
-            panic!("no single! need to search");
-        }
-    }
+    let allergens = Assignment::from_candidates(input.allergens)
+        .solve()
+        .expect("cannot map allergens");
 
     allergens.values().join(",")
 }
@@ -22,7 +22,7 @@ impl TryFrom<u8> for Level {
 }
 
 fn parse_grid(input: &str) -> Result<Grid<Level, Taxicab>, Error> {
-    Grid::try_from(input)
+    Ok(Grid::try_from(input)?)
 }
 
 fn part_1(input: &str) -> Result<u64, Error> {
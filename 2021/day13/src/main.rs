@@ -1,3 +1,4 @@
+use aoc_utils::ocr::{self, OcrError};
 use itertools::{Itertools, MinMaxResult};
 use std::collections::BTreeSet;
 
@@ -68,13 +69,21 @@ fn part_1(input: &str) -> usize {
     folds[0].fold(&points).len()
 }
 
-fn part_2(input: &str) {
+fn fold_all(input: &str) -> BTreeSet<(isize, isize)> {
     let (mut points, folds) = parse_input(input);
 
     for fold in folds {
         points = fold.fold(&points);
     }
 
+    points
+}
+
+fn part_2(input: &str) -> Result<String, OcrError> {
+    ocr::read_points(fold_all(input).into_iter().map(|(x, y)| (x as i64, y as i64)))
+}
+
+fn draw(points: &BTreeSet<(isize, isize)>) {
     let (xmin, xmax) = match points.iter().map(|(x, _)| *x).minmax() {
         MinMaxResult::OneElement(x) => (x, x),
         MinMaxResult::MinMax(min, max) => (min, max),
@@ -86,8 +95,6 @@ fn part_2(input: &str) {
         MinMaxResult::NoElements => panic!("no points?"),
     };
 
-    println!("Part 2:");
-
     for y in ymin..=ymax {
         for x in xmin..=xmax {
             if points.contains(&(x, y)) {
@@ -103,12 +110,18 @@ fn part_2(input: &str) {
 
 fn main() {
     println!("Part 1: {}", part_1(INPUT));
-    part_2(INPUT);
+    match part_2(INPUT) {
+        Ok(code) => println!("Part 2: {}", code),
+        Err(e) => {
+            println!("Part 2: {}", e);
+            draw(&fold_all(INPUT));
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::part_1;
+    use crate::{fold_all, part_1, part_2};
 
     static TEST_INPUT: &str = r"6,10
 0,14
@@ -136,4 +149,43 @@ fold along x=5";
     fn test_part_1() {
         assert_eq!(17, part_1(TEST_INPUT));
     }
+
+    static CODE: &str = r"
+#### #     ###  ##  #  # #### ###   ###
+#    #      #  #  # # #  #    #  # #
+###  #      #  #    ##   ###  #  # #
+#    #      #  #    # #  #    ###   ##
+#    #      #  #  # # #  #    # #     #
+#    ####  ###  ##  #  # #### #  # ###";
+
+    // the dots of the code, scattered over the four quarters of the paper
+    // so that the two folds bring them back together
+    fn transparent_paper(code: &str) -> String {
+        let dots: Vec<String> = code
+            .lines()
+            .skip(1)
+            .enumerate()
+            .flat_map(|(y, l)| {
+                l.char_indices()
+                    .filter(|(_, c)| *c == '#')
+                    .map(move |(x, _)| (x, y))
+            })
+            .enumerate()
+            .map(|(i, (x, y))| {
+                let x = if i % 2 == 0 { x } else { 80 - x };
+                let y = if i % 3 == 0 { y } else { 12 - y };
+                format!("{},{}", x, y)
+            })
+            .collect();
+        format!("{}\n\nfold along x=40\nfold along y=6", dots.join("\n"))
+    }
+
+    #[test]
+    fn test_part_2() {
+        // the example folds into a square, which is not a letter
+        assert!(part_2(TEST_INPUT).is_err());
+        assert_eq!(16, fold_all(TEST_INPUT).len());
+
+        assert_eq!(Ok("FLICKERS".to_owned()), part_2(&transparent_paper(CODE)));
+    }
 }
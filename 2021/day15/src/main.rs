@@ -94,7 +94,7 @@ fn part_2(input: &str) -> Result<u64, Error> {
         .take(width * height + height)
         .collect();
     let init = String::from_utf8(init)?;
-    let mut large_grid: Grid<Risk, Taxicab> = Grid::try_from(init.as_ref())?;
+    let mut large_grid: Grid<Risk, Taxicab> = Grid::try_from(init.as_str())?;
 
     for my in 0..5 {
         for mx in 0..5 {
@@ -7,21 +7,22 @@
 //  if >= 12 in common, already identified those and everything else
 //  we have n^2 operations no matter what, this seems to go to the
 //  right answer faster
+//  (PointCloud::align in aoc_utils::vec3 does the search, each pair of
+//  beacons voting for a translation)
 
 static INPUT: &str = include_str!("input.txt");
 
-mod math;
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{HashMap, HashSet, VecDeque},
     ops::Index,
 };
 
-use math::Vec3D;
+use aoc_utils::vec3::{Alignment, PointCloud, Vec3};
 
 #[derive(Debug, Clone)]
 struct Scanner {
     id: usize,
-    beacons: Vec<Vec3D>,
+    beacons: Vec<Vec3>,
 }
 
 impl Scanner {
@@ -30,10 +31,12 @@ impl Scanner {
         let mut id = 0;
         for line in input.lines() {
             if let Some(i) = line.strip_prefix("--- scanner ") {
-                let Some(i) = i.strip_suffix(" ---") else { panic!("cannot parse id in {line}")};
+                let Some(i) = i.strip_suffix(" ---") else {
+                    panic!("cannot parse id in {line}")
+                };
                 id = i.parse().unwrap();
             } else {
-                beacons.push(Vec3D::parse(line));
+                beacons.push(line.parse().unwrap());
             }
         }
         Self { id, beacons }
@@ -67,83 +70,48 @@ impl Index<usize> for Problem {
 
 fn solve_problem(input: &str) -> (usize, i64) {
     let problem = Problem::parse(input);
-    let mut merged = HashSet::new();
-    let mut merged_beacons: HashMap<usize, (Vec3D, HashSet<Vec3D>)> = HashMap::new();
+    let clouds: Vec<_> = problem
+        .scanners
+        .iter()
+        .map(|s| PointCloud::new(s.beacons.clone()))
+        .collect();
 
-    let mut beacons: HashSet<Vec3D> = HashSet::new();
-
-    let mut scanners = Vec::new();
-    scanners.push(Vec3D::origin());
-
-    for beacon in &problem[0].beacons {
-        beacons.insert(*beacon);
-    }
-
-    let mut fingerprints = HashMap::new();
-
-    for s in &problem.scanners {
-        for b1 in &s.beacons {
-            for b2 in &s.beacons {
-                if b1 != b2 {
-                    fingerprints
-                        .entry(s.id)
-                        .or_insert(HashSet::new())
-                        .insert((b1 - b2).len());
-                }
-            }
-        }
-    }
-
-    merged.insert(problem[0].id);
-    merged_beacons.insert(
-        problem[0].id,
-        (
-            Vec3D::origin(),
-            HashSet::from_iter(problem[0].beacons.iter().cloned()),
-        ),
-    );
+    // alignment of each scanner into the frame of scanner 0
+    let mut aligned: HashMap<usize, Alignment> = HashMap::new();
+    aligned.insert(0, Alignment::identity());
 
     // optimised after checking reddit
     // had almost the right idea: distance between pairs of beacon
-    // instead of just differences
-    while merged.len() < problem.len() {
-        'search_loop: for s in problem.scanners.iter().filter(|s| !merged.contains(&s.id)) {
-            for (id, mb) in &merged_beacons {
-                // 66 = (12 * 11)/2 is the number of distances between any two among 12 different beacons
-                if fingerprints[id].intersection(&fingerprints[&s.id]).count() >= 66 {
-                    for r in math::ROTATIONS {
-                        let rotated_beacons: Vec<Vec3D> =
-                            Vec::from_iter(s.beacons.iter().map(|b| r * b));
-                        for b1 in &mb.1 {
-                            for b2 in &rotated_beacons {
-                                let s2 = b1 - b2;
-                                let s2_beacons: HashSet<Vec3D> =
-                                    HashSet::from_iter(rotated_beacons.iter().map(|b2| s2 + *b2));
-
-                                if mb.1.intersection(&s2_beacons).count() >= 12 {
-                                    merged.insert(s.id);
-                                    for b in &s2_beacons {
-                                        beacons.insert(mb.0 + *b);
-                                    }
-                                    scanners.push(mb.0 + s2);
-
-                                    merged_beacons.insert(s.id, (mb.0, s2_beacons));
-
-                                    break 'search_loop;
-                                }
-                            }
-                        }
-                    }
-                }
+    // instead of just differences (the fingerprints of PointCloud)
+    let mut queue = VecDeque::from([0]);
+    while let Some(known) = queue.pop_front() {
+        for s in 0..problem.len() {
+            if aligned.contains_key(&s) {
+                continue;
+            }
+            if let Some(a) = clouds[known].align(&clouds[s], 12) {
+                aligned.insert(s, aligned[&known].compose(&a));
+                queue.push_back(s);
             }
         }
     }
+    assert_eq!(
+        problem.len(),
+        aligned.len(),
+        "some scanners cannot be aligned"
+    );
+
+    let beacons: HashSet<Vec3> = aligned
+        .iter()
+        .flat_map(|(s, a)| problem[*s].beacons.iter().map(|b| a.apply(*b)))
+        .collect();
 
+    let scanners: Vec<Vec3> = aligned.values().map(|a| a.translation).collect();
     let mut distances = Vec::new();
 
     for s1 in &scanners {
         for s2 in &scanners {
-            distances.push((s1 - s2).len());
+            distances.push(s1.distance(s2));
         }
     }
 
@@ -159,8 +127,84 @@ fn main() {
 #[cfg(test)]
 mod tests {
     use crate::solve_problem;
+    use aoc_utils::vec3::{Vec3, ROTATIONS};
     use test_case::test_case;
 
+    #[test]
+    fn matrix_sanity_checks() {
+        assert!(ROTATIONS.iter().all(|m| m.determinant() == 1));
+    }
+
+    static TEST_VECTOR_ROTATIONS_DATA: &str = r"--- scanner 0 ---
+-1,-1,1
+-2,-2,2
+-3,-3,3
+-2,-3,1
+5,6,-4
+8,0,7
+
+--- scanner 0 ---
+1,-1,1
+2,-2,2
+3,-3,3
+2,-1,3
+-5,4,-6
+-8,-7,0
+
+--- scanner 0 ---
+-1,-1,-1
+-2,-2,-2
+-3,-3,-3
+-1,-3,-2
+4,6,5
+-7,0,8
+
+--- scanner 0 ---
+1,1,-1
+2,2,-2
+3,3,-3
+1,3,-2
+-4,-6,5
+7,0,8
+
+--- scanner 0 ---
+1,1,1
+2,2,2
+3,3,3
+3,1,2
+-6,-4,-5
+0,7,-8";
+
+    #[test]
+    fn test_vector_rotations() {
+        let samples: Vec<_> = TEST_VECTOR_ROTATIONS_DATA.split("\n\n").collect();
+        let samples: Vec<Vec<Vec3>> = samples
+            .into_iter()
+            .map(|s| {
+                s.lines()
+                    .filter_map(|l| {
+                        if l.starts_with("--") {
+                            None
+                        } else {
+                            Some(l.parse().unwrap())
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let reference = &samples[0];
+        for s in &samples[1..] {
+            if !ROTATIONS.iter().any(|r| {
+                s.iter()
+                    .enumerate()
+                    .all(|(idx, s)| *r * *s == reference[idx])
+            }) {
+                panic!("No suitable rotation found")
+            }
+        }
+    }
+
     static TEST_INPUT: &str = r"--- scanner 0 ---
 404,-588,-901
 528,-643,409
@@ -1,4 +1,5 @@
 use anyhow::{bail, Error};
+use aoc_utils::cuboid::{Cuboid, DisjointCuboids};
 use std::collections::HashSet;
 
 static INPUT: &str = include_str!("input.txt");
@@ -64,7 +65,7 @@ enum Switch {
 #[derive(Debug)]
 struct Step {
     switch: Switch,
-    bounding_box: BoundingBox,
+    bounding_box: Cuboid<3>,
 }
 
 impl TryFrom<&str> for Step {
@@ -77,7 +78,7 @@ impl TryFrom<&str> for Step {
             "off" => Switch::Off,
             _ => bail!("Invalid switch mode {}", parts[0]),
         };
-        let bounding_box = BoundingBox::try_from(parts[1])?;
+        let bounding_box = parse_box(parts[1])?;
 
         Ok(Step {
             switch,
@@ -98,187 +99,40 @@ impl TryFrom<&str> for Step {
 // the intersections
 //
 // In the end, just sum the volume of the "On" boxes
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
-struct Point(i64, i64, i64);
-
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
-struct BoundingBox {
-    min_point: Point,
-    max_point: Point,
-}
-
-impl BoundingBox {
-    fn volume(&self) -> i64 {
-        (self.max_point.0 - self.min_point.0)
-            * (self.max_point.1 - self.min_point.1)
-            * (self.max_point.2 - self.min_point.2)
-    }
-
-    fn intersection(&self, other: &Self) -> Option<Self> {
-        let x_min = self.min_point.0.max(other.min_point.0);
-        let y_min = self.min_point.1.max(other.min_point.1);
-        let z_min = self.min_point.2.max(other.min_point.2);
-
-        let x_max = self.max_point.0.min(other.max_point.0);
-        let y_max = self.max_point.1.min(other.max_point.1);
-        let z_max = self.max_point.2.min(other.max_point.2);
-
-        if x_min < x_max && y_min < y_max && z_min < z_max {
-            Some(BoundingBox {
-                min_point: Point(x_min, y_min, z_min),
-                max_point: Point(x_max, y_max, z_max),
-            })
-        } else {
-            None
-        }
+fn parse_box(value: &str) -> Result<Cuboid<3>, Error> {
+    fn parse_range(dims: &str) -> Result<(i64, i64), Error> {
+        let range = dims.split('=').last().unwrap();
+        let ends: Vec<_> = range.split("..").collect();
+        assert_eq!(2, ends.len());
+        Ok((ends[0].parse()?, ends[1].parse()?))
     }
-
-    fn intersects(&self, other: &Self) -> bool {
-        !(self.max_point.0 <= other.min_point.0
-            || other.max_point.0 <= self.min_point.0
-            || self.max_point.1 <= other.min_point.1
-            || other.max_point.1 <= self.min_point.1
-            || self.max_point.2 <= other.min_point.2
-            || other.max_point.2 <= self.min_point.2)
-    }
-
-    // assumes other is contained in self
-    fn cut_out(&self, other: &Self) -> Vec<Self> {
-        let mut parts = vec![];
-
-        let mut block = *self;
-
-        if block.min_point.0 != other.min_point.0 {
-            parts.push(BoundingBox {
-                max_point: Point(other.min_point.0, block.max_point.1, block.max_point.2),
-                ..block
-            });
-
-            block = BoundingBox {
-                min_point: Point(other.min_point.0, block.min_point.1, block.min_point.2),
-                ..block
-            };
-        }
-
-        if block.max_point.0 != other.max_point.0 {
-            parts.push(BoundingBox {
-                min_point: Point(other.max_point.0, block.min_point.1, block.min_point.2),
-                ..block
-            });
-
-            block = BoundingBox {
-                max_point: Point(other.max_point.0, block.max_point.1, block.max_point.2),
-                ..block
-            };
-        }
-
-        if block.min_point.1 != other.min_point.1 {
-            parts.push(BoundingBox {
-                max_point: Point(block.max_point.0, other.min_point.1, block.max_point.2),
-                ..block
-            });
-
-            block = BoundingBox {
-                min_point: Point(block.min_point.0, other.min_point.1, block.min_point.2),
-                ..block
-            }
-        }
-
-        if block.max_point.1 != other.max_point.1 {
-            parts.push(BoundingBox {
-                min_point: Point(block.min_point.0, other.max_point.1, block.min_point.2),
-                ..block
-            });
-
-            block = BoundingBox {
-                max_point: Point(block.max_point.0, other.max_point.1, block.max_point.2),
-                ..block
-            };
-        }
-
-        if block.min_point.2 != other.min_point.2 {
-            parts.push(BoundingBox {
-                max_point: Point(block.max_point.0, block.max_point.1, other.min_point.2),
-                ..block
-            });
-
-            block = BoundingBox {
-                min_point: Point(block.min_point.0, other.min_point.1, other.min_point.2),
-                ..block
-            }
-        }
-
-        if block.max_point.2 != other.max_point.2 {
-            parts.push(BoundingBox {
-                min_point: Point(block.min_point.0, block.min_point.1, other.max_point.2),
-                ..block
-            });
-
-            block = BoundingBox {
-                max_point: Point(block.max_point.0, block.max_point.1, other.max_point.2),
-                ..block
-            };
-        }
-
-        assert_eq!(&block, other);
-
-        assert_eq!(
-            parts.iter().map(|b| b.volume()).sum::<i64>() + block.volume(),
-            self.volume()
-        );
-
-        parts
-    }
-}
-
-impl TryFrom<&str> for BoundingBox {
-    type Error = Error;
-
-    fn try_from(value: &str) -> Result<Self, Self::Error> {
-        fn parse_range(dims: &str) -> Result<(i64, i64), Error> {
-            let range = dims.split('=').last().unwrap();
-            let ends: Vec<_> = range.split("..").collect();
-            assert_eq!(2, ends.len());
-            Ok((ends[0].parse()?, ends[1].parse()?))
-        }
-        let dims: Vec<_> = value.split(',').collect();
-        if dims.len() != 3 {
-            bail!("cannot parse {value} as Box");
-        }
-        let ((x_min, x_max), (y_min, y_max), (z_min, z_max)) = (
-            parse_range(dims[0])?,
-            parse_range(dims[1])?,
-            parse_range(dims[2])?,
-        );
-        Ok(BoundingBox {
-            min_point: Point(x_min, y_min, z_min),
-            max_point: Point(x_max + 1, y_max + 1, z_max + 1),
-        })
+    let dims: Vec<_> = value.split(',').collect();
+    if dims.len() != 3 {
+        bail!("cannot parse {value} as Box");
     }
+    let ((x_min, x_max), (y_min, y_max), (z_min, z_max)) = (
+        parse_range(dims[0])?,
+        parse_range(dims[1])?,
+        parse_range(dims[2])?,
+    );
+    Ok(Cuboid::from_inclusive(
+        [x_min, y_min, z_min],
+        [x_max, y_max, z_max],
+    ))
 }
 
 fn part_2(input: &str) -> i64 {
-    let mut blocks = HashSet::new();
+    let mut blocks = DisjointCuboids::new();
 
     for line in input.lines() {
         let step = Step::try_from(line).unwrap();
-        let tmp_blocks: Vec<_> = blocks
-            .iter()
-            .filter(|b| step.bounding_box.intersects(b))
-            .cloned()
-            .collect();
-        blocks.retain(|b| !step.bounding_box.intersects(b));
-        for block in tmp_blocks {
-            let i = step.bounding_box.intersection(&block).unwrap();
-            let remains = block.cut_out(&i);
-            blocks.extend(remains);
-        }
-        if step.switch == Switch::On {
-            blocks.insert(step.bounding_box);
+        match step.switch {
+            Switch::On => blocks.insert(step.bounding_box),
+            Switch::Off => blocks.remove(&step.bounding_box),
         }
     }
 
-    blocks.into_iter().map(|b| b.volume()).sum()
+    blocks.volume()
 }
 
 fn main() {
@@ -290,7 +144,7 @@ fn main() {
 mod tests {
     use test_case::test_case;
 
-    use crate::{part_1, part_2, BoundingBox};
+    use crate::{parse_box, part_1, part_2};
     static TEST_INPUT_SMALL: &str = r"on x=10..12,y=10..12,z=10..12
 on x=11..13,y=11..13,z=11..13
 off x=9..11,y=9..11,z=9..11
@@ -395,7 +249,7 @@ off x=-93533..-4276,y=-16170..68771,z=-104985..-24507";
             input
                 .lines()
                 .map(|l| l.split_whitespace().last().unwrap())
-                .map(|l| BoundingBox::try_from(l).unwrap()),
+                .map(|l| parse_box(l).unwrap()),
         );
         for b1 in &boxes {
             for b2 in &boxes {
@@ -1,8 +1,9 @@
 use std::fmt::Display;
 
 use anyhow::{bail, Error};
+use aoc_utils::bitgrid::BitGrid;
 use aoc_utils::grid::{Grid, Taxicab};
-#[derive(Eq, PartialEq)]
+#[derive(Copy, Clone, Eq, PartialEq)]
 enum Tile {
     East,
     South,
@@ -38,62 +39,47 @@ impl Display for Tile {
 type Map = Grid<Tile, Taxicab>;
 
 fn parse_map(input: &str) -> Result<Map, Error> {
-    Grid::try_from(input)
+    Ok(Grid::try_from(input)?)
 }
 
-fn move_east(map: &mut Map) -> bool {
-    let mut to_move = vec![];
-    for (idx, t) in map.iter().enumerate() {
-        if t == &Tile::East {
-            let from = map.idx_to_pos(idx);
-            let to = ((from.0 + 1) % map.width(), from.1);
-            if map[to] == Tile::Empty {
-                to_move.push((from, to));
-            }
-        }
-    }
-
-    let moved = !to_move.is_empty();
-
-    for (from, to) in to_move {
-        map[from] = Tile::Empty;
-        map[to] = Tile::East;
-    }
-
-    moved
+// the two herds on the sea floor, which wraps around
+struct Herds {
+    east: BitGrid,
+    south: BitGrid,
 }
 
-fn move_south(map: &mut Map) -> bool {
-    let mut to_move = vec![];
-    for (idx, t) in map.iter().enumerate() {
-        if t == &Tile::South {
-            let from = map.idx_to_pos(idx);
-            let to = (from.0, (from.1 + 1) % map.height());
-            if map[to] == Tile::Empty {
-                to_move.push((from, to));
-            }
+impl Herds {
+    fn new(map: &Map) -> Self {
+        Herds {
+            east: BitGrid::from_grid(map, |t| t == &Tile::East),
+            south: BitGrid::from_grid(map, |t| t == &Tile::South),
         }
     }
 
-    let moved = !to_move.is_empty();
-
-    for (from, to) in to_move {
-        map[from] = Tile::Empty;
-        map[to] = Tile::South;
+    // the cucumbers of herd facing an empty cell move into it
+    fn advance(herd: &mut BitGrid, other: &BitGrid, (dx, dy): (isize, isize)) -> bool {
+        let empty = !&(&*herd | other);
+        let moving = &*herd & &empty.wrapped(-dx, -dy);
+        if moving.is_empty() {
+            return false;
+        }
+        *herd ^= &moving;
+        *herd |= &moving.wrapped(dx, dy);
+        true
     }
 
-    moved
-}
-
-fn step(map: &mut Map) -> bool {
-    let moved = move_east(map);
-    move_south(map) || moved
+    // the east herd moves first, then the south one
+    fn step(&mut self) -> bool {
+        let moved = Self::advance(&mut self.east, &self.south, (1, 0));
+        Self::advance(&mut self.south, &self.east, (0, 1)) || moved
+    }
 }
 
 fn part_1(input: &str) -> usize {
-    let mut map = parse_map(input).unwrap();
+    let map = parse_map(input).unwrap();
+    let mut herds = Herds::new(&map);
     for s in 1.. {
-        let moved = step(&mut map);
+        let moved = herds.step();
         if !moved {
             return s;
         }
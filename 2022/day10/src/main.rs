@@ -1,5 +1,6 @@
 use std::fmt::Write;
 
+use aoc_utils::ocr;
 use color_eyre::Report;
 static INPUT: &str = include_str!("input.txt");
 
@@ -94,7 +95,11 @@ fn part_02(input: &str) -> color_eyre::Result<String> {
 fn main() -> color_eyre::Result<()> {
     color_eyre::install()?;
     println!("Part 1: {}", part_01(INPUT)?);
-    println!("Part 2: \n{}", part_02(INPUT)?);
+    let screen = part_02(INPUT)?;
+    match ocr::read_text(&screen) {
+        Ok(letters) => println!("Part 2: {letters}"),
+        Err(e) => println!("Part 2: {e}\n{screen}"),
+    }
     Ok(())
 }
 
@@ -285,5 +290,6 @@ noop
     #[test]
     fn real_part_2() {
         assert_eq!(OUTPUT_2, part_02(INPUT).unwrap());
+        assert_eq!(Ok("RKPJBPLA".to_owned()), ocr::read_text(OUTPUT_2));
     }
 }
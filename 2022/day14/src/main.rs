@@ -1,35 +1,22 @@
-use std::{collections::HashMap, fmt::Display, ops::Index};
+use std::fmt::Display;
 
-use itertools::{Itertools, MinMaxResult::MinMax};
+use aoc_utils::gravity::{Floor, Sand};
+use aoc_utils::render::{Cell, Frame, Rgb, Visualizer};
 
 static INPUT: &str = include_str!("input.txt");
 
-type Coord = i32;
-
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
-enum Cell {
-    Rock,
-    Sand,
-    Empty,
-}
-
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
-enum Outcome {
-    Settle((Coord, Coord)),
-    FallOff,
-    Blocked,
-}
+type Coord = i64;
 
 #[derive(Debug, Clone)]
 struct Cave {
-    cells: HashMap<(Coord, Coord), Cell>,
+    rocks: Vec<(Coord, Coord)>,
     depth: Coord,
-    floor: Option<Coord>,
+    sand: Sand,
 }
 
 impl Cave {
     fn parse(input: &str) -> Self {
-        let mut cells = HashMap::new();
+        let mut rocks = vec![];
         let mut depth = 0;
         input
             .lines()
@@ -38,7 +25,7 @@ impl Cave {
                 let pairs: Vec<(Coord, Coord)> = l
                     .split(" -> ")
                     .map(|p| {
-                        if let Some((x, y)) = p.split(',').tuples().next() {
+                        if let Some((x, y)) = p.split_once(',') {
                             (x.parse().unwrap(), y.parse().unwrap())
                         } else {
                             panic!("cannot parse pair: {}", p);
@@ -52,15 +39,11 @@ impl Cave {
                     if p1.0 == p2.0 {
                         let from = p1.1.min(p2.1);
                         let to = p1.1.max(p2.1);
-                        for y in from..=to {
-                            cells.insert((p1.0, y), Cell::Rock);
-                        }
+                        rocks.extend((from..=to).map(|y| (p1.0, y)));
                     } else if p1.1 == p2.1 {
                         let from = p1.0.min(p2.0);
                         let to = p1.0.max(p2.0);
-                        for x in from..=to {
-                            cells.insert((x, p1.1), Cell::Rock);
-                        }
+                        rocks.extend((from..=to).map(|x| (x, p1.1)));
                     } else {
                         panic!("need to handle diagonals!");
                     }
@@ -71,93 +54,89 @@ impl Cave {
             });
 
         Cave {
-            cells,
+            sand: Sand::new(rocks.iter().copied(), Floor::Abyss(depth)),
+            rocks,
             depth,
-            floor: None,
         }
     }
 
-    fn drop_sand_grain(&mut self, mut rock_pos: (Coord, Coord)) -> Outcome {
-        if *self.index(rock_pos) != Cell::Empty {
-            return Outcome::Blocked;
+    fn drop_sand(&mut self, visualizer: &mut Visualizer) -> usize {
+        if !visualizer.is_enabled() {
+            return self.sand.pour((500, 0));
         }
-        'drop_lower: while rock_pos.1 <= self.depth {
-            for delta in [(0, 1), (-1, 1), (1, 1)].into_iter() {
-                let new_pos = (rock_pos.0 + delta.0, rock_pos.1 + delta.1);
-                if *self.index(new_pos) == Cell::Empty {
-                    rock_pos = new_pos;
-                    continue 'drop_lower;
-                }
-            }
-            self.cells.insert(rock_pos, Cell::Sand);
-            return Outcome::Settle(rock_pos);
+        let (mut frame, x_min) = self.frame();
+        let mut grains = 0;
+        while let Some((x, y)) = self.sand.drop((500, 0)) {
+            grains += 1;
+            frame.draw(
+                ((x - x_min) as usize, y as usize),
+                Cell::colored('o', Rgb::YELLOW),
+            );
+            visualizer.show(&frame).expect("cannot show cave");
         }
-
-        Outcome::FallOff
+        grains
     }
 
-    fn drop_sand(&mut self) -> usize {
-        for grains in 0.. {
-            if let Outcome::Settle(_) = self.drop_sand_grain((500, 0)) {
-                continue;
+    // the rocks and the floor, wide enough for the sand to pile up to the
+    // source, with the leftmost x
+    fn frame(&self) -> (Frame, Coord) {
+        let height = self.depth + 3;
+        let x_min = self
+            .rocks
+            .iter()
+            .map(|r| r.0)
+            .fold(500 - height, Coord::min);
+        let x_max = self
+            .rocks
+            .iter()
+            .map(|r| r.0)
+            .fold(500 + height, Coord::max);
+        let mut frame = Frame::new(
+            (x_max - x_min + 1) as usize,
+            height as usize,
+            Cell::new(' '),
+        );
+        for y in 0..height {
+            for x in x_min..=x_max {
+                if self.sand.is_blocked((x, y)) {
+                    frame.draw(
+                        ((x - x_min) as usize, y as usize),
+                        Cell::colored('#', Rgb::GREY),
+                    );
+                }
             }
-            return grains;
         }
-        0
+        (frame, x_min)
     }
 
+    // starts over with a floor two below the lowest rock
     fn add_floor(&mut self) {
-        let y_max = self.cells.keys().map(|(_, y)| *y).max().unwrap();
-        self.floor = Some(y_max + 2);
-        self.depth = y_max + 3;
-    }
-}
-
-impl Index<(Coord, Coord)> for Cave {
-    type Output = Cell;
-
-    fn index(&self, index: (Coord, Coord)) -> &Self::Output {
-        if let Some(cell) = self.cells.get(&index) {
-            cell
-        } else if Some(index.1) == self.floor {
-            &Cell::Rock
-        } else {
-            &Cell::Empty
-        }
+        self.sand = Sand::new(self.rocks.iter().copied(), Floor::Solid(self.depth + 2));
     }
 }
 
 impl Display for Cave {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if let MinMax(x_min, x_max) = self.cells.keys().map(|(x, _)| *x).minmax() {
-            if let MinMax(_, y_max) = self.cells.keys().map(|(_, y)| *y).minmax() {
-                let y_max = y_max.max(self.floor.unwrap_or(0));
-                for y in 0..=y_max {
-                    for x in x_min..=x_max {
-                        match self.index((x, y)) {
-                            Cell::Rock => write!(f, "#")?,
-                            Cell::Sand => write!(f, "o")?,
-                            Cell::Empty => write!(f, ".")?,
-                        };
-                    }
-                    writeln!(f)?;
-                }
-            }
-        }
-        Ok(())
+        self.sand.fmt(f)
     }
 }
 
 fn main() {
+    let mut visualizer = Visualizer::from_args();
     let mut cave = Cave::parse(INPUT);
-    println!("Part 1: {}", cave.clone().drop_sand());
+    let part_1 = cave.clone().drop_sand(&mut visualizer);
     cave.add_floor();
 
-    println!("Part 2: {}", cave.drop_sand());
+    let part_2 = cave.drop_sand(&mut visualizer);
+    visualizer.finish().expect("cannot write animation");
+    println!("Part 1: {}", part_1);
+    println!("Part 2: {}", part_2);
 }
 
 #[cfg(test)]
 mod test {
+    use aoc_utils::render::Visualizer;
+
     use crate::{Cave, INPUT};
 
     static TEST_INPUT: &str = r"498,4 -> 498,6 -> 496,6
@@ -167,20 +146,29 @@ mod test {
     #[test]
     fn test_part_01() {
         let mut cave = Cave::parse(TEST_INPUT);
-        assert_eq!(24, cave.drop_sand());
+        assert_eq!(24, cave.drop_sand(&mut Visualizer::off()));
+    }
+
+    #[test]
+    fn test_frame() {
+        let mut cave = Cave::parse(TEST_INPUT);
+        cave.add_floor();
+        let (frame, x_min) = cave.frame();
+        assert_eq!((488, 25, 12), (x_min, frame.width(), frame.height()));
+        assert_eq!("#".repeat(25), frame.to_string().lines().last().unwrap());
     }
 
     #[test]
     fn real_part_01() {
         let mut cave = Cave::parse(INPUT);
-        assert_eq!(745, cave.drop_sand());
+        assert_eq!(745, cave.drop_sand(&mut Visualizer::off()));
     }
 
     #[test]
     fn test_part_02() {
         let mut cave = Cave::parse(TEST_INPUT);
         cave.add_floor();
-        let grains = cave.drop_sand();
+        let grains = cave.drop_sand(&mut Visualizer::off());
         println!("{}", cave);
         assert_eq!(93, grains);
     }
@@ -189,7 +177,7 @@ mod test {
     fn real_part_02() {
         let mut cave = Cave::parse(INPUT);
         cave.add_floor();
-        let grains = cave.drop_sand();
+        let grains = cave.drop_sand(&mut Visualizer::off());
         println!("{}", cave);
         assert_eq!(27551, grains);
     }
@@ -1,18 +1,14 @@
-use std::mem;
-
 // TODO:
 // rename Sensor; stupid name
 // use Sensor::range instead of sensor.sensor.dist(&sensor.beacon)
 
-use lazy_static::lazy_static;
-use regex::Regex;
+use aoc_utils::{
+    error::{final_parse, ParseError},
+    interval::IntervalSet,
+    parse::{line_integers, lines, spanned},
+};
 
 static INPUT: &str = include_str!("input.txt");
-lazy_static! {
-    static ref SENSOR_PARSER: Regex =
-        Regex::new(r"Sensor at x=(-?\d+), y=(-?\d+): closest beacon is at x=(-?\d+), y=(-?\d+)")
-            .unwrap();
-}
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 struct Coord(i32, i32);
@@ -42,103 +38,68 @@ struct Sensor {
 }
 
 impl Sensor {
-    fn parse(input: &str) -> Self {
-        let captures = SENSOR_PARSER.captures(input).unwrap();
-        Sensor {
-            sensor: Coord(
-                captures.get(1).unwrap().as_str().parse().unwrap(),
-                captures.get(2).unwrap().as_str().parse().unwrap(),
-            ),
-            beacon: Coord(
-                captures.get(3).unwrap().as_str().parse().unwrap(),
-                captures.get(4).unwrap().as_str().parse().unwrap(),
-            ),
-        }
-    }
-
     fn range(&self) -> u32 {
         self.sensor.dist(&self.beacon)
     }
 }
 
-#[derive(Debug)]
-struct Line(Vec<(i32, i32)>);
-
-impl Line {
-    fn new(limit: i32) -> Self {
-        Line(vec![(0, limit + 1)])
-    }
-
-    fn remove(&mut self, from: i32, to: i32) {
-        let orig = mem::take(&mut self.0);
-        self.0 = orig
-            .into_iter()
-            .filter_map(|(b, e)| {
-                if from <= b && e <= to {
-                    None
-                } else if to < b || e < from {
-                    Some(vec![(b, e)])
-                } else if from <= b && to >= b && to < e {
-                    Some(vec![(to, e)])
-                } else if b < from && from <= e && e <= to {
-                    Some(vec![(b, from)])
-                } else {
-                    Some(vec![(b, from), (to, e)])
-                }
-            })
-            .flatten()
-            .collect()
-    }
-
-    fn len(&self) -> usize {
-        self.0.iter().map(|(b, e)| (e - b) as usize).sum()
-    }
+// Sensor at x=2, y=18: closest beacon is at x=-2, y=15
+fn parse(input: &str) -> Result<Vec<Sensor>, ParseError> {
+    final_parse(input, lines(spanned(line_integers)))?
+        .into_iter()
+        .map(|line| {
+            let coords = line
+                .value
+                .iter()
+                .map(|n| i32::try_from(n.value).map_err(|_| n.error(input, "coordinate too large")))
+                .collect::<Result<Vec<_>, _>>()?;
+            match coords[..] {
+                [sx, sy, bx, by] => Ok(Sensor {
+                    sensor: Coord(sx, sy),
+                    beacon: Coord(bx, by),
+                }),
+                _ => Err(line.error(input, "expected the positions of a sensor and a beacon")),
+            }
+        })
+        .collect()
 }
 
 fn part_01(input: &str, row: i32) -> usize {
-    let sensors: Vec<_> = input
-        .lines()
-        .filter(|l| !l.trim().is_empty())
-        .map(Sensor::parse)
-        .collect();
+    let sensors = parse(input).unwrap();
 
-    let (from, to) = sensors
+    let covered: IntervalSet<i32> = sensors
         .iter()
         .map(|s| {
-            let dist = s.range();
-            s.sensor.ball_boundary_at(dist, row)
+            let (from, to) = s.sensor.ball_boundary_at(s.range(), row);
+            from..to
         })
-        .fold((i32::MAX, i32::MIN), |s, o| (s.0.min(o.0), s.1.max(o.1)));
-
-    let mut line = Line::new(to);
+        .collect();
 
-    for sensor in sensors {
-        let (from, to) = sensor.sensor.ball_boundary_at(sensor.range(), row);
-        if from < to {
-            line.remove(from, to);
-        }
-    }
+    // there's a beacon at every position of the row where one is known
+    let mut beacons: Vec<_> = sensors
+        .iter()
+        .filter(|s| s.beacon.1 == row && covered.contains(s.beacon.0))
+        .map(|s| s.beacon.0)
+        .collect();
+    beacons.sort();
+    beacons.dedup();
 
-    (to - line.len() as i32 - from) as usize
+    covered.len() as usize - beacons.len()
 }
 
 fn part_02(input: &str, limit: i32) -> u64 {
-    let sensors: Vec<_> = input
-        .lines()
-        .filter(|l| !l.trim().is_empty())
-        .map(Sensor::parse)
-        .collect();
+    let sensors = parse(input).unwrap();
     for row in 0..=limit {
-        let mut line = Line::new(limit);
-        for sensor in &sensors {
-            let (from, to) = sensor.sensor.ball_boundary_at(sensor.range(), row);
-            if from < to {
-                line.remove(from, to);
-            }
-        }
-        if line.len() == 1 {
-            let (b, _) = line.0[0];
-            return b as u64 * 4000000 + row as u64;
+        let covered: IntervalSet<i32> = sensors
+            .iter()
+            .map(|s| {
+                let (from, to) = s.sensor.ball_boundary_at(s.range(), row);
+                from..to
+            })
+            .collect();
+        let gap = covered.gaps(0..=limit).next();
+        if let Some(gap) = gap {
+            return *gap.start() as u64 * 4000000 + row as u64;
         }
     }
     0
@@ -151,7 +112,7 @@ fn main() {
 
 #[cfg(test)]
 mod test {
-    use crate::{part_01, part_02, INPUT};
+    use crate::{parse, part_01, part_02, INPUT};
 
     static TEST_INPUT: &str = r"Sensor at x=2, y=18: closest beacon is at x=-2, y=15
 Sensor at x=9, y=16: closest beacon is at x=10, y=16
@@ -179,6 +140,16 @@ Sensor at x=20, y=1: closest beacon is at x=15, y=3
         assert_eq!(56000011, part_02(TEST_INPUT, 20));
     }
 
+    #[test]
+    fn test_parse_errors() {
+        let err = parse("Sensor at x=2, y=18: closest beacon is at x=-2\n").unwrap_err();
+        assert_eq!((1, 1), (err.line, err.column));
+        let err =
+            parse("Sensor at x=2, y=18: closest beacon is at x=-2, y=9999999999").unwrap_err();
+        assert_eq!((1, 51), (err.line, err.column));
+        assert_eq!("coordinate too large", err.message());
+    }
+
     #[test]
     fn real_part_01() {
         assert_eq!(4985193, part_01(INPUT, 2000000));
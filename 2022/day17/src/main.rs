@@ -1,7 +1,11 @@
-use std::collections::HashSet;
+use aoc_utils::gravity::{Chamber, Shape};
+use aoc_utils::render::{Cell, Frame, Rgb, Visualizer};
+use aoc_utils::sequence::PeriodicDifferences;
 
 static INPUT: &str = include_str!("input.txt");
 const WIDTH: usize = 7;
+// rows of the top of the tower shown
+const WINDOW: usize = 40;
 
 #[derive(Debug)]
 enum Jet {
@@ -9,72 +13,26 @@ enum Jet {
     Right,
 }
 
-struct Shape {
-    width: usize,
-    height: usize,
-    shape: Vec<(usize, usize)>,
-}
-
-impl Shape {
-    fn is_blocked(&self, pos: (usize, usize), blocks: &HashSet<(usize, usize)>) -> bool {
-        for block in &self.shape {
-            let block = (block.0 + pos.0, pos.1 - block.1);
-            if blocks.contains(&block) {
-                return true;
-            }
-
-            // that's the floor
-            if block.1 == 0 {
-                return true;
-            }
-        }
-
-        false
-    }
-
-    fn settle(
-        &self,
-        pos: (usize, usize),
-        blocks: &mut HashSet<(usize, usize)>,
-        mut max_height: usize,
-    ) -> usize {
-        for block in &self.shape {
-            let block = (block.0 + pos.0, pos.1 - block.1);
-            blocks.insert(block);
-            max_height = max_height.max(block.1);
+impl Jet {
+    fn push(&self) -> i64 {
+        match self {
+            Jet::Left => -1,
+            Jet::Right => 1,
         }
-        max_height
     }
 }
 
 fn get_shapes() -> Vec<Shape> {
-    vec![
-        Shape {
-            width: 4,
-            height: 1,
-            shape: vec![(0, 0), (1, 0), (2, 0), (3, 0)],
-        },
-        Shape {
-            width: 3,
-            height: 3,
-            shape: vec![(1, 2), (0, 1), (1, 1), (2, 1), (1, 0)],
-        },
-        Shape {
-            width: 3,
-            height: 3,
-            shape: vec![(0, 2), (1, 2), (2, 2), (2, 1), (2, 0)],
-        },
-        Shape {
-            width: 1,
-            height: 4,
-            shape: vec![(0, 3), (0, 2), (0, 1), (0, 0)],
-        },
-        Shape {
-            width: 2,
-            height: 2,
-            shape: vec![(0, 1), (1, 1), (0, 0), (1, 0)],
-        },
+    [
+        "####",
+        ".#.\n###\n.#.",
+        "..#\n..#\n###",
+        "#\n#\n#\n#",
+        "##\n##",
     ]
+    .into_iter()
+    .map(Shape::parse)
+    .collect()
 }
 
 fn parse(input: &str) -> Vec<Jet> {
@@ -88,150 +46,136 @@ fn parse(input: &str) -> Vec<Jet> {
         .collect()
 }
 
-fn dump(max_height: usize, blocks: &HashSet<(usize, usize)>) {
-    for row in (1..max_height + 1).rev() {
-        print!("|");
-        for col in 0..WIDTH {
-            if blocks.contains(&(col, row)) {
-                print!("#");
-            } else {
-                print!(".");
+// the top WINDOW rows of the tower between the walls, and the floor once
+// it is in sight
+fn frame(chamber: &Chamber) -> Frame {
+    let mut frame = Frame::new(WIDTH + 2, WINDOW + 1, Cell::new(' '));
+    let top = chamber.height().max(WINDOW);
+    for line in 0..WINDOW {
+        let y = top - 1 - line;
+        frame.draw((0, line), Cell::colored('|', Rgb::GREY));
+        frame.draw((WIDTH + 1, line), Cell::colored('|', Rgb::GREY));
+        if let Some(row) = chamber.rows().get(y) {
+            for col in (0..WIDTH).filter(|col| row & 1 << col != 0) {
+                frame.draw((col + 1, line), Cell::colored('#', Rgb::YELLOW));
             }
         }
-        println!("|");
     }
-    println!("+-------+");
+    if top == WINDOW {
+        for col in 0..WIDTH + 2 {
+            let c = if col == 0 || col == WIDTH + 1 {
+                '+'
+            } else {
+                '-'
+            };
+            frame.draw((col, WINDOW), Cell::colored(c, Rgb::GREY));
+        }
+    }
+    frame
 }
 
-fn drop_blocks(input: &str, max_blocks: usize) -> usize {
-    let mut blocks = HashSet::new();
-
+// the height of the tower after each rock, from 0 rocks on
+fn tower_heights(input: &str, rocks: usize, visualizer: &mut Visualizer) -> Vec<usize> {
     let jets = parse(input);
     let shapes = get_shapes();
-
-    let mut max_height = 0;
-
-    let mut prev_max_height = 0;
-    let mut height_delta = 0;
-
-    // ok, let's document the thinking here before I forget
-    // the basic idea is to look for stable increments over
-    // long enough strides. The first stride to look at is
-    // over individual jets, to see whether the number of
-    // rock dropped becomes stable. The stride len was
-    // chosen as jets.len() * shapes.len() on the assumption
-    // that this would be compatible with repeated patterns.
-    // Once we know how many rocks drop over this stride,
-    // we check whether there is a stable increase in height
-    // over that many rocks dropped.
-    //
-    // the check is rather crude and will pretend the increase
-    // is stable when it matches the previous measure...
-    // presumably we could be a bit more thorough and keep the
-    // N previous values to confirm we have a stable increase,
-    // but 1 here seems to work for test and real data
-    //
-    // ugly, but fast
-    let round_len = jets.len() * shapes.len();
-
-    let round_modulo = max_blocks % round_len;
-    let mut prev_block = 0;
-    let mut block_delta = 0;
-
-    let mut shortcut = false;
-    let mut block_start = 0;
-
-    let mut jet = 0;
-    for rock in 0..max_blocks {
-        let shape = &shapes[rock % shapes.len()];
-        let mut pos: (usize, usize) = (2, max_height + 3 + shape.height);
-        if shortcut && (rock - block_start) % block_delta == 0 {
-            if height_delta == max_height - prev_max_height {
-                return max_height + height_delta * ((max_blocks - rock) / block_delta);
-            }
-            height_delta = max_height - prev_max_height;
-            prev_max_height = max_height;
+    let mut chamber = Chamber::new(WIDTH);
+    let mut heights = vec![0];
+
+    let mut jets = jets.iter().cycle();
+    for shape in shapes.iter().cycle().take(rocks) {
+        let start = (2, chamber.height() + 3);
+        chamber.drop(shape, start, || jets.next().unwrap().push());
+        heights.push(chamber.height());
+        if visualizer.is_enabled() {
+            visualizer
+                .show(&frame(&chamber))
+                .expect("cannot show chamber");
         }
-        loop {
-            if jet > round_modulo && (jet - round_modulo) % round_len == 0 {
-                if block_delta == rock - prev_block {
-                    shortcut = true;
-                    block_start = max_blocks % block_delta;
-                }
-                block_delta = rock - prev_block;
-                prev_block = rock;
-            }
-
-            // push
-            let new_pos = match jets[jet % jets.len()] {
-                Jet::Left => (pos.0.saturating_sub(1), pos.1),
-                Jet::Right => {
-                    if pos.0 + shape.width < WIDTH {
-                        (pos.0 + 1, pos.1)
-                    } else {
-                        pos
-                    }
-                }
-            };
-
-            if !shape.is_blocked(new_pos, &blocks) {
-                pos = new_pos;
-            }
-            jet += 1;
-
-            let new_pos = (pos.0, pos.1 - 1);
+    }
 
-            if shape.is_blocked(new_pos, &blocks) {
-                max_height = shape.settle(pos, &mut blocks, max_height);
-                break;
-            }
+    heights
+}
 
-            pos = new_pos;
+// Once the tower is high enough, the same sequence of rocks and jets
+// keeps adding the same heights, so the increments of the height are
+// eventually periodic. The cycle must cover at least half the simulated
+// rocks to be trusted, so the simulation is doubled until one does
+fn drop_blocks(input: &str, max_blocks: usize, visualizer: &mut Visualizer) -> usize {
+    let mut rocks = 1000;
+    let mut off = Visualizer::off();
+    let mut visualizer = visualizer;
+    loop {
+        let heights: Vec<i128> = tower_heights(input, max_blocks.min(rocks), visualizer)
+            .into_iter()
+            .map(|h| h as i128)
+            .collect();
+        if max_blocks < heights.len() {
+            return heights[max_blocks] as usize;
         }
+        if let Some(periodic) = PeriodicDifferences::find(&heights) {
+            return periodic.value_at(max_blocks) as usize;
+        }
+        rocks *= 2;
+        // only the first simulation is shown, the longer ones start over
+        visualizer = &mut off;
     }
-
-    max_height
 }
 
-fn part_01(input: &str) -> usize {
-    drop_blocks(input, 2022)
+fn part_01(input: &str, visualizer: &mut Visualizer) -> usize {
+    drop_blocks(input, 2022, visualizer)
 }
 
-fn part_02(input: &str) -> usize {
-    drop_blocks(input, 1000000000000)
-    //drop_blocks(input, 1000000)
+fn part_02(input: &str, visualizer: &mut Visualizer) -> usize {
+    drop_blocks(input, 1000000000000, visualizer)
 }
 
 fn main() {
-    println!("Part 1: {}", part_01(INPUT));
-    println!("Part 2: {}", part_02(INPUT));
+    let mut visualizer = Visualizer::from_args();
+    let part_1 = part_01(INPUT, &mut visualizer);
+    let part_2 = part_02(INPUT, &mut visualizer);
+    visualizer.finish().expect("cannot write animation");
+    println!("Part 1: {}", part_1);
+    println!("Part 2: {}", part_2);
 }
 
 #[cfg(test)]
 mod test {
-    use crate::{part_01, part_02, INPUT};
+    use aoc_utils::gravity::Chamber;
+    use aoc_utils::render::Visualizer;
+
+    use crate::{frame, get_shapes, part_01, part_02, INPUT, WIDTH, WINDOW};
 
     static TEST_INPUT: &str = r">>><<><>><<<>><>>><<<>>><<<><<<>><>><<>>";
 
     #[test]
     fn test_part_01() {
-        assert_eq!(3068, part_01(TEST_INPUT));
+        assert_eq!(3068, part_01(TEST_INPUT, &mut Visualizer::off()));
         //assert!(false);
     }
 
     #[test]
     fn test_part_02() {
-        assert_eq!(1514285714288, part_02(TEST_INPUT));
+        assert_eq!(1514285714288, part_02(TEST_INPUT, &mut Visualizer::off()));
+    }
+
+    #[test]
+    fn test_frame() {
+        let mut chamber = Chamber::new(WIDTH);
+        chamber.drop(&get_shapes()[0], (2, 3), || 1);
+        let text = frame(&chamber).to_string();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(WINDOW + 1, lines.len());
+        assert_eq!(["|   ####|", "+-------+"], lines[WINDOW - 1..]);
     }
 
     #[test]
     fn real_part_01() {
-        assert_eq!(3224, part_01(INPUT));
+        assert_eq!(3224, part_01(INPUT, &mut Visualizer::off()));
         //assert!(false);
     }
 
     #[test]
     fn real_part_02() {
-        assert_eq!(1595988538691, part_02(INPUT));
+        assert_eq!(1595988538691, part_02(INPUT, &mut Visualizer::off()));
     }
 }
@@ -1,33 +1,16 @@
 use std::collections::HashSet;
 
-use aoc_utils::union_find::UnionFind;
-use itertools::Itertools;
+use aoc_utils::{union_find::UnionFind, vec3::Vec3};
 
 static INPUT: &str = include_str!("input.txt");
 
-type Coord = (i32, i32, i32);
-
-fn neighbours(pos: Coord) -> impl Iterator<Item = Coord> {
-    [
-        (-1, 0, 0),
-        (1, 0, 0),
-        (0, -1, 0),
-        (0, 1, 0),
-        (0, 0, -1),
-        (0, 0, 1),
-    ]
-    .into_iter()
-    .map(move |d| (d.0 + pos.0, d.1 + pos.1, d.2 + pos.2))
-}
+type Coord = Vec3<i32>;
 
 fn parse_input(input: &str) -> Vec<Coord> {
     input
         .lines()
         .filter(|l| !l.trim().is_empty())
-        .map(|l| {
-            let (x, y, z) = l.split(',').tuples().next().unwrap();
-            (x.parse().unwrap(), y.parse().unwrap(), z.parse().unwrap())
-        })
+        .map(|l| l.parse().unwrap())
         .collect()
 }
 
@@ -35,7 +18,7 @@ fn part_01(grid: &[Coord]) -> usize {
     let model: HashSet<Coord> = HashSet::from_iter(grid.iter().cloned());
     let mut faces = 0;
     for pos in &model {
-        for n in neighbours(*pos) {
+        for n in pos.neighbours() {
             if !model.contains(&n) {
                 faces += 1;
             }
@@ -45,25 +28,28 @@ fn part_01(grid: &[Coord]) -> usize {
 }
 
 fn dim(grid: &[Coord]) -> (Coord, Coord) {
-    let min_x = grid.iter().map(|p| p.0).min().unwrap();
-    let max_x = grid.iter().map(|p| p.0).max().unwrap();
-    let min_y = grid.iter().map(|p| p.1).min().unwrap();
-    let max_y = grid.iter().map(|p| p.1).max().unwrap();
-    let min_z = grid.iter().map(|p| p.2).min().unwrap();
-    let max_z = grid.iter().map(|p| p.2).max().unwrap();
-    ((min_x, min_y, min_z), (max_x, max_y, max_z))
+    let min_x = grid.iter().map(|p| p.x).min().unwrap();
+    let max_x = grid.iter().map(|p| p.x).max().unwrap();
+    let min_y = grid.iter().map(|p| p.y).min().unwrap();
+    let max_y = grid.iter().map(|p| p.y).max().unwrap();
+    let min_z = grid.iter().map(|p| p.z).min().unwrap();
+    let max_z = grid.iter().map(|p| p.z).max().unwrap();
+    (
+        Vec3::new(min_x, min_y, min_z),
+        Vec3::new(max_x, max_y, max_z),
+    )
 }
 
 struct Id {
-    base: (i32, i32, i32),
+    base: Coord,
     dim: (usize, usize, usize),
 }
 
 impl Id {
     fn id(&self, pos: Coord, face: usize) -> usize {
-        let x = (pos.0 - self.base.0) as usize;
-        let y = (pos.1 - self.base.1) as usize;
-        let z = (pos.2 - self.base.2) as usize;
+        let x = (pos.x - self.base.x) as usize;
+        let y = (pos.y - self.base.y) as usize;
+        let z = (pos.z - self.base.z) as usize;
         x + self.dim.0 * (y + self.dim.1 * (z + self.dim.2 * face))
     }
 }
@@ -72,13 +58,15 @@ fn part_02(grid: &[Coord]) -> usize {
     let model: HashSet<Coord> = HashSet::from_iter(grid.iter().cloned());
     let mut faces = 0;
 
-    let ((x_min, y_min, z_min), (x_max, y_max, z_max)) = dim(grid);
+    let (min, max) = dim(grid);
+    let (x_min, y_min, z_min) = (min.x, min.y, min.z);
+    let (x_max, y_max, z_max) = (max.x, max.y, max.z);
 
     let x_len = (x_max - x_min + 4) as usize;
     let y_len = (y_max - y_min + 4) as usize;
     let z_len = (z_max - z_min + 4) as usize;
     let id = Id {
-        base: (x_min - 2, y_min - 2, z_min - 2),
+        base: Vec3::new(x_min - 2, y_min - 2, z_min - 2),
         dim: (x_len, y_len, z_len),
     };
 
@@ -92,15 +80,15 @@ fn part_02(grid: &[Coord]) -> usize {
     for x in x_min - 1..=x_max + 1 {
         for y in y_min - 1..=y_max + 1 {
             for z in z_min - 1..=z_max + 1 {
-                let pos = (x, y, z);
+                let pos = Vec3::new(x, y, z);
                 if model.contains(&pos) {
-                    for (face, n) in neighbours(pos).enumerate() {
+                    for (face, n) in pos.neighbours().enumerate() {
                         if !model.contains(&n) {
                             uf.join(id.id(pos, face), id.id(n, 0));
                         }
                     }
                 } else {
-                    for n in neighbours(pos) {
+                    for n in pos.neighbours() {
                         if !model.contains(&n) {
                             uf.join(id.id(pos, 0), id.id(n, 0));
                         }
@@ -109,7 +97,7 @@ fn part_02(grid: &[Coord]) -> usize {
             }
         }
     }
-    let root_leader = uf.leader(id.id((x_min - 1, y_min - 1, z_min - 1), 0));
+    let root_leader = uf.leader(id.id(Vec3::new(x_min - 1, y_min - 1, z_min - 1), 0));
 
     for face in 0..6 {
         for pos in &model {
@@ -1,11 +1,13 @@
-use itertools::Itertools;
-use itertools::MinMaxResult;
-use std::collections::BTreeMap;
-use std::collections::BTreeSet;
+use aoc_utils::automaton::Neighbourhood;
+use aoc_utils::bitgrid::BitGrid;
 use std::fmt::Display;
 
 static INPUT: &str = include_str!("input.txt");
 
+// the room left around the elves when they reach the edge of the grid
+const MARGIN: usize = 16;
+
+#[derive(Copy, Clone)]
 enum Directions {
     North,
     South,
@@ -21,59 +23,45 @@ static DIRECTIONS: [Directions; 4] = [
 ];
 
 impl Directions {
-    fn adjacent_positions(&self, pos: &(isize, isize)) -> [(isize, isize); 3] {
+    fn delta(&self) -> (isize, isize) {
         match self {
-            Directions::North => [
-                (pos.0 - 1, pos.1 - 1),
-                (pos.0, pos.1 - 1),
-                (pos.0 + 1, pos.1 - 1),
-            ],
-            Directions::South => [
-                (pos.0 - 1, pos.1 + 1),
-                (pos.0, pos.1 + 1),
-                (pos.0 + 1, pos.1 + 1),
-            ],
-            Directions::West => [
-                (pos.0 - 1, pos.1 - 1),
-                (pos.0 - 1, pos.1),
-                (pos.0 - 1, pos.1 + 1),
-            ],
-            Directions::East => [
-                (pos.0 + 1, pos.1 - 1),
-                (pos.0 + 1, pos.1),
-                (pos.0 + 1, pos.1 + 1),
-            ],
+            Directions::North => (0, -1),
+            Directions::South => (0, 1),
+            Directions::West => (-1, 0),
+            Directions::East => (1, 0),
+        }
+    }
+
+    // the three cells to look at before moving in this direction
+    fn adjacent_deltas(&self) -> [(isize, isize); 3] {
+        let (dx, dy) = self.delta();
+        if dx == 0 {
+            [(-1, dy), (0, dy), (1, dy)]
+        } else {
+            [(dx, -1), (dx, 0), (dx, 1)]
         }
     }
-}
 
-fn pos_all_around(pos: &(isize, isize)) -> [(isize, isize); 12] {
-    [
-        (pos.0 - 1, pos.1 - 1),
-        (pos.0, pos.1 - 1),
-        (pos.0 + 1, pos.1 - 1),
-        (pos.0 - 1, pos.1 + 1),
-        (pos.0, pos.1 + 1),
-        (pos.0 + 1, pos.1 + 1),
-        (pos.0 - 1, pos.1 - 1),
-        (pos.0 - 1, pos.1),
-        (pos.0 - 1, pos.1 + 1),
-        (pos.0 + 1, pos.1 - 1),
-        (pos.0 + 1, pos.1),
-        (pos.0 + 1, pos.1 + 1),
-    ]
+    fn opposite(&self) -> Self {
+        match self {
+            Directions::North => Directions::South,
+            Directions::South => Directions::North,
+            Directions::West => Directions::East,
+            Directions::East => Directions::West,
+        }
+    }
 }
 
 struct Elves {
-    elves: BTreeSet<(isize, isize)>,
+    elves: BitGrid,
 }
 
 impl Display for Elves {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let (cols, rows) = self.boundaries();
-        for r in rows.0..=rows.1 {
-            for c in cols.0..=cols.1 {
-                if self.elves.contains(&(c, r)) {
+        let ((x0, y0), (x1, y1)) = self.boundaries();
+        for r in y0..=y1 {
+            for c in x0..=x1 {
+                if self.elves.get((c, r)) {
                     write!(f, "#")?;
                 } else {
                     write!(f, ".")?;
@@ -87,46 +75,64 @@ impl Display for Elves {
 
 impl Elves {
     fn parse(input: &str) -> Self {
-        let mut elves = BTreeSet::new();
-        input.lines().enumerate().for_each(|(row, line)| {
+        let lines: Vec<&str> = input.lines().filter(|l| !l.is_empty()).collect();
+        let width = lines.iter().map(|l| l.len()).max().unwrap_or(0);
+        let mut elves = BitGrid::new(width, lines.len());
+        lines.iter().enumerate().for_each(|(row, line)| {
             line.bytes().enumerate().for_each(|(col, b)| {
                 if b == b'#' {
-                    elves.insert((col as isize, row as isize));
+                    elves.insert((col, row));
                 }
             })
         });
         Elves { elves }
     }
 
+    // makes room when elves are on the edge of the grid
+    fn make_room(&mut self) {
+        let (width, height) = (self.elves.width(), self.elves.height());
+        let mut edges = BitGrid::row_mask(width, height, 0);
+        edges |= &BitGrid::row_mask(width, height, height - 1);
+        edges |= &BitGrid::column_mask(width, height, 0);
+        edges |= &BitGrid::column_mask(width, height, width - 1);
+        if !(&self.elves & &edges).is_empty() {
+            self.elves = self.elves.expanded(MARGIN);
+        }
+    }
+
+    // All the elves move at once: each elf with another around checks
+    // the directions in turn, and proposes to move in the first direction
+    // free of elves. Only two elves facing each other two cells apart can
+    // propose the same cell, and then neither moves
     fn round(&mut self, round: usize) -> bool {
-        let mut proposed: BTreeMap<(isize, isize), Vec<(isize, isize)>> = BTreeMap::new();
-
-        'elves: for pos in &self.elves {
-            let around = pos_all_around(pos);
-
-            if around.iter().any(|t| self.elves.contains(t)) {
-                for dir in 0..4 {
-                    let base = (round + dir) % 4;
-                    let targets = &around[base * 3..(base + 1) * 3];
-                    if targets.iter().all(|t| !self.elves.contains(t)) {
-                        proposed.entry(targets[1]).or_default().push(*pos);
-                        continue 'elves;
-                    }
-                }
+        self.make_room();
+        let elves = &self.elves;
+        // the cells with an elf at (dx, dy)
+        let elf_at = |(dx, dy): (isize, isize)| elves.shifted(-dx, -dy);
+
+        let mut undecided = elves & &elves.neighbour_counts(Neighbourhood::Moore).any();
+        let mut proposed: Vec<BitGrid> = vec![BitGrid::new(elves.width(), elves.height()); 4];
+        for dir in 0..4 {
+            let base = (round + dir) % 4;
+            let mut blocked = BitGrid::new(elves.width(), elves.height());
+            for delta in DIRECTIONS[base].adjacent_deltas() {
+                blocked |= &elf_at(delta);
             }
-
-            // stay in place
-            proposed.entry(*pos).or_default().push(*pos);
+            let free = &undecided & &!&blocked;
+            undecided ^= &free;
+            let (dx, dy) = DIRECTIONS[base].delta();
+            proposed[base] = free.shifted(dx, dy);
         }
 
-        let mut new_elves = BTreeSet::new();
-
-        for (k, v) in proposed {
-            if v.len() == 1 {
-                new_elves.insert(k);
-            } else {
-                new_elves.extend(v.into_iter());
-            }
+        // the elves alone on their target move there
+        let mut new_elves = elves.clone();
+        for dir in DIRECTIONS {
+            let targets = &proposed[dir as usize];
+            let opposite = &proposed[dir.opposite() as usize];
+            let moved = targets & &!opposite;
+            let (dx, dy) = dir.delta();
+            new_elves ^= &moved.shifted(-dx, -dy);
+            new_elves |= &moved;
         }
 
         let has_moved = self.elves != new_elves;
@@ -134,40 +140,25 @@ impl Elves {
         has_moved
     }
 
-    fn len(&self) -> isize {
-        self.elves.len() as isize
+    fn len(&self) -> usize {
+        self.elves.count()
     }
 
-    fn dimensions(&self) -> (isize, isize) {
-        let (cols, rows) = self.boundaries();
-        ((cols.1 - cols.0 + 1), (rows.1 - rows.0 + 1))
+    fn dimensions(&self) -> (usize, usize) {
+        let ((x0, y0), (x1, y1)) = self.boundaries();
+        ((x1 - x0 + 1), (y1 - y0 + 1))
     }
 
-    fn boundaries(&self) -> ((isize, isize), (isize, isize)) {
-        let cols = self.elves.iter().map(|(c, _)| *c).minmax();
-        let rows = self.elves.iter().map(|(_, r)| *r).minmax();
-
-        let cols = match cols {
-            MinMaxResult::NoElements => panic!("no elves?"),
-            MinMaxResult::OneElement(c) => (c, c),
-            MinMaxResult::MinMax(l, h) => (l, h),
-        };
-
-        let rows = match rows {
-            MinMaxResult::NoElements => panic!("no elves?"),
-            MinMaxResult::OneElement(r) => (r, r),
-            MinMaxResult::MinMax(l, h) => (l, h),
-        };
-        (cols, rows)
+    fn boundaries(&self) -> ((usize, usize), (usize, usize)) {
+        self.elves.bounds().expect("no elves?")
     }
 
-    fn emptiness(&self) -> isize {
+    fn emptiness(&self) -> usize {
         let (cols, rows) = self.dimensions();
         (cols * rows) - self.len()
     }
 }
-
-fn part_01(input: &str) -> isize {
+fn part_01(input: &str) -> usize {
     let mut elves = Elves::parse(input);
     //println!("{}\n\n", elves);
     for idx in 0..10 {
@@ -1,31 +1,22 @@
+use aoc_utils::sequence::next_value;
+
 use crate::custom_error::AocError;
 
 #[tracing::instrument]
 pub fn process(input: &str) -> Result<String, AocError> {
-    let next: i64 = input
+    let next: i128 = input
         .lines()
         .map(|line| {
             let sequence = line
                 .split_whitespace()
-                .map(|n| n.parse::<i64>().unwrap())
+                .map(|n| n.parse::<i128>().unwrap())
                 .collect::<Vec<_>>();
-            predict_next_number(sequence)
+            next_value(&sequence)
         })
         .sum();
     Ok(format!("{next}"))
 }
 
-fn predict_next_number(mut sequence: Vec<i64>) -> i64 {
-    let mut next = 0;
-    loop {
-        next += *sequence.last().unwrap();
-        sequence = sequence.windows(2).map(|c| c[1] - c[0]).collect();
-        if sequence.iter().all(|n| *n == 0) {
-            return next;
-        }
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use rstest::rstest;
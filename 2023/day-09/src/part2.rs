@@ -1,32 +1,22 @@
+use aoc_utils::sequence::previous_value;
+
 use crate::custom_error::AocError;
 
 #[tracing::instrument]
 pub fn process(input: &str) -> Result<String, AocError> {
-    let prev: i64 = input
+    let prev: i128 = input
         .lines()
         .map(|line| {
             let sequence = line
                 .split_whitespace()
-                .map(|n| n.parse::<i64>().unwrap())
+                .map(|n| n.parse::<i128>().unwrap())
                 .collect::<Vec<_>>();
-            predict_prev_number(sequence)
+            previous_value(&sequence)
         })
         .sum();
     Ok(format!("{prev}"))
 }
 
-fn predict_prev_number(mut sequence: Vec<i64>) -> i64 {
-    let mut first = Vec::new();
-    loop {
-        first.push(*sequence.first().unwrap());
-        sequence = sequence.windows(2).map(|c| c[1] - c[0]).collect();
-        if sequence.iter().all(|n| *n == 0) {
-            first.reverse();
-            return first.into_iter().fold(0, |p, n| n - p);
-        }
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
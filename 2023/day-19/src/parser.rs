@@ -1,15 +1,16 @@
 use std::ops::Range;
 
+use aoc_utils::{
+    error::{final_parse, Span},
+    parse::{assignments, lines, name, PResult},
+};
 use nom::{
     branch::alt,
-    character::complete::{alpha1, newline},
-    combinator::map,
-    multi::{many1, separated_list1},
-    sequence::{preceded, separated_pair, terminated, tuple},
-    IResult,
+    character::complete::{char, line_ending, u32},
+    combinator::{map, map_res, value},
+    multi::separated_list1,
+    sequence::{delimited, pair, preceded, separated_pair, tuple},
 };
-use nom_locate::LocatedSpan;
-use nom_supreme::{error::ErrorTree, final_parser::final_parser, tag::complete::tag};
 
 use crate::custom_error::AocError;
 
@@ -120,85 +121,76 @@ impl<'a> Workflow<'a> {
     }
 }
 
-pub type Span = LocatedSpan<&'static str>;
-
 pub fn parse_puzzle(input: &'static str) -> Result<(Vec<Workflow<'static>>, Vec<Part>), AocError> {
-    final_parser(parse_all)(Span::new(input))
-        .map_err(|e: ErrorTree<Span>| AocError::from_error_tree(input, e))
-}
-
-fn parse_all(input: Span) -> IResult<Span, (Vec<Workflow<'static>>, Vec<Part>), ErrorTree<Span>> {
-    separated_pair(parse_workflows, newline, parse_parts)(input)
-}
-
-fn parse_workflows(input: Span) -> IResult<Span, Vec<Workflow<'static>>, ErrorTree<Span>> {
-    many1(terminated(parse_workflow, newline))(input)
-}
-
-fn parse_workflow(input: Span) -> IResult<Span, Workflow<'static>, ErrorTree<Span>> {
-    let (input, (name, conditions)) = tuple((
-        alpha1,
-        preceded(
-            tag("{"),
-            terminated(separated_list1(tag(","), parse_condition), tag("}")),
+    Ok(final_parse(input, parse_all)?)
+}
+
+fn parse_all(input: Span<'static>) -> PResult<'static, (Vec<Workflow<'static>>, Vec<Part>)> {
+    separated_pair(
+        lines(parse_workflow),
+        pair(line_ending, line_ending),
+        lines(parse_part),
+    )(input)
+}
+
+fn parse_workflow(input: Span<'static>) -> PResult<'static, Workflow<'static>> {
+    map(
+        pair(
+            name,
+            delimited(
+                char('{'),
+                separated_list1(char(','), parse_condition),
+                char('}'),
+            ),
         ),
-    ))(input)?;
-    Ok((
-        input,
-        Workflow {
+        |(name, conditions)| Workflow {
             name: name.fragment(),
             conditions,
         },
-    ))
+    )(input)
 }
 
-fn parse_condition(input: Span) -> IResult<Span, Condition<'static>, ErrorTree<Span>> {
+fn parse_condition(input: Span<'static>) -> PResult<'static, Condition<'static>> {
     alt((parse_comparison, map(parse_command, Condition::Command)))(input)
 }
-fn parse_comparison(input: Span) -> IResult<Span, Condition<'static>, ErrorTree<Span>> {
-    use nom::character::complete as cc;
+
+fn parse_comparison(input: Span<'static>) -> PResult<'static, Condition<'static>> {
     let (input, (cat, comp, target, command)) = tuple((
         alt((
-            map(tag("x"), |_| Category::X),
-            map(tag("m"), |_| Category::M),
-            map(tag("a"), |_| Category::A),
-            map(tag("s"), |_| Category::S),
+            value(Category::X, char('x')),
+            value(Category::M, char('m')),
+            value(Category::A, char('a')),
+            value(Category::S, char('s')),
         )),
         alt((
-            map(tag("<"), |_| Comparison::LessThan),
-            map(tag(">"), |_| Comparison::GreaterThan),
+            value(Comparison::LessThan, char('<')),
+            value(Comparison::GreaterThan, char('>')),
         )),
-        cc::u32,
-        preceded(tag(":"), parse_command),
+        u32,
+        preceded(char(':'), parse_command),
     ))(input)?;
     Ok((input, Condition::Compare(cat, comp, target, command)))
 }
 
-fn parse_command(input: Span) -> IResult<Span, Command<'static>, ErrorTree<Span>> {
+fn parse_command(input: Span<'static>) -> PResult<'static, Command<'static>> {
     alt((
-        map(tag("A"), |_| Command::Accept),
-        map(tag("R"), |_| Command::Reject),
-        map(alpha1, |name: Span| Command::Goto(name.fragment())),
+        value(Command::Accept, char('A')),
+        value(Command::Reject, char('R')),
+        map(name, |name| Command::Goto(name.fragment())),
     ))(input)
 }
 
-fn parse_parts(input: Span) -> IResult<Span, Vec<Part>, ErrorTree<Span>> {
-    many1(terminated(parse_part, newline))(input)
-}
-
-fn parse_part(input: Span) -> IResult<Span, Part, ErrorTree<Span>> {
-    use nom::character::complete as cc;
-    let (input, (x, m, a, s)) = preceded(
-        tag("{"),
-        terminated(
-            tuple((
-                preceded(tag("x="), cc::u32),
-                preceded(tag(",m="), cc::u32),
-                preceded(tag(",a="), cc::u32),
-                preceded(tag(",s="), cc::u32),
-            )),
-            tag("}"),
-        ),
-    )(input)?;
-    Ok((input, Part { x, m, a, s }))
+// {x=787,m=2655,a=1222,s=2876}
+fn parse_part(input: Span<'static>) -> PResult<'static, Part> {
+    map_res(
+        delimited(char('{'), assignments(u32), char('}')),
+        |ratings| {
+            let ratings: Vec<(&str, u32)> =
+                ratings.iter().map(|(k, v)| (*k.fragment(), *v)).collect();
+            match ratings[..] {
+                [("x", x), ("m", m), ("a", a), ("s", s)] => Ok(Part { x, m, a, s }),
+                _ => Err("expected the ratings x, m, a and s"),
+            }
+        },
+    )(input)
 }
@@ -1,6 +1,7 @@
 use std::collections::{HashSet, VecDeque};
 
 use aoc_utils::grid::{Grid, Taxicab};
+use aoc_utils::sequence::lagrange;
 
 use crate::{custom_error::AocError, part1::Tile};
 
@@ -95,24 +96,20 @@ pub fn process(input: &str) -> Result<String, AocError> {
     println!("65 + 2 * 131: {}", reachable_in(&grid, 65 + 2 * 131));
     */
 
-    let y0 = reachable_in(&grid, pos, 65);
-    let y1 = reachable_in(&grid, pos, 65 + 131);
-    let y2 = reachable_in(&grid, pos, 65 + 2 * 131);
-
-    let a0 = y0;
-    let a1 = y1 - y0;
-    let a2 = (y2 - 2 * y1 + y0) / 2;
-
-    // f(n) = a0 + (a1 - a2) * n + a2 * n^2
-    /*
-    let y3 = reachable_in(&grid, pos, 65 + 3 * 131);
-    let check = a0 + (a1 - a2) * 3 + a2 * (3 * 3);
-    assert_eq!(check, y3);
-    */
-    let count = a0 + (a1 - a2) * 202300 + a2 * (202300 * 202300);
-
-    // ok, that's bad, but I used Wolfram Alpha... next is to compute
-    // this quadratic myself, and input the target value
+    // the quadratic through f(0), f(1) and f(2), evaluated at the
+    // number of steps
+    let points: Vec<(i128, i128)> = (0..3)
+        .map(|n| {
+            let steps = 65 + n * 131;
+            (steps as i128, reachable_in(&grid, pos, steps) as i128)
+        })
+        .collect();
+    let count = lagrange(&points, 26501365)
+        .to_integer()
+        .expect("not a quadratic");
+
+    // the interpolation is exact (in rationals), so a count that is not an
+    // integer means the growth is not quadratic
 
     // OUTLINE OF THE SOLUTION
     // as I already found out, the test data is useless; the actual input
@@ -1,120 +1,38 @@
-use std::{cmp::Reverse, collections::BinaryHeap};
+use aoc_utils::{
+    cuboid::Cuboid,
+    gravity::{settle, Settled},
+};
 
 use crate::custom_error::AocError;
 
-type Position = (usize, usize, usize);
-
-type Brick = (Position, Position);
-
-pub fn overlap(b: &Brick, o: &Brick) -> bool {
-    let xmin = b.0 .0.max(o.0 .0);
-    let ymin = b.0 .1.max(o.0 .1);
-    let xmax = b.1 .0.min(o.1 .0);
-    let ymax = b.1 .1.min(o.1 .1);
-
-    xmin <= xmax && ymin <= ymax
+pub fn parse_bricks(input: &str) -> Vec<Cuboid<3>> {
+    input
+        .lines()
+        .map(|line| {
+            let (part_1, part_2) = line.split_once('~').unwrap();
+            let parse = |part: &str| {
+                let coords: Vec<i64> = part.split(',').map(|d| d.parse().unwrap()).collect();
+                [coords[0], coords[1], coords[2]]
+            };
+            let (part_1, part_2) = (parse(part_1), parse(part_2));
+            // normalize so that min is always smaller than max
+            Cuboid::from_inclusive(
+                std::array::from_fn(|i| part_1[i].min(part_2[i])),
+                std::array::from_fn(|i| part_1[i].max(part_2[i])),
+            )
+        })
+        .collect()
 }
 
-pub fn settle(bricks: Vec<Brick>) -> Vec<Brick> {
-    // try a new approach:
-    // split bricks into two sets: settled, and in the air
-    // find the one in the air with the lowest bottom z (maybe use a priority queue)
-    // no matter what, it must be below anything else (or same level, which is same)
-    // take it out, and find where to add it: compute the list of overlapping settled
-    // bricks, and take the one with the highest top z
-
-    let mut queue = BinaryHeap::from_iter(bricks.into_iter().map(|b| (Reverse(b.0 .2), b)));
-
-    let mut bricks = Vec::new();
-
-    while let Some((_, mut b)) = queue.pop() {
-        let z = bricks
-            .iter()
-            .filter_map(|s| if overlap(s, &b) { Some(s.1 .2) } else { None })
-            .max()
-            .unwrap_or(0)
-            + 1;
-        let h = b.1 .2 - b.0 .2;
-        b.0 .2 = z;
-        b.1 .2 = z + h;
-        bricks.push(b);
-    }
-
-    bricks.sort_by_key(|b| b.0 .2);
-
-    bricks
-}
-
-pub fn parse_bricks(input: &str) -> Vec<Brick> {
-    let mut bricks: Vec<Brick> = Vec::new();
-    for line in input.lines() {
-        let mut parts = line.split('~');
-        let part_1: Vec<usize> = parts
-            .next()
-            .unwrap()
-            .split(',')
-            .map(|d| d.parse().unwrap())
-            .collect();
-        let part_2: Vec<usize> = parts
-            .next()
-            .unwrap()
-            .split(',')
-            .map(|d| d.parse().unwrap())
-            .collect();
-
-        // normalize so that first part is always smaller than second part
-        bricks.push((
-            (
-                part_1[0].min(part_2[0]),
-                part_1[1].min(part_2[1]),
-                part_1[2].min(part_2[2]),
-            ),
-            (
-                part_1[0].max(part_2[0]),
-                part_1[1].max(part_2[1]),
-                part_1[2].max(part_2[2]),
-            ),
-        ));
-    }
-    bricks
-}
-
-pub fn compute_support(input: &str) -> (Vec<Vec<usize>>, Vec<usize>) {
-    let bricks = parse_bricks(input);
-    let bricks = settle(bricks);
-    // now, a bricks supports another one iff they overlap and bottom one top z is
-    // top one bottom z - 1
-
-    // ids of bricks supported
-    let mut supports: Vec<Vec<usize>> = vec![Vec::new(); bricks.len()];
-    // number of bricks supporting
-    let mut supported_by: Vec<usize> = vec![0; bricks.len()];
-
-    for (idx, b) in bricks.iter().enumerate() {
-        for (o_idx, o) in bricks[idx + 1..].iter().enumerate() {
-            let o_idx = idx + 1 + o_idx;
-
-            if b.1 .2 + 1 == o.0 .2 && overlap(b, o) {
-                supports[idx].push(o_idx);
-                supported_by[o_idx] += 1;
-            } else if o.0 .2 > b.1 .2 + 1 {
-                continue;
-            }
-        }
-    }
-
-    (supports, supported_by)
+// the bricks once settled on the ground at z = 1, with which brick
+// supports which
+pub fn compute_support(input: &str) -> Settled {
+    settle(parse_bricks(input), 1)
 }
 
 #[tracing::instrument]
 pub fn process(input: &str) -> Result<String, AocError> {
-    let (supports, supported_by) = compute_support(input);
-
-    let count = supports
-        .into_iter()
-        .enumerate()
-        .filter(|(_, v)| v.iter().all(|s| supported_by[*s] > 1))
-        .count();
+    let count = compute_support(input).removable().count();
 
     Ok(format!("{count}"))
 }
@@ -1,48 +1,13 @@
-use std::collections::HashSet;
-
 use crate::{custom_error::AocError, part1::compute_support};
 
 #[tracing::instrument]
 pub fn process(input: &str) -> Result<String, AocError> {
-    let (supports, supported_by) = compute_support(input);
-
-    let critical_bricks: Vec<usize> = supports
-        .iter()
-        .enumerate()
-        .filter_map(|(idx, v)| {
-            if v.iter().filter(|s| supported_by[**s] == 1).count() > 0 {
-                Some(idx)
-            } else {
-                None
-            }
-        })
-        .collect();
-    let count: usize = critical_bricks
-        .iter()
-        .map(|idx| {
-            let mut supported_by = supported_by.clone();
-            let mut falling: HashSet<usize> = HashSet::new();
-            let mut queue = vec![*idx];
-            while let Some(idx) = queue.pop() {
-                if falling.contains(&idx) {
-                    continue;
-                }
-                falling.insert(idx);
+    let settled = compute_support(input);
 
-                for s in &supports[idx] {
-                    supported_by[*s] -= 1;
-                    if supported_by[*s] == 0 {
-                        queue.push(*s);
-                    }
-                }
-            }
-
-            falling.len() - 1
-        })
+    let count: usize = (0..settled.bricks.len())
+        .map(|idx| settled.chain_reaction(idx))
         .sum();
 
-    //eprintln!("{critical_bricks:?}");
-
     Ok(format!("{count}"))
 }
 
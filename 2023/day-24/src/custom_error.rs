@@ -7,4 +7,8 @@ pub enum AocError {
     #[error(transparent)]
     #[diagnostic(code(aoc::io_error))]
     IoError(#[from] std::io::Error),
+
+    #[error("hailstone positions overflow i128")]
+    #[diagnostic(code(aoc::overflow))]
+    Overflow,
 }
\ No newline at end of file
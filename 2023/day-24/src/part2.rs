@@ -1,10 +1,43 @@
-use crate::custom_error::AocError;
+use aoc_utils::linalg::solve_integer;
+
+use crate::{custom_error::AocError, part1::Trajectory};
+
+// For the rock at P with velocity V, and each hailstone at p with
+// velocity v, (P - p) and (V - v) are parallel:
+//   (P - p) x (V - v) = 0
+// The only non linear term, P x V, is the same for all hailstones, so
+// the difference of these equations for two hailstones i and j is
+// linear:
+//   P x (vi - vj) + (pi - pj) x V = pi x vi - pj x vj
+// Hailstone pairs (0, 1) and (0, 2) give six equations for the six
+// unknowns. The values are too large for the rationals, but the solution
+// is an integer, so solve_integer works modulo primes instead
+fn rock_equations(hi: &Trajectory, hj: &Trajectory) -> Vec<(Vec<i128>, i128)> {
+    let (pi, vi, pj, vj) = (hi.position, hi.velocity, hj.position, hj.velocity);
+    [(0, 1), (0, 2), (1, 2)]
+        .into_iter()
+        .map(|(a, b)| {
+            // the component of the cross products normal to the (a, b) plane
+            let mut row = vec![0; 6];
+            row[a] = vi[b] - vj[b];
+            row[b] = vj[a] - vi[a];
+            row[3 + a] = pj[b] - pi[b];
+            row[3 + b] = pi[a] - pj[a];
+            let rhs = (pi[a] * vi[b] - pi[b] * vi[a]) - (pj[a] * vj[b] - pj[b] * vj[a]);
+            (row, rhs)
+        })
+        .collect()
+}
 
 #[tracing::instrument]
-pub fn process(_input: &str) -> Result<String, AocError> {
-    // adapt the z3 solver, because I'm not going to do that
-    // myself
-    todo!("day 01 - part 1");
+pub fn process(input: &str) -> Result<String, AocError> {
+    let trajectories: Vec<Trajectory> = input.lines().map(Trajectory::parse).collect();
+    let (matrix, rhs): (Vec<_>, Vec<_>) = rock_equations(&trajectories[0], &trajectories[1])
+        .into_iter()
+        .chain(rock_equations(&trajectories[0], &trajectories[2]))
+        .unzip();
+    let rock = solve_integer(&matrix, &rhs).expect("no integer solution");
+    Ok(format!("{}", rock[0] + rock[1] + rock[2]))
 }
 
 #[cfg(test)]
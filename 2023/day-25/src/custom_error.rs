@@ -7,4 +7,10 @@ pub enum AocError {
     #[error(transparent)]
     #[diagnostic(code(aoc::io_error))]
     IoError(#[from] std::io::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    ParseError(#[from] aoc_utils::error::ParseError),
+    #[error("the minimum cut has {0} wires, expected 3")]
+    #[diagnostic(code(aoc::cut_weight))]
+    CutWeight(i64),
 }
\ No newline at end of file
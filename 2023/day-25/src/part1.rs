@@ -1,67 +1,23 @@
-use std::collections::{HashMap, HashSet};
+use aoc_utils::graph::Graph;
+use aoc_utils::min_cut::stoer_wagner;
 
 use crate::custom_error::AocError;
 
-use faer::{prelude::*, Mat, Side};
-
-// Graph partition with Fiedler eigenvalue and eigenvector:
-// https://en.wikipedia.org/wiki/Graph_partition#Fiedler_eigenvalue_and_eigenvector
-
+// the three wires are the edges of a minimum cut, of weight 3
 #[tracing::instrument]
-pub fn process<'a>(input: &'a str) -> Result<String, AocError> {
-    let mut graph: HashMap<&'a str, HashSet<&'a str>> = HashMap::new();
-    for line in input.lines() {
-        let mut parts = line.split(':');
-        let comp = parts.next().unwrap().trim();
-        let connected = parts.next().unwrap().split_whitespace().collect::<Vec<_>>();
-
-        for conn in connected {
-            graph.entry(comp).or_default().insert(conn);
-            graph.entry(conn).or_default().insert(comp);
-        }
-    }
-
-    let names = graph.keys().collect::<Vec<_>>();
-    let names_rev: HashMap<&'a str, usize> =
-        HashMap::from_iter(names.iter().enumerate().map(|(idx, n)| (**n, idx)));
-    let mut a: Mat<f64> = Mat::zeros(names.len(), names.len());
-    let d = Mat::from_fn(names.len(), names.len(), |i, j| {
-        if i == j {
-            graph[names[i]].len() as f64
-        } else {
-            0f64
-        }
-    });
-
-    for (idx, name) in names.iter().enumerate() {
-        for conn in &graph[*name] {
-            let oidx = names_rev[conn];
-            a[(idx, oidx)] = 1f64;
-        }
-    }
-
-    let l = d - a;
-    let v = l.selfadjoint_eigendecomposition(Side::Upper);
-
-    let mut part_1 = 0;
-    let mut part_2 = 0;
-
-    // ok, order is not specified but for my data it is increasing,
-    // so second value is also second smallest ev
-    //eprintln!("{:?}", v.s_diagonal());
-
-    for i in 0..v.u().nrows() {
-        if v.u().read(i, 1) < 0f64 {
-            part_1 += 1;
-        } else {
-            part_2 += 1;
-        }
+pub fn process(input: &str) -> Result<String, AocError> {
+    let graph = Graph::parse_adjacency(input, ":", " ", false)?;
+    let cut = stoer_wagner(&graph, |_| 1);
+    if cut.weight != 3 {
+        return Err(AocError::CutWeight(cut.weight));
     }
+    let (part_1, part_2) = cut.sizes();
     Ok(format!("{}", part_1 * part_2))
 }
 
 #[cfg(test)]
 mod tests {
+    use aoc_utils::min_cut::{flow_min_cut, karger};
     use rstest::rstest;
 
     use super::*;
@@ -87,4 +43,27 @@ frs: qnr lhk lsr
         assert_eq!(res, process(input)?);
         Ok(())
     }
+
+    #[test]
+    fn test_cut_weight() {
+        // a square has no cut of three wires
+        let square = "a: b d\nc: b d\n";
+        assert!(matches!(process(square), Err(AocError::CutWeight(2))));
+    }
+
+    #[test]
+    fn test_cross_check() {
+        let graph = Graph::parse_adjacency(INPUT, ":", " ", false).unwrap();
+        let cut = stoer_wagner(&graph, |_| 1);
+        let flow = flow_min_cut(&graph, 0, 1..graph.len(), |_| 1).unwrap();
+        let random = karger(&graph, 100, 25, |_| 1);
+        for other in [flow, random] {
+            assert_eq!(cut.weight, other.weight);
+            let mut sizes = [other.sizes().0, other.sizes().1];
+            sizes.sort();
+            let mut expected = [cut.sizes().0, cut.sizes().1];
+            expected.sort();
+            assert_eq!(expected, sizes);
+        }
+    }
 }
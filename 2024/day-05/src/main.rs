@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use aoc_utils::graph::Graph;
 
 const INPUT: &str = include_str!("input.txt");
 
@@ -7,12 +7,14 @@ const INPUT: &str = include_str!("input.txt");
 // then scan each of the lists and check for each item if there's
 // one of that item's successors in the slice before the item
 //
-// part 2: dumb topological sort
+// part 2: topological sort of the rules restricted to the pages of the
+// sequence (the full rules have cycles)
 
-type Successors = HashMap<usize, Vec<usize>>;
+// edges from each page to the pages that must come after it
+type Successors = Graph<usize>;
 
 fn parse(input: &str) -> (Successors, Vec<Vec<usize>>) {
-    let mut successors = HashMap::new();
+    let mut successors = Graph::directed();
     let mut sequences = Vec::new();
 
     let mut rules = true;
@@ -25,10 +27,7 @@ fn parse(input: &str) -> (Successors, Vec<Vec<usize>>) {
 
         if rules {
             let rule: Vec<usize> = line.split('|').map(|n| n.parse().unwrap()).collect();
-            successors
-                .entry(rule[0])
-                .or_insert(Vec::new())
-                .push(rule[1]);
+            successors.add_edge(rule[0], rule[1], ());
         } else {
             sequences.push(line.split(',').map(|n| n.parse().unwrap()).collect());
         }
@@ -39,9 +38,9 @@ fn parse(input: &str) -> (Successors, Vec<Vec<usize>>) {
 
 fn is_sorted(successors: &Successors, sequence: &[usize]) -> bool {
     for (idx, elt) in sequence.iter().enumerate() {
-        if successors.contains_key(elt) {
-            for succ in &successors[elt] {
-                if sequence[0..idx].contains(succ) {
+        if let Some(id) = successors.id(elt) {
+            for succ in successors.neighbours(id) {
+                if sequence[0..idx].contains(successors.label(succ)) {
                     return false;
                 }
             }
@@ -64,37 +63,15 @@ fn part1(input: &str) -> usize {
     sum
 }
 
-fn sort(successors: &Successors, mut sequence: Vec<usize>) -> Vec<usize> {
-    // dumbest topological sort...
-    let mut successors = successors.clone();
-
-    // remove keys not used
-    let keys: Vec<usize> = successors.keys().copied().collect();
-    for key in keys {
-        if !sequence.contains(&key) {
-            successors.remove(&key);
-        } else {
-            successors
-                .get_mut(&key)
-                .unwrap()
-                .retain(|succ| sequence.contains(succ));
-        }
-    }
-
-    let mut new_seq = Vec::new();
-
-    while !sequence.is_empty() {
-        // assume all items are different; turns out correct
-        for elt in &sequence {
-            if !successors.values().any(|succs| succs.contains(elt)) {
-                new_seq.push(*elt);
-                successors.remove(elt);
-            }
-        }
-        sequence.retain(|elt| !new_seq.contains(elt));
+fn sort(successors: &Successors, sequence: Vec<usize>) -> Vec<usize> {
+    let mut rules = successors.subgraph(sequence.iter().filter_map(|page| successors.id(page)));
+    // pages without rules
+    for page in sequence {
+        rules.node(page);
     }
 
-    new_seq
+    let order = rules.topological_order().expect("no order for the pages");
+    order.into_iter().map(|id| *rules.label(id)).collect()
 }
 
 fn part2(input: &str) -> usize {
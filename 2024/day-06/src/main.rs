@@ -1,9 +1,6 @@
-use std::{
-    fmt::Display,
-    ops::{Index, IndexMut},
-};
+use std::fmt::Display;
 
-use aoc_utils::grid::Grid;
+use aoc_utils::{bitgrid::BitGrid, grid::Grid};
 
 const INPUT: &str = include_str!("input.txt");
 
@@ -90,50 +87,21 @@ impl Direction {
     }
 }
 
-struct Visited([bool; 4]);
+// the cells visited, going in each direction
+type Visited = [BitGrid; 4];
 
-impl From<&Tile> for Visited {
-    fn from(_: &Tile) -> Self {
-        Visited([false; 4])
-    }
-}
-
-impl Visited {
-    fn visited(&self) -> bool {
-        self.0.iter().any(|b| *b)
-    }
-}
-
-impl Index<Direction> for Visited {
-    type Output = bool;
-
-    fn index(&self, index: Direction) -> &Self::Output {
-        &self.0[index as usize]
-    }
-}
-
-impl IndexMut<Direction> for Visited {
-    fn index_mut(&mut self, index: Direction) -> &mut Self::Output {
-        &mut self.0[index as usize]
-    }
-}
-
-fn visit(
-    grid: &Grid<Tile, ()>,
-    mut pos: (usize, usize),
-    mut dir: Direction,
-) -> Option<Grid<Visited, ()>> {
-    let mut visited: Grid<Visited, ()> = grid.into();
+fn visit(blocks: &BitGrid, mut pos: (usize, usize), mut dir: Direction) -> Option<Visited> {
+    let mut visited: Visited =
+        std::array::from_fn(|_| BitGrid::new(blocks.width(), blocks.height()));
 
     'main: loop {
-        if visited[pos][dir] {
+        if !visited[dir as usize].insert(pos) {
             return None;
         }
-        visited[pos][dir] = true;
         loop {
             if let Some(new_pos) = dir.step_forward(pos) {
-                if new_pos.0 < grid.width() && new_pos.1 < grid.height() {
-                    if grid[new_pos] == Tile::Empty {
+                if new_pos.0 < blocks.width() && new_pos.1 < blocks.height() {
+                    if !blocks.get(new_pos) {
                         pos = new_pos;
                         break;
                     } else {
@@ -149,8 +117,9 @@ fn visit(
     Some(visited)
 }
 
-fn part1(input: &str) -> usize {
-    let mut grid: Grid<Tile, ()> = Grid::try_from(input).unwrap();
+// the blocks, and the start position and direction of the guard
+fn parse(input: &str) -> (BitGrid, (usize, usize), Direction) {
+    let grid: Grid<Tile, ()> = Grid::try_from(input).unwrap();
 
     let idx = grid
         .iter()
@@ -164,56 +133,37 @@ fn part1(input: &str) -> usize {
 
     let pos = grid.idx_to_pos(idx);
     let dir = Direction::from(grid[pos]);
-    grid[pos] = Tile::Empty;
-
-    visit(&grid, pos, dir)
-        .unwrap()
-        .iter()
-        .filter(|v| v.visited())
-        .count()
+    (BitGrid::from_grid(&grid, |t| *t == Tile::Block), pos, dir)
 }
 
-impl From<&Tile> for bool {
-    fn from(_value: &Tile) -> Self {
-        false
-    }
+fn part1(input: &str) -> usize {
+    let (blocks, pos, dir) = parse(input);
+
+    let [up, rest @ ..] = visit(&blocks, pos, dir).unwrap();
+    rest.iter().fold(up, |all, visited| all | visited).count()
 }
 
 fn part2(input: &str) -> usize {
-    let mut grid: Grid<Tile, ()> = Grid::try_from(input).unwrap();
-
-    let idx = grid
-        .iter()
-        .position(|t| {
-            *t == Tile::StartUp
-                || *t == Tile::StartDown
-                || *t == Tile::StartLeft
-                || *t == Tile::StartRight
-        })
-        .unwrap();
-
-    let orig = grid.idx_to_pos(idx);
+    let (mut blocks, orig, mut dir) = parse(input);
     let mut pos = orig;
-    let mut dir = Direction::from(grid[pos]);
-    grid[pos] = Tile::Empty;
 
-    let mut loops: Grid<bool, ()> = (&grid).into();
+    let mut loops = BitGrid::new(blocks.width(), blocks.height());
     // can't put blocks on pos we already visited, so keep track of that
-    let mut visited: Grid<bool, ()> = (&grid).into();
+    let mut visited = BitGrid::new(blocks.width(), blocks.height());
 
     'main: loop {
         loop {
-            visited[pos] = true;
+            visited.insert(pos);
             if let Some(new_pos) = dir.step_forward(pos) {
-                if new_pos.0 < grid.width() && new_pos.1 < grid.height() {
-                    if grid[new_pos] == Tile::Empty {
-                        if !visited[new_pos] {
+                if new_pos.0 < blocks.width() && new_pos.1 < blocks.height() {
+                    if !blocks.get(new_pos) {
+                        if !visited.get(new_pos) {
                             // try to block it
-                            grid[new_pos] = Tile::Block;
-                            if visit(&grid, pos, dir).is_none() {
-                                loops[new_pos] = true;
+                            blocks.insert(new_pos);
+                            if visit(&blocks, pos, dir).is_none() {
+                                loops.insert(new_pos);
                             }
-                            grid[new_pos] = Tile::Empty;
+                            blocks.remove(new_pos);
                         }
 
                         pos = new_pos;
@@ -228,9 +178,9 @@ fn part2(input: &str) -> usize {
         }
     }
 
-    loops[orig] = false;
+    loops.remove(orig);
 
-    loops.iter().filter(|b| **b).count()
+    loops.count()
 }
 
 fn main() {
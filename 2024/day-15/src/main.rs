@@ -1,6 +1,9 @@
 use std::fmt::Display;
 
-use aoc_utils::grid::Grid;
+use aoc_utils::{
+    grid::Grid,
+    render::{Cell, Frame, Rgb, Visualizer},
+};
 
 const INPUT: &str = include_str!("input.txt");
 
@@ -151,6 +154,21 @@ fn get_robot_pos(grid: &Grid<Tile, ()>) -> (usize, usize) {
     grid.idx_to_pos(idx)
 }
 
+fn show(grid: &Grid<Tile, ()>, visualizer: &mut Visualizer) {
+    if visualizer.is_enabled() {
+        let frame = Frame::from_grid(grid, |t| {
+            let c = t.to_string().chars().next().unwrap();
+            match t {
+                Tile::Wall => Cell::colored(c, Rgb::GREY),
+                Tile::Empty => Cell::new(' '),
+                Tile::Robot => Cell::colored(c, Rgb::RED),
+                _ => Cell::colored(c, Rgb::YELLOW),
+            }
+        });
+        visualizer.show(&frame).expect("cannot show warehouse");
+    }
+}
+
 fn gps_sum(grid: &Grid<Tile, ()>) -> usize {
     grid.iter()
         .enumerate()
@@ -165,7 +183,7 @@ fn gps_sum(grid: &Grid<Tile, ()>) -> usize {
         .sum()
 }
 
-fn part1(input: &str) -> usize {
+fn part1(input: &str, visualizer: &mut Visualizer) -> usize {
     let parts: Vec<&str> = input.split("\n\n").collect();
     let mut grid: Grid<Tile, ()> = Grid::try_from(parts[0]).unwrap();
     let instructions: Vec<Instruction> = parts[1]
@@ -179,13 +197,14 @@ fn part1(input: &str) -> usize {
     for i in instructions {
         if try_move(&mut grid, pos, i) {
             pos = i.target(pos);
+            show(&grid, visualizer);
         }
     }
 
     gps_sum(&grid)
 }
 
-fn part2(input: &str) -> usize {
+fn part2(input: &str, visualizer: &mut Visualizer) -> usize {
     let parts: Vec<&str> = input.split("\n\n").collect();
     let instructions: Vec<Instruction> = parts[1]
         .trim()
@@ -210,14 +229,23 @@ fn part2(input: &str) -> usize {
     for i in instructions {
         if can_move(&grid, pos, i) && try_move(&mut grid, pos, i) {
             pos = i.target(pos);
+            show(&grid, visualizer);
         }
     }
     gps_sum(&grid)
 }
 
 fn main() {
-    println!("part 1: {}", part1(INPUT));
-    println!("part 2: {}", part2(INPUT));
+    // the warehouses of the two parts do not have the same size
+    let visualizer = Visualizer::from_args();
+    let mut visualizer1 = visualizer.part("part1");
+    let part1 = part1(INPUT, &mut visualizer1);
+    visualizer1.finish().expect("cannot write animation");
+    let mut visualizer2 = visualizer.part("part2");
+    let part2 = part2(INPUT, &mut visualizer2);
+    visualizer2.finish().expect("cannot write animation");
+    println!("part 1: {part1}");
+    println!("part 2: {part2}");
 }
 
 #[cfg(test)]
@@ -262,12 +290,12 @@ v^^>>><<^^<>>^v^<v^vv<>v^<<>^<^v^v><^<<<><<^<v><v<>vv>>v><v^<vv<>v^<<^";
     #[test_case(LARGE_TEST_INPUT, 10092; "large test input")]
     #[test_case(INPUT, 1505963; "input")]
     fn test_part1(input: &str, gps_sum: usize) {
-        assert_eq!(gps_sum, part1(input));
+        assert_eq!(gps_sum, part1(input, &mut Visualizer::off()));
     }
 
     #[test_case(LARGE_TEST_INPUT, 9021; "large test input")]
     #[test_case(INPUT, 1543141; "input")]
     fn test_part2(input: &str, gps_sum: usize) {
-        assert_eq!(gps_sum, part2(input));
+        assert_eq!(gps_sum, part2(input, &mut Visualizer::off()));
     }
 }
@@ -1,166 +1,29 @@
-use std::collections::{HashMap, HashSet};
+use aoc_utils::graph::Graph;
 
 const INPUT: &str = include_str!("input.txt");
 
-// approach:
-// map all ids to numbers 0..
-// build adjacent lists for both directions
-// then for each c1, for each c2 in adjacency_map with c2 > c1, check the intersection between
-// their adjacency map
-fn part1(input: &str) -> usize {
-    let mut id_map: HashMap<&str, usize> = HashMap::new();
-    let mut name_map: Vec<&str> = vec![];
-
-    let mut adjacency_map: Vec<HashSet<usize>> = vec![];
-    let mut t_computers = HashSet::new();
-
-    for line in input.lines() {
-        let parts: Vec<&str> = line.split('-').collect();
-        let c1 = parts[0];
-        let c2 = parts[1];
-
-        let c1_idx = *id_map.entry(c1).or_insert(adjacency_map.len());
-
-        if c1_idx >= adjacency_map.len() {
-            adjacency_map.push(HashSet::new());
-            name_map.push(c1);
-            if c1.starts_with('t') {
-                t_computers.insert(c1_idx);
-            }
-        }
-
-        let c2_idx = *id_map.entry(c2).or_insert(adjacency_map.len());
-
-        if c2_idx >= adjacency_map.len() {
-            adjacency_map.push(HashSet::new());
-            name_map.push(c2);
-            if c2.starts_with('t') {
-                t_computers.insert(c2_idx);
-            }
-        }
-
-        adjacency_map[c1_idx].insert(c2_idx);
-        adjacency_map[c2_idx].insert(c1_idx);
-    }
-
-    let mut triples = HashSet::new();
-
-    for (c1, adj) in adjacency_map.iter().enumerate() {
-        for &c2 in adj {
-            if c2 > c1 {
-                for &shared in adj.intersection(&adjacency_map[c2]) {
-                    if shared != c1 && shared != c2 {
-                        // already c1 < c2
-                        let triple = if shared < c1 {
-                            (shared, c1, c2)
-                        } else if shared < c2 {
-                            (c1, shared, c2)
-                        } else {
-                            (c1, c2, shared)
-                        };
-                        if t_computers.contains(&c1)
-                            || t_computers.contains(&c2)
-                            || t_computers.contains(&shared)
-                        {
-                            triples.insert(triple);
-                        }
-                    }
-                }
-            }
-        }
-    }
-
-    triples.len()
+fn parse(input: &str) -> Graph<&str> {
+    Graph::parse_pairs(input, "-", false).unwrap()
 }
 
-fn bron_kerbosch2(
-    adjacency_lists: &[HashSet<usize>],
-    r: HashSet<usize>,
-    mut p: HashSet<usize>,
-    mut x: HashSet<usize>,
-    mc: &mut Vec<usize>,
-) {
-    if p.is_empty() && x.is_empty() {
-        if r.len() > mc.len() {
-            *mc = r.into_iter().collect();
-        }
-    } else {
-        let u = p
-            .iter()
-            .next()
-            .copied()
-            .unwrap_or_else(|| x.iter().next().copied().unwrap());
-
-        let diff = p
-            .difference(&adjacency_lists[u])
-            .copied()
-            .collect::<HashSet<usize>>();
-
-        for v in diff {
-            let mut r = r.clone();
-            r.insert(v);
-            bron_kerbosch2(
-                adjacency_lists,
-                r,
-                p.intersection(&adjacency_lists[v]).copied().collect(),
-                x.intersection(&adjacency_lists[v]).copied().collect(),
-                mc,
-            );
-            p.remove(&v);
-            x.insert(v);
-        }
-    }
+// every triangle with a computer starting with t
+fn part1(input: &str) -> usize {
+    let network = parse(input);
+    network
+        .cliques_of_size(3)
+        .iter()
+        .filter(|triple| triple.iter().any(|&c| network.label(c).starts_with('t')))
+        .count()
 }
 
-// that's a maximal clique problem...
+// that's a maximum clique problem (Bron-Kerbosch, in the graph module)
 fn part2(input: &str) -> String {
-    let mut id_map: HashMap<&str, usize> = HashMap::new();
-    let mut name_map: Vec<&str> = vec![];
-
-    let mut adjacency_lists: Vec<HashSet<usize>> = vec![];
-    let mut t_computers = HashSet::new();
-
-    for line in input.lines() {
-        let parts: Vec<&str> = line.split('-').collect();
-        let c1 = parts[0];
-        let c2 = parts[1];
-
-        let c1_idx = *id_map.entry(c1).or_insert(adjacency_lists.len());
-
-        if c1_idx >= adjacency_lists.len() {
-            adjacency_lists.push(HashSet::new());
-            name_map.push(c1);
-            if c1.starts_with('t') {
-                t_computers.insert(c1_idx);
-            }
-        }
-
-        let c2_idx = *id_map.entry(c2).or_insert(adjacency_lists.len());
-
-        if c2_idx >= adjacency_lists.len() {
-            adjacency_lists.push(HashSet::new());
-            name_map.push(c2);
-            if c2.starts_with('t') {
-                t_computers.insert(c2_idx);
-            }
-        }
-
-        adjacency_lists[c1_idx].insert(c2_idx);
-        adjacency_lists[c2_idx].insert(c1_idx);
-    }
-
-    let mut maximum_clique = vec![];
-    bron_kerbosch2(
-        &adjacency_lists,
-        HashSet::new(),
-        HashSet::from_iter(0..adjacency_lists.len()),
-        HashSet::new(),
-        &mut maximum_clique,
-    );
-
-    dbg!(&maximum_clique);
-
-    let mut names: Vec<&str> = maximum_clique.into_iter().map(|c| name_map[c]).collect();
+    let network = parse(input);
+    let mut names: Vec<&str> = network
+        .maximum_clique()
+        .into_iter()
+        .map(|c| *network.label(c))
+        .collect();
     names.sort();
     names.join(",")
 }
@@ -0,0 +1,216 @@
+// The hot loops of 2021 day 25, 2022 day 23, 2023 day 14 and 2024 day 6,
+// cell by cell on a Grid and a word at a time on a BitGrid, on random
+// grids of the size of the puzzle inputs
+use aoc_utils::{
+    automaton::Neighbourhood,
+    bitgrid::BitGrid,
+    grid::{Grid, MaxDist, Taxicab},
+};
+
+fn main() {
+    // Run registered benchmarks.
+    divan::main();
+}
+
+// a size x size grid of the given tiles, at random
+fn random_grid(size: usize, tiles: &[u8]) -> Grid<u8, Taxicab> {
+    let mut seed = 0x2545f4914f6cdd1du64;
+    let cells = (0..size * size)
+        .map(|_| {
+            // xorshift
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            tiles[seed as usize % tiles.len()]
+        })
+        .collect();
+    Grid::from_vec(size, cells)
+}
+
+// 2021 day 25: one step of the east herd, on a torus
+mod sea_cucumbers {
+    use super::*;
+
+    fn map() -> Grid<u8, Taxicab> {
+        random_grid(139, b">v..")
+    }
+
+    #[divan::bench]
+    fn grid(bencher: divan::Bencher) {
+        let map = map();
+        bencher.bench(|| {
+            let mut next = map.clone();
+            let width = map.width();
+            for y in 0..map.height() {
+                for x in 0..width {
+                    let right = ((x + 1) % width, y);
+                    if map[(x, y)] == b'>' && map[right] == b'.' {
+                        next[(x, y)] = b'.';
+                        next[right] = b'>';
+                    }
+                }
+            }
+            next
+        });
+    }
+
+    #[divan::bench]
+    fn bitgrid(bencher: divan::Bencher) {
+        let map = map();
+        let east = BitGrid::from_grid(&map, |&t| t == b'>');
+        let south = BitGrid::from_grid(&map, |&t| t == b'v');
+        bencher.bench(|| {
+            let empty = !&(&east | &south);
+            let moving = &east & &empty.wrapped(-1, 0);
+            (&east ^ &moving) | &moving.wrapped(1, 0)
+        });
+    }
+}
+
+// 2022 day 23: the elves with another elf around
+mod diffusion {
+    use super::*;
+
+    fn map() -> Grid<u8, MaxDist> {
+        let map = random_grid(150, b"#...");
+        Grid::from_vec(map.width(), map.iter().copied().collect())
+    }
+
+    #[divan::bench]
+    fn grid(bencher: divan::Bencher) {
+        let map = map();
+        bencher.bench(|| {
+            (0..map.width() * map.height())
+                .map(|idx| map.idx_to_pos(idx))
+                .filter(|&pos| map[pos] == b'#' && map.neighbours(pos).any(|n| map[n] == b'#'))
+                .count()
+        });
+    }
+
+    #[divan::bench]
+    fn bitgrid(bencher: divan::Bencher) {
+        let elves = BitGrid::from_grid(&map(), |&t| t == b'#');
+        bencher.bench(|| (&elves & &elves.neighbour_counts(Neighbourhood::Moore).any()).count());
+    }
+}
+
+// 2023 day 14: tilting the platform north
+mod tilt {
+    use super::*;
+
+    fn map() -> Grid<u8, Taxicab> {
+        random_grid(100, b"O#.....")
+    }
+
+    #[divan::bench]
+    fn grid(bencher: divan::Bencher) {
+        let map = map();
+        bencher.bench(|| {
+            let mut map = map.clone();
+            for c in 0..map.width() {
+                let mut empty = 0;
+                for r in 0..map.height() {
+                    match map[(c, r)] {
+                        b'#' => empty = r + 1,
+                        b'O' => {
+                            map[(c, r)] = b'.';
+                            map[(c, empty)] = b'O';
+                            empty += 1;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            map
+        });
+    }
+
+    #[divan::bench]
+    fn bitgrid(bencher: divan::Bencher) {
+        let map = map();
+        let rocks = BitGrid::from_grid(&map, |&t| t == b'O');
+        let cubes = BitGrid::from_grid(&map, |&t| t == b'#');
+        bencher.bench(|| {
+            let mut rocks = rocks.clone();
+            rocks.slide(&cubes, (0, -1));
+            rocks
+        });
+    }
+}
+
+// 2024 day 6: the guard walking until leaving the map or looping; lookups
+// go one cell at a time, so the grid of bits mostly saves memory here
+mod patrol {
+    use super::*;
+
+    const DELTAS: [(isize, isize); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+
+    fn map() -> Grid<u8, Taxicab> {
+        random_grid(130, b"#.......................")
+    }
+
+    // the steps taken, with the blocks and visited cells in any grid
+    fn walk<G>(
+        size: usize,
+        blocked: impl Fn((usize, usize)) -> bool,
+        mut visited: G,
+        mut visit: impl FnMut(&mut G, (usize, usize), usize) -> bool,
+    ) -> usize {
+        let (mut pos, mut dir) = ((size / 2, size / 2), 0);
+        let mut steps = 0;
+        while visit(&mut visited, pos, dir) {
+            let (dx, dy) = DELTAS[dir];
+            let next = match (pos.0.checked_add_signed(dx), pos.1.checked_add_signed(dy)) {
+                (Some(x), Some(y)) if x < size && y < size => (x, y),
+                _ => break,
+            };
+            if blocked(next) {
+                dir = (dir + 1) % 4;
+            } else {
+                pos = next;
+                steps += 1;
+            }
+        }
+        steps
+    }
+
+    // the directions a cell was visited in, made for each walk from the
+    // map as the puzzle does
+    struct Visited([bool; 4]);
+
+    impl From<&u8> for Visited {
+        fn from(_: &u8) -> Self {
+            Visited([false; 4])
+        }
+    }
+
+    #[divan::bench]
+    fn grid(bencher: divan::Bencher) {
+        let map = map();
+        bencher.bench(|| {
+            let visited: Grid<Visited, Taxicab> = (&map).into();
+            walk(
+                map.width(),
+                |pos| map[pos] == b'#',
+                visited,
+                |v, pos, dir| !std::mem::replace(&mut v[pos].0[dir], true),
+            )
+        });
+    }
+
+    #[divan::bench]
+    fn bitgrid(bencher: divan::Bencher) {
+        let map = map();
+        let blocks = BitGrid::from_grid(&map, |&t| t == b'#');
+        bencher.bench(|| {
+            let visited: [BitGrid; 4] =
+                std::array::from_fn(|_| BitGrid::new(map.width(), map.height()));
+            walk(
+                map.width(),
+                |pos| blocks.get(pos),
+                visited,
+                |v, pos, dir| v[dir].insert(pos),
+            )
+        });
+    }
+}
@@ -0,0 +1,461 @@
+use anyhow::{anyhow, Error};
+use std::{collections::HashSet, fmt::Display, ops::RangeInclusive, str::FromStr};
+
+use crate::{
+    error::{parse_lines, parse_token, ParseError},
+    symbolic::{self, Expr},
+};
+
+// ALU of 2021 day 24: registers w, x, y and z, with a concrete
+// interpreter, a symbolic one that decompiles each input block into a
+// formula, and a search for inputs accepted by the program
+
+pub type Registers = [i64; 4];
+
+const NAMES: [&str; 4] = ["w", "x", "y", "z"];
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Operand {
+    Register(usize),
+    Immediate(i64),
+}
+
+fn register(s: &str) -> Option<usize> {
+    NAMES.iter().position(|n| *n == s)
+}
+
+impl FromStr for Operand {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.parse() {
+            Ok(i) => Ok(Operand::Immediate(i)),
+            Err(_) => register(s)
+                .map(Operand::Register)
+                .ok_or_else(|| anyhow!("cannot find register {s}")),
+        }
+    }
+}
+
+impl Display for Operand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Operand::Register(r) => write!(f, "{}", NAMES[*r]),
+            Operand::Immediate(i) => write!(f, "{i}"),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Instruction {
+    Inp(usize),
+    Add(usize, Operand),
+    Mul(usize, Operand),
+    Div(usize, Operand),
+    Mod(usize, Operand),
+    Eql(usize, Operand),
+}
+
+impl FromStr for Instruction {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<_> = s.split_whitespace().collect();
+        let target = |p| {
+            register(p).ok_or_else(|| ParseError::at(s, p, format!("cannot find register {p}")))
+        };
+        let operand = |p| parse_token::<Operand>(s, p);
+        Ok(match parts[..] {
+            ["inp", a] => Instruction::Inp(target(a)?),
+            ["add", a, b] => Instruction::Add(target(a)?, operand(b)?),
+            ["mul", a, b] => Instruction::Mul(target(a)?, operand(b)?),
+            ["div", a, b] => Instruction::Div(target(a)?, operand(b)?),
+            ["mod", a, b] => Instruction::Mod(target(a)?, operand(b)?),
+            ["eql", a, b] => Instruction::Eql(target(a)?, operand(b)?),
+            [op, ..] if !["inp", "add", "mul", "div", "mod", "eql"].contains(&op) => {
+                return Err(ParseError::at(s, op, "unknown instruction"))
+            }
+            _ => return Err(ParseError::at(s, s.trim(), "wrong number of operands")),
+        })
+    }
+}
+
+impl Display for Instruction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Instruction::Inp(a) => write!(f, "inp {}", NAMES[*a]),
+            Instruction::Add(a, b) => write!(f, "add {} {b}", NAMES[*a]),
+            Instruction::Mul(a, b) => write!(f, "mul {} {b}", NAMES[*a]),
+            Instruction::Div(a, b) => write!(f, "div {} {b}", NAMES[*a]),
+            Instruction::Mod(a, b) => write!(f, "mod {} {b}", NAMES[*a]),
+            Instruction::Eql(a, b) => write!(f, "eql {} {b}", NAMES[*a]),
+        }
+    }
+}
+
+impl Instruction {
+    // None if the instruction would crash the ALU
+    fn eval(&self, registers: &mut Registers, input: &mut impl Iterator<Item = i64>) -> Option<()> {
+        let get = |o: &Operand, registers: &Registers| match o {
+            Operand::Register(r) => registers[*r],
+            Operand::Immediate(i) => *i,
+        };
+        match self {
+            Instruction::Inp(a) => registers[*a] = input.next()?,
+            Instruction::Add(a, b) => registers[*a] += get(b, registers),
+            Instruction::Mul(a, b) => registers[*a] *= get(b, registers),
+            Instruction::Div(a, b) => {
+                registers[*a] = registers[*a].checked_div(get(b, registers))?;
+            }
+            Instruction::Mod(a, b) => {
+                let b = get(b, registers);
+                if registers[*a] < 0 || b <= 0 {
+                    return None;
+                }
+                registers[*a] %= b;
+            }
+            Instruction::Eql(a, b) => registers[*a] = (registers[*a] == get(b, registers)).into(),
+        }
+        Some(())
+    }
+
+    // None if the instruction divides by a constant zero, which would
+    // crash the ALU whatever the registers
+    fn eval_symbolic(&self, registers: &mut [Expr; 4], input: Expr) -> Option<()> {
+        let get = |o: &Operand, registers: &[Expr; 4]| match o {
+            Operand::Register(r) => registers[*r].clone(),
+            Operand::Immediate(i) => symbolic::constant(*i),
+        };
+        type Build = fn(Expr, Expr) -> Option<Expr>;
+        let (a, b, f): (usize, &Operand, Build) = match self {
+            Instruction::Inp(a) => {
+                registers[*a] = input;
+                return Some(());
+            }
+            Instruction::Add(a, b) => (*a, b, |x, y| Some(symbolic::add(x, y))),
+            Instruction::Mul(a, b) => (*a, b, |x, y| Some(symbolic::mul(x, y))),
+            Instruction::Div(a, b) => (*a, b, symbolic::div),
+            Instruction::Mod(a, b) => (*a, b, symbolic::rem),
+            Instruction::Eql(a, b) => (*a, b, |x, y| Some(symbolic::eql(x, y))),
+        };
+        registers[a] = f(registers[a].clone(), get(b, registers))?;
+        Some(())
+    }
+}
+
+// the values of the registers after one block, as formulas over the
+// values before the block (named after the registers) and the digit read
+// by the block
+#[derive(Debug, Clone)]
+pub struct Block(pub [Expr; 4]);
+
+impl Block {
+    // registers whose value before the block matters
+    pub fn live(&self) -> Vec<usize> {
+        let vars: HashSet<_> = self.0.iter().flat_map(|e| e.vars()).collect();
+        (0..4).filter(|r| vars.contains(NAMES[*r])).collect()
+    }
+}
+
+impl Display for Block {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut first = true;
+        for (name, e) in NAMES.iter().zip(&self.0) {
+            // skip registers that keep their value
+            if e.to_string() != *name {
+                if !first {
+                    writeln!(f)?;
+                }
+                first = false;
+                write!(f, "{name} = {e}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Program {
+    pub instructions: Vec<Instruction>,
+}
+
+impl FromStr for Program {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Program {
+            instructions: parse_lines(s, Instruction::from_str)?,
+        })
+    }
+}
+
+impl Program {
+    pub fn run(&self, input: &[i64]) -> Option<Registers> {
+        let mut registers = [0; 4];
+        let mut input = input.iter().copied();
+        for i in &self.instructions {
+            i.eval(&mut registers, &mut input)?;
+        }
+        Some(registers)
+    }
+
+    // instructions before the first inp, then one slice per inp
+    fn blocks(&self) -> (&[Instruction], Vec<&[Instruction]>) {
+        let mut starts: Vec<_> = self
+            .instructions
+            .iter()
+            .enumerate()
+            .filter(|(_, i)| matches!(i, Instruction::Inp(_)))
+            .map(|(idx, _)| idx)
+            .collect();
+        let prefix = &self.instructions[..*starts.first().unwrap_or(&self.instructions.len())];
+        starts.push(self.instructions.len());
+        let blocks = starts
+            .windows(2)
+            .map(|w| &self.instructions[w[0]..w[1]])
+            .collect();
+        (prefix, blocks)
+    }
+
+    // registers may hold any value on entry to a block (add and mul can
+    // make them negative), so only the simplifications valid for all of
+    // them are made here; substituting the actual ranges simplifies
+    // further. None if a block divides by a constant zero, as no input
+    // could get through it
+    pub fn decompile(&self, digits: RangeInclusive<i64>) -> Option<Vec<Block>> {
+        let (_, blocks) = self.blocks();
+        blocks
+            .iter()
+            .enumerate()
+            .map(|(idx, block)| {
+                let mut registers = NAMES.map(|n| symbolic::var(n, i64::MIN, i64::MAX));
+                let digit = symbolic::input(idx, *digits.start(), *digits.end());
+                for i in *block {
+                    i.eval_symbolic(&mut registers, digit.clone())?;
+                }
+                Some(Block(registers))
+            })
+            .collect()
+    }
+
+    // depth first search for the first input, trying digits in the given
+    // order, that leaves value in register. States known to lead nowhere
+    // are remembered, keyed on the registers that are live at the start of
+    // each block, and states from which the decompiled blocks show value
+    // cannot be reached are pruned
+    pub fn find_input(&self, digits: &[i64], register: usize, value: i64) -> Option<Vec<i64>> {
+        let (prefix, blocks) = self.blocks();
+        let (min, max) = (*digits.iter().min()?, *digits.iter().max()?);
+        let decompiled = self.decompile(min..=max)?;
+
+        let mut registers = [0; 4];
+        for i in prefix {
+            i.eval(&mut registers, &mut std::iter::empty())?;
+        }
+
+        struct Search<'a> {
+            blocks: Vec<&'a [Instruction]>,
+            decompiled: Vec<Block>,
+            live: Vec<Vec<usize>>,
+            digits: &'a [i64],
+            register: usize,
+            value: i64,
+            dead: HashSet<(usize, Registers)>,
+        }
+
+        impl Search<'_> {
+            // propagates the ranges of the registers through the remaining
+            // blocks, forgetting the correlations between registers after
+            // each block to keep the expressions small
+            fn reachable(&self, block: usize, registers: &Registers) -> bool {
+                let mut ranges = registers.map(symbolic::constant);
+                // a divisor that becomes a constant zero crashes the ALU
+                for b in &self.decompiled[block..] {
+                    let lookup = |name: &str| {
+                        NAMES
+                            .iter()
+                            .position(|n| *n == name)
+                            .map(|r| ranges[r].clone())
+                    };
+                    let Some(next) =
+                        b.0.iter()
+                            .map(|e| e.substitute(&lookup))
+                            .collect::<Option<Vec<_>>>()
+                    else {
+                        return false;
+                    };
+                    ranges = std::array::from_fn(|r| match next[r].as_const() {
+                        Some(_) => next[r].clone(),
+                        None => symbolic::var(NAMES[r], next[r].min, next[r].max),
+                    });
+                }
+                let target = &ranges[self.register];
+                (target.min..=target.max).contains(&self.value)
+            }
+
+            fn search(&mut self, block: usize, registers: Registers) -> Option<Vec<i64>> {
+                if block == self.blocks.len() {
+                    return (registers[self.register] == self.value).then(Vec::new);
+                }
+                let mut key = [0; 4];
+                for r in &self.live[block] {
+                    key[*r] = registers[*r];
+                }
+                let key = (block, key);
+                if self.dead.contains(&key) {
+                    return None;
+                }
+                if self.reachable(block, &registers) {
+                    for &digit in self.digits {
+                        let mut next = registers;
+                        let mut input = std::iter::once(digit);
+                        if self.blocks[block]
+                            .iter()
+                            .try_for_each(|i| i.eval(&mut next, &mut input))
+                            .is_none()
+                        {
+                            continue;
+                        }
+                        if let Some(mut rest) = self.search(block + 1, next) {
+                            rest.insert(0, digit);
+                            return Some(rest);
+                        }
+                    }
+                }
+                self.dead.insert(key);
+                None
+            }
+        }
+
+        Search {
+            blocks,
+            live: decompiled.iter().map(Block::live).collect(),
+            decompiled,
+            digits,
+            register,
+            value,
+            dead: HashSet::new(),
+        }
+        .search(0, registers)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // MONAD-style block: z is used as a base 26 stack
+    fn block(div: i64, add_x: i64, add_y: i64) -> String {
+        format!(
+            "inp w
+mul x 0
+add x z
+mod x 26
+div z {div}
+add x {add_x}
+eql x w
+eql x 0
+mul y 0
+add y 25
+mul y x
+add y 1
+mul z y
+mul y 0
+add y w
+add y {add_y}
+mul y x
+add z y
+"
+        )
+    }
+
+    // d2 = d1 + 7, d4 = d3 - 7, d5 = d0 + 4
+    fn monad() -> Program {
+        [
+            (1, 11, 6),
+            (1, 13, 14),
+            (26, -7, 2),
+            (1, 15, 3),
+            (26, -10, 8),
+            (26, -2, 1),
+        ]
+        .iter()
+        .map(|(d, x, y)| block(*d, *x, *y))
+        .collect::<String>()
+        .parse()
+        .unwrap()
+    }
+
+    #[test]
+    fn test_run() {
+        let program: Program = "inp x\nmul x -1".parse().unwrap();
+        assert_eq!(Some([0, -7, 0, 0]), program.run(&[7]));
+        let program: Program = "inp z\ninp x\nmul z 3\neql z x".parse().unwrap();
+        assert_eq!(Some([0, 6, 0, 1]), program.run(&[2, 6]));
+        assert_eq!(None, "mod x 0".parse::<Program>().unwrap().run(&[]));
+    }
+
+    #[test]
+    fn test_decompile() {
+        let blocks = monad().decompile(1..=9).unwrap();
+        // z could be negative, so x could be 0 in the block alone, but not
+        // once z is known to be non-negative
+        assert_eq!(
+            "x = ((z % 26) + 11) != d0",
+            blocks[0].to_string().lines().nth(1).unwrap()
+        );
+        let z = |name: &str| (name == "z").then(|| symbolic::var("z", 0, i64::MAX));
+        assert_eq!(Some(1), blocks[0].0[1].substitute(&z).unwrap().as_const());
+        assert_eq!(
+            "w = d2\nx = ((z % 26) - 7) != d2\ny = (d2 + 2) * (((z % 26) - 7) != d2)\nz = ((z / 26) * ((25 * (((z % 26) - 7) != d2)) + 1)) + ((d2 + 2) * (((z % 26) - 7) != d2))",
+            blocks[2].to_string()
+        );
+        assert!(blocks.iter().all(|b| b.live() == vec![3]));
+
+        // dividing by zero crashes every run, or every run reaching it
+        let program: Program = "inp w\nadd z w\ndiv z 0".parse().unwrap();
+        assert!(program.decompile(1..=9).is_none());
+        assert_eq!(None, program.find_input(&[1, 2], 3, 1));
+        let program: Program = "inp w\nadd z w\ndiv z x".parse().unwrap();
+        assert!(program.decompile(1..=9).is_some());
+        assert_eq!(None, program.find_input(&[1, 2], 3, 1));
+    }
+
+    #[test]
+    fn test_find_input() {
+        let program = monad();
+        let largest = program
+            .find_input(&[9, 8, 7, 6, 5, 4, 3, 2, 1], 3, 0)
+            .unwrap();
+        assert_eq!(vec![5, 2, 9, 9, 2, 9], largest);
+        let smallest = program
+            .find_input(&[1, 2, 3, 4, 5, 6, 7, 8, 9], 3, 0)
+            .unwrap();
+        assert_eq!(vec![1, 1, 8, 8, 1, 5], smallest);
+        assert_eq!(Some(0), program.run(&smallest).map(|r| r[3]));
+    }
+
+    #[test]
+    fn test_negative_registers() {
+        // x is negative between the blocks, which must not rule out 8
+        let program: Program = "inp w\nadd x w\nadd x -5\ninp w\nadd x 12\neql x w"
+            .parse()
+            .unwrap();
+        assert_eq!(Some(1), program.run(&[1, 8]).map(|r| r[1]));
+        let digits: Vec<i64> = (1..=9).collect();
+        assert_eq!(Some(vec![1, 8]), program.find_input(&digits, 1, 1));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = "inp w\nadd x 1\nmul q 2".parse::<Program>().unwrap_err();
+        assert_eq!((3, 5), (err.line, err.column));
+        assert_eq!("cannot find register q", err.message());
+
+        let err = "inp w\nadd x v".parse::<Program>().unwrap_err();
+        assert_eq!(
+            "cannot find register v at line 2, column 7",
+            err.to_string()
+        );
+    }
+}
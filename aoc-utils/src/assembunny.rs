@@ -0,0 +1,678 @@
+use anyhow::{anyhow, Error};
+use std::{fmt::Display, str::FromStr};
+
+use crate::error::{parse_lines, parse_token, ParseError};
+
+// Assembunny interpreter (2016 days 12, 23 and 25), with a peephole
+// optimizer that replaces the add, multiply and divide-by-counting loops
+// with macro operations
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Value {
+    Register(usize),
+    Immediate(i64),
+}
+
+impl Value {
+    fn register(&self) -> Option<usize> {
+        match self {
+            Value::Register(r) => Some(*r),
+            Value::Immediate(_) => None,
+        }
+    }
+}
+
+impl FromStr for Value {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "a" | "b" | "c" | "d" => Ok(Value::Register((s.as_bytes()[0] - b'a') as usize)),
+            _ => Ok(Value::Immediate(
+                s.parse().map_err(|_| anyhow!("invalid operand {s}"))?,
+            )),
+        }
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Register(r) => write!(f, "{}", (b'a' + *r as u8) as char),
+            Value::Immediate(i) => write!(f, "{i}"),
+        }
+    }
+}
+
+// all operands are kept as Value so that toggled instructions that
+// became invalid (cpy 1 2) can still be represented and skipped
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Instruction {
+    Cpy(Value, Value),
+    Inc(Value),
+    Dec(Value),
+    Jnz(Value, Value),
+    Tgl(Value),
+    Out(Value),
+}
+
+impl Instruction {
+    pub fn toggle(self) -> Self {
+        use Instruction::*;
+        match self {
+            Inc(v) => Dec(v),
+            Dec(v) | Tgl(v) | Out(v) => Inc(v),
+            Jnz(a, b) => Cpy(a, b),
+            Cpy(a, b) => Jnz(a, b),
+        }
+    }
+}
+
+impl FromStr for Instruction {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<_> = s.split_whitespace().collect();
+        let value = |p| parse_token::<Value>(s, p);
+        Ok(match parts[..] {
+            ["cpy", a, b] => Instruction::Cpy(value(a)?, value(b)?),
+            ["inc", a] => Instruction::Inc(value(a)?),
+            ["dec", a] => Instruction::Dec(value(a)?),
+            ["jnz", a, b] => Instruction::Jnz(value(a)?, value(b)?),
+            ["tgl", a] => Instruction::Tgl(value(a)?),
+            ["out", a] => Instruction::Out(value(a)?),
+            [op, ..] if !["cpy", "inc", "dec", "jnz", "tgl", "out"].contains(&op) => {
+                return Err(ParseError::at(s, op, "unknown instruction"))
+            }
+            _ => return Err(ParseError::at(s, s.trim(), "wrong number of operands")),
+        })
+    }
+}
+
+impl Display for Instruction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Instruction::Cpy(a, b) => write!(f, "cpy {a} {b}"),
+            Instruction::Inc(a) => write!(f, "inc {a}"),
+            Instruction::Dec(a) => write!(f, "dec {a}"),
+            Instruction::Jnz(a, b) => write!(f, "jnz {a} {b}"),
+            Instruction::Tgl(a) => write!(f, "tgl {a}"),
+            Instruction::Out(a) => write!(f, "out {a}"),
+        }
+    }
+}
+
+pub fn parse(input: &str) -> Result<Vec<Instruction>, ParseError> {
+    parse_lines(input, Instruction::from_str)
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Pattern {
+    // inc target; dec counter; jnz counter -2
+    Add {
+        target: usize,
+        counter: usize,
+        sign: i64,
+    },
+    // cpy source inner; <add loop on target, inner>; dec outer; jnz outer -5
+    Multiply {
+        target: usize,
+        source: Value,
+        inner: usize,
+        outer: usize,
+        sign: i64,
+    },
+    // cpy divisor remainder; jnz dividend 2; jnz 1 6; dec dividend;
+    // dec remainder; jnz remainder -4; inc quotient; jnz 1 -7
+    DivMod {
+        quotient: usize,
+        dividend: usize,
+        divisor: Value,
+        remainder: usize,
+    },
+}
+
+impl Pattern {
+    fn len(&self) -> usize {
+        match self {
+            Pattern::Add { .. } => 3,
+            Pattern::Multiply { .. } => 6,
+            Pattern::DivMod { .. } => 8,
+        }
+    }
+}
+
+impl Display for Pattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let r = |r: &usize| Value::Register(*r);
+        let op = |s: &i64| if *s > 0 { '+' } else { '-' };
+        match self {
+            Pattern::Add {
+                target,
+                counter,
+                sign,
+            } => write!(
+                f,
+                "add: {} {}= {}; {} = 0",
+                r(target),
+                op(sign),
+                r(counter),
+                r(counter)
+            ),
+            Pattern::Multiply {
+                target,
+                source,
+                inner,
+                outer,
+                sign,
+            } => write!(
+                f,
+                "multiply: {} {}= {source} * {}; {} = 0; {} = 0",
+                r(target),
+                op(sign),
+                r(outer),
+                r(inner),
+                r(outer)
+            ),
+            Pattern::DivMod {
+                quotient,
+                dividend,
+                divisor,
+                remainder,
+            } => write!(
+                f,
+                "divide: {} += {} / {divisor}; {} = {divisor} - {} % {divisor}; {} = 0",
+                r(quotient),
+                r(dividend),
+                r(remainder),
+                r(dividend),
+                r(dividend)
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Rewrite {
+    pub start: usize,
+    pub pattern: Pattern,
+}
+
+impl Display for Rewrite {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}..{}: {}",
+            self.start,
+            self.start + self.pattern.len(),
+            self.pattern
+        )
+    }
+}
+
+fn match_add(code: &[Instruction]) -> Option<Pattern> {
+    use Instruction::*;
+    let (step, counter) = match code.get(..3)? {
+        [step @ (Inc(_) | Dec(_)), Dec(Value::Register(c)), Jnz(Value::Register(j), Value::Immediate(-2))]
+        | [Dec(Value::Register(c)), step @ (Inc(_) | Dec(_)), Jnz(Value::Register(j), Value::Immediate(-2))]
+            if c == j =>
+        {
+            (*step, *c)
+        }
+        _ => return None,
+    };
+    let (target, sign) = match step {
+        Inc(Value::Register(t)) => (t, 1),
+        Dec(Value::Register(t)) => (t, -1),
+        _ => return None,
+    };
+    (target != counter).then_some(Pattern::Add {
+        target,
+        counter,
+        sign,
+    })
+}
+
+fn match_multiply(code: &[Instruction]) -> Option<Pattern> {
+    use Instruction::*;
+    let [Cpy(source, Value::Register(inner)), _, _, _, Dec(Value::Register(outer)), Jnz(Value::Register(j), Value::Immediate(-5))] =
+        *code.get(..6)?
+    else {
+        return None;
+    };
+    let Pattern::Add {
+        target,
+        counter,
+        sign,
+    } = match_add(&code[1..])?
+    else {
+        return None;
+    };
+    let distinct = counter == inner
+        && j == outer
+        && outer != inner
+        && outer != target
+        && source
+            .register()
+            .is_none_or(|s| s != target && s != inner && s != outer);
+    distinct.then_some(Pattern::Multiply {
+        target,
+        source,
+        inner,
+        outer,
+        sign,
+    })
+}
+
+fn match_divmod(code: &[Instruction]) -> Option<Pattern> {
+    use Instruction::*;
+    use Value::*;
+    let [Cpy(divisor, Register(remainder)), Jnz(Register(dividend), Immediate(2)), Jnz(Immediate(exit), Immediate(6)), Dec(Register(d)), Dec(Register(r)), Jnz(Register(j), Immediate(-4)), Inc(Register(quotient)), Jnz(Immediate(back), Immediate(-7))] =
+        *code.get(..8)?
+    else {
+        return None;
+    };
+    let distinct = exit != 0
+        && back != 0
+        && d == dividend
+        && r == remainder
+        && j == remainder
+        && dividend != remainder
+        && quotient != remainder
+        && quotient != dividend
+        && divisor
+            .register()
+            .is_none_or(|s| s != quotient && s != remainder && s != dividend);
+    distinct.then_some(Pattern::DivMod {
+        quotient,
+        dividend,
+        divisor,
+        remainder,
+    })
+}
+
+// find all the loops that can be replaced by a macro operation; the
+// longest pattern wins when several start at the same position
+pub fn optimize(code: &[Instruction]) -> Vec<Rewrite> {
+    (0..code.len())
+        .filter_map(|start| {
+            match_divmod(&code[start..])
+                .or_else(|| match_multiply(&code[start..]))
+                .or_else(|| match_add(&code[start..]))
+                .map(|pattern| Rewrite { start, pattern })
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone)]
+pub struct Computer {
+    code: Vec<Instruction>,
+    macros: Vec<Option<Pattern>>,
+    optimize: bool,
+    pub registers: [i64; 4],
+    ip: usize,
+    steps: usize,
+}
+
+impl Computer {
+    pub fn new(code: Vec<Instruction>) -> Self {
+        let mut computer = Self {
+            macros: vec![None; code.len()],
+            code,
+            optimize: true,
+            registers: [0; 4],
+            ip: 0,
+            steps: 0,
+        };
+        computer.reoptimize();
+        computer
+    }
+
+    pub fn without_optimizer(mut self) -> Self {
+        self.optimize = false;
+        self.reoptimize();
+        self
+    }
+
+    fn reoptimize(&mut self) {
+        self.macros.iter_mut().for_each(|m| *m = None);
+        if self.optimize {
+            for rewrite in optimize(&self.code) {
+                self.macros[rewrite.start] = Some(rewrite.pattern);
+            }
+        }
+    }
+
+    // the loops currently replaced by macro operations; this changes
+    // when tgl modifies the program
+    pub fn rewrites(&self) -> Vec<Rewrite> {
+        self.macros
+            .iter()
+            .enumerate()
+            .filter_map(|(start, m)| m.map(|pattern| Rewrite { start, pattern }))
+            .collect()
+    }
+
+    pub fn code(&self) -> &[Instruction] {
+        &self.code
+    }
+
+    // number of instructions and macro operations executed so far
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    fn get(&self, v: Value) -> i64 {
+        match v {
+            Value::Register(r) => self.registers[r],
+            Value::Immediate(i) => i,
+        }
+    }
+
+    fn jump(&mut self, offset: i64) {
+        self.ip = self
+            .ip
+            .checked_add_signed(offset as isize)
+            .unwrap_or(usize::MAX);
+    }
+
+    // returns false if the preconditions of the pattern do not hold, in
+    // which case the original instruction must be executed instead
+    fn execute_macro(&mut self, pattern: Pattern) -> bool {
+        let regs = &mut self.registers;
+        match pattern {
+            Pattern::Add {
+                target,
+                counter,
+                sign,
+            } => {
+                if regs[counter] <= 0 {
+                    return false;
+                }
+                regs[target] += sign * regs[counter];
+                regs[counter] = 0;
+            }
+            Pattern::Multiply {
+                target,
+                source,
+                inner,
+                outer,
+                sign,
+            } => {
+                let source = self.get(source);
+                let regs = &mut self.registers;
+                if source <= 0 || regs[outer] <= 0 {
+                    return false;
+                }
+                regs[target] += sign * source * regs[outer];
+                regs[inner] = 0;
+                regs[outer] = 0;
+            }
+            Pattern::DivMod {
+                quotient,
+                dividend,
+                divisor,
+                remainder,
+            } => {
+                let divisor = self.get(divisor);
+                let regs = &mut self.registers;
+                if divisor <= 0 || regs[dividend] < 0 {
+                    return false;
+                }
+                regs[quotient] += regs[dividend] / divisor;
+                regs[remainder] = divisor - regs[dividend] % divisor;
+                regs[dividend] = 0;
+            }
+        }
+        self.ip += pattern.len();
+        true
+    }
+
+    // executes one instruction or macro operation; returns None if the
+    // program has halted, Some(Some(v)) if it executed out v
+    fn step(&mut self) -> Option<Option<i64>> {
+        let instruction = *self.code.get(self.ip)?;
+        self.steps += 1;
+        if let Some(pattern) = self.macros[self.ip] {
+            if self.execute_macro(pattern) {
+                return Some(None);
+            }
+        }
+
+        use Instruction::*;
+        match instruction {
+            Cpy(v, Value::Register(r)) => self.registers[r] = self.get(v),
+            Inc(Value::Register(r)) => self.registers[r] += 1,
+            Dec(Value::Register(r)) => self.registers[r] -= 1,
+            Jnz(v, o) if self.get(v) != 0 => {
+                self.jump(self.get(o));
+                return Some(None);
+            }
+            Tgl(v) => {
+                let target = self.ip as i64 + self.get(v);
+                if (0..self.code.len() as i64).contains(&target) {
+                    let target = target as usize;
+                    self.code[target] = self.code[target].toggle();
+                    self.reoptimize();
+                }
+            }
+            Out(v) => {
+                self.ip += 1;
+                return Some(Some(self.get(v)));
+            }
+            // invalid instructions created by tgl are skipped
+            _ => {}
+        }
+        self.ip += 1;
+        Some(None)
+    }
+
+    pub fn run(&mut self) {
+        while self.step().is_some() {}
+    }
+
+    // runs until the next out instruction, or None if the program halts
+    pub fn next_output(&mut self) -> Option<i64> {
+        loop {
+            if let Some(v) = self.step()? {
+                return Some(v);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    static TGL_TEST: &str = "cpy 2 a
+tgl a
+tgl a
+tgl a
+cpy 1 a
+dec a
+dec a";
+
+    // shape of the 2016 day 23 inputs: a! + 96 * 91
+    static FACTORIAL: &str = "cpy a b
+dec b
+cpy a d
+cpy 0 a
+cpy b c
+inc a
+dec c
+jnz c -2
+dec d
+jnz d -5
+dec b
+cpy b c
+cpy c d
+dec d
+inc c
+jnz d -2
+tgl c
+cpy -16 c
+jnz 1 c
+cpy 96 c
+jnz 91 d
+inc a
+inc d
+jnz d -2
+inc c
+jnz c -5";
+
+    // shape of the 2016 day 25 inputs: outputs the bits of a + 2538
+    static CLOCK: &str = "cpy a d
+cpy 9 c
+cpy 282 b
+inc d
+dec b
+jnz b -2
+dec c
+jnz c -5
+cpy d a
+jnz 0 0
+cpy a b
+cpy 0 a
+cpy 2 c
+jnz b 2
+jnz 1 6
+dec b
+dec c
+jnz c -4
+inc a
+jnz 1 -7
+cpy 2 b
+jnz c 2
+jnz 1 4
+dec b
+dec c
+jnz 1 -4
+jnz 0 0
+out b
+jnz a -19
+jnz 1 -21";
+
+    fn run(input: &str, a: i64, optimize: bool) -> Computer {
+        let mut computer = Computer::new(parse(input).unwrap());
+        if !optimize {
+            computer = computer.without_optimizer();
+        }
+        computer.registers[0] = a;
+        computer.run();
+        computer
+    }
+
+    #[test]
+    fn test_toggle() {
+        assert_eq!(3, run(TGL_TEST, 0, true).registers[0]);
+        assert_eq!(3, run(TGL_TEST, 0, false).registers[0]);
+    }
+
+    #[test]
+    fn test_detect() {
+        let rewrites = optimize(&parse(FACTORIAL).unwrap());
+        assert_eq!(
+            vec![
+                "4..10: multiply: a += b * d; c = 0; d = 0",
+                "5..8: add: a += c; c = 0",
+                "13..16: add: c += d; d = 0",
+            ],
+            rewrites.iter().map(|r| r.to_string()).collect::<Vec<_>>()
+        );
+
+        let rewrites = optimize(&parse(CLOCK).unwrap());
+        assert_eq!(
+            vec![
+                "2..8: multiply: d += 282 * c; b = 0; c = 0",
+                "3..6: add: d += b; b = 0",
+                "12..20: divide: a += b / 2; c = 2 - b % 2; b = 0",
+            ],
+            rewrites.iter().map(|r| r.to_string()).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_factorial() {
+        let slow = run(FACTORIAL, 7, false);
+        let fast = run(FACTORIAL, 7, true);
+        assert_eq!(5040 + 96 * 91, slow.registers[0]);
+        assert_eq!(slow.registers, fast.registers);
+        assert!(fast.steps() * 100 < slow.steps());
+
+        // the tgl instructions turn the tail of the program into another
+        // multiplication, which must be picked up after the rewrite
+        assert!(fast
+            .rewrites()
+            .iter()
+            .any(|r| r.to_string() == "20..26: multiply: a += 91 * c; d = 0; c = 0"));
+
+        assert_eq!(479001600 + 96 * 91, run(FACTORIAL, 12, true).registers[0]);
+    }
+
+    #[test]
+    fn test_clock() {
+        for optimize in [false, true] {
+            let mut computer = Computer::new(parse(CLOCK).unwrap());
+            if !optimize {
+                computer = computer.without_optimizer();
+            }
+            computer.registers[0] = 192;
+            let output: Vec<_> = (0..12).map_while(|_| computer.next_output()).collect();
+            assert_eq!(vec![0, 1, 0, 1, 0, 1, 0, 1, 0, 1, 0, 1], output);
+        }
+    }
+
+    #[test]
+    fn test_fallback() {
+        // counter starts negative: the add loop must run instruction by
+        // instruction, and only becomes a macro once it is positive again
+        let mut computer = Computer::new(parse("inc a\ndec b\njnz b -2").unwrap());
+        let rewrites = |c: &Computer| {
+            c.rewrites()
+                .iter()
+                .map(|r| r.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(vec!["0..3: add: a += b; b = 0"], rewrites(&computer));
+        for _ in 0..3 {
+            computer.step();
+        }
+        assert_eq!([1, -1, 0, 0], computer.registers);
+
+        // same with a zero source for the multiplication: the macro is
+        // recognised but both it and the inner add loop are refused
+        let mut computer =
+            Computer::new(parse("cpy b c\ninc a\ndec c\njnz c -2\ndec d\njnz d -5").unwrap());
+        assert_eq!(
+            vec![
+                "0..6: multiply: a += b * d; c = 0; d = 0",
+                "1..4: add: a += c; c = 0",
+            ],
+            rewrites(&computer)
+        );
+        computer.registers[3] = 2;
+        for _ in 0..3 {
+            computer.step();
+        }
+        assert_eq!([1, 0, -1, 2], computer.registers);
+        assert_eq!(3, computer.steps());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = parse("cpy 41 a\ninc e\njnz a 2").unwrap_err();
+        assert_eq!((2, 5), (err.line, err.column));
+        assert_eq!("invalid operand e", err.message());
+
+        let err = parse("cpy 41 a\n\nmov a b").unwrap_err();
+        assert_eq!((3, 1), (err.line, err.column));
+        assert_eq!("unknown instruction", err.message());
+
+        let err = parse("cpy 41").unwrap_err();
+        assert_eq!(
+            "wrong number of operands at line 1, column 1",
+            err.to_string()
+        );
+    }
+}
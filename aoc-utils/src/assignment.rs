@@ -0,0 +1,220 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::{Debug, Display},
+};
+
+// Finds a one to one assignment of keys to values (opcode numbers to
+// operations, ticket columns to fields, allergens to ingredients) from the
+// candidate values left for each key. Singletons are propagated first;
+// when that stalls, the key with the fewest candidates is searched
+// exhaustively, so that ambiguous inputs are reported rather than silently
+// resolved.
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AssignmentError<K, V> {
+    // this key has no candidate left
+    Contradiction(K),
+    // no assignment satisfies all the constraints, although each key has
+    // candidates
+    NoSolution,
+    // at least two different assignments
+    Ambiguous(BTreeMap<K, V>, BTreeMap<K, V>),
+}
+
+impl<K: Debug, V: Debug> Display for AssignmentError<K, V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AssignmentError::Contradiction(k) => write!(f, "no candidate left for {k:?}"),
+            AssignmentError::NoSolution => write!(f, "no assignment satisfies the constraints"),
+            AssignmentError::Ambiguous(a, b) => {
+                write!(f, "ambiguous assignment: {a:?} and {b:?}")
+            }
+        }
+    }
+}
+
+impl<K: Debug, V: Debug> std::error::Error for AssignmentError<K, V> {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Assignment<K, V> {
+    candidates: BTreeMap<K, BTreeSet<V>>,
+}
+
+impl<K: Ord + Clone, V: Ord + Clone> Assignment<K, V> {
+    // every key starts with every value as candidate
+    pub fn new(keys: impl IntoIterator<Item = K>, values: impl IntoIterator<Item = V>) -> Self {
+        let values: BTreeSet<V> = values.into_iter().collect();
+        Self {
+            candidates: keys.into_iter().map(|k| (k, values.clone())).collect(),
+        }
+    }
+
+    pub fn from_candidates(candidates: impl IntoIterator<Item = (K, BTreeSet<V>)>) -> Self {
+        Self {
+            candidates: candidates.into_iter().collect(),
+        }
+    }
+
+    pub fn candidates(&self, key: &K) -> Option<&BTreeSet<V>> {
+        self.candidates.get(key)
+    }
+
+    // rules out one value for a key
+    pub fn remove(&mut self, key: &K, value: &V) {
+        if let Some(c) = self.candidates.get_mut(key) {
+            c.remove(value);
+        }
+    }
+
+    // keeps only the allowed values for a key
+    pub fn restrict<'a>(&mut self, key: &K, allowed: impl IntoIterator<Item = &'a V>)
+    where
+        V: 'a,
+    {
+        if let Some(c) = self.candidates.get_mut(key) {
+            let allowed: BTreeSet<_> = allowed.into_iter().collect();
+            c.retain(|v| allowed.contains(v));
+        }
+    }
+
+    // propagates singletons until nothing changes; returns the number of
+    // keys still undecided
+    fn eliminate(
+        candidates: &mut BTreeMap<K, BTreeSet<V>>,
+    ) -> Result<usize, AssignmentError<K, V>> {
+        let mut propagated = BTreeSet::new();
+        loop {
+            if let Some((k, _)) = candidates.iter().find(|(_, c)| c.is_empty()) {
+                return Err(AssignmentError::Contradiction(k.clone()));
+            }
+            let Some((key, value)) = candidates
+                .iter()
+                .find(|(k, c)| c.len() == 1 && !propagated.contains(*k))
+                .map(|(k, c)| (k.clone(), c.first().unwrap().clone()))
+            else {
+                break;
+            };
+            propagated.insert(key.clone());
+            for (k, c) in candidates.iter_mut() {
+                if *k != key {
+                    c.remove(&value);
+                }
+            }
+        }
+        Ok(candidates.values().filter(|c| c.len() > 1).count())
+    }
+
+    // collects up to 2 solutions
+    fn search(
+        mut candidates: BTreeMap<K, BTreeSet<V>>,
+        solutions: &mut Vec<BTreeMap<K, V>>,
+    ) -> Result<(), AssignmentError<K, V>> {
+        if Self::eliminate(&mut candidates)? == 0 {
+            solutions.push(
+                candidates
+                    .into_iter()
+                    .map(|(k, c)| (k, c.into_iter().next().unwrap()))
+                    .collect(),
+            );
+            return Ok(());
+        }
+        let (key, values) = candidates
+            .iter()
+            .filter(|(_, c)| c.len() > 1)
+            .min_by_key(|(_, c)| c.len())
+            .map(|(k, c)| (k.clone(), c.clone()))
+            .unwrap();
+        for value in values {
+            let mut next = candidates.clone();
+            next.insert(key.clone(), BTreeSet::from([value]));
+            // a dead end in one branch is not an error for the whole search
+            let _ = Self::search(next, solutions);
+            if solutions.len() > 1 {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn solve(self) -> Result<BTreeMap<K, V>, AssignmentError<K, V>> {
+        let mut solutions = vec![];
+        Self::search(self.candidates, &mut solutions)?;
+        match solutions.len() {
+            0 => Err(AssignmentError::NoSolution),
+            1 => Ok(solutions.pop().unwrap()),
+            _ => {
+                let b = solutions.pop().unwrap();
+                let a = solutions.pop().unwrap();
+                Err(AssignmentError::Ambiguous(a, b))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn set(values: &[&'static str]) -> BTreeSet<&'static str> {
+        values.iter().copied().collect()
+    }
+
+    #[test]
+    fn test_elimination() {
+        // 2020 day 21 example
+        let assignment = Assignment::from_candidates([
+            ("dairy", set(&["mxmxvkd"])),
+            ("fish", set(&["mxmxvkd", "sqjhc"])),
+            ("soy", set(&["sqjhc", "fvjkl"])),
+        ]);
+        let solution = assignment.solve().unwrap();
+        assert_eq!(
+            vec!["mxmxvkd", "sqjhc", "fvjkl"],
+            solution.values().copied().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_restrict_and_remove() {
+        let mut assignment = Assignment::new(0..3, ["class", "row", "seat"]);
+        assignment.restrict(&0, &["row"]);
+        assignment.remove(&1, &"seat");
+        assert_eq!(Some(&set(&["class", "row"])), assignment.candidates(&1));
+        let solution = assignment.solve().unwrap();
+        assert_eq!(
+            BTreeMap::from([(0, "row"), (1, "class"), (2, "seat")]),
+            solution
+        );
+    }
+
+    #[test]
+    fn test_backtracking() {
+        // elimination stalls; the search finds two solutions
+        let assignment = Assignment::from_candidates([
+            (0, BTreeSet::from([0, 1])),
+            (1, BTreeSet::from([0, 1])),
+            (2, BTreeSet::from([2, 3])),
+            (3, BTreeSet::from([2, 3])),
+        ]);
+        assert_eq!(
+            Err(AssignmentError::Ambiguous(
+                BTreeMap::from([(0, 0), (1, 1), (2, 2), (3, 3)]),
+                BTreeMap::from([(0, 0), (1, 1), (2, 3), (3, 2)])
+            )),
+            assignment.solve()
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let assignment = Assignment::from_candidates([("a", set(&["x"])), ("b", set(&["x"]))]);
+        assert_eq!(Err(AssignmentError::Contradiction("b")), assignment.solve());
+
+        let assignment = Assignment::from_candidates([
+            ("a", set(&["x", "y"])),
+            ("b", set(&["x", "y"])),
+            ("c", set(&["x", "y"])),
+        ]);
+        assert_eq!(Err(AssignmentError::NoSolution), assignment.solve());
+    }
+}
@@ -0,0 +1,570 @@
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+};
+
+use crate::grid::Grid;
+
+// Cellular automata. Automaton is the dense backend: the cells are in a
+// Vec, and the neighbours of each cell are precomputed as a list of
+// indices, so any topology fits: grids with Moore or von Neumann
+// neighbourhoods, wrapping edges, line of sight (2020 day 11), recursive
+// levels (2019 day 24)... Neighbours outside (None) have the background
+// state, which evolves with the rule too (the flipping infinite image of
+// 2021 day 20). Sparse is the backend for unbounded worlds of live cells,
+// in any number of dimensions (2020 day 17 and 24)
+
+// neighbourhoods on grids, each in reading order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Neighbourhood {
+    // the 4 orthogonal cells
+    VonNeumann,
+    // the 8 cells around
+    Moore,
+    // the 3x3 block around the cell, the cell included
+    Block,
+}
+
+impl Neighbourhood {
+    pub(crate) fn offsets(&self) -> Vec<(isize, isize)> {
+        (-1..=1)
+            .flat_map(|dy| (-1..=1).map(move |dx| (dx, dy)))
+            .filter(|&(dx, dy)| match self {
+                Neighbourhood::VonNeumann => dx * dy == 0 && dx != dy,
+                Neighbourhood::Moore => (dx, dy) != (0, 0),
+                Neighbourhood::Block => true,
+            })
+            .collect()
+    }
+}
+
+// the neighbours of levels copies of a width x height grid, each nested in
+// the centre cell of the one before (level 0 is the outermost). The cell
+// (x, y) of a level has index level * width * height + x + y * width. The
+// centre cells are holes without neighbours; the edges of the outermost
+// level and the centre of the innermost one touch the background
+pub fn recursive_levels(width: usize, height: usize, levels: usize) -> Vec<Vec<Option<usize>>> {
+    assert!(width % 2 == 1 && height % 2 == 1, "no centre cell");
+    let size = width * height;
+    let centre = (width / 2, height / 2);
+    let mut neighbours = vec![];
+    for level in 0..levels {
+        let cell = |x: usize, y: usize, level: Option<usize>| {
+            level
+                .filter(|&l| l < levels)
+                .map(|l| l * size + x + y * width)
+        };
+        for y in 0..height {
+            for x in 0..width {
+                if (x, y) == centre {
+                    neighbours.push(vec![]);
+                    continue;
+                }
+                let mut around = vec![];
+                for (dx, dy) in Neighbourhood::VonNeumann.offsets() {
+                    match (x.checked_add_signed(dx), y.checked_add_signed(dy)) {
+                        // the edge of the level inside
+                        (Some(nx), Some(ny)) if (nx, ny) == centre => {
+                            let inner = Some(level + 1);
+                            match (dx, dy) {
+                                (0, 1) => around.extend((0..width).map(|ix| cell(ix, 0, inner))),
+                                (0, -1) => {
+                                    around.extend((0..width).map(|ix| cell(ix, height - 1, inner)))
+                                }
+                                (1, 0) => around.extend((0..height).map(|iy| cell(0, iy, inner))),
+                                _ => {
+                                    around.extend((0..height).map(|iy| cell(width - 1, iy, inner)))
+                                }
+                            }
+                        }
+                        (Some(nx), Some(ny)) if nx < width && ny < height => {
+                            around.push(cell(nx, ny, Some(level)))
+                        }
+                        // the cell next to the centre of the level outside
+                        _ => around.push(cell(
+                            centre.0.saturating_add_signed(dx),
+                            centre.1.saturating_add_signed(dy),
+                            level.checked_sub(1),
+                        )),
+                    }
+                }
+                neighbours.push(around);
+            }
+        }
+    }
+    neighbours
+}
+
+#[derive(Debug, Clone)]
+pub struct Neighbours<'a, T> {
+    cells: &'a [T],
+    background: &'a T,
+    ids: std::slice::Iter<'a, Option<usize>>,
+}
+
+impl<'a, T> Iterator for Neighbours<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.ids
+            .next()
+            .map(|id| id.map_or(self.background, |id| &self.cells[id]))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.ids.size_hint()
+    }
+}
+
+impl<T> ExactSizeIterator for Neighbours<'_, T> {}
+
+// the generation where a state is seen again, period generations later
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    pub start: usize,
+    pub period: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct Automaton<T> {
+    cells: Vec<T>,
+    // the next generation, to avoid allocating at each step
+    next: Vec<T>,
+    neighbours: Vec<Vec<Option<usize>>>,
+    background: T,
+    // the neighbours of the background, all outside
+    outside: Vec<Option<usize>>,
+    // no cell has an outside neighbour on a torus, so the background
+    // never steps there
+    fixed_background: bool,
+    generation: usize,
+}
+
+impl<T: Clone + Eq> Automaton<T> {
+    pub fn new(cells: Vec<T>, neighbours: Vec<Vec<Option<usize>>>, background: T) -> Self {
+        assert_eq!(cells.len(), neighbours.len());
+        let outside = vec![None; neighbours.iter().map(Vec::len).max().unwrap_or(0)];
+        Self {
+            next: cells.clone(),
+            cells,
+            neighbours,
+            background,
+            outside,
+            fixed_background: false,
+            generation: 0,
+        }
+    }
+
+    // the cells of the grid (in the same order), with the background all
+    // around
+    pub fn from_grid<D>(grid: &Grid<T, D>, neighbourhood: Neighbourhood, background: T) -> Self {
+        Self::on_grid(grid, neighbourhood, background, false)
+    }
+
+    // the cells of the grid, the edges wrapping around. The background is
+    // unused, and stays the first cell
+    pub fn from_torus<D>(grid: &Grid<T, D>, neighbourhood: Neighbourhood) -> Self {
+        let background = grid.iter().next().expect("empty grid").clone();
+        Self {
+            fixed_background: true,
+            ..Self::on_grid(grid, neighbourhood, background, true)
+        }
+    }
+
+    fn on_grid<D>(
+        grid: &Grid<T, D>,
+        neighbourhood: Neighbourhood,
+        background: T,
+        wrap: bool,
+    ) -> Self {
+        let (width, height) = (grid.width() as isize, grid.height() as isize);
+        let offsets = neighbourhood.offsets();
+        let neighbours = (0..grid.width() * grid.height())
+            .map(|idx| {
+                let (x, y) = grid.idx_to_pos(idx);
+                offsets
+                    .iter()
+                    .map(|&(dx, dy)| {
+                        let (mut nx, mut ny) = (x as isize + dx, y as isize + dy);
+                        if wrap {
+                            (nx, ny) = (nx.rem_euclid(width), ny.rem_euclid(height));
+                        }
+                        ((0..width).contains(&nx) && (0..height).contains(&ny))
+                            .then(|| grid.pos_to_idx((nx as usize, ny as usize)))
+                    })
+                    .collect()
+            })
+            .collect();
+        Self::new(grid.iter().cloned().collect(), neighbours, background)
+    }
+
+    // the neighbours of each cell are the first cells seen in each of the 8
+    // directions, in reading order (or the background when seeing nothing)
+    pub fn line_of_sight<D>(grid: &Grid<T, D>, background: T, seen: impl Fn(&T) -> bool) -> Self {
+        let (width, height) = (grid.width(), grid.height());
+        let neighbours = (0..grid.width() * grid.height())
+            .map(|idx| {
+                Neighbourhood::Moore
+                    .offsets()
+                    .into_iter()
+                    .map(|(dx, dy)| {
+                        let mut pos = grid.idx_to_pos(idx);
+                        loop {
+                            pos = match (pos.0.checked_add_signed(dx), pos.1.checked_add_signed(dy))
+                            {
+                                (Some(x), Some(y)) if x < width && y < height => (x, y),
+                                _ => return None,
+                            };
+                            if seen(&grid[pos]) {
+                                return Some(grid.pos_to_idx(pos));
+                            }
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        Self::new(grid.iter().cloned().collect(), neighbours, background)
+    }
+
+    pub fn cells(&self) -> &[T] {
+        &self.cells
+    }
+
+    // to force some cells between steps
+    pub fn cells_mut(&mut self) -> &mut [T] {
+        &mut self.cells
+    }
+
+    pub fn background(&self) -> &T {
+        &self.background
+    }
+
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    pub fn count(&self, what: impl Fn(&T) -> bool) -> usize {
+        self.cells.iter().filter(|c| what(c)).count()
+    }
+
+    // the cells as a grid of the given width
+    pub fn to_grid<D>(&self, width: usize) -> Grid<T, D> {
+        Grid::from_vec(width, self.cells.clone())
+    }
+
+    // computes the next generation: rule gets each cell and its neighbours,
+    // in order. Whether anything changed
+    pub fn step(&mut self, rule: impl Fn(&T, Neighbours<'_, T>) -> T) -> bool {
+        let (cells, background) = (&self.cells, &self.background);
+        for (next, (cell, ids)) in self.next.iter_mut().zip(cells.iter().zip(&self.neighbours)) {
+            *next = rule(
+                cell,
+                Neighbours {
+                    cells,
+                    background,
+                    ids: ids.iter(),
+                },
+            );
+        }
+        let background = if self.fixed_background {
+            background.clone()
+        } else {
+            rule(
+                background,
+                Neighbours {
+                    cells,
+                    background,
+                    ids: self.outside.iter(),
+                },
+            )
+        };
+        let changed = self.next != self.cells || background != self.background;
+        std::mem::swap(&mut self.cells, &mut self.next);
+        self.background = background;
+        self.generation += 1;
+        changed
+    }
+
+    pub fn run(&mut self, generations: usize, rule: impl Fn(&T, Neighbours<'_, T>) -> T) {
+        for _ in 0..generations {
+            self.step(&rule);
+        }
+    }
+
+    // steps until nothing changes; the generation of the step that did not
+    // change anything
+    pub fn run_until_stable(&mut self, rule: impl Fn(&T, Neighbours<'_, T>) -> T) -> usize {
+        while self.step(&rule) {}
+        self.generation
+    }
+}
+
+impl<T: Clone + Eq + Hash> Automaton<T> {
+    // steps until a state comes back; the automaton is then at generation
+    // start + period, in the same state as at start
+    pub fn find_cycle(&mut self, rule: impl Fn(&T, Neighbours<'_, T>) -> T) -> Cycle {
+        let mut seen = HashMap::new();
+        loop {
+            let state = (self.cells.clone(), self.background.clone());
+            if let Some(&start) = seen.get(&state) {
+                return Cycle {
+                    start,
+                    period: self.generation - start,
+                };
+            }
+            seen.insert(state, self.generation);
+            self.step(&rule);
+        }
+    }
+
+    // runs up to the given generation, skipping the repeats once a state
+    // comes back
+    pub fn run_to(&mut self, generation: usize, rule: impl Fn(&T, Neighbours<'_, T>) -> T) {
+        let first = self.generation;
+        let mut seen = HashMap::new();
+        let mut states = vec![];
+        while self.generation < generation {
+            let state = (self.cells.clone(), self.background.clone());
+            if let Some(&start) = seen.get(&state) {
+                let period = self.generation - start;
+                (self.cells, self.background) =
+                    states.swap_remove(start + (generation - start) % period - first);
+                self.generation = generation;
+                return;
+            }
+            seen.insert(state.clone(), self.generation);
+            states.push(state);
+            self.step(&rule);
+        }
+    }
+}
+
+// The live cells of an unbounded world in N dimensions. The neighbourhood
+// is a list of offsets, which must be symmetric (-o for each o)
+#[derive(Debug, Clone)]
+pub struct Sparse<const N: usize> {
+    live: HashSet<[i64; N]>,
+    offsets: Vec<[i64; N]>,
+    generation: usize,
+}
+
+impl<const N: usize> Sparse<N> {
+    pub fn new(live: impl IntoIterator<Item = [i64; N]>, offsets: Vec<[i64; N]>) -> Self {
+        Self {
+            live: live.into_iter().collect(),
+            offsets,
+            generation: 0,
+        }
+    }
+
+    // the 3^N - 1 cells around
+    pub fn moore(live: impl IntoIterator<Item = [i64; N]>) -> Self {
+        let offsets = (0..3usize.pow(N as u32))
+            .map(|mut n| {
+                std::array::from_fn(|_| {
+                    let d = (n % 3) as i64 - 1;
+                    n /= 3;
+                    d
+                })
+            })
+            .filter(|o: &[i64; N]| o.iter().any(|&d| d != 0))
+            .collect();
+        Self::new(live, offsets)
+    }
+
+    // the 2N orthogonal cells
+    pub fn von_neumann(live: impl IntoIterator<Item = [i64; N]>) -> Self {
+        let offsets = (0..N)
+            .flat_map(|axis| {
+                [-1, 1].map(|d| std::array::from_fn(|i| if i == axis { d } else { 0 }))
+            })
+            .collect();
+        Self::new(live, offsets)
+    }
+
+    // the live cells of a grid at (x, y, 0, ...)
+    pub fn from_grid<T, D>(grid: &Grid<T, D>, live: impl Fn(&T) -> bool) -> Vec<[i64; N]> {
+        assert!(N >= 2, "a grid needs 2 dimensions");
+        grid.iter()
+            .enumerate()
+            .filter(|(_, t)| live(t))
+            .map(|(idx, _)| {
+                let (x, y) = grid.idx_to_pos(idx);
+                std::array::from_fn(|i| match i {
+                    0 => x as i64,
+                    1 => y as i64,
+                    _ => 0,
+                })
+            })
+            .collect()
+    }
+
+    pub fn live(&self) -> &HashSet<[i64; N]> {
+        &self.live
+    }
+
+    pub fn len(&self) -> usize {
+        self.live.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.live.is_empty()
+    }
+
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    // rule gets whether the cell is live and its count of live neighbours;
+    // dead cells without live neighbours must stay dead
+    pub fn step(&mut self, rule: impl Fn(bool, usize) -> bool) {
+        let mut counts: HashMap<[i64; N], usize> = HashMap::new();
+        for cell in &self.live {
+            for offset in &self.offsets {
+                let neighbour = std::array::from_fn(|i| cell[i] + offset[i]);
+                *counts.entry(neighbour).or_default() += 1;
+            }
+        }
+        let mut live: HashSet<_> = counts
+            .iter()
+            .filter(|(cell, &count)| rule(self.live.contains(*cell), count))
+            .map(|(cell, _)| *cell)
+            .collect();
+        live.extend(
+            self.live
+                .iter()
+                .filter(|cell| !counts.contains_key(*cell) && rule(true, 0)),
+        );
+        self.live = live;
+        self.generation += 1;
+    }
+
+    pub fn run(&mut self, generations: usize, rule: impl Fn(bool, usize) -> bool) {
+        for _ in 0..generations {
+            self.step(&rule);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::grid::MaxDist;
+
+    fn life(alive: &bool, neighbours: Neighbours<'_, bool>) -> bool {
+        let count = neighbours.filter(|&&n| n).count();
+        count == 3 || (*alive && count == 2)
+    }
+
+    #[test]
+    fn test_dense() {
+        // a blinker
+        let grid: Grid<bool, MaxDist> = Grid::from_vec(3, [false, true, false].repeat(3));
+        let mut automaton = Automaton::from_grid(&grid, Neighbourhood::Moore, false);
+        assert!(automaton.step(life));
+        assert_eq!(
+            &[false, false, false, true, true, true, false, false, false],
+            automaton.cells()
+        );
+        assert_eq!(
+            Cycle {
+                start: 1,
+                period: 2
+            },
+            automaton.find_cycle(life)
+        );
+        automaton.run_to(1001, life);
+        assert_eq!(3, automaton.count(|&c| c));
+        assert!(automaton.cells()[3]);
+
+        // a block is stable
+        let grid: Grid<bool, MaxDist> = Grid::from_vec(2, vec![true; 4]);
+        let mut automaton = Automaton::from_grid(&grid, Neighbourhood::Moore, false);
+        assert_eq!(1, automaton.run_until_stable(life));
+
+        // the background flips, as all dark turns bright and back
+        let flip = |c: &bool, n: Neighbours<'_, bool>| {
+            assert_eq!(9, n.len());
+            !*c
+        };
+        let mut automaton = Automaton::from_grid(&grid, Neighbourhood::Block, false);
+        automaton.run(3, flip);
+        assert!(*automaton.background());
+        assert_eq!(0, automaton.count(|&c| c));
+
+        // on a torus, a glider comes back where it started
+        let mut cells = vec![false; 25];
+        for idx in [1, 7, 10, 11, 12] {
+            cells[idx] = true;
+        }
+        let grid: Grid<bool, MaxDist> = Grid::from_vec(5, cells.clone());
+        let mut automaton = Automaton::from_torus(&grid, Neighbourhood::Moore);
+        automaton.run(20, life);
+        assert_eq!(&cells[..], automaton.cells());
+
+        // a block in the corner of a torus is stable, though the unused
+        // background (its first cell) would die alone
+        let mut cells = vec![false; 16];
+        for idx in [0, 1, 4, 5] {
+            cells[idx] = true;
+        }
+        let grid: Grid<bool, MaxDist> = Grid::from_vec(4, cells);
+        let mut automaton = Automaton::from_torus(&grid, Neighbourhood::Moore);
+        assert_eq!(1, automaton.run_until_stable(life));
+        assert!(*automaton.background());
+        assert_eq!(
+            Cycle {
+                start: 1,
+                period: 1
+            },
+            automaton.find_cycle(life)
+        );
+    }
+
+    #[test]
+    fn test_neighbourhoods() {
+        let grid: Grid<u8, MaxDist> = Grid::try_from("a.b\n...\nc.d").unwrap();
+        let automaton = Automaton::line_of_sight(&grid, b'#', |&c| c != b'.');
+        let centre: Vec<u8> = Neighbours {
+            cells: automaton.cells(),
+            background: automaton.background(),
+            ids: automaton.neighbours[4].iter(),
+        }
+        .copied()
+        .collect();
+        assert_eq!(vec![b'a', b'#', b'b', b'#', b'#', b'c', b'#', b'd'], centre);
+        // seen from a, through the dots
+        let from_a: Vec<_> = automaton.neighbours[0].iter().flatten().collect();
+        assert_eq!(vec![&2, &6, &8], from_a);
+
+        let levels = recursive_levels(5, 5, 3);
+        // 2019 day 24: 19 (or N on the middle level) has 4 neighbours, 14
+        // (or I) and 18 (or M) are next to the inner level
+        assert_eq!(4, levels[25 + 18].len());
+        assert_eq!(8, levels[25 + 13].len());
+        assert!(levels[25 + 13].contains(&Some(50 + 4)));
+        assert!(levels[25 + 13].contains(&Some(50 + 24)));
+        assert_eq!(Some(13), levels[25 + 14][2]);
+        assert!(levels[25 + 12].is_empty());
+        // the outermost level touches the background
+        assert!(levels[0].contains(&None));
+    }
+
+    #[test]
+    fn test_sparse() {
+        // 2020 day 17
+        let grid: Grid<u8, MaxDist> = Grid::try_from(".#.\n..#\n###").unwrap();
+        let rule = |alive, count| count == 3 || (alive && count == 2);
+        let mut cubes: Sparse<3> = Sparse::moore(Sparse::from_grid(&grid, |&b| b == b'#'));
+        assert_eq!(5, cubes.len());
+        cubes.run(6, rule);
+        assert_eq!(112, cubes.len());
+        let mut cubes: Sparse<4> = Sparse::moore(Sparse::from_grid(&grid, |&b| b == b'#'));
+        cubes.run(6, rule);
+        assert_eq!(848, cubes.len());
+        assert_eq!(6, cubes.generation());
+
+        let cross: Sparse<2> = Sparse::von_neumann([[0, 0]]);
+        assert_eq!(4, cross.offsets.len());
+    }
+}
@@ -0,0 +1,349 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt::Display,
+    hash::Hash,
+};
+
+// Turn based battles (2015 day 22, 2018 days 15 and 24): the order units
+// play in, target selection, effects that last a number of turns, a log of
+// the events to replay a battle, and the search for the smallest boost
+// that wins
+
+// sorting key of (x, y) positions in reading order: top to bottom, then
+// left to right
+pub fn reading_order(pos: (usize, usize)) -> (usize, usize) {
+    (pos.1, pos.0)
+}
+
+// the indices of units in playing order, smallest key first; use Reverse
+// for highest first (initiative, effective power). Ties keep their index
+// order
+pub fn turn_order<T, K: Ord>(units: &[T], key: impl Fn(&T) -> K) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..units.len()).collect();
+    order.sort_by_key(|&i| key(&units[i]));
+    order
+}
+
+// Each attacker, in order, picks among the targets not picked yet the one
+// it prefers most; prefer gives None for the targets an attacker would
+// not pick at all. Returns the (attacker, target) pairs, in order
+pub fn select_targets<K: Ord>(
+    order: &[usize],
+    targets: usize,
+    prefer: impl Fn(usize, usize) -> Option<K>,
+) -> Vec<(usize, usize)> {
+    let mut picked = vec![false; targets];
+    let mut selection = vec![];
+    for &attacker in order {
+        let best = (0..targets)
+            .filter(|&t| !picked[t])
+            .filter_map(|t| prefer(attacker, t).map(|k| (k, t)))
+            .max_by(|(k1, _), (k2, _)| k1.cmp(k2));
+        if let Some((_, target)) = best {
+            picked[target] = true;
+            selection.push((attacker, target));
+        }
+    }
+    selection
+}
+
+fn distances_from<P, I>(start: P, neighbours: &impl Fn(P) -> I) -> HashMap<P, usize>
+where
+    P: Copy + Eq + Hash,
+    I: IntoIterator<Item = P>,
+{
+    let mut distances = HashMap::from([(start, 0)]);
+    let mut queue = VecDeque::from([start]);
+    while let Some(pos) = queue.pop_front() {
+        let d = distances[&pos];
+        for n in neighbours(pos) {
+            distances.entry(n).or_insert_with(|| {
+                queue.push_back(n);
+                d + 1
+            });
+        }
+    }
+    distances
+}
+
+// The step of a unit at from toward the nearest square in range, the
+// first in reading order on ties, along the shortest path whose first
+// step comes first in reading order (2018 day 15). neighbours gives the
+// open squares next to a square. None if the unit is in range already or
+// cannot reach any square in range
+pub fn first_step<I>(
+    from: (usize, usize),
+    in_range: impl Fn((usize, usize)) -> bool,
+    neighbours: impl Fn((usize, usize)) -> I,
+) -> Option<(usize, usize)>
+where
+    I: IntoIterator<Item = (usize, usize)>,
+{
+    let (distance, _, target) = distances_from(from, &neighbours)
+        .into_iter()
+        .filter(|&(pos, _)| in_range(pos))
+        .map(|(pos, d)| (d, reading_order(pos), pos))
+        .min()?;
+    if distance == 0 {
+        return None;
+    }
+    let back = distances_from(target, &neighbours);
+    neighbours(from)
+        .into_iter()
+        .filter_map(|n| back.get(&n).map(|&d| (d, reading_order(n), n)))
+        .min()
+        .map(|(_, _, n)| n)
+}
+
+// Effects that last a number of turns, such as spells (2015 day 22). Each
+// tick applies every active effect once then counts it down; an effect
+// still active after the tick holds for the rest of the turn
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Effects<E> {
+    active: Vec<(E, u32)>,
+}
+
+impl<E> Default for Effects<E> {
+    fn default() -> Self {
+        Self { active: vec![] }
+    }
+}
+
+impl<E: PartialEq> Effects<E> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.active.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.active.is_empty()
+    }
+
+    pub fn is_active(&self, effect: &E) -> bool {
+        self.remaining(effect).is_some()
+    }
+
+    // the turns left for effect, if active
+    pub fn remaining(&self, effect: &E) -> Option<u32> {
+        self.active
+            .iter()
+            .find(|(e, _)| e == effect)
+            .map(|&(_, turns)| turns)
+    }
+
+    // false (and no change) if the effect is already active
+    pub fn start(&mut self, effect: E, turns: u32) -> bool {
+        assert!(turns > 0, "an effect lasts at least a turn");
+        if self.is_active(&effect) {
+            return false;
+        }
+        self.active.push((effect, turns));
+        true
+    }
+
+    // apply gets each effect and the turns it has left after this one
+    pub fn tick(&mut self, mut apply: impl FnMut(&E, u32)) {
+        for (effect, turns) in &mut self.active {
+            *turns -= 1;
+            apply(effect, *turns);
+        }
+        self.active.retain(|&(_, turns)| turns > 0);
+    }
+}
+
+// The events of a battle, by round, when recording; battles are
+// deterministic so the same battle gives the same log
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Replay {
+    recording: bool,
+    round: usize,
+    events: Vec<(usize, String)>,
+}
+
+impl Replay {
+    pub fn recording() -> Self {
+        Self {
+            recording: true,
+            ..Self::default()
+        }
+    }
+
+    // a log that records nothing
+    pub fn off() -> Self {
+        Self::default()
+    }
+
+    // event is only formatted when recording
+    pub fn record(&mut self, event: impl FnOnce() -> String) {
+        if self.recording {
+            self.events.push((self.round, event()));
+        }
+    }
+
+    pub fn next_round(&mut self) {
+        self.round += 1;
+    }
+
+    pub fn round(&self) -> usize {
+        self.round
+    }
+
+    pub fn events(&self) -> &[(usize, String)] {
+        &self.events
+    }
+
+    // the events of a round
+    pub fn round_events(&self, round: usize) -> impl Iterator<Item = &str> {
+        self.events
+            .iter()
+            .filter(move |(r, _)| *r == round)
+            .map(|(_, e)| e.as_str())
+    }
+}
+
+impl Display for Replay {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (round, event) in &self.events {
+            writeln!(f, "{round}: {event}")?;
+        }
+        Ok(())
+    }
+}
+
+// The smallest boost, from low, for which wins holds: doubling the boost
+// until a win, then trying the boosts since the last loss in order. Wins
+// need not hold for all the boosts above the smallest one (a stalemate
+// counts as a loss), so the last window is not bisected. None if no boost
+// wins before overflowing
+pub fn minimal_boost(low: u64, mut wins: impl FnMut(u64) -> bool) -> Option<u64> {
+    let (mut lo, mut hi) = (low, low);
+    let mut step = 1u64;
+    while !wins(hi) {
+        lo = hi.checked_add(1)?;
+        hi = lo.checked_add(step)?;
+        step = step.checked_mul(2)?;
+    }
+    (lo..=hi).find(|&boost| boost == hi || wins(boost))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::cmp::Reverse;
+
+    #[test]
+    fn test_order_and_targets() {
+        // (effective power, initiative) of 2018 day 24
+        let groups = [(76619, 2), (24725, 3), (92916, 1), (53820, 4)];
+        let order = turn_order(&groups, |&(power, initiative)| {
+            (Reverse(power), Reverse(initiative))
+        });
+        assert_eq!(vec![2, 0, 3, 1], order);
+        assert_eq!(vec![3, 1, 0, 2], turn_order(&groups, |g| Reverse(g.1)));
+
+        // groups 0 and 1 against 2 and 3, preferring the strongest
+        let side = |g: usize| g / 2;
+        let selection = select_targets(&order, groups.len(), |a, t| {
+            (side(a) != side(t)).then_some(groups[t].0)
+        });
+        assert_eq!(vec![(2, 0), (0, 2), (3, 1), (1, 3)], selection);
+
+        let mut positions = vec![(3, 1), (1, 2), (2, 1)];
+        positions.sort_by_key(|&p| reading_order(p));
+        assert_eq!(vec![(2, 1), (3, 1), (1, 2)], positions);
+    }
+
+    #[test]
+    fn test_first_step() {
+        // 2018 day 15: the elf moves right, toward the top target
+        let map = ["#######", "#E..G.#", "#...#.#", "#.G.#G#", "#######"];
+        let open = |(x, y): (usize, usize)| map[y].as_bytes()[x] == b'.';
+        let neighbours = |(x, y): (usize, usize)| {
+            [(x, y - 1), (x - 1, y), (x + 1, y), (x, y + 1)]
+                .into_iter()
+                .filter(move |&p| open(p))
+        };
+        let goblins: [(usize, usize); 3] = [(4, 1), (2, 3), (5, 3)];
+        let in_range = |p: (usize, usize)| {
+            goblins
+                .iter()
+                .any(|&(x, y)| x.abs_diff(p.0) + y.abs_diff(p.1) == 1)
+        };
+        assert_eq!(Some((2, 1)), first_step((1, 1), in_range, neighbours));
+        // in range already
+        assert_eq!(None, first_step((3, 1), in_range, neighbours));
+        // cannot reach
+        assert_eq!(None, first_step((1, 1), |_| false, neighbours));
+
+        // ties between first steps broken in reading order
+        let map = ["#######", "#.E...#", "#.....#", "#...G.#", "#######"];
+        let open = |(x, y): (usize, usize)| map[y].as_bytes()[x] == b'.';
+        let neighbours = |(x, y): (usize, usize)| {
+            [(x, y - 1), (x - 1, y), (x + 1, y), (x, y + 1)]
+                .into_iter()
+                .filter(move |&p| open(p))
+        };
+        let in_range = |p: (usize, usize)| p.0.abs_diff(4) + p.1.abs_diff(3) == 1;
+        assert_eq!(Some((3, 1)), first_step((2, 1), in_range, neighbours));
+    }
+
+    #[test]
+    fn test_effects() {
+        let mut effects = Effects::new();
+        assert!(effects.start("shield", 6));
+        assert!(effects.start("poison", 2));
+        assert!(!effects.start("shield", 6));
+        let mut damage = 0;
+        for _ in 0..2 {
+            effects.tick(|&e, _| {
+                if e == "poison" {
+                    damage += 3;
+                }
+            });
+        }
+        assert_eq!(6, damage);
+        assert!(!effects.is_active(&"poison"));
+        assert_eq!(Some(4), effects.remaining(&"shield"));
+        assert!(effects.start("poison", 6));
+        assert_eq!(2, effects.len());
+    }
+
+    #[test]
+    fn test_replay() {
+        let mut replay = Replay::recording();
+        replay.record(|| "first".to_owned());
+        replay.next_round();
+        replay.record(|| "second".to_owned());
+        replay.record(|| "third".to_owned());
+        assert_eq!(
+            vec!["second", "third"],
+            replay.round_events(1).collect::<Vec<_>>()
+        );
+        assert_eq!("0: first\n1: second\n1: third\n", replay.to_string());
+
+        let mut off = Replay::off();
+        off.record(|| unreachable!());
+        assert!(off.events().is_empty());
+    }
+
+    #[test]
+    fn test_minimal_boost() {
+        for threshold in [0, 1, 7, 1570, 123456] {
+            let mut calls = 0;
+            let boost = minimal_boost(0, |b| {
+                calls += 1;
+                b >= threshold
+            });
+            assert_eq!(Some(threshold), boost);
+            assert!(calls <= threshold + 64);
+        }
+        assert_eq!(Some(4), minimal_boost(4, |_| true));
+        assert_eq!(None, minimal_boost(0, |_| false));
+        assert_eq!(None, minimal_boost(u64::MAX, |_| false));
+        // 12 wins, but 13 to 15 are stalemates in the window 11..=19
+        assert_eq!(Some(12), minimal_boost(0, |b| b == 12 || b >= 16));
+    }
+}
@@ -0,0 +1,623 @@
+use std::{
+    fmt::Display,
+    ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not},
+};
+
+use crate::automaton::Neighbourhood;
+use crate::grid::Grid;
+
+// A grid of booleans packed as bits, for the hot loops over whole grids
+// (2021 day 25, 2022 day 23, 2023 day 14, 2024 day 6). Each row is a run of
+// u64 words, bit i of word j being column 64 * j + i, so rows up to 64 (or
+// 128) wide are one (or two) words, and wider ones a wide bitset. Whole
+// grids are shifted, combined as sets and counted a word at a time;
+// neighbours are counted by adding shifted copies of the grid as bit
+// planes. The bits past the width are always 0
+
+const BITS: usize = 64;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BitGrid {
+    width: usize,
+    height: usize,
+    // words per row
+    stride: usize,
+    words: Vec<u64>,
+}
+
+impl BitGrid {
+    pub fn new(width: usize, height: usize) -> Self {
+        let stride = width.div_ceil(BITS);
+        Self {
+            width,
+            height,
+            stride,
+            words: vec![0; stride * height],
+        }
+    }
+
+    // every cell set
+    pub fn full(width: usize, height: usize) -> Self {
+        let mut full = Self::new(width, height);
+        full.words.fill(!0);
+        full.trim();
+        full
+    }
+
+    // the cells of row y set
+    pub fn row_mask(width: usize, height: usize, y: usize) -> Self {
+        let mut mask = Self::new(width, height);
+        mask.row_mut(y).fill(!0);
+        mask.trim();
+        mask
+    }
+
+    // the cells of column x set
+    pub fn column_mask(width: usize, height: usize, x: usize) -> Self {
+        let mut mask = Self::new(width, height);
+        for y in 0..height {
+            mask.insert((x, y));
+        }
+        mask
+    }
+
+    pub fn from_grid<T, D>(grid: &Grid<T, D>, set: impl Fn(&T) -> bool) -> Self {
+        let mut bits = Self::new(grid.width(), grid.height());
+        for (idx, t) in grid.iter().enumerate() {
+            if set(t) {
+                bits.insert(grid.idx_to_pos(idx));
+            }
+        }
+        bits
+    }
+
+    pub fn to_grid<D>(&self) -> Grid<bool, D> {
+        let cells = (0..self.height)
+            .flat_map(|y| (0..self.width).map(move |x| self.get((x, y))))
+            .collect();
+        Grid::from_vec(self.width, cells)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    // the bits of the last word of a row inside the grid
+    fn last_word_mask(&self) -> u64 {
+        match self.width % BITS {
+            0 => !0,
+            extra => (1 << extra) - 1,
+        }
+    }
+
+    // clears the bits past the width
+    fn trim(&mut self) {
+        let mask = self.last_word_mask();
+        for row in self.words.chunks_mut(self.stride.max(1)) {
+            if let Some(last) = row.last_mut() {
+                *last &= mask;
+            }
+        }
+    }
+
+    // the index of the word holding pos, and its bit there
+    fn bit(&self, (x, y): (usize, usize)) -> (usize, u64) {
+        assert!(
+            x < self.width && y < self.height,
+            "{:?} outside the grid",
+            (x, y)
+        );
+        (y * self.stride + x / BITS, 1 << (x % BITS))
+    }
+
+    pub fn get(&self, pos: (usize, usize)) -> bool {
+        let (idx, bit) = self.bit(pos);
+        self.words[idx] & bit != 0
+    }
+
+    pub fn set(&mut self, pos: (usize, usize), value: bool) {
+        let (idx, bit) = self.bit(pos);
+        if value {
+            self.words[idx] |= bit;
+        } else {
+            self.words[idx] &= !bit;
+        }
+    }
+
+    // whether pos was not set yet
+    pub fn insert(&mut self, pos: (usize, usize)) -> bool {
+        let (idx, bit) = self.bit(pos);
+        let was = self.words[idx] & bit != 0;
+        self.words[idx] |= bit;
+        !was
+    }
+
+    // whether pos was set
+    pub fn remove(&mut self, pos: (usize, usize)) -> bool {
+        let (idx, bit) = self.bit(pos);
+        let was = self.words[idx] & bit != 0;
+        self.words[idx] &= !bit;
+        was
+    }
+
+    pub fn clear(&mut self) {
+        self.words.fill(0);
+    }
+
+    // the cells set
+    pub fn count(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|&w| w == 0)
+    }
+
+    // the words of row y, lowest columns first
+    pub fn row(&self, y: usize) -> &[u64] {
+        &self.words[y * self.stride..(y + 1) * self.stride]
+    }
+
+    fn row_mut(&mut self, y: usize) -> &mut [u64] {
+        &mut self.words[y * self.stride..(y + 1) * self.stride]
+    }
+
+    // the positions of the cells set, in reading order
+    pub fn iter(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.words.iter().enumerate().flat_map(move |(idx, &word)| {
+            let (y, base) = (idx / self.stride, idx % self.stride * BITS);
+            let mut word = word;
+            std::iter::from_fn(move || {
+                (word != 0).then(|| {
+                    let bit = word.trailing_zeros() as usize;
+                    word &= word - 1;
+                    (base + bit, y)
+                })
+            })
+        })
+    }
+
+    // the top left and bottom right corners of the smallest box holding
+    // the cells set
+    pub fn bounds(&self) -> Option<((usize, usize), (usize, usize))> {
+        let rows: Vec<usize> = (0..self.height)
+            .filter(|&y| self.row(y).iter().any(|&w| w != 0))
+            .collect();
+        let (&top, &bottom) = (rows.first()?, rows.last()?);
+        let mut columns = vec![0; self.stride];
+        for &y in &rows {
+            for (c, w) in columns.iter_mut().zip(self.row(y)) {
+                *c |= w;
+            }
+        }
+        let first = columns.iter().position(|&w| w != 0)?;
+        let last = columns.iter().rposition(|&w| w != 0)?;
+        let left = first * BITS + columns[first].trailing_zeros() as usize;
+        let right = last * BITS + BITS - 1 - columns[last].leading_zeros() as usize;
+        Some(((left, top), (right, bottom)))
+    }
+
+    // the cells moved by (dx, dy); the ones moved outside are lost
+    pub fn shifted(&self, dx: isize, dy: isize) -> Self {
+        let mut shifted = Self::new(self.width, self.height);
+        for y in 0..self.height {
+            if let Some(from) = y.checked_add_signed(-dy).filter(|&f| f < self.height) {
+                shift_row(self.row(from), shifted.row_mut(y), dx);
+            }
+        }
+        shifted.trim();
+        shifted
+    }
+
+    // the cells moved by (dx, dy), wrapping around the edges
+    pub fn wrapped(&self, dx: isize, dy: isize) -> Self {
+        if self.width == 0 || self.height == 0 {
+            return self.clone();
+        }
+        let (width, height) = (self.width as isize, self.height as isize);
+        let (dx, dy) = (dx.rem_euclid(width), dy.rem_euclid(height));
+        let mut wrapped = self.shifted(dx, dy);
+        wrapped |= &self.shifted(dx - width, dy);
+        wrapped |= &self.shifted(dx, dy - height);
+        wrapped |= &self.shifted(dx - width, dy - height);
+        wrapped
+    }
+
+    // the grid grown by margin on every side, for cells spreading out
+    pub fn expanded(&self, margin: usize) -> Self {
+        let mut expanded = Self::new(self.width + 2 * margin, self.height + 2 * margin);
+        for y in 0..self.height {
+            shift_row(self.row(y), expanded.row_mut(y + margin), margin as isize);
+        }
+        expanded
+    }
+
+    // Moves the cells a step at a time in direction (dx, dy) until stopped
+    // by the edge, walls or cells stopped already, as rocks rolling on a
+    // tilted platform (2023 day 14). In place, a row at a time: rows are
+    // slid one after the other from the edge, or each slid on its own
+    pub fn slide(&mut self, walls: &BitGrid, (dx, dy): (isize, isize)) {
+        assert_eq!(
+            (self.width, self.height),
+            (walls.width, walls.height),
+            "grids of different sizes"
+        );
+        let stride = self.stride;
+        match (dx, dy) {
+            (0, -1 | 1) => {
+                let rows: Vec<usize> = if dy < 0 {
+                    (1..self.height).collect()
+                } else {
+                    (0..self.height.saturating_sub(1)).rev().collect()
+                };
+                for y in rows {
+                    for i in 0..stride {
+                        let (mut from, mut moving) = (y, self.words[y * stride + i]);
+                        while let Some(to) =
+                            from.checked_add_signed(dy).filter(|&t| t < self.height)
+                        {
+                            let (here, there) = (from * stride + i, to * stride + i);
+                            moving &= !(self.words[there] | walls.words[there]);
+                            if moving == 0 {
+                                break;
+                            }
+                            self.words[here] &= !moving;
+                            self.words[there] |= moving;
+                            from = to;
+                        }
+                    }
+                }
+            }
+            (-1 | 1, 0) => {
+                let mut free = vec![0; stride];
+                let mut ahead = vec![0; stride];
+                for y in 0..self.height {
+                    loop {
+                        let row = &self.words[y * stride..(y + 1) * stride];
+                        for ((f, r), w) in free.iter_mut().zip(row).zip(walls.row(y)) {
+                            *f = !(r | w);
+                        }
+                        if let Some(last) = free.last_mut() {
+                            *last &= self.last_word_mask();
+                        }
+                        // the cells with a free cell in front of them
+                        shift_row(&free, &mut ahead, -dx);
+                        let row = &mut self.words[y * stride..(y + 1) * stride];
+                        for (a, r) in ahead.iter_mut().zip(row.iter()) {
+                            *a &= r;
+                        }
+                        if ahead.iter().all(|&a| a == 0) {
+                            break;
+                        }
+                        for (r, a) in row.iter_mut().zip(&ahead) {
+                            *r ^= a;
+                        }
+                        shift_row(&ahead, &mut free, dx);
+                        for (r, f) in row.iter_mut().zip(&free) {
+                            *r |= f;
+                        }
+                    }
+                }
+            }
+            _ => panic!("cannot slide by {:?}", (dx, dy)),
+        }
+    }
+
+    // for each cell, how many of its neighbours are set; neighbours
+    // outside the grid are not
+    pub fn neighbour_counts(&self, neighbourhood: Neighbourhood) -> NeighbourCounts {
+        let mut planes = [(); 4].map(|_| Self::new(self.width, self.height));
+        for (dx, dy) in neighbourhood.offsets() {
+            // the cells whose neighbour at (dx, dy) is set, added to the
+            // count one plane at a time
+            let mut carry = self.shifted(-dx, -dy);
+            for plane in &mut planes {
+                let sum = &*plane ^ &carry;
+                carry &= plane;
+                *plane = sum;
+            }
+        }
+        NeighbourCounts { planes }
+    }
+}
+
+// src moved by dx bits into dst, which may be wider
+fn shift_row(src: &[u64], dst: &mut [u64], dx: isize) {
+    let (words, bits) = (dx.unsigned_abs() / BITS, dx.unsigned_abs() % BITS);
+    let word = |idx: Option<usize>| idx.and_then(|i| src.get(i)).copied().unwrap_or(0);
+    for (idx, d) in dst.iter_mut().enumerate() {
+        *d = if dx >= 0 {
+            let from = idx.checked_sub(words);
+            let carry = if bits == 0 {
+                0
+            } else {
+                word(from.and_then(|f| f.checked_sub(1))) >> (BITS - bits)
+            };
+            word(from) << bits | carry
+        } else {
+            let from = idx + words;
+            let carry = if bits == 0 {
+                0
+            } else {
+                word(Some(from + 1)) << (BITS - bits)
+            };
+            word(Some(from)) >> bits | carry
+        };
+    }
+}
+
+// The number of neighbours of each cell, as the bits of 4 planes
+#[derive(Debug, Clone)]
+pub struct NeighbourCounts {
+    planes: [BitGrid; 4],
+}
+
+impl NeighbourCounts {
+    // the cells with exactly n neighbours set
+    pub fn exactly(&self, n: usize) -> BitGrid {
+        let [first, ..] = &self.planes;
+        let mut cells = BitGrid::full(first.width, first.height);
+        for (bit, plane) in self.planes.iter().enumerate() {
+            if n >> bit & 1 == 1 {
+                cells &= plane;
+            } else {
+                cells &= &!plane;
+            }
+        }
+        cells
+    }
+
+    // the cells whose number of neighbours set satisfies rule
+    pub fn matching(&self, rule: impl Fn(usize) -> bool) -> BitGrid {
+        let [first, ..] = &self.planes;
+        let mut cells = BitGrid::new(first.width, first.height);
+        for n in (0..=9).filter(|&n| rule(n)) {
+            cells |= &self.exactly(n);
+        }
+        cells
+    }
+
+    // the cells with at least a neighbour set
+    pub fn any(&self) -> BitGrid {
+        let [first, rest @ ..] = &self.planes;
+        rest.iter().fold(first.clone(), |any, plane| any | plane)
+    }
+}
+
+macro_rules! bit_op {
+    ($($op:ident $f:ident $assign:ident $fa:ident),*) => {
+        $(impl $assign<&BitGrid> for BitGrid {
+            fn $fa(&mut self, rhs: &BitGrid) {
+                assert_eq!(
+                    (self.width, self.height),
+                    (rhs.width, rhs.height),
+                    "grids of different sizes"
+                );
+                for (w, r) in self.words.iter_mut().zip(&rhs.words) {
+                    $assign::$fa(w, r);
+                }
+            }
+        }
+
+        impl $op<&BitGrid> for BitGrid {
+            type Output = BitGrid;
+
+            fn $f(mut self, rhs: &BitGrid) -> BitGrid {
+                $assign::$fa(&mut self, rhs);
+                self
+            }
+        }
+
+        impl $op<&BitGrid> for &BitGrid {
+            type Output = BitGrid;
+
+            fn $f(self, rhs: &BitGrid) -> BitGrid {
+                $op::$f(self.clone(), rhs)
+            }
+        })*
+    };
+}
+
+bit_op!(
+    BitAnd bitand BitAndAssign bitand_assign,
+    BitOr bitor BitOrAssign bitor_assign,
+    BitXor bitxor BitXorAssign bitxor_assign
+);
+
+impl Not for &BitGrid {
+    type Output = BitGrid;
+
+    fn not(self) -> BitGrid {
+        let mut not = self.clone();
+        for w in &mut not.words {
+            *w = !*w;
+        }
+        not.trim();
+        not
+    }
+}
+
+impl<D> From<&Grid<bool, D>> for BitGrid {
+    fn from(grid: &Grid<bool, D>) -> Self {
+        Self::from_grid(grid, |&b| b)
+    }
+}
+
+impl<D> From<&BitGrid> for Grid<bool, D> {
+    fn from(bits: &BitGrid) -> Self {
+        bits.to_grid()
+    }
+}
+
+impl Display for BitGrid {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for y in 0..self.height {
+            if y > 0 {
+                writeln!(f)?;
+            }
+            for x in 0..self.width {
+                write!(f, "{}", if self.get((x, y)) { '#' } else { '.' })?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::grid::Taxicab;
+
+    fn parse(picture: &str) -> BitGrid {
+        let grid: Grid<u8, Taxicab> = Grid::try_from(picture).unwrap();
+        BitGrid::from_grid(&grid, |&b| b == b'#')
+    }
+
+    #[test]
+    fn test_bits() {
+        // wider than two words
+        let mut bits = BitGrid::new(130, 3);
+        assert!(bits.is_empty());
+        assert!(bits.insert((0, 0)));
+        assert!(!bits.insert((0, 0)));
+        bits.set((64, 1), true);
+        bits.set((129, 2), true);
+        bits.set((70, 2), true);
+        assert_eq!(4, bits.count());
+        assert!(bits.get((64, 1)) && !bits.get((63, 1)));
+        assert_eq!(
+            vec![(0, 0), (64, 1), (70, 2), (129, 2)],
+            bits.iter().collect::<Vec<_>>()
+        );
+        assert_eq!(Some(((0, 0), (129, 2))), bits.bounds());
+        assert!(bits.remove((0, 0)));
+        assert_eq!(Some(((64, 1), (129, 2))), bits.bounds());
+        bits.clear();
+        assert_eq!(None, bits.bounds());
+
+        assert_eq!(130, BitGrid::row_mask(130, 3, 1).count());
+        assert_eq!(390, BitGrid::full(130, 3).count());
+        let column = BitGrid::column_mask(130, 3, 100);
+        assert_eq!(
+            vec![(100, 0), (100, 1), (100, 2)],
+            column.iter().collect::<Vec<_>>()
+        );
+        assert_eq!(387, (!&column).count());
+    }
+
+    #[test]
+    fn test_shifts() {
+        let mut bits = BitGrid::new(130, 4);
+        for pos in [(0, 0), (63, 1), (64, 2), (129, 3)] {
+            bits.insert(pos);
+        }
+        let right = bits.shifted(1, 1);
+        assert_eq!(
+            vec![(1, 1), (64, 2), (65, 3)],
+            right.iter().collect::<Vec<_>>()
+        );
+        let left = bits.shifted(-65, 0);
+        assert_eq!(vec![(64, 3)], left.iter().collect::<Vec<_>>());
+        assert_eq!(
+            bits,
+            bits.shifted(70, 0).shifted(-70, 0) | &bits.shifted(-60, 0).shifted(60, 0)
+        );
+
+        let wrapped = bits.wrapped(1, -1);
+        assert_eq!(
+            vec![(64, 0), (65, 1), (0, 2), (1, 3)],
+            wrapped.iter().collect::<Vec<_>>()
+        );
+        assert_eq!(bits, wrapped.wrapped(-131, 5));
+        // nothing to wrap around in an empty grid
+        for empty in [BitGrid::new(0, 4), BitGrid::new(130, 0)] {
+            assert_eq!(empty, empty.wrapped(1, -1));
+        }
+
+        let expanded = bits.expanded(2);
+        assert_eq!((134, 8), (expanded.width(), expanded.height()));
+        assert_eq!(
+            vec![(2, 2), (65, 3), (66, 4), (131, 5)],
+            expanded.iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_slide() {
+        // 2023 day 14
+        let platform = "O....#....\nO.OO#....#\n.....##...\nOO.#O....O\n.O.....O#.\n\
+                        O.#..O.#.#\n..O..#O..O\n.......O..\n#....###..\n#OO..#....";
+        let grid: Grid<u8, Taxicab> = Grid::try_from(platform).unwrap();
+        let mut rocks = BitGrid::from_grid(&grid, |&b| b == b'O');
+        let cubes = BitGrid::from_grid(&grid, |&b| b == b'#');
+        rocks.slide(&cubes, (0, -1));
+        let load: usize = rocks.iter().map(|(_, y)| 10 - y).sum();
+        assert_eq!(136, load);
+        for direction in [(-1, 0), (0, 1), (1, 0)] {
+            rocks.slide(&cubes, direction);
+        }
+        let cycle = ".....#....\n....#...O#\n...OO##...\n.OO#......\n.....OOO#.\n\
+                     .O#...O#.#\n....O#....\n......OOOO\n#...O###..\n#..OO#....";
+        let expected: Grid<u8, Taxicab> = Grid::try_from(cycle).unwrap();
+        assert_eq!(BitGrid::from_grid(&expected, |&b| b == b'O'), rocks);
+
+        // across words, and in any order
+        let mut bits = BitGrid::new(130, 2);
+        for pos in [(0, 0), (70, 0), (129, 0), (3, 1)] {
+            bits.insert(pos);
+        }
+        let mut walls = BitGrid::new(130, 2);
+        walls.insert((65, 0));
+        bits.slide(&walls, (-1, 0));
+        assert_eq!(
+            vec![(0, 0), (66, 0), (67, 0), (0, 1)],
+            bits.iter().collect::<Vec<_>>()
+        );
+        bits.slide(&walls, (1, 0));
+        assert_eq!(
+            vec![(64, 0), (128, 0), (129, 0), (129, 1)],
+            bits.iter().collect::<Vec<_>>()
+        );
+        // (129, 0) is stopped by (129, 1)
+        bits.slide(&walls, (0, 1));
+        assert_eq!(
+            vec![(129, 0), (64, 1), (128, 1), (129, 1)],
+            bits.iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_neighbour_counts() {
+        // a glider, as in the life automata
+        let glider = parse(".#...\n..#..\n###..\n.....\n.....");
+        let counts = glider.neighbour_counts(Neighbourhood::Moore);
+        let next = (&glider & &counts.exactly(2)) | &counts.exactly(3);
+        assert_eq!(parse(".....\n#.#..\n.##..\n.#...\n....."), next);
+        assert_eq!(
+            next,
+            counts.matching(|n| n == 3) | &(&glider & &counts.matching(|n| n == 2))
+        );
+
+        let full = BitGrid::full(3, 3);
+        let counts = full.neighbour_counts(Neighbourhood::Block);
+        assert!(counts.exactly(0).is_empty());
+        assert!(counts.exactly(9).get((1, 1)));
+        assert_eq!(4, counts.exactly(4).count());
+        let counts = full.neighbour_counts(Neighbourhood::VonNeumann);
+        assert_eq!(vec![(1, 1)], counts.exactly(4).iter().collect::<Vec<_>>());
+        assert_eq!(9, counts.any().count());
+    }
+
+    #[test]
+    fn test_conversions() {
+        let bits = parse("#..\n.#.");
+        assert_eq!("#..\n.#.", bits.to_string());
+        let grid: Grid<bool, Taxicab> = (&bits).into();
+        assert!(grid[(1, 1)] && !grid[(0, 1)]);
+        assert_eq!(bits, BitGrid::from(&grid));
+    }
+}
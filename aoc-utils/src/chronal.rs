@@ -0,0 +1,320 @@
+use std::{fmt::Display, str::FromStr};
+
+use crate::{
+    error::{parse_token, ParseError},
+    symbolic::{self, Expr, Op as ExprOp},
+};
+
+// 3-bit chronospatial computer of 2024 day 17, with a concrete
+// interpreter, a symbolic one that decompiles one iteration of the main
+// loop, and a backward search for the value of register A that produces
+// a given output
+
+// instructions run before giving up on a program that does not halt
+const MAX_STEPS: usize = 1 << 20;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Op {
+    Adv,
+    Bxl,
+    Bst,
+    Jnz,
+    Bxc,
+    Out,
+    Bdv,
+    Cdv,
+}
+
+impl From<u8> for Op {
+    fn from(value: u8) -> Self {
+        match value & 7 {
+            0 => Op::Adv,
+            1 => Op::Bxl,
+            2 => Op::Bst,
+            3 => Op::Jnz,
+            4 => Op::Bxc,
+            5 => Op::Out,
+            6 => Op::Bdv,
+            _ => Op::Cdv,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Computer {
+    pub a: u64,
+    pub b: u64,
+    pub c: u64,
+    pub program: Vec<u8>,
+}
+
+impl FromStr for Computer {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut computer = Computer {
+            a: 0,
+            b: 0,
+            c: 0,
+            program: vec![],
+        };
+        for line in s.lines() {
+            if let Some(n) = line.strip_prefix("Register A: ") {
+                computer.a = parse_token(s, n.trim())?;
+            } else if let Some(n) = line.strip_prefix("Register B: ") {
+                computer.b = parse_token(s, n.trim())?;
+            } else if let Some(n) = line.strip_prefix("Register C: ") {
+                computer.c = parse_token(s, n.trim())?;
+            } else if let Some(p) = line.strip_prefix("Program: ") {
+                computer.program = p
+                    .trim()
+                    .split(',')
+                    .map(|n| parse_token(s, n))
+                    .collect::<Result<_, _>>()?;
+            } else if !line.trim().is_empty() {
+                return Err(ParseError::at(s, line, "cannot parse line"));
+            }
+        }
+        Ok(computer)
+    }
+}
+
+// one pass through the program, up to the jump back to the start
+#[derive(Debug, Clone)]
+pub struct Iteration {
+    pub outputs: Vec<Expr>,
+    pub registers: [Expr; 3],
+}
+
+impl Display for Iteration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut lines = vec![];
+        lines.extend(self.outputs.iter().map(|o| format!("out {o}")));
+        for (name, e) in ["a", "b", "c"].iter().zip(&self.registers) {
+            if e.to_string() != *name {
+                lines.push(format!("{name} = {e}"));
+            }
+        }
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl Computer {
+    // output of the program when started with the given value in A, or
+    // None if it reads the reserved combo operand 7 or is still running
+    // after MAX_STEPS instructions
+    pub fn run(&self, a: u64) -> Option<Vec<u8>> {
+        let mut registers = [a, self.b, self.c];
+        let mut output = vec![];
+        let mut ip = 0;
+        for _ in 0..MAX_STEPS {
+            if ip + 1 >= self.program.len() {
+                return Some(output);
+            }
+            let operand = self.program[ip + 1];
+            // only the instructions that take a combo operand read it
+            let combo = |registers: &[u64; 3]| match operand {
+                0..=3 => Some(operand as u64),
+                4..=6 => Some(registers[operand as usize - 4]),
+                _ => None,
+            };
+            let shifted = |registers: &[u64; 3]| {
+                combo(registers).map(|s| {
+                    u32::try_from(s)
+                        .ok()
+                        .and_then(|s| registers[0].checked_shr(s))
+                        .unwrap_or(0)
+                })
+            };
+            match Op::from(self.program[ip]) {
+                Op::Adv => registers[0] = shifted(&registers)?,
+                Op::Bxl => registers[1] ^= operand as u64,
+                Op::Bst => registers[1] = combo(&registers)? % 8,
+                Op::Jnz => {
+                    if registers[0] != 0 {
+                        ip = operand as usize;
+                        continue;
+                    }
+                }
+                Op::Bxc => registers[1] ^= registers[2],
+                Op::Out => output.push((combo(&registers)? % 8) as u8),
+                Op::Bdv => registers[1] = shifted(&registers)?,
+                Op::Cdv => registers[2] = shifted(&registers)?,
+            }
+            ip += 2;
+        }
+        None
+    }
+
+    // symbolic execution of the program until the first jump, in terms
+    // of the registers at the start of the iteration; None if it reads the
+    // reserved combo operand 7
+    pub fn decompile(&self) -> Option<Iteration> {
+        let mut registers = ["a", "b", "c"].map(|n| symbolic::var(n, 0, i64::MAX));
+        let mut outputs = vec![];
+        for chunk in self.program.chunks_exact(2) {
+            let operand = chunk[1];
+            let combo = || match operand {
+                0..=3 => Some(symbolic::constant(operand as i64)),
+                4..=6 => Some(registers[operand as usize - 4].clone()),
+                _ => None,
+            };
+            let mod8 = |e| symbolic::rem(e, symbolic::constant(8)).expect("8 is not zero");
+            match Op::from(chunk[0]) {
+                Op::Adv => registers[0] = symbolic::shr(registers[0].clone(), combo()?),
+                Op::Bxl => {
+                    registers[1] =
+                        symbolic::xor(registers[1].clone(), symbolic::constant(operand as i64))
+                }
+                Op::Bst => registers[1] = mod8(combo()?),
+                Op::Jnz => break,
+                Op::Bxc => registers[1] = symbolic::xor(registers[1].clone(), registers[2].clone()),
+                Op::Out => outputs.push(mod8(combo()?)),
+                Op::Bdv => registers[1] = symbolic::shr(registers[0].clone(), combo()?),
+                Op::Cdv => registers[2] = symbolic::shr(registers[0].clone(), combo()?),
+            }
+        }
+        Some(Iteration { outputs, registers })
+    }
+
+    // number of bits the main loop drops from A on each iteration, if the
+    // program has the expected shape: a single loop ending in jnz 0 that
+    // shifts A by a constant
+    fn shift(&self) -> Option<u32> {
+        let n = self.program.len();
+        if n < 2 || self.program[n - 2..] != [3, 0] {
+            return None;
+        }
+        let iteration = self.decompile()?;
+        let ExprOp::Shr(a, k) = &iteration.registers[0].op else {
+            return None;
+        };
+        (matches!(&a.op, ExprOp::Var(name) if name == "a"))
+            .then(|| k.as_const())
+            .flatten()
+            .filter(|k| (1..=8).contains(k))
+            .map(|k| k as u32)
+    }
+
+    // smallest value of A for which the program outputs target. As the
+    // output of each iteration only depends on the bits of A that have not
+    // been shifted out yet, the last output constrains the highest bits,
+    // so A is built backward a few bits at a time
+    pub fn find_a(&self, target: &[u8]) -> Option<u64> {
+        // an empty output has no meaningful A
+        if target.is_empty() {
+            return None;
+        }
+        let k = self.shift()?;
+        let mut candidates = vec![0];
+        for len in 1..=target.len() {
+            let suffix = &target[target.len() - len..];
+            candidates = candidates
+                .iter()
+                .flat_map(|c| (0..1 << k).map(move |low| c << k | low))
+                .filter(|a| self.run(*a).as_deref() == Some(suffix))
+                .collect();
+        }
+        candidates.into_iter().min()
+    }
+
+    pub fn find_quine(&self) -> Option<u64> {
+        self.find_a(&self.program)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    static TEST_INPUT: &str = "Register A: 729
+Register B: 0
+Register C: 0
+
+Program: 0,1,5,4,3,0";
+
+    static QUINE: &str = "Register A: 2024
+Register B: 0
+Register C: 0
+
+Program: 0,3,5,4,3,0";
+
+    // shape of the puzzle inputs
+    static INPUT: &str = "Register A: 0
+Register B: 0
+Register C: 0
+
+Program: 2,4,1,1,7,5,1,5,4,0,0,3,5,5,3,0";
+
+    #[test]
+    fn test_run() {
+        let computer: Computer = TEST_INPUT.parse().unwrap();
+        assert_eq!(
+            Some(vec![4, 6, 3, 5, 6, 3, 5, 2, 1, 0]),
+            computer.run(computer.a)
+        );
+
+        // 7 is a valid literal operand
+        let computer: Computer =
+            "Register A: 0\nRegister B: 0\nRegister C: 0\n\nProgram: 2,4,1,7,5,5"
+                .parse()
+                .unwrap();
+        assert_eq!(Some(vec![7]), computer.run(0));
+        assert_eq!(Some(vec![2]), computer.run(5));
+
+        // but not a valid combo operand
+        let computer: Computer = "Register A: 0\n\nProgram: 2,7,5,4".parse().unwrap();
+        assert_eq!(None, computer.run(0));
+        assert!(computer.decompile().is_none());
+        assert_eq!(None, computer.find_a(&[0]));
+
+        // jumps back to the start as long as A is not zero
+        let computer: Computer = "Register A: 0\n\nProgram: 3,0".parse().unwrap();
+        assert_eq!(Some(vec![]), computer.run(0));
+        assert_eq!(None, computer.run(1));
+    }
+
+    #[test]
+    fn test_decompile() {
+        let computer: Computer = QUINE.parse().unwrap();
+        assert_eq!(
+            "out (a >> 3) % 8\na = a >> 3",
+            computer.decompile().unwrap().to_string()
+        );
+
+        let computer: Computer = INPUT.parse().unwrap();
+        assert_eq!(
+            "out (((a % 8) ^ 4) ^ (a >> ((a % 8) ^ 1))) % 8\na = a >> 3\nb = ((a % 8) ^ 4) ^ (a >> ((a % 8) ^ 1))\nc = a >> ((a % 8) ^ 1)",
+            computer.decompile().unwrap().to_string()
+        );
+    }
+
+    #[test]
+    fn test_quine() {
+        let computer: Computer = QUINE.parse().unwrap();
+        assert_eq!(Some(117440), computer.find_quine());
+        assert_eq!(None, TEST_INPUT.parse::<Computer>().unwrap().find_quine());
+    }
+
+    #[test]
+    fn test_find_a() {
+        let computer: Computer = INPUT.parse().unwrap();
+        let target = computer.run(202367025818154).unwrap();
+        let a = computer.find_a(&target).unwrap();
+        assert!(a <= 202367025818154);
+        assert_eq!(Some(target), computer.run(a));
+        assert_eq!(None, computer.find_a(&[]));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = "Register A: 729\nRegister B: 0\n\nProgram: 0,1,5,x,3,0"
+            .parse::<Computer>()
+            .unwrap_err();
+        assert_eq!((4, 16), (err.line, err.column));
+        assert_eq!("invalid digit found in string", err.message());
+
+        let err = "Register D: 0".parse::<Computer>().unwrap_err();
+        assert_eq!("cannot parse line at line 1, column 1", err.to_string());
+    }
+}
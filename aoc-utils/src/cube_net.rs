@@ -0,0 +1,436 @@
+use std::collections::VecDeque;
+
+use thiserror::Error;
+
+use crate::{grid::Grid, vec3::Vec3};
+
+// Maps made of the six faces of a cube laid flat (2022 day 22), and the
+// two ways of walking off their edges: wrapping around the flat map, or
+// folding the map into a cube. Any of the 11 nets works, with faces of any
+// size: the faces are folded one by one from the first one (in reading
+// order), which gives each face its place on the cube, and the edges are
+// derived from that.
+//
+// Positions are (col, row) in the whole map; faces are numbered in
+// reading order
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Facing {
+    Right,
+    Down,
+    Left,
+    Up,
+}
+
+pub const FACINGS: [Facing; 4] = [Facing::Right, Facing::Down, Facing::Left, Facing::Up];
+
+impl Facing {
+    pub fn turn_left(self) -> Self {
+        FACINGS[(self as usize + 3) % 4]
+    }
+
+    pub fn turn_right(self) -> Self {
+        FACINGS[(self as usize + 1) % 4]
+    }
+
+    pub fn reverse(self) -> Self {
+        FACINGS[(self as usize + 2) % 4]
+    }
+
+    // (col, row) offset
+    pub fn delta(self) -> (isize, isize) {
+        match self {
+            Facing::Right => (1, 0),
+            Facing::Down => (0, 1),
+            Facing::Left => (-1, 0),
+            Facing::Up => (0, -1),
+        }
+    }
+
+    // the password value of the facing
+    pub fn score(self) -> usize {
+        self as usize
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Fold {
+    // leaving the map on one side comes back on the other side
+    Flat,
+    // leaving a face goes to the face next to it on the cube
+    Cube,
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum NetError {
+    #[error("{0} tiles cannot make the 6 square faces of a cube")]
+    TileCount(usize),
+    #[error("the map is not made of whole faces of size {0}")]
+    PartialFace(usize),
+    #[error("the faces do not fold into a cube")]
+    NotANet,
+}
+
+// Where a face ends up on the cube: its outward normal, and the directions
+// of its columns and rows
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Frame {
+    normal: Vec3,
+    right: Vec3,
+    down: Vec3,
+}
+
+impl Frame {
+    fn direction(&self, facing: Facing) -> Vec3 {
+        match facing {
+            Facing::Right => self.right,
+            Facing::Down => self.down,
+            Facing::Left => -self.right,
+            Facing::Up => -self.down,
+        }
+    }
+
+    // the frame of the face next to this one on the net, folded away
+    fn fold(&self, facing: Facing) -> Self {
+        let Frame {
+            normal,
+            right,
+            down,
+        } = *self;
+        match facing {
+            Facing::Right => Frame {
+                normal: right,
+                right: -normal,
+                down,
+            },
+            Facing::Down => Frame {
+                normal: down,
+                right,
+                down: -normal,
+            },
+            Facing::Left => Frame {
+                normal: -right,
+                right: normal,
+                down,
+            },
+            Facing::Up => Frame {
+                normal: -down,
+                right,
+                down: normal,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CubeNet {
+    fold: Fold,
+    size: usize,
+    // in faces
+    width: usize,
+    height: usize,
+    // top left corner of each face, in faces
+    faces: Vec<(usize, usize)>,
+    frames: Vec<Frame>,
+    // face and facing reached by leaving each face in each facing
+    edges: Vec<[(usize, Facing); 4]>,
+}
+
+impl CubeNet {
+    // is_tile tells the cells of the map from the blank around it
+    pub fn new<T, D>(
+        grid: &Grid<T, D>,
+        is_tile: impl Fn(&T) -> bool,
+        fold: Fold,
+    ) -> Result<Self, NetError> {
+        let tiles = grid.iter().filter(|t| is_tile(t)).count();
+        let size = (1..)
+            .take_while(|s| 6 * s * s <= tiles)
+            .last()
+            .filter(|s| 6 * s * s == tiles)
+            .ok_or(NetError::TileCount(tiles))?;
+        if grid.width() % size != 0 || grid.height() % size != 0 {
+            return Err(NetError::PartialFace(size));
+        }
+        let (width, height) = (grid.width() / size, grid.height() / size);
+
+        let mut faces = vec![];
+        for row in 0..height {
+            for col in 0..width {
+                let cells = (0..size * size)
+                    .filter(|i| is_tile(&grid[(col * size + i % size, row * size + i / size)]))
+                    .count();
+                match cells {
+                    0 => {}
+                    c if c == size * size => faces.push((col, row)),
+                    _ => return Err(NetError::PartialFace(size)),
+                }
+            }
+        }
+
+        let mut net = CubeNet {
+            fold,
+            size,
+            width,
+            height,
+            faces,
+            frames: vec![],
+            edges: vec![],
+        };
+        net.frames = net.fold_faces()?;
+        net.edges = (0..6)
+            .map(|f| FACINGS.map(|facing| net.edge(f, facing)))
+            .collect();
+        Ok(net)
+    }
+
+    // the face at (col, row), in faces
+    fn face_at(&self, col: usize, row: usize) -> Option<usize> {
+        self.faces.iter().position(|&p| p == (col, row))
+    }
+
+    // frames of the faces, from a walk over the net starting from the
+    // first face
+    fn fold_faces(&self) -> Result<Vec<Frame>, NetError> {
+        let mut frames = vec![None; 6];
+        frames[0] = Some(Frame {
+            normal: Vec3::new(0, 0, 1),
+            right: Vec3::new(1, 0, 0),
+            down: Vec3::new(0, 1, 0),
+        });
+        let mut queue = VecDeque::from([0]);
+        while let Some(f) = queue.pop_front() {
+            let frame = frames[f].unwrap();
+            let (col, row) = self.faces[f];
+            for facing in FACINGS {
+                let (dc, dr) = facing.delta();
+                let next = col
+                    .checked_add_signed(dc)
+                    .zip(row.checked_add_signed(dr))
+                    .and_then(|(c, r)| self.face_at(c, r));
+                if let Some(g) = next {
+                    if frames[g].is_none() {
+                        frames[g] = Some(frame.fold(facing));
+                        queue.push_back(g);
+                    }
+                }
+            }
+        }
+        let frames: Vec<Frame> = frames
+            .into_iter()
+            .collect::<Option<_>>()
+            .ok_or(NetError::NotANet)?;
+        // two faces folded on the same side of the cube
+        for (i, f) in frames.iter().enumerate() {
+            if frames[i + 1..].iter().any(|g| g.normal == f.normal) {
+                return Err(NetError::NotANet);
+            }
+        }
+        Ok(frames)
+    }
+
+    // face reached when leaving face f in facing, and the facing there
+    fn edge(&self, f: usize, facing: Facing) -> (usize, Facing) {
+        match self.fold {
+            Fold::Flat => {
+                let (mut col, mut row) = self.faces[f];
+                let (dc, dr) = facing.delta();
+                loop {
+                    col = (col as isize + dc).rem_euclid(self.width as isize) as usize;
+                    row = (row as isize + dr).rem_euclid(self.height as isize) as usize;
+                    if let Some(g) = self.face_at(col, row) {
+                        return (g, facing);
+                    }
+                }
+            }
+            Fold::Cube => {
+                let frame = &self.frames[f];
+                let direction = frame.direction(facing);
+                let g = self
+                    .frames
+                    .iter()
+                    .position(|g| g.normal == direction)
+                    .unwrap();
+                // going over the edge, then away from the normal of f
+                let entering = FACINGS
+                    .into_iter()
+                    .find(|&e| self.frames[g].direction(e) == -frame.normal)
+                    .unwrap();
+                (g, entering)
+            }
+        }
+    }
+
+    pub fn face_size(&self) -> usize {
+        self.size
+    }
+
+    // top left corner of each face, in cells
+    pub fn faces(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.faces
+            .iter()
+            .map(|&(col, row)| (col * self.size, row * self.size))
+    }
+
+    pub fn face_of(&self, pos: (usize, usize)) -> Option<usize> {
+        self.face_at(pos.0 / self.size, pos.1 / self.size)
+    }
+
+    // face and facing reached by leaving face in facing
+    pub fn neighbour(&self, face: usize, facing: Facing) -> (usize, Facing) {
+        self.edges[face][facing as usize]
+    }
+
+    // The cell after pos in facing, and the facing there: the next cell
+    // on the same face, or the cell across the edge
+    pub fn wrap(&self, pos: (usize, usize), facing: Facing) -> ((usize, usize), Facing) {
+        let f = self.face_of(pos).expect("position outside the map");
+        let s = self.size as i64;
+        let (col, row) = ((pos.0 % self.size) as i64, (pos.1 % self.size) as i64);
+        let (dc, dr) = facing.delta();
+        let (nc, nr) = (col + dc as i64, row + dr as i64);
+        if (0..s).contains(&nc) && (0..s).contains(&nr) {
+            return (
+                (pos.0.wrapping_add_signed(dc), pos.1.wrapping_add_signed(dr)),
+                facing,
+            );
+        }
+
+        let (g, entering) = self.neighbour(f, facing);
+        let (col, row) = match self.fold {
+            // same place on the opposite side
+            Fold::Flat => (nc.rem_euclid(s), nr.rem_euclid(s)),
+            // in doubled coordinates centered on the cube (so that cell
+            // centers are integers), the cell across the edge is one step
+            // further and one step down from the edge
+            Fold::Cube => {
+                let (from, to) = (&self.frames[f], &self.frames[g]);
+                let center = from.normal * s
+                    + from.right * (2 * col + 1 - s)
+                    + from.down * (2 * row + 1 - s);
+                let p = center + from.direction(facing) - from.normal;
+                (
+                    (p.dot(&to.right) + s - 1) / 2,
+                    (p.dot(&to.down) + s - 1) / 2,
+                )
+            }
+        };
+        let (gc, gr) = self.faces[g];
+        (
+            (gc * self.size + col as usize, gr * self.size + row as usize),
+            entering,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::grid::Taxicab;
+
+    // the 11 nets, each face a single cell
+    static NETS: [&str; 11] = [
+        "#...\n####\n#...",
+        "#...\n####\n.#..",
+        "#...\n####\n..#.",
+        "#...\n####\n...#",
+        ".#..\n####\n.#..",
+        ".#..\n####\n..#.",
+        "##..\n.###\n...#",
+        "##..\n.###\n.#..",
+        "##..\n.###\n..#.",
+        "###..\n..###",
+        "##...\n.##..\n..##.",
+    ];
+
+    // the net with each cell blown up to a face of the given size
+    fn scale(net: &str, size: usize) -> Grid<bool, Taxicab> {
+        let rows: Vec<Vec<bool>> = net
+            .lines()
+            .map(|l| l.bytes().map(|b| b == b'#').collect())
+            .collect();
+        let width = rows.iter().map(|r| r.len()).max().unwrap();
+        let cells = rows
+            .iter()
+            .flat_map(|r| {
+                (0..size).flat_map(move |_| {
+                    (0..width * size).map(move |c| r.get(c / size).copied().unwrap_or(false))
+                })
+            })
+            .collect();
+        Grid::from_vec(width * size, cells)
+    }
+
+    #[test]
+    fn test_example() {
+        // 2022 day 22
+        let grid = scale("..#.\n###.\n..##", 4);
+        let cube = CubeNet::new(&grid, |&t| t, Fold::Cube).unwrap();
+        assert_eq!(4, cube.face_size());
+        assert_eq!(
+            vec![(8, 0), (0, 4), (4, 4), (8, 4), (8, 8), (12, 8)],
+            cube.faces().collect::<Vec<_>>()
+        );
+        // A to B, C to D
+        assert_eq!(((14, 8), Facing::Down), cube.wrap((11, 5), Facing::Right));
+        assert_eq!(((1, 7), Facing::Up), cube.wrap((10, 11), Facing::Down));
+        assert_eq!(((11, 5), Facing::Left), cube.wrap((14, 8), Facing::Up));
+        assert_eq!(((6, 4), Facing::Right), cube.wrap((5, 4), Facing::Right));
+
+        let flat = CubeNet::new(&grid, |&t| t, Fold::Flat).unwrap();
+        assert_eq!(((0, 5), Facing::Right), flat.wrap((11, 5), Facing::Right));
+        assert_eq!(((10, 0), Facing::Down), flat.wrap((10, 11), Facing::Down));
+        assert_eq!(((5, 7), Facing::Up), flat.wrap((5, 4), Facing::Up));
+    }
+
+    #[test]
+    fn test_nets() {
+        for net in NETS {
+            for size in 1..=3 {
+                let grid = scale(net, size);
+                let cube = CubeNet::new(&grid, |&t| t, Fold::Cube).unwrap();
+                for (col, row) in cube.faces() {
+                    for i in 0..size * size {
+                        let pos = (col + i % size, row + i / size);
+                        for facing in FACINGS {
+                            // going back returns to the same cell
+                            let (next, entering) = cube.wrap(pos, facing);
+                            assert_eq!(
+                                (pos, facing.reverse()),
+                                cube.wrap(next, entering.reverse()),
+                                "{net} {size} {pos:?} {facing:?}"
+                            );
+                        }
+                    }
+                }
+                // each face meets four different faces, none of them itself
+                // or the opposite face
+                for f in 0..6 {
+                    let mut neighbours: Vec<_> = FACINGS
+                        .iter()
+                        .map(|&facing| cube.neighbour(f, facing).0)
+                        .collect();
+                    neighbours.sort();
+                    neighbours.dedup();
+                    assert_eq!(4, neighbours.len());
+                    assert!(!neighbours.contains(&f));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        let tiles = |net| CubeNet::new(&scale(net, 1), |&t| t, Fold::Cube).map(|_| ());
+        assert_eq!(Err(NetError::TileCount(5)), tiles("#...\n####"));
+        assert_eq!(Err(NetError::NotANet), tiles("######"));
+        assert_eq!(Err(NetError::NotANet), tiles("##.\n##.\n.##"));
+        let grid = scale("#...\n####\n#...", 2);
+        let mut cells: Vec<bool> = grid.iter().copied().collect();
+        cells.swap(0, 2);
+        assert_eq!(
+            Err(NetError::PartialFace(2)),
+            CubeNet::new(&grid.replace_with(cells), |&t| t, Fold::Cube).map(|_| ())
+        );
+    }
+}
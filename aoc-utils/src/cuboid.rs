@@ -0,0 +1,326 @@
+use std::array;
+
+// Axis aligned boxes in N dimensions (rectangles for N = 2, cuboids for
+// N = 3). Bounds are half open: a box contains the points p with
+// min[i] <= p[i] < max[i] on every axis, so volumes are products of
+// differences and adjacent boxes do not overlap. Inclusive bounds (as
+// in the puzzle inputs) go through from_inclusive
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Cuboid<const N: usize> {
+    pub min: [i64; N],
+    pub max: [i64; N],
+}
+
+pub type Rect = Cuboid<2>;
+
+impl<const N: usize> Cuboid<N> {
+    pub fn new(min: [i64; N], max: [i64; N]) -> Self {
+        Self { min, max }
+    }
+
+    // the box from min to max, both included
+    pub fn from_inclusive(min: [i64; N], max: [i64; N]) -> Self {
+        Self {
+            min,
+            max: max.map(|m| m + 1),
+        }
+    }
+
+    // the box at pos with the given size, as the 2018 day 3 claims
+    pub fn with_size(pos: [i64; N], size: [i64; N]) -> Self {
+        Self {
+            min: pos,
+            max: array::from_fn(|i| pos[i] + size[i]),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        (0..N).any(|i| self.min[i] >= self.max[i])
+    }
+
+    pub fn volume(&self) -> i64 {
+        if self.is_empty() {
+            0
+        } else {
+            (0..N).map(|i| self.max[i] - self.min[i]).product()
+        }
+    }
+
+    pub fn contains(&self, point: &[i64; N]) -> bool {
+        (0..N).all(|i| self.min[i] <= point[i] && point[i] < self.max[i])
+    }
+
+    // whether other is entirely inside this box
+    pub fn contains_box(&self, other: &Self) -> bool {
+        other.is_empty()
+            || (0..N).all(|i| self.min[i] <= other.min[i] && other.max[i] <= self.max[i])
+    }
+
+    pub fn intersects(&self, other: &Self) -> bool {
+        (0..N).all(|i| self.min[i].max(other.min[i]) < self.max[i].min(other.max[i]))
+    }
+
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        let b = Self {
+            min: array::from_fn(|i| self.min[i].max(other.min[i])),
+            max: array::from_fn(|i| self.max[i].min(other.max[i])),
+        };
+        (!b.is_empty()).then_some(b)
+    }
+
+    // The parts of this box outside of other, as at most 2N disjoint
+    // boxes: on each axis in turn, the slabs before and after other are
+    // cut off, and the rest is narrowed to other on that axis
+    pub fn subtract(&self, other: &Self) -> Vec<Self> {
+        let Some(inner) = self.intersection(other) else {
+            return vec![*self];
+        };
+        let mut pieces = vec![];
+        let mut rest = *self;
+        for i in 0..N {
+            if rest.min[i] < inner.min[i] {
+                let mut slab = rest;
+                slab.max[i] = inner.min[i];
+                pieces.push(slab);
+                rest.min[i] = inner.min[i];
+            }
+            if inner.max[i] < rest.max[i] {
+                let mut slab = rest;
+                slab.min[i] = inner.max[i];
+                pieces.push(slab);
+                rest.max[i] = inner.max[i];
+            }
+        }
+        pieces
+    }
+
+    pub fn translate(&self, delta: [i64; N]) -> Self {
+        Self {
+            min: array::from_fn(|i| self.min[i] + delta[i]),
+            max: array::from_fn(|i| self.max[i] + delta[i]),
+        }
+    }
+
+    // the point of the box closest to point
+    pub fn clamp(&self, point: &[i64; N]) -> [i64; N] {
+        array::from_fn(|i| point[i].clamp(self.min[i], self.max[i] - 1))
+    }
+
+    // the smallest box containing the points
+    pub fn bounding<'a>(points: impl IntoIterator<Item = &'a [i64; N]>) -> Option<Self> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        let mut b = Self::from_inclusive(*first, *first);
+        for p in points {
+            for (i, &c) in p.iter().enumerate() {
+                b.min[i] = b.min[i].min(c);
+                b.max[i] = b.max[i].max(c + 1);
+            }
+        }
+        Some(b)
+    }
+}
+
+// Boxes with no point in common, so that the volume of their union is the
+// sum of their volumes. Adding a box keeps only its parts that are not
+// already covered; removing one cuts it out of all the boxes (as the on
+// and off steps of 2021 day 22)
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct DisjointCuboids<const N: usize> {
+    boxes: Vec<Cuboid<N>>,
+}
+
+impl<const N: usize> DisjointCuboids<N> {
+    pub fn new() -> Self {
+        Self { boxes: vec![] }
+    }
+
+    pub fn insert(&mut self, b: Cuboid<N>) {
+        let mut pieces = vec![b];
+        for existing in self.boxes.iter().filter(|e| e.intersects(&b)) {
+            pieces = pieces.iter().flat_map(|p| p.subtract(existing)).collect();
+        }
+        self.boxes
+            .extend(pieces.into_iter().filter(|p| !p.is_empty()));
+    }
+
+    pub fn remove(&mut self, b: &Cuboid<N>) {
+        let boxes = std::mem::take(&mut self.boxes);
+        self.boxes = boxes.into_iter().flat_map(|e| e.subtract(b)).collect();
+    }
+
+    pub fn contains(&self, point: &[i64; N]) -> bool {
+        self.boxes.iter().any(|b| b.contains(point))
+    }
+
+    pub fn volume(&self) -> i64 {
+        self.boxes.iter().map(Cuboid::volume).sum()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Cuboid<N>> {
+        self.boxes.iter()
+    }
+}
+
+impl<const N: usize> FromIterator<Cuboid<N>> for DisjointCuboids<N> {
+    fn from_iter<I: IntoIterator<Item = Cuboid<N>>>(iter: I) -> Self {
+        let mut cuboids = Self::new();
+        for b in iter {
+            cuboids.insert(b);
+        }
+        cuboids
+    }
+}
+
+// volume covered by at least one of the boxes
+pub fn union_volume<const N: usize>(boxes: impl IntoIterator<Item = Cuboid<N>>) -> i64 {
+    boxes.into_iter().collect::<DisjointCuboids<N>>().volume()
+}
+
+pub fn manhattan<const N: usize>(p1: &[i64; N], p2: &[i64; N]) -> i64 {
+    (0..N).map(|i| (p1[i] - p2[i]).abs()).sum()
+}
+
+// Octahedron: the points within radius of center, in Manhattan distance
+// (the range of the 2018 day 23 nanobots). With the coordinates rotated
+// to x+y+z, x+y-z, x-y+z and -x+y+z, a point is in the octahedron if and
+// only if its rotation is in an axis aligned box in 4 dimensions. The
+// intersection of octahedra is not an octahedron, and the intersection of
+// their boxes is only an upper bound: rotated points have u = v + w + t
+// and v + w even, so a non empty box may hold no rotated point at all
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Octahedron {
+    pub center: [i64; 3],
+    pub radius: i64,
+}
+
+pub fn rotate(p: &[i64; 3]) -> [i64; 4] {
+    let [x, y, z] = *p;
+    [x + y + z, x + y - z, x - y + z, -x + y + z]
+}
+
+impl Octahedron {
+    pub fn new(center: [i64; 3], radius: i64) -> Self {
+        Self { center, radius }
+    }
+
+    pub fn contains(&self, point: &[i64; 3]) -> bool {
+        manhattan(&self.center, point) <= self.radius
+    }
+
+    pub fn intersects(&self, other: &Self) -> bool {
+        manhattan(&self.center, &other.center) <= self.radius + other.radius
+    }
+
+    pub fn intersects_box(&self, b: &Cuboid<3>) -> bool {
+        !b.is_empty() && self.contains(&b.clamp(&self.center))
+    }
+
+    // the octahedron in rotated coordinates; to test a point, rotate it
+    // and check it is in the box, but a box (or an intersection of boxes)
+    // may contain points that are not the rotation of any point
+    pub fn rotated(&self) -> Cuboid<4> {
+        let c = rotate(&self.center);
+        Cuboid::from_inclusive(c.map(|c| c - self.radius), c.map(|c| c + self.radius))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_rect() {
+        // 2018 day 3
+        let claims = [
+            Rect::with_size([1, 3], [4, 4]),
+            Rect::with_size([3, 1], [4, 4]),
+            Rect::with_size([5, 5], [2, 2]),
+        ];
+        assert_eq!(
+            Some(Rect::new([3, 3], [5, 5])),
+            claims[0].intersection(&claims[1])
+        );
+        assert!(!claims[0].intersects(&claims[2]) && !claims[1].intersects(&claims[2]));
+        assert_eq!(32, union_volume(claims));
+        assert!(claims[0].contains(&[4, 6]) && !claims[0].contains(&[5, 6]));
+        assert!(claims[0].contains_box(&Rect::new([2, 4], [4, 6])));
+    }
+
+    #[test]
+    fn test_subtract() {
+        let outer = Cuboid::from_inclusive([0, 0, 0], [9, 9, 9]);
+        let inner = Cuboid::from_inclusive([3, 3, 3], [5, 5, 5]);
+        let pieces = outer.subtract(&inner);
+        assert_eq!(6, pieces.len());
+        assert_eq!(1000 - 27, pieces.iter().map(Cuboid::volume).sum::<i64>());
+        for (i, p) in pieces.iter().enumerate() {
+            assert!(!p.intersects(&inner));
+            assert!(pieces[i + 1..].iter().all(|q| !p.intersects(q)));
+        }
+        // on a corner, and away
+        let corner = Cuboid::new([5, 5, 5], [20, 20, 20]);
+        assert_eq!(3, outer.subtract(&corner).len());
+        assert_eq!(vec![inner], inner.subtract(&corner.translate([10, 10, 10])));
+    }
+
+    #[test]
+    fn test_disjoint() {
+        // 2021 day 22, small example
+        let mut reactor = DisjointCuboids::new();
+        reactor.insert(Cuboid::from_inclusive([10, 10, 10], [12, 12, 12]));
+        reactor.insert(Cuboid::from_inclusive([11, 11, 11], [13, 13, 13]));
+        assert_eq!(27 + 19, reactor.volume());
+        reactor.remove(&Cuboid::from_inclusive([9, 9, 9], [11, 11, 11]));
+        reactor.insert(Cuboid::from_inclusive([10, 10, 10], [10, 10, 10]));
+        assert_eq!(39, reactor.volume());
+        assert!(reactor.contains(&[10, 10, 10]) && !reactor.contains(&[11, 10, 10]));
+    }
+
+    #[test]
+    fn test_octahedron() {
+        let bot = Octahedron::new([0, 0, 0], 4);
+        assert!(bot.contains(&[1, 1, 2]) && !bot.contains(&[4, 1, 0]));
+        assert!(bot.intersects(&Octahedron::new([4, 4, 0], 4)));
+        assert!(!bot.intersects(&Octahedron::new([4, 4, 1], 4)));
+        assert!(bot.intersects_box(&Cuboid::new([2, 2, 0], [10, 10, 10])));
+        assert!(!bot.intersects_box(&Cuboid::new([2, 2, 1], [10, 10, 10])));
+
+        // the rotated box contains the rotated points of the octahedron
+        let rotated = bot.rotated();
+        for x in -5..=5 {
+            for y in -5..=5 {
+                for z in -5..=5 {
+                    let p = [x, y, z];
+                    assert_eq!(bot.contains(&p), rotated.contains(&rotate(&p)));
+                }
+            }
+        }
+        // octahedra touching on a single point
+        let touch = rotate(&[4, 0, 0]);
+        assert_eq!(
+            Some(Cuboid::from_inclusive(touch, touch)),
+            rotated.intersection(&Octahedron::new([8, 0, 0], 4).rotated())
+        );
+
+        // the boxes meet, but the octahedra share no point
+        let bots = [
+            Octahedron::new([-2, 0, 0], 4),
+            Octahedron::new([1, 0, 2], 3),
+            Octahedron::new([-1, -2, 3], 3),
+            Octahedron::new([0, -1, 1], 1),
+        ];
+        let meet = bots[1..]
+            .iter()
+            .try_fold(bots[0].rotated(), |b, o| b.intersection(&o.rotated()));
+        assert!(meet.is_some_and(|b| !b.is_empty()));
+        for x in -6..=6 {
+            for y in -6..=6 {
+                for z in -6..=6 {
+                    assert!(!bots.iter().all(|o| o.contains(&[x, y, z])));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,619 @@
+use anyhow::{anyhow, Error};
+use std::{fmt::Display, str::FromStr};
+
+use crate::error::{parse_token, ParseError};
+
+// Elfcode interpreter (2018 days 16, 19 and 21), with a peephole optimizer
+// for the divisor sum loop of day 19 and the divide-by-counting loop of
+// day 21
+
+pub type Registers = [i64; 6];
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Opcode {
+    Addr,
+    Addi,
+    Mulr,
+    Muli,
+    Banr,
+    Bani,
+    Borr,
+    Bori,
+    Setr,
+    Seti,
+    Gtir,
+    Gtri,
+    Gtrr,
+    Eqir,
+    Eqri,
+    Eqrr,
+}
+
+use Opcode::*;
+
+impl Opcode {
+    pub const ALL: [Opcode; 16] = [
+        Addr, Addi, Mulr, Muli, Banr, Bani, Borr, Bori, Setr, Seti, Gtir, Gtri, Gtrr, Eqir, Eqri,
+        Eqrr,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Addr => "addr",
+            Addi => "addi",
+            Mulr => "mulr",
+            Muli => "muli",
+            Banr => "banr",
+            Bani => "bani",
+            Borr => "borr",
+            Bori => "bori",
+            Setr => "setr",
+            Seti => "seti",
+            Gtir => "gtir",
+            Gtri => "gtri",
+            Gtrr => "gtrr",
+            Eqir => "eqir",
+            Eqri => "eqri",
+            Eqrr => "eqrr",
+        }
+    }
+
+    // whether the a and b operands are registers
+    fn inputs(&self) -> (bool, bool) {
+        match self {
+            Addr | Mulr | Banr | Borr | Gtrr | Eqrr => (true, true),
+            Addi | Muli | Bani | Bori | Gtri | Eqri => (true, false),
+            Setr => (true, false),
+            Seti => (false, false),
+            Gtir | Eqir => (false, true),
+        }
+    }
+
+    // value written to register c, or None if an operand refers to a
+    // register that does not exist
+    pub fn eval(&self, a: i64, b: i64, registers: &[i64]) -> Option<i64> {
+        let (ra, rb) = self.inputs();
+        let a = if ra {
+            *registers.get(usize::try_from(a).ok()?)?
+        } else {
+            a
+        };
+        // b is not read by setr and seti
+        let b = if rb {
+            *registers.get(usize::try_from(b).ok()?)?
+        } else {
+            b
+        };
+        Some(match self {
+            Addr | Addi => a + b,
+            Mulr | Muli => a * b,
+            Banr | Bani => a & b,
+            Borr | Bori => a | b,
+            Setr | Seti => a,
+            Gtir | Gtri | Gtrr => (a > b).into(),
+            Eqir | Eqri | Eqrr => (a == b).into(),
+        })
+    }
+}
+
+impl FromStr for Opcode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Opcode::ALL
+            .into_iter()
+            .find(|o| o.name() == s)
+            .ok_or_else(|| anyhow!("unknown mnemonic {s}"))
+    }
+}
+
+impl Display for Opcode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Instruction {
+    pub opcode: Opcode,
+    pub a: i64,
+    pub b: i64,
+    pub c: usize,
+}
+
+impl Instruction {
+    pub fn eval(&self, registers: &mut [i64]) -> Option<()> {
+        let value = self.opcode.eval(self.a, self.b, registers)?;
+        *registers.get_mut(self.c)? = value;
+        Some(())
+    }
+
+    fn is(&self, opcode: Opcode, a: i64, b: i64, c: usize) -> bool {
+        self.opcode == opcode && self.a == a && self.b == b && self.c == c
+    }
+
+    // same as is, but accepts the a and b operands in either order
+    fn is_commutative(&self, opcode: Opcode, a: usize, b: usize, c: usize) -> bool {
+        let (a, b) = (a as i64, b as i64);
+        self.is(opcode, a, b, c) || self.is(opcode, b, a, c)
+    }
+
+    // for an rr instruction involving register r, the other register
+    fn other(&self, opcode: Opcode, r: usize) -> Option<usize> {
+        let r = r as i64;
+        if self.opcode != opcode {
+            None
+        } else if self.a == r {
+            usize::try_from(self.b).ok()
+        } else if self.b == r {
+            usize::try_from(self.a).ok()
+        } else {
+            None
+        }
+    }
+}
+
+impl FromStr for Instruction {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<_> = s.split_whitespace().collect();
+        let [opcode, a, b, c] = parts[..] else {
+            return Err(ParseError::at(
+                s,
+                s.trim(),
+                "expected an opcode and 3 operands",
+            ));
+        };
+        Ok(Instruction {
+            opcode: parse_token(s, opcode)?,
+            a: parse_token(s, a)?,
+            b: parse_token(s, b)?,
+            c: parse_token(s, c)?,
+        })
+    }
+}
+
+impl Display for Instruction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} {} {}", self.opcode, self.a, self.b, self.c)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Program {
+    pub ip: usize,
+    pub instructions: Vec<Instruction>,
+}
+
+impl FromStr for Program {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut ip: usize = 0;
+        let mut instructions = Vec::new();
+        for line in s.lines().filter(|l| !l.trim().is_empty()) {
+            if let Some(i) = line.strip_prefix("#ip ") {
+                ip = parse_token(s, i.trim())?;
+            } else {
+                instructions.push(line.parse().map_err(|e: ParseError| e.within(s, line))?);
+            }
+        }
+        Ok(Self { ip, instructions })
+    }
+}
+
+impl Display for Program {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "#ip {}", self.ip)?;
+        for i in &self.instructions {
+            writeln!(f, "{i}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Pattern {
+    // for multiple in multiple..=number {
+    //     if factor * multiple == number { sum += factor }
+    // }
+    DivisorSum {
+        factor: usize,
+        multiple: usize,
+        number: usize,
+        sum: usize,
+        tmp: usize,
+    },
+    // while (quotient + 1) * divisor <= dividend { quotient += 1 }
+    Divide {
+        quotient: usize,
+        divisor: i64,
+        dividend: usize,
+        tmp: usize,
+    },
+}
+
+impl Pattern {
+    fn len(&self) -> usize {
+        match self {
+            Pattern::DivisorSum { .. } => 9,
+            Pattern::Divide { .. } => 8,
+        }
+    }
+}
+
+impl Display for Pattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Pattern::DivisorSum {
+                factor,
+                multiple,
+                number,
+                sum,
+                tmp,
+            } => write!(
+                f,
+                "divisor sum: if r{number} % r{factor} == 0 && r{number} / r{factor} >= r{multiple} {{ r{sum} += r{factor} }}; r{multiple} = r{number} + 1; r{tmp} = 1"
+            ),
+            Pattern::Divide {
+                quotient,
+                divisor,
+                dividend,
+                tmp,
+            } => write!(
+                f,
+                "divide: r{quotient} = max(r{quotient}, r{dividend} / {divisor}); r{tmp} = 1"
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Rewrite {
+    pub start: usize,
+    pub pattern: Pattern,
+}
+
+impl Display for Rewrite {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}..{}: {}",
+            self.start,
+            self.start + self.pattern.len(),
+            self.pattern
+        )
+    }
+}
+
+fn distinct(registers: &[usize]) -> bool {
+    registers
+        .iter()
+        .enumerate()
+        .all(|(i, r)| !registers[i + 1..].contains(r))
+}
+
+fn match_divisor_sum(code: &[Instruction], start: usize, ip: usize) -> Option<Pattern> {
+    let c = code.get(start..start + 9)?;
+    let (multiple, tmp) = (c[5].c, c[0].c);
+    let factor = c[0].other(Mulr, multiple)?;
+    let number = c[1].other(Eqrr, tmp)?;
+    let sum = c[4].c;
+    let matches = c[1].c == tmp
+        && c[2].is_commutative(Addr, tmp, ip, ip)
+        && c[3].is(Addi, ip as i64, 1, ip)
+        && c[4].is_commutative(Addr, factor, sum, sum)
+        && c[5].is(Addi, multiple as i64, 1, multiple)
+        && c[6].is(Gtrr, multiple as i64, number as i64, tmp)
+        && c[7].is_commutative(Addr, ip, tmp, ip)
+        && c[8].opcode == Seti
+        && c[8].a + 1 == start as i64
+        && c[8].c == ip
+        && distinct(&[factor, multiple, number, sum, tmp, ip]);
+    matches.then_some(Pattern::DivisorSum {
+        factor,
+        multiple,
+        number,
+        sum,
+        tmp,
+    })
+}
+
+fn match_divide(code: &[Instruction], start: usize, ip: usize) -> Option<Pattern> {
+    let c = code.get(start..start + 8)?;
+    let (quotient, tmp) = (usize::try_from(c[0].a).ok()?, c[0].c);
+    let divisor = c[1].b;
+    let dividend = usize::try_from(c[2].b).ok()?;
+    let matches = c[0].is(Addi, quotient as i64, 1, tmp)
+        && c[1].is(Muli, tmp as i64, divisor, tmp)
+        && c[2].is(Gtrr, tmp as i64, dividend as i64, tmp)
+        && c[3].is_commutative(Addr, tmp, ip, ip)
+        && c[4].is(Addi, ip as i64, 1, ip)
+        && c[5].opcode == Seti
+        && c[5].a == (start + 7) as i64
+        && c[5].c == ip
+        && c[6].is(Addi, quotient as i64, 1, quotient)
+        && c[7].opcode == Seti
+        && c[7].a + 1 == start as i64
+        && c[7].c == ip
+        && distinct(&[quotient, tmp, dividend, ip]);
+    matches.then_some(Pattern::Divide {
+        quotient,
+        divisor,
+        dividend,
+        tmp,
+    })
+}
+
+pub fn optimize(program: &Program) -> Vec<Rewrite> {
+    let code = &program.instructions;
+    (0..code.len())
+        .filter_map(|start| {
+            match_divisor_sum(code, start, program.ip)
+                .or_else(|| match_divide(code, start, program.ip))
+                .map(|pattern| Rewrite { start, pattern })
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone)]
+pub struct Machine {
+    program: Program,
+    macros: Vec<Option<Pattern>>,
+    pub registers: Registers,
+    ip: usize,
+    steps: usize,
+}
+
+impl Machine {
+    pub fn new(program: Program) -> Self {
+        let mut macros = vec![None; program.instructions.len()];
+        for rewrite in optimize(&program) {
+            macros[rewrite.start] = Some(rewrite.pattern);
+        }
+        Self {
+            program,
+            macros,
+            registers: [0; 6],
+            ip: 0,
+            steps: 0,
+        }
+    }
+
+    pub fn without_optimizer(mut self) -> Self {
+        self.macros.iter_mut().for_each(|m| *m = None);
+        self
+    }
+
+    pub fn rewrites(&self) -> Vec<Rewrite> {
+        self.macros
+            .iter()
+            .enumerate()
+            .filter_map(|(start, m)| m.map(|pattern| Rewrite { start, pattern }))
+            .collect()
+    }
+
+    pub fn program(&self) -> &Program {
+        &self.program
+    }
+
+    // index of the next instruction to execute
+    pub fn ip(&self) -> usize {
+        self.ip
+    }
+
+    // number of instructions and macro operations executed so far
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    // returns false if the preconditions of the pattern do not hold, in
+    // which case the original instruction must be executed instead
+    fn execute_macro(&mut self, pattern: Pattern) -> bool {
+        let regs = &mut self.registers;
+        match pattern {
+            Pattern::DivisorSum {
+                factor,
+                multiple,
+                number,
+                sum,
+                tmp,
+            } => {
+                let (f, m, n) = (regs[factor], regs[multiple], regs[number]);
+                if f < 1 || m < 1 || m > n {
+                    return false;
+                }
+                if n % f == 0 && n / f >= m {
+                    regs[sum] += f;
+                }
+                regs[multiple] = n + 1;
+                regs[tmp] = 1;
+                self.ip += 9;
+                regs[self.program.ip] = self.ip as i64;
+            }
+            Pattern::Divide {
+                quotient,
+                divisor,
+                dividend,
+                tmp,
+            } => {
+                if divisor < 1 {
+                    return false;
+                }
+                regs[quotient] = regs[quotient].max(regs[dividend].div_euclid(divisor));
+                regs[tmp] = 1;
+                self.ip += 8;
+                regs[self.program.ip] = self.ip as i64;
+            }
+        }
+        true
+    }
+
+    // executes one instruction or macro operation; returns false if the
+    // program has halted
+    pub fn step(&mut self) -> bool {
+        let Some(&instruction) = self.program.instructions.get(self.ip) else {
+            return false;
+        };
+        self.steps += 1;
+        if let Some(pattern) = self.macros[self.ip] {
+            if self.execute_macro(pattern) {
+                return true;
+            }
+        }
+
+        self.registers[self.program.ip] = self.ip as i64;
+        if instruction.eval(&mut self.registers).is_none() {
+            // invalid register: halt
+            self.ip = usize::MAX;
+            return false;
+        }
+        // the incremented pointer is written back as well, so a halted
+        // program shows where it stopped
+        let next = self.registers[self.program.ip] + 1;
+        self.registers[self.program.ip] = next;
+        self.ip = usize::try_from(next).unwrap_or(usize::MAX);
+        true
+    }
+
+    pub fn run(&mut self) {
+        while self.step() {}
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    static TEST_INPUT: &str = "#ip 0
+seti 5 0 1
+seti 6 0 2
+addi 0 1 0
+addr 1 2 3
+setr 1 0 0
+seti 8 0 4
+seti 9 0 5";
+
+    // shape of the 2018 day 19 inputs: sum of the divisors of r4
+    static DIVISORS: &str = "#ip 2
+seti 0 0 0
+seti 1 0 1
+seti 1 8 3
+mulr 1 3 5
+eqrr 5 4 5
+addr 5 2 2
+addi 2 1 2
+addr 1 0 0
+addi 3 1 3
+gtrr 3 4 5
+addr 2 5 2
+seti 2 6 2
+addi 1 1 1
+gtrr 1 4 5
+addr 5 2 2
+seti 1 1 2
+mulr 2 2 2";
+
+    // shape of the 2018 day 21 inputs, reduced to the division loop:
+    // r2 = r3 / 256
+    static DIVIDE: &str = "#ip 1
+seti 0 0 2
+addi 2 1 5
+muli 5 256 5
+gtrr 5 3 5
+addr 5 1 1
+addi 1 1 1
+seti 8 8 1
+addi 2 1 2
+seti 0 3 1";
+
+    #[test]
+    fn test_example() {
+        let mut machine = Machine::new(TEST_INPUT.parse().unwrap());
+        machine.run();
+        assert_eq!([7, 5, 6, 0, 0, 9], machine.registers);
+        assert!(machine.rewrites().is_empty());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let program: Program = DIVISORS.parse().unwrap();
+        assert_eq!(format!("{DIVISORS}\n"), program.to_string());
+    }
+
+    #[test]
+    fn test_divisor_sum() {
+        let program: Program = DIVISORS.parse().unwrap();
+        assert_eq!(
+            vec!["3..12: divisor sum: if r4 % r1 == 0 && r4 / r1 >= r3 { r0 += r1 }; r3 = r4 + 1; r5 = 1"],
+            optimize(&program)
+                .iter()
+                .map(|r| r.to_string())
+                .collect::<Vec<_>>()
+        );
+
+        let mut slow = Machine::new(program.clone()).without_optimizer();
+        slow.registers[4] = 360;
+        slow.run();
+        let mut fast = Machine::new(program.clone());
+        fast.registers[4] = 360;
+        fast.run();
+        assert_eq!(1170, slow.registers[0]);
+        assert_eq!(slow.registers, fast.registers);
+        assert!(fast.steps() * 50 < slow.steps());
+
+        let mut fast = Machine::new(program);
+        fast.registers[4] = 1000000;
+        fast.run();
+        assert_eq!(2480437, fast.registers[0]);
+    }
+
+    #[test]
+    fn test_divide() {
+        let program: Program = DIVIDE.parse().unwrap();
+        assert_eq!(
+            vec!["1..9: divide: r2 = max(r2, r3 / 256); r5 = 1"],
+            optimize(&program)
+                .iter()
+                .map(|r| r.to_string())
+                .collect::<Vec<_>>()
+        );
+        for r3 in [0, 255, 256, 65535, 1234567] {
+            let mut slow = Machine::new(program.clone()).without_optimizer();
+            slow.registers[3] = r3;
+            slow.run();
+            let mut fast = Machine::new(program.clone());
+            fast.registers[3] = r3;
+            fast.run();
+            assert_eq!(r3 / 256, fast.registers[2]);
+            assert_eq!(slow.registers, fast.registers);
+        }
+    }
+
+    #[test]
+    fn test_opcode_eval() {
+        // example from 2018 day 16
+        let matching: Vec<_> = Opcode::ALL
+            .into_iter()
+            .filter(|o| o.eval(2, 1, &[3, 2, 1, 1]) == Some(2))
+            .collect();
+        assert_eq!(vec![Addi, Mulr, Seti], matching);
+        assert_eq!(None, Addr.eval(4, 1, &[3, 2, 1, 1]));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = "#ip 0\nseti 5 0 1\nsetx 6 0 2"
+            .parse::<Program>()
+            .unwrap_err();
+        assert_eq!((3, 1), (err.line, err.column));
+        assert_eq!("unknown mnemonic setx", err.message());
+
+        let err = "#ip 0\nseti 5 0 -1".parse::<Program>().unwrap_err();
+        assert_eq!((2, 10), (err.line, err.column));
+
+        let err = "#ip x".parse::<Program>().unwrap_err();
+        assert_eq!((1, 5), (err.line, err.column));
+    }
+}
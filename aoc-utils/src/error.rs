@@ -0,0 +1,262 @@
+use miette::{Diagnostic, GraphicalReportHandler, SourceSpan};
+use nom::{
+    error::{ErrorKind, FromExternalError},
+    IResult,
+};
+use nom_locate::LocatedSpan;
+use std::{
+    fmt::{Debug, Display},
+    str::FromStr,
+};
+use thiserror::Error;
+
+// Parse error pointing at the offending characters of the input. Display
+// gives the line and column (both 1-based); as a miette diagnostic it
+// renders the input with the bad characters underlined; Debug uses the
+// same rendering, so that unwrap shows where the input is wrong
+
+pub type Span<'a> = LocatedSpan<&'a str>;
+
+#[derive(Error, Diagnostic, Clone, PartialEq, Eq)]
+#[error("{message} at line {line}, column {column}")]
+#[diagnostic(code(aoc::parse_error))]
+pub struct ParseError {
+    #[source_code]
+    src: String,
+    #[label("{message}")]
+    span: SourceSpan,
+    message: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+// nom error on located spans that keeps the message of a failed
+// conversion (map_res, grid cells), so that final_parse reports it rather
+// than the kind of the parser that failed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NomError<'a> {
+    pub input: Span<'a>,
+    pub kind: ErrorKind,
+    pub message: Option<String>,
+}
+
+impl<'a> nom::error::ParseError<Span<'a>> for NomError<'a> {
+    fn from_error_kind(input: Span<'a>, kind: ErrorKind) -> Self {
+        Self {
+            input,
+            kind,
+            message: None,
+        }
+    }
+
+    fn append(_: Span<'a>, _: ErrorKind, other: Self) -> Self {
+        other
+    }
+}
+
+impl<'a, E: Display> FromExternalError<Span<'a>, E> for NomError<'a> {
+    fn from_external_error(input: Span<'a>, kind: ErrorKind, e: E) -> Self {
+        Self {
+            input,
+            kind,
+            message: Some(e.to_string()),
+        }
+    }
+}
+
+// offset of part in src, if part is a slice of src (as returned by lines,
+// split_whitespace, ...)
+fn offset_in(src: &str, part: &str) -> Option<usize> {
+    let start = (part.as_ptr() as usize).checked_sub(src.as_ptr() as usize)?;
+    (start + part.len() <= src.len()).then_some(start)
+}
+
+impl ParseError {
+    pub fn new(src: &str, offset: usize, len: usize, message: impl Into<String>) -> Self {
+        let mut offset = offset.min(src.len());
+        // byte offsets (from grids) may fall inside a character
+        while !src.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        let len = len.min(src.len() - offset);
+        let before = &src[..offset];
+        let line = before.matches('\n').count() + 1;
+        let column = before.chars().rev().take_while(|&c| c != '\n').count() + 1;
+        Self {
+            src: src.to_owned(),
+            span: (offset, len).into(),
+            message: message.into(),
+            line,
+            column,
+        }
+    }
+
+    // error on part, a slice of src; anything else points at the whole
+    // input
+    pub fn at(src: &str, part: &str, message: impl Into<String>) -> Self {
+        match offset_in(src, part) {
+            Some(offset) => Self::new(src, offset, part.len(), message),
+            None => Self::new(src, 0, src.len(), message),
+        }
+    }
+
+    // error at span, extended to the end of the offending word
+    pub fn from_span(src: &str, span: Span, message: impl Into<String>) -> Self {
+        let fragment = span.fragment();
+        let len = fragment.find(char::is_whitespace).unwrap_or(fragment.len());
+        Self::new(src, span.location_offset(), len, message)
+    }
+
+    pub fn from_nom(src: &str, err: nom::Err<NomError>) -> Self {
+        match err {
+            nom::Err::Incomplete(_) => Self::new(src, src.len(), 0, "unexpected end of input"),
+            nom::Err::Error(e) | nom::Err::Failure(e) => Self::from_span(
+                src,
+                e.input,
+                e.message
+                    .unwrap_or_else(|| format!("unexpected input ({})", e.kind.description())),
+            ),
+        }
+    }
+
+    // the same error, in src that contains the text this error was
+    // reported on at offset
+    pub fn relocate(self, src: &str, offset: usize) -> Self {
+        Self::new(
+            src,
+            offset + self.span.offset(),
+            self.span.len(),
+            self.message,
+        )
+    }
+
+    // the same error, for part a slice of src
+    pub fn within(self, src: &str, part: &str) -> Self {
+        let offset = offset_in(src, part).unwrap_or(0);
+        self.relocate(src, offset)
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn offset(&self) -> usize {
+        self.span.offset()
+    }
+
+    pub fn render(&self) -> String {
+        let mut s = String::new();
+        GraphicalReportHandler::new()
+            .render_report(&mut s, self)
+            .unwrap();
+        s
+    }
+}
+
+impl Debug for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.render())
+    }
+}
+
+// parses part, a slice of src, reporting errors at its position
+pub fn parse_token<T: FromStr>(src: &str, part: &str) -> Result<T, ParseError>
+where
+    T::Err: Display,
+{
+    part.parse()
+        .map_err(|e: T::Err| ParseError::at(src, part, e.to_string()))
+}
+
+// parses each non blank line of src, reporting errors in src
+pub fn parse_lines<T>(
+    src: &str,
+    parse: impl Fn(&str) -> Result<T, ParseError>,
+) -> Result<Vec<T>, ParseError> {
+    src.lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| parse(l).map_err(|e| e.within(src, l)))
+        .collect()
+}
+
+// runs parser on the whole of src; only whitespace may be left over
+pub fn final_parse<'a, O>(
+    src: &'a str,
+    mut parser: impl FnMut(Span<'a>) -> IResult<Span<'a>, O, NomError<'a>>,
+) -> Result<O, ParseError> {
+    match parser(Span::new(src)) {
+        Ok((rest, o)) if rest.fragment().trim().is_empty() => Ok(o),
+        Ok((rest, _)) => Err(ParseError::from_span(
+            src,
+            rest,
+            "unexpected trailing input",
+        )),
+        Err(e) => Err(ParseError::from_nom(src, e)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use nom::{
+        character::complete::{char, i64},
+        combinator::map_res,
+        multi::separated_list1,
+    };
+
+    static INPUT: &str = "cpy 41 a\ninc a\ndec x\njnz a 2";
+
+    #[test]
+    fn test_position() {
+        let err = ParseError::new(INPUT, 19, 1, "invalid register");
+        assert_eq!((3, 5), (err.line, err.column));
+        assert_eq!("invalid register at line 3, column 5", err.to_string());
+
+        let line = INPUT.lines().nth(2).unwrap();
+        let err = ParseError::at(INPUT, &line[4..], "invalid register");
+        assert_eq!((19, 3, 5), (err.offset(), err.line, err.column));
+    }
+
+    #[test]
+    fn test_parse_lines() {
+        let err = parse_lines(INPUT, |l| {
+            let parts: Vec<_> = l.split_whitespace().collect();
+            parts[1..]
+                .iter()
+                .map(|p| parse_token::<i64>(l, p))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .unwrap_err();
+        assert_eq!((1, 8), (err.line, err.column));
+        assert_eq!("invalid digit found in string", err.message());
+    }
+
+    #[test]
+    fn test_final_parse() {
+        let numbers = |i| separated_list1(char(','), i64)(i);
+        assert_eq!(Ok(vec![1, -2, 3]), final_parse("1,-2,3\n", numbers));
+
+        let err = final_parse("1,2\n3,x4", numbers).unwrap_err();
+        assert_eq!((1, 4), (err.line, err.column));
+        assert_eq!("unexpected trailing input", err.message());
+
+        let err = final_parse("x", numbers).unwrap_err();
+        assert_eq!((1, 1), (err.line, err.column));
+
+        // conversion errors keep their message
+        let small = |i| map_res(i64, u8::try_from)(i);
+        let err = final_parse("1000", small).unwrap_err();
+        assert_eq!(
+            "out of range integral type conversion attempted",
+            err.message()
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        let err = ParseError::new(INPUT, 19, 1, "invalid register");
+        let report = err.render();
+        assert!(report.contains("dec x"));
+        assert!(report.contains("invalid register"));
+    }
+}
@@ -0,0 +1,769 @@
+use std::{
+    borrow::Borrow,
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet, VecDeque},
+    hash::Hash,
+};
+
+use crate::{error::ParseError, union_find::UnionFind};
+
+// Graph with labelled nodes (usually the names from the puzzle input).
+// Labels are interned: each gets a dense id in 0..len() in order of
+// appearance, and the algorithms work on the ids, so there is no need for
+// the usual name to index maps. Edges are directed or not depending on
+// the graph, and carry a weight E (() when there is none). Adding an edge
+// that is already there replaces its weight
+#[derive(Debug, Clone)]
+pub struct Graph<N, E = ()> {
+    directed: bool,
+    labels: Vec<N>,
+    ids: HashMap<N, usize>,
+    // outgoing edges, in order of insertion
+    edges: Vec<Vec<(usize, E)>>,
+    // sources of incoming edges, for directed graphs only
+    incoming: Vec<Vec<usize>>,
+}
+
+impl<N: Clone + Eq + Hash, E: Clone> Graph<N, E> {
+    pub fn directed() -> Self {
+        Self::new(true)
+    }
+
+    pub fn undirected() -> Self {
+        Self::new(false)
+    }
+
+    fn new(directed: bool) -> Self {
+        Self {
+            directed,
+            labels: vec![],
+            ids: HashMap::new(),
+            edges: vec![],
+            incoming: vec![],
+        }
+    }
+
+    pub fn is_directed(&self) -> bool {
+        self.directed
+    }
+
+    // the id of label, added as a new node if needed
+    pub fn node(&mut self, label: N) -> usize {
+        if let Some(&id) = self.ids.get(&label) {
+            return id;
+        }
+        let id = self.labels.len();
+        self.labels.push(label.clone());
+        self.ids.insert(label, id);
+        self.edges.push(vec![]);
+        self.incoming.push(vec![]);
+        id
+    }
+
+    pub fn id<Q>(&self, label: &Q) -> Option<usize>
+    where
+        N: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.ids.get(label).copied()
+    }
+
+    pub fn label(&self, id: usize) -> &N {
+        &self.labels[id]
+    }
+
+    pub fn labels(&self) -> &[N] {
+        &self.labels
+    }
+
+    pub fn len(&self) -> usize {
+        self.labels.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        let count: usize = self.edges.iter().map(Vec::len).sum();
+        if self.directed {
+            count
+        } else {
+            // self loops are only stored once
+            let loops = (0..self.len()).filter(|&i| self.has_edge(i, i)).count();
+            (count + loops) / 2
+        }
+    }
+
+    pub fn add_edge(&mut self, from: N, to: N, weight: E) {
+        let from = self.node(from);
+        let to = self.node(to);
+        self.add_edge_ids(from, to, weight);
+    }
+
+    pub fn add_edge_ids(&mut self, from: usize, to: usize, weight: E) {
+        if self.directed {
+            if !self.has_edge(from, to) {
+                self.incoming[to].push(from);
+            }
+            Self::insert(&mut self.edges[from], to, weight);
+        } else {
+            if from != to {
+                Self::insert(&mut self.edges[to], from, weight.clone());
+            }
+            Self::insert(&mut self.edges[from], to, weight);
+        }
+    }
+
+    fn insert(edges: &mut Vec<(usize, E)>, to: usize, weight: E) {
+        match edges.iter_mut().find(|(t, _)| *t == to) {
+            Some(edge) => edge.1 = weight,
+            None => edges.push((to, weight)),
+        }
+    }
+
+    pub fn has_edge(&self, from: usize, to: usize) -> bool {
+        self.weight(from, to).is_some()
+    }
+
+    pub fn weight(&self, from: usize, to: usize) -> Option<&E> {
+        self.edges[from]
+            .iter()
+            .find(|(t, _)| *t == to)
+            .map(|(_, w)| w)
+    }
+
+    // outgoing edges, with their weight
+    pub fn edges(&self, id: usize) -> impl Iterator<Item = (usize, &E)> + '_ {
+        self.edges[id].iter().map(|(t, w)| (*t, w))
+    }
+
+    pub fn neighbours(&self, id: usize) -> impl Iterator<Item = usize> + '_ {
+        self.edges[id].iter().map(|(t, _)| *t)
+    }
+
+    // the nodes with an edge to id (the neighbours in an undirected graph)
+    pub fn predecessors(&self, id: usize) -> impl Iterator<Item = usize> + '_ {
+        let incoming = if self.directed {
+            &self.incoming[id][..]
+        } else {
+            &[]
+        };
+        incoming
+            .iter()
+            .copied()
+            .chain(self.neighbours(id).filter(|_| !self.directed))
+    }
+
+    // out degree in a directed graph
+    pub fn degree(&self, id: usize) -> usize {
+        self.edges[id].len()
+    }
+
+    pub fn in_degree(&self, id: usize) -> usize {
+        if self.directed {
+            self.incoming[id].len()
+        } else {
+            self.degree(id)
+        }
+    }
+
+    // ids of the nodes reachable from start (included), in breadth first
+    // order
+    pub fn reachable(&self, start: usize) -> Vec<usize> {
+        let mut seen = vec![false; self.len()];
+        seen[start] = true;
+        let mut order = vec![start];
+        let mut next = 0;
+        while next < order.len() {
+            for n in self.neighbours(order[next]) {
+                if !seen[n] {
+                    seen[n] = true;
+                    order.push(n);
+                }
+            }
+            next += 1;
+        }
+        order
+    }
+
+    // the number of edges on the shortest paths from start (None for the
+    // nodes out of reach)
+    pub fn distances(&self, start: usize) -> Vec<Option<usize>> {
+        let mut distances = vec![None; self.len()];
+        distances[start] = Some(0);
+        let mut queue = VecDeque::from([start]);
+        while let Some(id) = queue.pop_front() {
+            let next = distances[id].map(|d| d + 1);
+            for n in self.neighbours(id) {
+                if distances[n].is_none() {
+                    distances[n] = next;
+                    queue.push_back(n);
+                }
+            }
+        }
+        distances
+    }
+
+    // the connected components (ignoring the direction of edges), each
+    // sorted by id
+    pub fn components(&self) -> Vec<Vec<usize>> {
+        let mut uf = UnionFind::new();
+        uf.ensure_capacity(self.len().saturating_sub(1));
+        for (from, edges) in self.edges.iter().enumerate() {
+            for (to, _) in edges {
+                uf.join(from, *to);
+            }
+        }
+        let mut components: HashMap<usize, Vec<usize>> = HashMap::new();
+        for id in 0..self.len() {
+            components.entry(uf.leader(id)).or_default().push(id);
+        }
+        let mut components: Vec<_> = components.into_values().collect();
+        components.sort();
+        components
+    }
+
+    // The subgraph induced by nodes: these nodes and the edges between
+    // them. Ids are renumbered in the order of nodes
+    pub fn subgraph(&self, nodes: impl IntoIterator<Item = usize>) -> Self {
+        let mut sub = Self::new(self.directed);
+        let nodes: Vec<usize> = nodes.into_iter().collect();
+        for &id in &nodes {
+            sub.node(self.labels[id].clone());
+        }
+        for &from in &nodes {
+            for (to, weight) in self.edges(from) {
+                if let Some(to) = sub.id(&self.labels[to]) {
+                    let from = sub.ids[&self.labels[from]];
+                    sub.add_edge_ids(from, to, weight.clone());
+                }
+            }
+        }
+        sub
+    }
+}
+
+impl<N: Clone + Eq + Hash, E: Clone> FromIterator<(N, N, E)> for Graph<N, E> {
+    // a directed graph; use extend on an undirected one otherwise
+    fn from_iter<I: IntoIterator<Item = (N, N, E)>>(iter: I) -> Self {
+        let mut graph = Self::directed();
+        graph.extend(iter);
+        graph
+    }
+}
+
+impl<N: Clone + Eq + Hash, E: Clone> Extend<(N, N, E)> for Graph<N, E> {
+    fn extend<I: IntoIterator<Item = (N, N, E)>>(&mut self, iter: I) {
+        for (from, to, weight) in iter {
+            self.add_edge(from, to, weight);
+        }
+    }
+}
+
+impl<'a> Graph<&'a str> {
+    // one edge per line, as "kh-tc" (separator "-") or "COM)B" (")")
+    pub fn parse_pairs(
+        input: &'a str,
+        separator: &str,
+        directed: bool,
+    ) -> Result<Self, ParseError> {
+        let mut graph = Self::new(directed);
+        for line in input.lines().filter(|l| !l.trim().is_empty()) {
+            let (from, to) = split_line(input, line, separator)?;
+            graph.add_edge(from.trim(), to.trim(), ());
+        }
+        Ok(graph)
+    }
+
+    // One node and its neighbours per line, as "jqt: rhn xhk nvd"
+    // (separators ":" and " ") or "2 <-> 0, 3, 4" ("<->" and ","). Nodes
+    // without neighbours are added as well
+    pub fn parse_adjacency(
+        input: &'a str,
+        separator: &str,
+        list_separator: &str,
+        directed: bool,
+    ) -> Result<Self, ParseError> {
+        let mut graph = Self::new(directed);
+        for line in input.lines().filter(|l| !l.trim().is_empty()) {
+            let (from, to) = split_line(input, line, separator)?;
+            let from = graph.node(from.trim());
+            for to in to.split(list_separator).map(str::trim) {
+                if !to.is_empty() {
+                    let to = graph.node(to);
+                    graph.add_edge_ids(from, to, ());
+                }
+            }
+        }
+        Ok(graph)
+    }
+}
+
+// the two sides of a line of the input, or an error pointing at the line
+fn split_line<'a>(
+    input: &str,
+    line: &'a str,
+    separator: &str,
+) -> Result<(&'a str, &'a str), ParseError> {
+    line.split_once(separator)
+        .ok_or_else(|| ParseError::at(input, line, format!("expected {separator:?}")))
+}
+
+// Cliques, for undirected graphs
+impl<N: Clone + Eq + Hash, E: Clone> Graph<N, E> {
+    // neighbours as sets, without self loops
+    fn neighbour_sets(&self) -> Vec<HashSet<usize>> {
+        (0..self.len())
+            .map(|id| self.neighbours(id).filter(|&n| n != id).collect())
+            .collect()
+    }
+
+    // All the maximal cliques (cliques that cannot be extended), each
+    // sorted by id: Bron-Kerbosch, pivoting on the node with the most
+    // neighbours among the candidates
+    pub fn maximal_cliques(&self) -> Vec<Vec<usize>> {
+        fn extend(
+            neighbours: &[HashSet<usize>],
+            clique: &mut Vec<usize>,
+            mut candidates: HashSet<usize>,
+            mut excluded: HashSet<usize>,
+            cliques: &mut Vec<Vec<usize>>,
+        ) {
+            let Some(pivot) = candidates
+                .iter()
+                .chain(&excluded)
+                .max_by_key(|&&u| candidates.intersection(&neighbours[u]).count())
+                .copied()
+            else {
+                let mut found = clique.clone();
+                found.sort();
+                cliques.push(found);
+                return;
+            };
+            let others: Vec<usize> = candidates.difference(&neighbours[pivot]).copied().collect();
+            for v in others {
+                clique.push(v);
+                extend(
+                    neighbours,
+                    clique,
+                    candidates.intersection(&neighbours[v]).copied().collect(),
+                    excluded.intersection(&neighbours[v]).copied().collect(),
+                    cliques,
+                );
+                clique.pop();
+                candidates.remove(&v);
+                excluded.insert(v);
+            }
+        }
+
+        assert!(!self.directed, "cliques need an undirected graph");
+        let mut cliques = vec![];
+        // the empty clique is not a clique of the empty graph
+        if self.is_empty() {
+            return cliques;
+        }
+        extend(
+            &self.neighbour_sets(),
+            &mut vec![],
+            (0..self.len()).collect(),
+            HashSet::new(),
+            &mut cliques,
+        );
+        cliques.sort();
+        cliques
+    }
+
+    // the largest clique (the first one in id order if there are several)
+    pub fn maximum_clique(&self) -> Vec<usize> {
+        self.maximal_cliques()
+            .into_iter()
+            .rev()
+            .max_by_key(Vec::len)
+            .unwrap_or_default()
+    }
+
+    // all the cliques of k nodes, each sorted by id, in id order
+    pub fn cliques_of_size(&self, k: usize) -> Vec<Vec<usize>> {
+        fn extend(
+            neighbours: &[HashSet<usize>],
+            k: usize,
+            clique: &mut Vec<usize>,
+            candidates: &[usize],
+            cliques: &mut Vec<Vec<usize>>,
+        ) {
+            if clique.len() == k {
+                cliques.push(clique.clone());
+                return;
+            }
+            for (i, &v) in candidates.iter().enumerate() {
+                let next: Vec<usize> = candidates[i + 1..]
+                    .iter()
+                    .filter(|u| neighbours[v].contains(u))
+                    .copied()
+                    .collect();
+                if next.len() + clique.len() + 1 >= k {
+                    clique.push(v);
+                    extend(neighbours, k, clique, &next, cliques);
+                    clique.pop();
+                }
+            }
+        }
+
+        assert!(!self.directed, "cliques need an undirected graph");
+        let mut cliques = vec![];
+        if k > 0 {
+            let candidates: Vec<usize> = (0..self.len()).collect();
+            extend(
+                &self.neighbour_sets(),
+                k,
+                &mut vec![],
+                &candidates,
+                &mut cliques,
+            );
+        }
+        cliques
+    }
+}
+
+// Orderings, for directed graphs
+impl<N: Clone + Eq + Hash, E: Clone> Graph<N, E> {
+    // Kahn's algorithm, always picking the smallest available node by
+    // key; None if there is a cycle
+    pub fn topological_sort_by_key<K: Ord>(&self, key: impl Fn(usize) -> K) -> Option<Vec<usize>> {
+        let mut in_degrees: Vec<usize> = (0..self.len()).map(|id| self.in_degree(id)).collect();
+        let mut available: BinaryHeap<Reverse<(K, usize)>> = (0..self.len())
+            .filter(|&id| in_degrees[id] == 0)
+            .map(|id| Reverse((key(id), id)))
+            .collect();
+        let mut order = Vec::with_capacity(self.len());
+        while let Some(Reverse((_, id))) = available.pop() {
+            order.push(id);
+            for n in self.neighbours(id) {
+                in_degrees[n] -= 1;
+                if in_degrees[n] == 0 {
+                    available.push(Reverse((key(n), n)));
+                }
+            }
+        }
+        (order.len() == self.len()).then_some(order)
+    }
+
+    // Tarjan's algorithm (without recursion). Components come out in
+    // reverse topological order: no edge goes from a component to a later
+    // one
+    pub fn strongly_connected_components(&self) -> Vec<Vec<usize>> {
+        let n = self.len();
+        let mut index = vec![usize::MAX; n];
+        let mut low_link = vec![0; n];
+        let mut on_stack = vec![false; n];
+        let mut stack = vec![];
+        let mut components = vec![];
+        let mut next_index = 0;
+
+        for root in 0..n {
+            if index[root] != usize::MAX {
+                continue;
+            }
+            // the nodes being visited, with the position of the next edge
+            let mut visiting = vec![(root, 0)];
+            while let Some(&mut (id, ref mut edge)) = visiting.last_mut() {
+                if *edge == 0 && index[id] == usize::MAX {
+                    index[id] = next_index;
+                    low_link[id] = next_index;
+                    next_index += 1;
+                    stack.push(id);
+                    on_stack[id] = true;
+                }
+                if let Some(&(to, _)) = self.edges[id].get(*edge) {
+                    *edge += 1;
+                    if index[to] == usize::MAX {
+                        visiting.push((to, 0));
+                    } else if on_stack[to] {
+                        low_link[id] = low_link[id].min(index[to]);
+                    }
+                    continue;
+                }
+                visiting.pop();
+                if let Some(&(parent, _)) = visiting.last() {
+                    low_link[parent] = low_link[parent].min(low_link[id]);
+                }
+                if low_link[id] == index[id] {
+                    let mut component = vec![];
+                    loop {
+                        let member = stack.pop().unwrap();
+                        on_stack[member] = false;
+                        component.push(member);
+                        if member == id {
+                            break;
+                        }
+                    }
+                    component.sort();
+                    components.push(component);
+                }
+            }
+        }
+        components
+    }
+
+    // the graph of the strongly connected components, which has no cycle
+    pub fn condensation(&self) -> Condensation {
+        let components = self.strongly_connected_components();
+        let mut component_of = vec![0; self.len()];
+        for (c, component) in components.iter().enumerate() {
+            component.iter().for_each(|&id| component_of[id] = c);
+        }
+        let mut graph = Graph::directed();
+        for c in 0..components.len() {
+            graph.node(c);
+        }
+        for from in 0..self.len() {
+            for to in self.neighbours(from) {
+                if component_of[from] != component_of[to] {
+                    graph.add_edge_ids(component_of[from], component_of[to], ());
+                }
+            }
+        }
+        Condensation {
+            components,
+            component_of,
+            graph,
+        }
+    }
+
+    // Runs the tasks of a dependency graph (edges from each task to the
+    // ones that wait for it) with some workers: whenever a worker is idle
+    // it takes the available task smallest by key, and spends duration on
+    // it. Panics if there is a cycle
+    pub fn schedule<K: Ord>(
+        &self,
+        workers: usize,
+        duration: impl Fn(usize) -> usize,
+        key: impl Fn(usize) -> K,
+    ) -> Schedule {
+        assert!(workers > 0, "no worker");
+        let mut in_degrees: Vec<usize> = (0..self.len()).map(|id| self.in_degree(id)).collect();
+        let mut available: BinaryHeap<Reverse<(K, usize)>> = (0..self.len())
+            .filter(|&id| in_degrees[id] == 0)
+            .map(|id| Reverse((key(id), id)))
+            .collect();
+        // tasks in progress by end time
+        let mut running: BinaryHeap<Reverse<(usize, usize)>> = BinaryHeap::new();
+        let mut schedule = Schedule {
+            order: vec![],
+            time: 0,
+        };
+        loop {
+            while running.len() < workers {
+                let Some(Reverse((_, id))) = available.pop() else {
+                    break;
+                };
+                running.push(Reverse((schedule.time + duration(id), id)));
+            }
+            let Some(&Reverse((time, _))) = running.peek() else {
+                break;
+            };
+            schedule.time = time;
+            // all the tasks done at that time
+            while let Some(&Reverse((end, id))) = running.peek() {
+                if end > time {
+                    break;
+                }
+                running.pop();
+                schedule.order.push(id);
+                for n in self.neighbours(id) {
+                    in_degrees[n] -= 1;
+                    if in_degrees[n] == 0 {
+                        available.push(Reverse((key(n), n)));
+                    }
+                }
+            }
+        }
+        assert_eq!(self.len(), schedule.order.len(), "cycle in the tasks");
+        schedule
+    }
+}
+
+impl<N: Clone + Eq + Hash + Ord, E: Clone> Graph<N, E> {
+    // the topological order that is smallest by label
+    pub fn topological_order(&self) -> Option<Vec<usize>> {
+        self.topological_sort_by_key(|id| self.labels[id].clone())
+    }
+}
+
+// the components, the component of each node, and the graph of
+// components (labelled by their index)
+#[derive(Debug, Clone)]
+pub struct Condensation {
+    pub components: Vec<Vec<usize>>,
+    pub component_of: Vec<usize>,
+    pub graph: Graph<usize>,
+}
+
+// the tasks in order of completion, and the time when all are done
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schedule {
+    pub order: Vec<usize>,
+    pub time: usize,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_undirected() {
+        // 2017 day 12
+        let pipes = "0 <-> 2
+1 <-> 1
+2 <-> 0, 3, 4
+3 <-> 2, 4
+4 <-> 2, 3, 6
+5 <-> 6
+6 <-> 4, 5";
+        let graph = Graph::parse_adjacency(pipes, "<->", ",", false).unwrap();
+        assert_eq!(7, graph.len());
+        assert_eq!(7, graph.edge_count());
+        let zero = graph.id("0").unwrap();
+        let mut group: Vec<_> = graph
+            .reachable(zero)
+            .iter()
+            .map(|&i| *graph.label(i))
+            .collect();
+        group.sort();
+        assert_eq!(vec!["0", "2", "3", "4", "5", "6"], group);
+        assert_eq!(2, graph.components().len());
+        let distances = graph.distances(zero);
+        assert_eq!(Some(4), distances[graph.id("5").unwrap()]);
+        assert_eq!(None, distances[graph.id("1").unwrap()]);
+        let four = graph.id("4").unwrap();
+        assert_eq!(3, graph.degree(four));
+        assert_eq!(3, graph.predecessors(four).count());
+        assert!(graph.has_edge(graph.id("6").unwrap(), four));
+
+        let sub = graph.subgraph(["2", "3", "4"].map(|l| graph.id(l).unwrap()));
+        assert_eq!((3, 3), (sub.len(), sub.edge_count()));
+        assert_eq!(Some(0), sub.id("2"));
+    }
+
+    #[test]
+    fn test_directed() {
+        // 2019 day 6
+        let orbits = "COM)B\nB)C\nC)D\nD)E\nE)F\nB)G\nG)H\nD)I\nE)J\nJ)K\nK)L";
+        let graph = Graph::parse_pairs(orbits, ")", true).unwrap();
+        assert_eq!((12, 11), (graph.len(), graph.edge_count()));
+        let err = Graph::parse_pairs("COM)B\nB-C\nC)D", ")", true).unwrap_err();
+        assert_eq!("expected \")\" at line 2, column 1", err.to_string());
+        let err = Graph::parse_adjacency("0 <-> 2\n\n  1, 2", "<->", ",", false).unwrap_err();
+        assert_eq!((3, 1), (err.line, err.column));
+        let b = graph.id("B").unwrap();
+        assert_eq!((2, 1), (graph.degree(b), graph.in_degree(b)));
+        assert_eq!(
+            vec![graph.id("COM").unwrap()],
+            graph.predecessors(b).collect::<Vec<_>>()
+        );
+        assert_eq!(11, graph.reachable(b).len());
+        assert_eq!(1, graph.components().len());
+
+        // weighted edges, as 2020 day 7 bags
+        let mut bags: Graph<&str, usize> = [
+            ("gold", "olive", 1),
+            ("gold", "plum", 2),
+            ("olive", "blue", 3),
+        ]
+        .into_iter()
+        .collect();
+        let gold = bags.id("gold").unwrap();
+        let plum = bags.id("plum").unwrap();
+        assert_eq!(Some(&2), bags.weight(gold, plum));
+        bags.add_edge("gold", "plum", 5);
+        assert_eq!(Some(&5), bags.weight(gold, plum));
+        assert_eq!(None, bags.weight(plum, gold));
+        assert_eq!(3, bags.edge_count());
+        assert_eq!(vec![(1, &1), (2, &5)], bags.edges(gold).collect::<Vec<_>>());
+    }
+
+    static LAN: &str = "kh-tc\nqp-kh\nde-cg\nka-co\nyn-aq\nqp-ub\ncg-tb\nvc-aq\ntb-ka\nwh-tc\nyn-cg
+kh-ub\nta-co\nde-co\ntc-td\ntb-wq\nwh-td\nta-ka\ntd-qp\naq-cg\nwq-ub\nub-vc\nde-ta\nwq-aq
+wq-vc\nwh-yn\nka-de\nkh-ta\nco-tc\nwh-qp\ntb-vc\ntd-yn";
+
+    #[test]
+    fn test_cliques() {
+        // 2024 day 23
+        let lan = Graph::parse_pairs(LAN, "-", false).unwrap();
+        let names = |clique: &[usize]| {
+            let mut names: Vec<&str> = clique.iter().map(|&c| *lan.label(c)).collect();
+            names.sort();
+            names.join(",")
+        };
+        let triangles = lan.cliques_of_size(3);
+        assert_eq!(12, triangles.len());
+        let with_t = triangles
+            .iter()
+            .filter(|t| t.iter().any(|&c| lan.label(c).starts_with('t')))
+            .count();
+        assert_eq!(7, with_t);
+        assert_eq!("co,de,ka,ta", names(&lan.maximum_clique()));
+        let maximal = lan.maximal_cliques();
+        assert!(maximal.iter().all(|c| c
+            .iter()
+            .all(|&a| c.iter().all(|&b| a == b || lan.has_edge(a, b)))));
+        assert!(triangles
+            .iter()
+            .all(|t| maximal.iter().any(|c| t.iter().all(|n| c.contains(n)))));
+        assert_eq!(lan.edge_count(), lan.cliques_of_size(2).len());
+
+        let empty: Graph<&str> = Graph::undirected();
+        assert!(empty.maximal_cliques().is_empty());
+        assert!(empty.maximum_clique().is_empty());
+    }
+
+    #[test]
+    fn test_ordering() {
+        // 2018 day 7
+        let mut steps = Graph::directed();
+        for (before, after) in ["CA", "CF", "AB", "AD", "BE", "DE", "FE"].map(|s| {
+            let mut c = s.chars();
+            (c.next().unwrap(), c.next().unwrap())
+        }) {
+            steps.add_edge(before, after, ());
+        }
+        let order: String = steps
+            .topological_order()
+            .unwrap()
+            .iter()
+            .map(|&id| *steps.label(id))
+            .collect();
+        assert_eq!("CABDFE", order);
+        let schedule = steps.schedule(
+            2,
+            |id| (*steps.label(id) as u8 - b'A') as usize + 1,
+            |id| *steps.label(id),
+        );
+        assert_eq!(15, schedule.time);
+        let order: String = schedule.order.iter().map(|&id| *steps.label(id)).collect();
+        assert_eq!("CABFDE", order);
+
+        steps.add_edge('E', 'C', ());
+        assert_eq!(None, steps.topological_order());
+    }
+
+    #[test]
+    fn test_components() {
+        // two cycles, a -> b -> c -> a and d <-> e, joined by c -> d, and f
+        let graph = Graph::parse_pairs("a-b\nb-c\nc-a\nc-d\nd-e\ne-d\ne-f", "-", true).unwrap();
+        let components = graph.strongly_connected_components();
+        let named: Vec<Vec<&str>> = components
+            .iter()
+            .map(|c| c.iter().map(|&id| *graph.label(id)).collect())
+            .collect();
+        assert_eq!(vec![vec!["f"], vec!["d", "e"], vec!["a", "b", "c"]], named);
+
+        let condensation = graph.condensation();
+        assert_eq!(3, condensation.graph.len());
+        assert_eq!(2, condensation.graph.edge_count());
+        let order = condensation.graph.topological_order().unwrap();
+        assert_eq!(vec![2, 1, 0], order);
+        assert_eq!(condensation.component_of[0], condensation.component_of[2]);
+    }
+}
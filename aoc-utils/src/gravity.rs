@@ -0,0 +1,628 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt::Display,
+};
+
+use crate::cuboid::Cuboid;
+use crate::graph::Graph;
+
+// Things falling on grids: rocks pushed around in a narrow chamber (2022
+// day 17), sand piling up (2022 day 14), water filling basins (2018 day
+// 17) and bricks settling onto each other (2023 day 22)
+
+// A shape as a bitboard: one mask per row, bottom row first, with bit i
+// for the i-th column from the left
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Shape {
+    rows: Vec<u8>,
+    width: usize,
+}
+
+impl Shape {
+    pub fn new(rows: Vec<u8>) -> Self {
+        assert!(rows.iter().all(|&r| r != 0), "empty row in shape");
+        let width = rows
+            .iter()
+            .map(|r| 8 - r.leading_zeros() as usize)
+            .max()
+            .unwrap_or(0);
+        Self { rows, width }
+    }
+
+    // the shape as drawn, top row first, with # for its cells
+    pub fn parse(picture: &str) -> Self {
+        let rows = picture
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .rev()
+            .map(|l| {
+                l.bytes()
+                    .enumerate()
+                    .filter(|(_, b)| *b == b'#')
+                    .fold(0, |row, (i, _)| row | 1 << i)
+            })
+            .collect();
+        Self::new(rows)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn rows(&self) -> &[u8] {
+        &self.rows
+    }
+}
+
+// A chamber at most 8 columns wide, with a floor below row 0, where shapes
+// fall until they rest
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Chamber {
+    width: usize,
+    rows: Vec<u8>,
+}
+
+impl Chamber {
+    pub fn new(width: usize) -> Self {
+        assert!(width <= 8, "a chamber is at most 8 wide");
+        Self {
+            width,
+            rows: vec![],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    // the height of the highest rock
+    pub fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn rows(&self) -> &[u8] {
+        &self.rows
+    }
+
+    // whether shape fits with its bottom left corner at (x, y)
+    pub fn fits(&self, shape: &Shape, x: usize, y: usize) -> bool {
+        x + shape.width <= self.width
+            && shape
+                .rows
+                .iter()
+                .enumerate()
+                .all(|(i, &row)| self.rows.get(y + i).is_none_or(|&r| r & row << x == 0))
+    }
+
+    pub fn place(&mut self, shape: &Shape, x: usize, y: usize) {
+        if self.rows.len() < y + shape.height() {
+            self.rows.resize(y + shape.height(), 0);
+        }
+        for (i, &row) in shape.rows.iter().enumerate() {
+            self.rows[y + i] |= row << x;
+        }
+    }
+
+    // Drops shape from (x, y): before each step down, push gives a move
+    // to the side (-1 for left, 1 for right) made if there is room. The
+    // shape stays where it rests, which is returned
+    pub fn drop(
+        &mut self,
+        shape: &Shape,
+        (mut x, mut y): (usize, usize),
+        mut push: impl FnMut() -> i64,
+    ) -> (usize, usize) {
+        loop {
+            if let Some(nx) = x.checked_add_signed(push() as isize) {
+                if self.fits(shape, nx, y) {
+                    x = nx;
+                }
+            }
+            if y == 0 || !self.fits(shape, x, y - 1) {
+                self.place(shape, x, y);
+                return (x, y);
+            }
+            y -= 1;
+        }
+    }
+
+    // for each column, how far below the top its highest rock is (the
+    // height for empty columns); with the shape and push to come next, the
+    // state of a falling rocks simulation for cycle detection
+    pub fn skyline(&self) -> Vec<usize> {
+        (0..self.width)
+            .map(|col| {
+                self.rows
+                    .iter()
+                    .rev()
+                    .position(|r| r & 1 << col != 0)
+                    .unwrap_or(self.rows.len())
+            })
+            .collect()
+    }
+}
+
+impl Display for Chamber {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in self.rows.iter().rev() {
+            write!(f, "|")?;
+            for col in 0..self.width {
+                write!(f, "{}", if row & 1 << col != 0 { '#' } else { '.' })?;
+            }
+            writeln!(f, "|")?;
+        }
+        write!(f, "+{}+", "-".repeat(self.width))
+    }
+}
+
+// What is below the lowest rock, with y growing downward
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Floor {
+    // nothing stops what falls below this level
+    Abyss(i64),
+    // solid ground at this level
+    Solid(i64),
+}
+
+// Sand poured from a source: each grain falls straight down, else down
+// and to the left, else down and to the right, until it rests on rock or
+// sand (2022 day 14). y grows downward
+#[derive(Debug, Clone)]
+pub struct Sand {
+    rocks: HashSet<(i64, i64)>,
+    sand: HashSet<(i64, i64)>,
+    floor: Floor,
+    // the way the last grain fell; the next one from the same source
+    // follows it down to the last free position
+    path: Vec<(i64, i64)>,
+}
+
+impl Sand {
+    pub fn new(rocks: impl IntoIterator<Item = (i64, i64)>, floor: Floor) -> Self {
+        Self {
+            rocks: rocks.into_iter().collect(),
+            sand: HashSet::new(),
+            floor,
+            path: vec![],
+        }
+    }
+
+    pub fn is_blocked(&self, pos: (i64, i64)) -> bool {
+        matches!(self.floor, Floor::Solid(y) if pos.1 >= y)
+            || self.rocks.contains(&pos)
+            || self.sand.contains(&pos)
+    }
+
+    // the grains at rest
+    pub fn grains(&self) -> usize {
+        self.sand.len()
+    }
+
+    // where the next grain from source comes to rest; None if it falls
+    // into the abyss, or the source is blocked
+    pub fn drop(&mut self, source: (i64, i64)) -> Option<(i64, i64)> {
+        if self.is_blocked(source) {
+            return None;
+        }
+        if self.path.first() != Some(&source) {
+            self.path = vec![source];
+        }
+        loop {
+            let (x, y) = *self.path.last().unwrap();
+            if matches!(self.floor, Floor::Abyss(depth) if y > depth) {
+                return None;
+            }
+            match [(x, y + 1), (x - 1, y + 1), (x + 1, y + 1)]
+                .into_iter()
+                .find(|&p| !self.is_blocked(p))
+            {
+                Some(next) => self.path.push(next),
+                None => {
+                    self.path.pop();
+                    self.sand.insert((x, y));
+                    return Some((x, y));
+                }
+            }
+        }
+    }
+
+    // the grains that come to rest, until one falls off or the source is
+    // blocked
+    pub fn pour(&mut self, source: (i64, i64)) -> usize {
+        std::iter::from_fn(|| self.drop(source)).count()
+    }
+}
+
+impl Display for Sand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let all = || self.rocks.iter().chain(&self.sand);
+        let (Some(x_min), Some(x_max)) = (all().map(|p| p.0).min(), all().map(|p| p.0).max())
+        else {
+            return Ok(());
+        };
+        let mut y_max = all().map(|p| p.1).max().unwrap();
+        if let Floor::Solid(y) = self.floor {
+            y_max = y_max.max(y);
+        }
+        for y in 0..=y_max {
+            for x in x_min..=x_max {
+                let c = if self.sand.contains(&(x, y)) {
+                    'o'
+                } else if self.is_blocked((x, y)) {
+                    '#'
+                } else {
+                    '.'
+                };
+                write!(f, "{c}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Water {
+    Flowing,
+    Still,
+}
+
+// Water from source falling down and spreading over walls, filling basins
+// up to their lowest side, and flowing off past depth (2018 day 17). y
+// grows downward
+pub fn pour_water(
+    walls: &HashSet<(i64, i64)>,
+    source: (i64, i64),
+    depth: i64,
+) -> HashMap<(i64, i64), Water> {
+    let mut water = HashMap::new();
+    // where each stream falls from, and the stream it spilled from
+    let mut streams = vec![(source, None)];
+    let mut pending = vec![0];
+    while let Some(stream) = pending.pop() {
+        let ((x, top), parent) = streams[stream];
+        // fall, through water flowing already, onto a wall or still water
+        let mut y = top;
+        while y < depth {
+            let below = (x, y + 1);
+            if walls.contains(&below) || water.get(&below) == Some(&Water::Still) {
+                break;
+            }
+            water.insert(below, Water::Flowing);
+            y += 1;
+        }
+        if y == depth {
+            continue;
+        }
+
+        // spread, rising while both sides are walled
+        loop {
+            let spread = |dx: i64| {
+                let mut end = x;
+                loop {
+                    let below = (end, y + 1);
+                    if !walls.contains(&below) && water.get(&below) != Some(&Water::Still) {
+                        // falls from there, unless already flowing
+                        let open = water.get(&below) != Some(&Water::Flowing);
+                        return (end, Some(open));
+                    }
+                    if walls.contains(&(end + dx, y)) {
+                        return (end, None);
+                    }
+                    end += dx;
+                }
+            };
+            let (left, left_fall) = spread(-1);
+            let (right, right_fall) = spread(1);
+            let level = if left_fall.is_none() && right_fall.is_none() {
+                Water::Still
+            } else {
+                Water::Flowing
+            };
+            for x in left..=right {
+                water.insert((x, y), level);
+            }
+            if level == Water::Flowing {
+                for (end, fall) in [(left, left_fall), (right, right_fall)] {
+                    if fall == Some(true) {
+                        pending.push(streams.len());
+                        streams.push(((end, y), Some(stream)));
+                    }
+                }
+                break;
+            }
+            if y == top {
+                // filled up to where the stream spilled from: the stream
+                // above rises from there
+                pending.extend(parent);
+                break;
+            }
+            y -= 1;
+        }
+    }
+    water
+}
+
+// Bricks, as boxes with z upward, after they all fell onto the ground or
+// onto each other, with the graph of which bricks rest on which (an edge
+// from each brick to the ones right above that it supports)
+#[derive(Debug, Clone)]
+pub struct Settled {
+    pub bricks: Vec<Cuboid<3>>,
+    pub supports: Graph<usize>,
+}
+
+// Settles bricks, keeping their order, on the ground below z = ground: the
+// lowest bricks fall first, each to the top of the highest brick below it
+// as seen in a skyline of the top of each (x, y) column
+pub fn settle(bricks: impl IntoIterator<Item = Cuboid<3>>, ground: i64) -> Settled {
+    let mut bricks: Vec<_> = bricks.into_iter().collect();
+    let mut supports = Graph::directed();
+    for id in 0..bricks.len() {
+        supports.node(id);
+    }
+
+    let mut order: Vec<usize> = (0..bricks.len()).collect();
+    order.sort_by_key(|&id| bricks[id].min[2]);
+    // the top and the brick there, for each column
+    let mut skyline: BTreeMap<(i64, i64), (i64, usize)> = BTreeMap::new();
+    for id in order {
+        let brick = bricks[id];
+        let columns: Vec<(i64, i64)> = (brick.min[0]..brick.max[0])
+            .flat_map(|x| (brick.min[1]..brick.max[1]).map(move |y| (x, y)))
+            .collect();
+        let tops: Vec<(i64, usize)> = columns
+            .iter()
+            .filter_map(|c| skyline.get(c).copied())
+            .collect();
+        let rest = tops.iter().map(|t| t.0).max().unwrap_or(ground).max(ground);
+        for &(top, below) in &tops {
+            if top == rest && !supports.has_edge(below, id) {
+                supports.add_edge_ids(below, id, ());
+            }
+        }
+        let fallen = brick.translate([0, 0, rest - brick.min[2]]);
+        for c in columns {
+            skyline.insert(c, (fallen.max[2], id));
+        }
+        bricks[id] = fallen;
+    }
+    Settled { bricks, supports }
+}
+
+impl Settled {
+    // the bricks that can go without any other falling
+    pub fn removable(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.bricks.len()).filter(|&id| {
+            self.supports
+                .neighbours(id)
+                .all(|above| self.supports.in_degree(above) > 1)
+        })
+    }
+
+    // the other bricks that fall when removed goes
+    pub fn chain_reaction(&self, removed: usize) -> usize {
+        let mut left: Vec<usize> = (0..self.bricks.len())
+            .map(|id| self.supports.in_degree(id))
+            .collect();
+        let mut falling = vec![removed];
+        let mut count = 0;
+        while let Some(id) = falling.pop() {
+            for above in self.supports.neighbours(id) {
+                left[above] -= 1;
+                if left[above] == 0 {
+                    count += 1;
+                    falling.push(above);
+                }
+            }
+        }
+        count
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn rocks() -> Vec<Shape> {
+        [
+            "####",
+            ".#.\n###\n.#.",
+            "..#\n..#\n###",
+            "#\n#\n#\n#",
+            "##\n##",
+        ]
+        .map(Shape::parse)
+        .to_vec()
+    }
+
+    #[test]
+    fn test_chamber() {
+        // 2022 day 17
+        let jets = b">>><<><>><<<>><>>><<<>>><<<><<<>><>><<>>";
+        let mut pushes = jets.iter().map(|&j| if j == b'<' { -1 } else { 1 }).cycle();
+        let rocks = rocks();
+        assert_eq!((3, 3), (rocks[1].width(), rocks[1].height()));
+        assert_eq!(&[0b111, 0b100, 0b100], rocks[2].rows());
+
+        let mut chamber = Chamber::new(7);
+        for rock in rocks.iter().cycle().take(2022) {
+            let start = (2, chamber.height() + 3);
+            chamber.drop(rock, start, || pushes.next().unwrap());
+        }
+        assert_eq!(3068, chamber.height());
+        assert_eq!(7, chamber.skyline().len());
+        assert_eq!(0, *chamber.skyline().iter().min().unwrap());
+
+        let mut chamber = Chamber::new(7);
+        for rock in &rocks[..3] {
+            let start = (2, chamber.height() + 3);
+            chamber.drop(rock, start, || 0);
+        }
+        assert_eq!(
+            "|....#..|\n|....#..|\n|..###..|\n|...#...|\n|..###..|\n|...#...|\n|..####.|\n+-------+",
+            chamber.to_string()
+        );
+    }
+
+    #[test]
+    fn test_sand() {
+        // 2022 day 14
+        let mut rocks = vec![];
+        rocks.extend((4..=6).map(|y| (498, y)));
+        rocks.extend((496..=497).map(|x| (x, 6)));
+        rocks.extend((502..=503).map(|x| (x, 4)));
+        rocks.extend((4..=9).map(|y| (502, y)));
+        rocks.extend((494..=501).map(|x| (x, 9)));
+
+        let mut sand = Sand::new(rocks.iter().copied(), Floor::Abyss(9));
+        assert_eq!(Some((500, 8)), sand.drop((500, 0)));
+        assert_eq!(23, sand.pour((500, 0)));
+        assert_eq!(24, sand.grains());
+        assert_eq!(None, sand.drop((500, 0)));
+
+        let mut sand = Sand::new(rocks, Floor::Solid(11));
+        assert_eq!(93, sand.pour((500, 0)));
+        assert!(sand.is_blocked((500, 0)));
+        assert!(sand.to_string().starts_with("..........o.........."));
+    }
+
+    #[test]
+    fn test_water() {
+        // 2018 day 17
+        let mut walls = HashSet::new();
+        let mut vein = |xs: std::ops::RangeInclusive<i64>, ys: std::ops::RangeInclusive<i64>| {
+            for x in xs {
+                for y in ys.clone() {
+                    walls.insert((x, y));
+                }
+            }
+        };
+        vein(495..=495, 2..=7);
+        vein(495..=501, 7..=7);
+        vein(501..=501, 3..=7);
+        vein(498..=498, 2..=4);
+        vein(506..=506, 1..=2);
+        vein(498..=498, 10..=13);
+        vein(504..=504, 10..=13);
+        vein(498..=504, 13..=13);
+
+        let water = pour_water(&walls, (500, 0), 13);
+        let in_range = |((_, y), _): &(&(i64, i64), &Water)| (1..=13).contains(y);
+        assert_eq!(57, water.iter().filter(in_range).count());
+        assert_eq!(
+            29,
+            water
+                .iter()
+                .filter(in_range)
+                .filter(|(_, &w)| w == Water::Still)
+                .count()
+        );
+    }
+
+    // walls (#) and the source (+) drawn as in the puzzle, with the water
+    // poured down to the line below the picture drawn in
+    fn pour_picture(picture: &str) -> String {
+        let mut walls = HashSet::new();
+        let mut source = (0, 0);
+        for (y, line) in picture.lines().enumerate() {
+            for (x, c) in line.chars().enumerate() {
+                match c {
+                    '#' => {
+                        walls.insert((x as i64, y as i64));
+                    }
+                    '+' => source = (x as i64, y as i64),
+                    _ => {}
+                }
+            }
+        }
+        let (width, depth) = (picture.find('\n').unwrap(), picture.lines().count());
+        let water = pour_water(&walls, source, depth as i64);
+        (0..=depth as i64)
+            .map(|y| {
+                (0..width as i64)
+                    .map(|x| match water.get(&(x, y)) {
+                        _ if walls.contains(&(x, y)) => '#',
+                        _ if (x, y) == source => '+',
+                        Some(Water::Still) => '~',
+                        Some(Water::Flowing) => '|',
+                        None => '.',
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn test_water_basins() {
+        // two streams falling into the same basin fill it once
+        let picture = "\
+......+.......
+..............
+.....###......
+..............
+..#.......#...
+..#.......#...
+..#########...";
+        let expected = "\
+......+.......
+....|||||.....
+....|###|.....
+.|||||||||||..
+.|#~~~~~~~#|..
+.|#~~~~~~~#|..
+.|#########|..
+.|.........|..";
+        assert_eq!(expected, pour_picture(picture));
+
+        // a basin inside another one, filled first
+        let picture = "\
+.....+......
+............
+.#........#.
+.#..#..#..#.
+.#..####..#.
+.#........#.
+.##########.";
+        let expected = "\
+.....+......
+||||||||||||
+|#~~~~~~~~#|
+|#~~#~~#~~#|
+|#~~####~~#|
+|#~~~~~~~~#|
+|##########|
+|..........|";
+        assert_eq!(expected, pour_picture(picture));
+    }
+
+    #[test]
+    fn test_settle() {
+        // 2023 day 22
+        let bricks = [
+            ([1, 0, 1], [1, 2, 1]),
+            ([0, 0, 2], [2, 0, 2]),
+            ([0, 2, 3], [2, 2, 3]),
+            ([0, 0, 4], [0, 2, 4]),
+            ([2, 0, 5], [2, 2, 5]),
+            ([0, 1, 6], [2, 1, 6]),
+            ([1, 1, 8], [1, 1, 9]),
+        ]
+        .map(|(min, max)| Cuboid::from_inclusive(min, max));
+        let settled = settle(bricks, 1);
+        assert_eq!([1, 1, 5], settled.bricks[6].min);
+        assert_eq!(
+            vec![1, 2],
+            settled.supports.neighbours(0).collect::<Vec<_>>()
+        );
+        assert_eq!(5, settled.removable().count());
+        let falls: Vec<_> = (0..7).map(|id| settled.chain_reaction(id)).collect();
+        assert_eq!(vec![6, 0, 0, 0, 0, 1, 0], falls);
+    }
+}
@@ -1,11 +1,13 @@
-use anyhow::Error;
 use std::{
+    collections::{HashMap, VecDeque},
     convert::TryFrom,
     fmt::Display,
     marker::PhantomData,
     ops::{Index, IndexMut},
 };
 
+use crate::error::ParseError;
+
 pub trait Distance {
     fn distance(pos1: (usize, usize), pos2: (usize, usize)) -> usize;
 }
@@ -68,6 +70,18 @@ impl<T, D> Grid<T, D> {
         self.grid.iter_mut()
     }
 
+    // cells in row order
+    pub fn from_vec(width: usize, grid: Vec<T>) -> Self {
+        let height = grid.len().checked_div(width).unwrap_or(0);
+        assert_eq!(grid.len(), width * height, "incomplete last row");
+        Self {
+            grid,
+            am: PhantomData,
+            width,
+            height,
+        }
+    }
+
     pub fn replace_with(&self, grid: Vec<T>) -> Self {
         assert_eq!(grid.len(), self.grid.len());
         Self { grid, ..*self }
@@ -98,33 +112,78 @@ impl<T, D: Distance> Grid<T, D> {
         self.around_pos(pos)
             .filter(move |p| D::distance(pos, *p) == 1)
     }
+
+    // Compresses the grid to the points of interest: the number of steps
+    // between each pair of them, walking only on open cells (None when
+    // there is no way). The points themselves need not be open
+    pub fn poi_distances(
+        &self,
+        pois: &[(usize, usize)],
+        open: impl Fn(&T) -> bool,
+    ) -> Vec<Vec<Option<usize>>> {
+        pois.iter()
+            .map(|&from| {
+                let mut steps = vec![None; self.grid.len()];
+                steps[self.pos_to_idx(from)] = Some(0);
+                let mut queue = VecDeque::from([from]);
+                while let Some(pos) = queue.pop_front() {
+                    let next = steps[self.pos_to_idx(pos)].map(|s| s + 1);
+                    for n in self.neighbours(pos) {
+                        let idx = self.pos_to_idx(n);
+                        if steps[idx].is_none() && (open(&self.grid[idx]) || pois.contains(&n)) {
+                            steps[idx] = next;
+                            // paths do not go through closed points of interest
+                            if open(&self.grid[idx]) {
+                                queue.push_back(n);
+                            }
+                        }
+                    }
+                }
+                pois.iter().map(|&to| steps[self.pos_to_idx(to)]).collect()
+            })
+            .collect()
+    }
 }
 
-impl<T: TryFrom<u8>, D> TryFrom<&[u8]> for Grid<T, D>
+impl<T: TryFrom<u8>, D> Grid<T, D>
 where
-    Error: From<<T as std::convert::TryFrom<u8>>::Error>,
+    T::Error: Display,
 {
-    type Error = Error;
-
-    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
-        let mut width = 0;
-        let grid = value
-            .iter()
-            .cloned()
-            .enumerate()
-            .filter_map(|(idx, b)| {
-                if b == 10 {
-                    if width == 0 {
-                        width = idx;
+    // src is the input as text, for error reporting. Blank lines are
+    // skipped; all the other rows must have the same width
+    fn parse_bytes(value: &[u8], src: &str) -> Result<Self, ParseError> {
+        let mut grid = Vec::with_capacity(value.len());
+        let mut width = None;
+        let mut column = 0;
+        // offset of the first byte of the current row
+        let mut row_start = 0;
+        for (idx, &b) in value.iter().chain(Some(&b'\n')).enumerate() {
+            if b == b'\r' && matches!(value.get(idx + 1), None | Some(b'\n')) {
+                // CRLF line endings
+                continue;
+            } else if b == b'\n' {
+                match width {
+                    _ if column == 0 => {}
+                    None => width = Some(column),
+                    Some(w) if w != column => {
+                        return Err(ParseError::new(
+                            src,
+                            row_start,
+                            column,
+                            format!("expected a row of width {w}"),
+                        ))
                     }
-                    None
-                } else {
-                    Some(b)
+                    _ => {}
                 }
-            })
-            .map(|b| T::try_from(b))
-            .collect::<Result<Vec<_>, _>>()?;
-        let height = grid.len() / width;
+                column = 0;
+                row_start = idx + 1;
+            } else {
+                grid.push(T::try_from(b).map_err(|e| ParseError::new(src, idx, 1, e.to_string()))?);
+                column += 1;
+            }
+        }
+        let width = width.unwrap_or(0);
+        let height = grid.len().checked_div(width).unwrap_or(0);
         Ok(Self {
             am: PhantomData,
             grid,
@@ -134,36 +193,25 @@ where
     }
 }
 
+impl<T: TryFrom<u8>, D> TryFrom<&[u8]> for Grid<T, D>
+where
+    T::Error: Display,
+{
+    type Error = ParseError;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        Self::parse_bytes(value, &String::from_utf8_lossy(value))
+    }
+}
+
 impl<T: TryFrom<u8>, D> TryFrom<&str> for Grid<T, D>
 where
-    Error: From<<T as std::convert::TryFrom<u8>>::Error>,
+    T::Error: Display,
 {
-    type Error = Error;
+    type Error = ParseError;
 
     fn try_from(value: &str) -> Result<Self, Self::Error> {
-        let mut width = 0;
-        let grid: Vec<T> = value
-            .bytes()
-            .enumerate()
-            .filter_map(|(idx, b)| {
-                if b == 10 {
-                    if width == 0 {
-                        width = idx;
-                    }
-                    None
-                } else {
-                    Some(b)
-                }
-            })
-            .map(|b| T::try_from(b))
-            .collect::<Result<Vec<_>, _>>()?;
-        let height = grid.len() / width;
-        Ok(Self {
-            am: PhantomData,
-            grid,
-            width,
-            height,
-        })
+        Self::parse_bytes(value.as_bytes(), value)
     }
 }
 
@@ -204,3 +252,216 @@ impl<T: Display, D> Display for Grid<T, D> {
         Ok(())
     }
 }
+
+// An unbounded grid holding the cells set so far, at signed positions.
+// get returns None for missing cells; entry sets them to the default
+// first, and render shows them as the fill value given
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SparseGrid<T> {
+    cells: HashMap<(i64, i64), T>,
+}
+
+impl<T> Default for SparseGrid<T> {
+    fn default() -> Self {
+        Self {
+            cells: HashMap::new(),
+        }
+    }
+}
+
+impl<T> SparseGrid<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // the cells of grid, with its middle cell at (0, 0)
+    pub fn centred<D>(grid: Grid<T, D>) -> Self {
+        let (mid_x, mid_y) = ((grid.width / 2) as i64, (grid.height / 2) as i64);
+        let width = grid.width;
+        grid.grid
+            .into_iter()
+            .enumerate()
+            .map(|(idx, t)| {
+                let (x, y) = (idx % width, idx / width);
+                ((x as i64 - mid_x, y as i64 - mid_y), t)
+            })
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn get(&self, pos: (i64, i64)) -> Option<&T> {
+        self.cells.get(&pos)
+    }
+
+    pub fn insert(&mut self, pos: (i64, i64), value: T) -> Option<T> {
+        self.cells.insert(pos, value)
+    }
+
+    pub fn remove(&mut self, pos: (i64, i64)) -> Option<T> {
+        self.cells.remove(&pos)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&(i64, i64), &T)> {
+        self.cells.iter()
+    }
+
+    // the cell at pos, set to the default first if missing
+    pub fn entry(&mut self, pos: (i64, i64)) -> &mut T
+    where
+        T: Default,
+    {
+        self.cells.entry(pos).or_default()
+    }
+
+    // the cells set among the 8 around pos
+    pub fn neighbours(&self, pos: (i64, i64)) -> impl Iterator<Item = ((i64, i64), &T)> {
+        (-1..=1)
+            .flat_map(move |dy| (-1..=1).map(move |dx| (pos.0 + dx, pos.1 + dy)))
+            .filter(move |&p| p != pos)
+            .filter_map(|p| self.cells.get(&p).map(|t| (p, t)))
+    }
+
+    // the top left and bottom right corners of the cells set
+    pub fn bounds(&self) -> Option<((i64, i64), (i64, i64))> {
+        let xs = self.cells.keys().map(|p| p.0);
+        let ys = self.cells.keys().map(|p| p.1);
+        Some((
+            (xs.clone().min()?, ys.clone().min()?),
+            (xs.max()?, ys.max()?),
+        ))
+    }
+}
+
+impl<T> FromIterator<((i64, i64), T)> for SparseGrid<T> {
+    fn from_iter<I: IntoIterator<Item = ((i64, i64), T)>>(iter: I) -> Self {
+        Self {
+            cells: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl<T: Display> SparseGrid<T> {
+    // the rows within the bounds, with fill for the missing cells
+    pub fn render(&self, fill: T) -> String {
+        let Some(((min_x, min_y), (max_x, max_y))) = self.bounds() else {
+            return String::new();
+        };
+        (min_y..=max_y)
+            .map(|y| {
+                (min_x..=max_x)
+                    .map(|x| self.cells.get(&(x, y)).unwrap_or(&fill).to_string())
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    struct Digit(u8);
+
+    impl TryFrom<u8> for Digit {
+        type Error = String;
+
+        fn try_from(value: u8) -> Result<Self, Self::Error> {
+            value
+                .is_ascii_digit()
+                .then_some(Digit(value - b'0'))
+                .ok_or_else(|| format!("expected a digit, not {}", value as char))
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        let grid: Grid<Digit, Taxicab> = Grid::try_from("123\n456\n").unwrap();
+        assert_eq!((3, 2), (grid.width, grid.height));
+        assert_eq!(6, grid[(2, 1)].0);
+
+        let grid: Grid<Digit, Taxicab> = Grid::try_from("123\n456".as_bytes()).unwrap();
+        assert_eq!((3, 2), (grid.width, grid.height));
+
+        let grid: Grid<Digit, Taxicab> = Grid::try_from("123\r\n456\r\n").unwrap();
+        assert_eq!((3, 2), (grid.width, grid.height));
+
+        let grid: Grid<u8, Taxicab> = Grid::from_vec(3, (1..=6).collect());
+        assert_eq!((3, 2), (grid.width, grid.height));
+        assert_eq!(6, grid[(2, 1)]);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = Grid::<Digit, Taxicab>::try_from("123\n4x6\n")
+            .err()
+            .unwrap();
+        assert_eq!((2, 2), (err.line, err.column));
+        assert_eq!("expected a digit, not x", err.message());
+
+        let err = Grid::<Digit, Taxicab>::try_from("123\n45\n789")
+            .err()
+            .unwrap();
+        assert_eq!((2, 1), (err.line, err.column));
+        assert_eq!("expected a row of width 3", err.message());
+
+        let err = Grid::<Digit, Taxicab>::try_from("123\r\n45\r\n789")
+            .err()
+            .unwrap();
+        assert_eq!((2, 1, 5), (err.line, err.column, err.offset()));
+        assert_eq!("expected a row of width 3", err.message());
+    }
+
+    #[test]
+    fn test_poi_distances() {
+        // 2016 day 24
+        let grid: Grid<u8, Taxicab> = Grid::try_from(
+            "###########
+#0.1.....2#
+#.#######.#
+#4.......3#
+###########",
+        )
+        .unwrap();
+        let pois: Vec<_> = (b'0'..=b'4')
+            .map(|d| grid.idx_to_pos(grid.iter().position(|&b| b == d).unwrap()))
+            .collect();
+        let distances = grid.poi_distances(&pois, |&b| b != b'#');
+        assert_eq!(
+            vec![Some(0), Some(2), Some(8), Some(10), Some(2)],
+            distances[0]
+        );
+        assert_eq!(Some(8), distances[3][4]);
+
+        // closed doors
+        let distances = grid.poi_distances(&pois, |&b| b == b'.');
+        assert_eq!(vec![Some(0), Some(2), None, None, Some(2)], distances[0]);
+        assert_eq!(vec![None, Some(6), Some(0), Some(2), None], distances[2]);
+    }
+
+    #[test]
+    fn test_sparse_grid() {
+        // 2017 day 22
+        let grid: Grid<u8, Taxicab> = Grid::try_from("..#\n#..\n...").unwrap();
+        let mut sparse: SparseGrid<char> = SparseGrid::centred(grid)
+            .iter()
+            .filter(|(_, &b)| b == b'#')
+            .map(|(&p, &b)| (p, b as char))
+            .collect();
+        assert_eq!(Some(&'#'), sparse.get((1, -1)));
+        assert_eq!(Some(((-1, -1), (1, 0))), sparse.bounds());
+        assert_eq!(2, sparse.neighbours((0, 0)).count());
+        *sparse.entry((2, 1)) = 'x';
+        assert_eq!("..#.\n#...\n...x", sparse.render('.'));
+        assert_eq!("", SparseGrid::<char>::new().render('.'));
+        sparse.remove((-1, 0));
+        assert_eq!(Some(((1, -1), (2, 1))), sparse.bounds());
+    }
+}
@@ -0,0 +1,243 @@
+use md5::{Digest as _, Md5};
+use rayon::prelude::*;
+use std::collections::VecDeque;
+
+// MD5 brute force, as in 2015 day 4 and 2016 days 5, 14 and 17. Digests
+// are checked on their nibbles (half bytes) directly, without formatting
+// them to hex strings first. Searches hash a batch of indices in parallel
+// and return the results in index order, so the first result is the one
+// a sequential loop would find
+
+pub type Digest = [u8; 16];
+
+// number of indices hashed in parallel by a search
+const BATCH: u64 = 1 << 14;
+
+// key followed by the decimal index, as in abc3231929
+fn md5_index(key: &Md5, index: u64) -> Digest {
+    let mut buffer = [0; 20];
+    let mut md5 = key.clone();
+    md5.update(decimal(index, &mut buffer));
+    md5.finalize().into()
+}
+
+// index in decimal, written at the end of buffer
+fn decimal(mut index: u64, buffer: &mut [u8; 20]) -> &[u8] {
+    let mut start = buffer.len();
+    loop {
+        start -= 1;
+        buffer[start] = b'0' + (index % 10) as u8;
+        index /= 10;
+        if index == 0 {
+            return &buffer[start..];
+        }
+    }
+}
+
+pub fn md5(data: impl AsRef<[u8]>) -> Digest {
+    Md5::digest(data).into()
+}
+
+// hash of salt followed by the decimal index
+pub fn md5_salted(salt: &[u8], index: u64) -> Digest {
+    md5_index(&Md5::new_with_prefix(salt), index)
+}
+
+// hash of the salted index, then rehashed as lowercase hex stretch times
+// (the key stretching of 2016 day 14)
+pub fn md5_stretched(salt: &[u8], index: u64, stretch: usize) -> Digest {
+    stretch_from(md5_index(&Md5::new_with_prefix(salt), index), stretch)
+}
+
+fn stretch_from(mut digest: Digest, stretch: usize) -> Digest {
+    let mut hex = [0; 32];
+    for _ in 0..stretch {
+        hex_into(&digest, &mut hex);
+        digest = Md5::digest(hex).into();
+    }
+    digest
+}
+
+// nibble i of the digest, most significant first (the order of the hex
+// representation)
+pub fn nibble(digest: &[u8], i: usize) -> u8 {
+    let byte = digest[i / 2];
+    if i % 2 == 0 {
+        byte >> 4
+    } else {
+        byte & 0xf
+    }
+}
+
+pub fn nibbles(digest: &[u8]) -> impl Iterator<Item = u8> + '_ {
+    digest.iter().flat_map(|b| [b >> 4, b & 0xf])
+}
+
+// whether the hex representation starts with count zeroes (never when
+// it has fewer digits)
+pub fn has_zero_prefix(digest: &[u8], count: usize) -> bool {
+    count <= 2 * digest.len()
+        && digest[..count / 2].iter().all(|&b| b == 0)
+        && (count % 2 == 0 || digest[count / 2] >> 4 == 0)
+}
+
+// number of zeroes at the start of the hex representation
+pub fn leading_zeros(digest: &[u8]) -> usize {
+    nibbles(digest).take_while(|&n| n == 0).count()
+}
+
+// lowercase hex digits of the digest; out must be twice as long
+pub fn hex_into(digest: &[u8], out: &mut [u8]) {
+    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+    for (i, n) in nibbles(digest).enumerate() {
+        out[i] = DIGITS[n as usize];
+    }
+}
+
+pub fn to_hex(digest: &[u8]) -> String {
+    nibbles(digest).map(hex_digit).collect()
+}
+
+pub fn hex_digit(nibble: u8) -> char {
+    char::from_digit(nibble as u32, 16).unwrap()
+}
+
+// Indices (from start on) whose salted hash matches the predicate, in
+// increasing order, with their hash. The iterator never ends by itself
+pub struct Search<P> {
+    key: Md5,
+    predicate: P,
+    next: u64,
+    found: VecDeque<(u64, Digest)>,
+}
+
+pub fn search<P>(salt: &[u8], start: u64, predicate: P) -> Search<P>
+where
+    P: Fn(&Digest) -> bool + Sync,
+{
+    Search {
+        key: Md5::new_with_prefix(salt),
+        predicate,
+        next: start,
+        found: VecDeque::new(),
+    }
+}
+
+impl<P> Iterator for Search<P>
+where
+    P: Fn(&Digest) -> bool + Sync,
+{
+    type Item = (u64, Digest);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.found.is_empty() {
+            let (key, predicate) = (&self.key, &self.predicate);
+            // collect keeps the order of the indices
+            let found: Vec<_> = (self.next..self.next + BATCH)
+                .into_par_iter()
+                .filter_map(|index| {
+                    let digest = md5_index(key, index);
+                    predicate(&digest).then_some((index, digest))
+                })
+                .collect();
+            self.found.extend(found);
+            self.next += BATCH;
+        }
+        self.found.pop_front()
+    }
+}
+
+// Stretched hashes of consecutive indices, for lookahead: get computes
+// the missing hashes up to the index (in parallel batches) and keeps them
+// until advance drops the ones before a given index
+pub struct HashWindow {
+    key: Md5,
+    stretch: usize,
+    first: u64,
+    hashes: VecDeque<Digest>,
+}
+
+impl HashWindow {
+    pub fn new(salt: &[u8], stretch: usize) -> Self {
+        Self {
+            key: Md5::new_with_prefix(salt),
+            stretch,
+            first: 0,
+            hashes: VecDeque::new(),
+        }
+    }
+
+    pub fn get(&mut self, index: u64) -> &Digest {
+        assert!(index >= self.first, "hash {index} was dropped");
+        let offset = (index - self.first) as usize;
+        if offset >= self.hashes.len() {
+            let from = self.first + self.hashes.len() as u64;
+            // stretched hashes are expensive, so batches are smaller
+            let to = (index + 1).max(from + BATCH / 16);
+            let (key, stretch) = (&self.key, self.stretch);
+            let hashes: Vec<_> = (from..to)
+                .into_par_iter()
+                .map(|index| stretch_from(md5_index(key, index), stretch))
+                .collect();
+            self.hashes.extend(hashes);
+        }
+        &self.hashes[offset]
+    }
+
+    // drops the hashes before index
+    pub fn advance(&mut self, index: u64) {
+        let count = index.saturating_sub(self.first) as usize;
+        self.hashes.drain(..count.min(self.hashes.len()));
+        self.first = self.first.max(index);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_hex() {
+        let digest = md5("abc3231929");
+        assert_eq!("00000155f8105dff7f56ee10fa9b9abd", to_hex(&digest));
+        let mut out = [0; 32];
+        hex_into(&digest, &mut out);
+        assert_eq!(to_hex(&digest).as_bytes(), &out);
+        assert_eq!(digest, md5_salted(b"abc", 3231929));
+        assert_eq!((1, 5), (nibble(&digest, 5), nibble(&digest, 6)));
+    }
+
+    #[test]
+    fn test_zero_prefix() {
+        let digest = md5_salted(b"abcdef", 609043);
+        assert_eq!(5, leading_zeros(&digest));
+        assert!(has_zero_prefix(&digest, 4));
+        assert!(has_zero_prefix(&digest, 5));
+        assert!(!has_zero_prefix(&digest, 6));
+        assert!(has_zero_prefix(&digest, 0));
+        assert!(has_zero_prefix(&[0; 16], 32));
+        assert!(!has_zero_prefix(&[0; 16], 33));
+    }
+
+    #[test]
+    fn test_search() {
+        let mut found = search(b"abcdef", 0, |d| has_zero_prefix(d, 5));
+        assert_eq!(609043, found.next().unwrap().0);
+        // the same order when starting in the middle of a batch
+        let mut found = search(b"abcdef", 609000, |d| has_zero_prefix(d, 5));
+        assert_eq!(609043, found.next().unwrap().0);
+    }
+
+    #[test]
+    fn test_stretch() {
+        assert_eq!(
+            "a107ff634856bb300138cac6568c0f24",
+            to_hex(&md5_stretched(b"abc", 0, 2016))
+        );
+        let mut window = HashWindow::new(b"abc", 2016);
+        assert_eq!(md5_stretched(b"abc", 10, 2016), *window.get(10));
+        window.advance(5);
+        assert_eq!(md5_stretched(b"abc", 600, 2016), *window.get(600));
+        assert_eq!(md5_stretched(b"abc", 5, 2016), *window.get(5));
+    }
+}
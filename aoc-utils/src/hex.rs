@@ -0,0 +1,419 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt::Display,
+    iter::Sum,
+    ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign},
+};
+
+use crate::automaton::Sparse;
+use crate::error::ParseError;
+
+// Hexagonal grids, after https://www.redblobgames.com/grids/hexagons/:
+// axial coordinates (q, r), with s = -q - r the third cube coordinate, and
+// r growing southward. The six directions are the same whatever the
+// layout, only their names change: pointy-top hexes have e and w and four
+// diagonals (2020 day 24), flat-top ones have n and s (2017 day 11)
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Hex {
+    pub q: i64,
+    pub r: i64,
+}
+
+// counterclockwise, from q + 1
+pub const DIRECTIONS: [Hex; 6] = [
+    Hex::new(1, 0),
+    Hex::new(1, -1),
+    Hex::new(0, -1),
+    Hex::new(-1, 0),
+    Hex::new(-1, 1),
+    Hex::new(0, 1),
+];
+
+impl Hex {
+    pub const fn new(q: i64, r: i64) -> Self {
+        Self { q, r }
+    }
+
+    pub fn origin() -> Self {
+        Self::default()
+    }
+
+    pub fn s(&self) -> i64 {
+        -self.q - self.r
+    }
+
+    pub fn cube(&self) -> [i64; 3] {
+        [self.q, self.r, self.s()]
+    }
+
+    pub fn from_cube([q, r, s]: [i64; 3]) -> Self {
+        assert_eq!(0, q + r + s, "cube coordinates must add up to 0");
+        Self::new(q, r)
+    }
+
+    pub fn to_array(self) -> [i64; 2] {
+        [self.q, self.r]
+    }
+
+    // the number of steps from the origin
+    pub fn length(&self) -> usize {
+        self.cube().iter().map(|c| c.unsigned_abs()).max().unwrap() as usize
+    }
+
+    pub fn distance(&self, other: &Self) -> usize {
+        (*self - *other).length()
+    }
+
+    pub fn neighbour(self, direction: usize) -> Self {
+        self + DIRECTIONS[direction]
+    }
+
+    // in the order of DIRECTIONS
+    pub fn neighbours(self) -> impl Iterator<Item = Self> {
+        DIRECTIONS.into_iter().map(move |d| self + d)
+    }
+
+    // the end of a path of steps from here
+    pub fn walk(self, steps: impl IntoIterator<Item = Self>) -> Self {
+        steps.into_iter().fold(self, |hex, step| hex + step)
+    }
+
+    // the hexes at radius from this one, counterclockwise; the ring of
+    // radius 0 is this hex
+    pub fn ring(self, radius: usize) -> impl Iterator<Item = Self> {
+        let mut hex = self + DIRECTIONS[4] * radius as i64;
+        let around = DIRECTIONS
+            .into_iter()
+            .flat_map(move |d| std::iter::repeat_n(d, radius))
+            .map(move |d| {
+                let current = hex;
+                hex += d;
+                current
+            });
+        std::iter::once(self)
+            .filter(move |_| radius == 0)
+            .chain(around)
+    }
+
+    // this hex then the rings around it, up to radius
+    pub fn spiral(self, radius: usize) -> impl Iterator<Item = Self> {
+        (0..=radius).flat_map(move |r| self.ring(r))
+    }
+}
+
+impl From<[i64; 2]> for Hex {
+    fn from([q, r]: [i64; 2]) -> Self {
+        Self::new(q, r)
+    }
+}
+
+impl Add for Hex {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self::new(self.q + rhs.q, self.r + rhs.r)
+    }
+}
+
+impl Sub for Hex {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self::new(self.q - rhs.q, self.r - rhs.r)
+    }
+}
+
+impl Neg for Hex {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::new(-self.q, -self.r)
+    }
+}
+
+impl Mul<i64> for Hex {
+    type Output = Self;
+
+    fn mul(self, rhs: i64) -> Self {
+        Self::new(self.q * rhs, self.r * rhs)
+    }
+}
+
+impl AddAssign for Hex {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign for Hex {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl Sum for Hex {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::origin(), |a, b| a + b)
+    }
+}
+
+impl Display for Hex {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{},{}", self.q, self.r)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Layout {
+    PointyTop,
+    FlatTop,
+}
+
+impl Layout {
+    // the names of DIRECTIONS
+    pub fn names(self) -> [&'static str; 6] {
+        match self {
+            Layout::PointyTop => ["e", "ne", "nw", "w", "sw", "se"],
+            Layout::FlatTop => ["se", "ne", "n", "nw", "sw", "s"],
+        }
+    }
+
+    pub fn direction(self, name: &str) -> Option<Hex> {
+        let idx = self.names().iter().position(|&n| n == name)?;
+        Some(DIRECTIONS[idx])
+    }
+
+    pub fn name(self, direction: Hex) -> Option<&'static str> {
+        let idx = DIRECTIONS.iter().position(|&d| d == direction)?;
+        Some(self.names()[idx])
+    }
+
+    // the steps of a path, with or without separators: "ne,ne,s,s" or
+    // "sesenwnenenew". Names are matched longest first, so flat-top "nw"
+    // is never read as "n" then "w"
+    pub fn parse_path(self, src: &str) -> Result<Vec<Hex>, ParseError> {
+        let mut steps = vec![];
+        let mut offset = 0;
+        while offset < src.len() {
+            let rest = &src[offset..];
+            let skip = rest.len() - rest.trim_start_matches([',', ' ', '\t', '\n', '\r']).len();
+            if skip > 0 {
+                offset += skip;
+                continue;
+            }
+            let (len, step) = [2, 1]
+                .into_iter()
+                .filter(|&len| rest.is_char_boundary(len.min(rest.len())))
+                .find_map(|len| {
+                    let name = rest.get(..len)?;
+                    self.direction(name).map(|d| (len, d))
+                })
+                .ok_or_else(|| ParseError::new(src, offset, 1, "unknown direction"))?;
+            steps.push(step);
+            offset += len;
+        }
+        Ok(steps)
+    }
+}
+
+// the cellular automaton of hexes and their six neighbours
+pub fn automaton(live: impl IntoIterator<Item = Hex>) -> Sparse<2> {
+    let offsets = DIRECTIONS.map(Hex::to_array).to_vec();
+    Sparse::new(live.into_iter().map(Hex::to_array), offsets)
+}
+
+// A sparse map of hexes, for unbounded or irregular areas
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HexGrid<T> {
+    cells: HashMap<Hex, T>,
+}
+
+impl<T> Default for HexGrid<T> {
+    fn default() -> Self {
+        Self {
+            cells: HashMap::new(),
+        }
+    }
+}
+
+impl<T> HexGrid<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn get(&self, hex: &Hex) -> Option<&T> {
+        self.cells.get(hex)
+    }
+
+    pub fn get_mut(&mut self, hex: &Hex) -> Option<&mut T> {
+        self.cells.get_mut(hex)
+    }
+
+    pub fn insert(&mut self, hex: Hex, value: T) -> Option<T> {
+        self.cells.insert(hex, value)
+    }
+
+    pub fn remove(&mut self, hex: &Hex) -> Option<T> {
+        self.cells.remove(hex)
+    }
+
+    pub fn contains(&self, hex: &Hex) -> bool {
+        self.cells.contains_key(hex)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Hex, &T)> {
+        self.cells.iter()
+    }
+
+    // the value at hex, inserted with default first if missing
+    pub fn entry(&mut self, hex: Hex) -> &mut T
+    where
+        T: Default,
+    {
+        self.cells.entry(hex).or_default()
+    }
+
+    // the neighbours of hex present in the grid
+    pub fn neighbours(&self, hex: Hex) -> impl Iterator<Item = (Hex, &T)> {
+        hex.neighbours()
+            .filter_map(|n| self.cells.get(&n).map(|t| (n, t)))
+    }
+
+    // breadth first distances from start, through the cells open accepts
+    pub fn distances(&self, start: Hex, open: impl Fn(&T) -> bool) -> HashMap<Hex, usize> {
+        let mut distances = HashMap::from([(start, 0)]);
+        let mut queue = VecDeque::from([start]);
+        while let Some(hex) = queue.pop_front() {
+            let d = distances[&hex];
+            for (n, t) in self.neighbours(hex) {
+                if open(t) && !distances.contains_key(&n) {
+                    distances.insert(n, d + 1);
+                    queue.push_back(n);
+                }
+            }
+        }
+        distances
+    }
+
+    // the automaton of the cells live accepts
+    pub fn automaton(&self, live: impl Fn(&T) -> bool) -> Sparse<2> {
+        automaton(
+            self.cells
+                .iter()
+                .filter(|(_, t)| live(t))
+                .map(|(hex, _)| *hex),
+        )
+    }
+}
+
+impl<T> FromIterator<(Hex, T)> for HexGrid<T> {
+    fn from_iter<I: IntoIterator<Item = (Hex, T)>>(iter: I) -> Self {
+        Self {
+            cells: iter.into_iter().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_paths() {
+        // 2017 day 11
+        let flat = Layout::FlatTop;
+        for (path, distance) in [
+            ("ne,ne,ne", 3),
+            ("ne,ne,sw,sw", 0),
+            ("ne,ne,s,s", 2),
+            ("se,sw,se,sw,sw", 3),
+        ] {
+            let end = Hex::origin().walk(flat.parse_path(path).unwrap());
+            assert_eq!(distance, end.length());
+        }
+
+        // 2020 day 24
+        let pointy = Layout::PointyTop;
+        assert_eq!(
+            Hex::new(0, 1),
+            Hex::origin().walk(pointy.parse_path("esew").unwrap())
+        );
+        assert_eq!(
+            Hex::origin(),
+            Hex::origin().walk(pointy.parse_path("nwwswee").unwrap())
+        );
+        assert_eq!(3, pointy.parse_path("sesenw").unwrap().len());
+        assert_eq!(vec![DIRECTIONS[3]], flat.parse_path("nw").unwrap());
+
+        let err = pointy.parse_path("ene\nnn").unwrap_err();
+        assert_eq!((2, 1), (err.line, err.column));
+        assert_eq!(Some("s"), flat.name(DIRECTIONS[5]));
+        assert_eq!(None, pointy.direction("n"));
+    }
+
+    #[test]
+    fn test_coordinates() {
+        let hex = Hex::from_cube([3, -1, -2]);
+        assert_eq!([3, -1, -2], hex.cube());
+        assert_eq!(3, hex.length());
+        assert_eq!(5, hex.distance(&Hex::new(-2, 0)));
+        assert_eq!(Hex::new(0, -1), Hex::origin().neighbour(2));
+        assert!(hex.neighbours().all(|n| n.distance(&hex) == 1));
+        assert_eq!(hex, DIRECTIONS.into_iter().sum::<Hex>() + hex);
+    }
+
+    #[test]
+    fn test_rings() {
+        let centre = Hex::new(2, -5);
+        assert_eq!(vec![centre], centre.ring(0).collect::<Vec<_>>());
+        for radius in 1..5 {
+            let ring: Vec<_> = centre.ring(radius).collect();
+            assert_eq!(6 * radius, ring.len());
+            assert!(ring.iter().all(|h| h.distance(&centre) == radius));
+            // each next hex is next to the one before
+            assert!(ring
+                .iter()
+                .zip(ring.iter().cycle().skip(1))
+                .all(|(a, b)| a.distance(b) == 1));
+        }
+        let spiral: Vec<_> = centre.spiral(3).collect();
+        assert_eq!(37, spiral.len());
+        assert_eq!(centre, spiral[0]);
+        let unique: std::collections::HashSet<_> = spiral.iter().collect();
+        assert_eq!(37, unique.len());
+    }
+
+    #[test]
+    fn test_grid() {
+        // a ring of walls around the origin, with a gap
+        let mut grid: HexGrid<bool> = Hex::origin().spiral(3).map(|h| (h, true)).collect();
+        for hex in Hex::origin().ring(2).skip(1) {
+            grid.insert(hex, false);
+        }
+        let distances = grid.distances(Hex::origin(), |&open| open);
+        let gap = Hex::origin().ring(2).next().unwrap();
+        assert_eq!(Some(&2), distances.get(&gap));
+        assert_eq!(None, distances.get(&Hex::origin().ring(2).nth(1).unwrap()));
+        // through the gap, then 8 steps around the outer ring
+        let far = Hex::new(3, -3);
+        assert_eq!(3, far.length());
+        assert_eq!(Some(&11), distances.get(&far));
+        // the walls as live cells; a pair of them grows
+        let mut life = grid.automaton(|&open| !open);
+        assert_eq!(11, life.len());
+        let mut pair = automaton([Hex::origin(), Hex::new(1, 0)]);
+        pair.step(|live, count| count == 2 || (live && count == 1));
+        assert_eq!(4, pair.len());
+        life.step(|_, _| false);
+        assert!(life.is_empty());
+    }
+}
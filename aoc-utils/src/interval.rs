@@ -0,0 +1,464 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Debug,
+    ops::{
+        Add,
+        Bound::{Included, Unbounded},
+        Range, RangeInclusive, Sub,
+    },
+};
+
+// Sets of integers stored as disjoint inclusive ranges (firewall
+// blacklists, beacon exclusion zones, seed ranges, ...), so that a range
+// can end at the largest value of its type. Ranges that overlap or touch
+// are merged on insertion, so iter returns the fewest possible ranges, in
+// increasing order. A RangeMap sends ranges through a list of offset
+// mappings, splitting them where the mappings start and end
+
+pub trait Integer: Copy + Ord + Debug + Add<Output = Self> + Sub<Output = Self> {
+    const ONE: Self;
+
+    // the next value, if there is one
+    fn succ(self) -> Option<Self>;
+}
+
+macro_rules! integer {
+    ($($t:ty),*) => {
+        $(impl Integer for $t {
+            const ONE: Self = 1;
+
+            fn succ(self) -> Option<Self> {
+                self.checked_add(1)
+            }
+        })*
+    };
+}
+
+integer!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+// start..end as start..=end - 1; None if the range is empty
+pub fn inclusive<T: Integer>(range: Range<T>) -> Option<RangeInclusive<T>> {
+    (!range.is_empty()).then(|| range.start..=range.end - T::ONE)
+}
+
+// start..=end as start..end + 1; None if end is the largest value of T
+pub fn half_open<T: Integer>(range: RangeInclusive<T>) -> Option<Range<T>> {
+    range.end().succ().map(|end| *range.start()..end)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct IntervalSet<T> {
+    // start -> last
+    ranges: BTreeMap<T, T>,
+}
+
+impl<T: Integer> IntervalSet<T> {
+    pub fn new() -> Self {
+        Self {
+            ranges: BTreeMap::new(),
+        }
+    }
+
+    pub fn insert(&mut self, range: Range<T>) {
+        if let Some(range) = inclusive(range) {
+            self.insert_inclusive(range);
+        }
+    }
+
+    pub fn insert_inclusive(&mut self, range: RangeInclusive<T>) {
+        if range.is_empty() {
+            return;
+        }
+        let (mut start, mut last) = range.into_inner();
+        // the ranges that start at most one after the last value and end
+        // at most one before the start overlap or touch the new one
+        let candidates = match last.succ() {
+            Some(next) => (Unbounded, Included(next)),
+            None => (Unbounded, Unbounded),
+        };
+        let merged: Vec<_> = self
+            .ranges
+            .range(candidates)
+            .rev()
+            .take_while(|(_, &l)| l.succ().is_none_or(|next| next >= start))
+            .map(|(&s, &l)| (s, l))
+            .collect();
+        for (s, l) in merged {
+            self.ranges.remove(&s);
+            start = start.min(s);
+            last = last.max(l);
+        }
+        self.ranges.insert(start, last);
+    }
+
+    pub fn remove(&mut self, range: Range<T>) {
+        if let Some(range) = inclusive(range) {
+            self.remove_inclusive(range);
+        }
+    }
+
+    pub fn remove_inclusive(&mut self, range: RangeInclusive<T>) {
+        if range.is_empty() {
+            return;
+        }
+        let (start, last) = range.into_inner();
+        let overlapping: Vec<_> = self
+            .ranges
+            .range(..=last)
+            .rev()
+            .take_while(|(_, &l)| l >= start)
+            .map(|(&s, &l)| (s, l))
+            .collect();
+        for (s, l) in overlapping {
+            self.ranges.remove(&s);
+            // s < start and last < l, so neither bound can overflow
+            if s < start {
+                self.ranges.insert(s, start - T::ONE);
+            }
+            if last < l {
+                self.ranges.insert(last + T::ONE, l);
+            }
+        }
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        self.ranges
+            .range(..=value)
+            .next_back()
+            .is_some_and(|(_, &l)| value <= l)
+    }
+
+    // whether the whole range is in the set
+    pub fn covers(&self, range: &Range<T>) -> bool {
+        inclusive(range.clone()).is_none_or(|range| {
+            self.ranges
+                .range(..=range.start())
+                .next_back()
+                .is_some_and(|(_, l)| range.end() <= l)
+        })
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = RangeInclusive<T>> + '_ {
+        self.ranges.iter().map(|(&s, &l)| s..=l)
+    }
+
+    // number of ranges
+    pub fn range_count(&self) -> usize {
+        self.ranges.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    pub fn first(&self) -> Option<T> {
+        self.ranges.keys().next().copied()
+    }
+
+    pub fn last(&self) -> Option<T> {
+        self.ranges.values().next_back().copied()
+    }
+
+    // number of values in the set; zero is the default of the integer
+    // types. Overflows if the set holds every value of T
+    pub fn len(&self) -> T
+    where
+        T: Default,
+    {
+        self.ranges
+            .iter()
+            .fold(T::default(), |len, (&s, &l)| len + (l - s) + T::ONE)
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let mut union = self.clone();
+        for range in other.iter() {
+            union.insert_inclusive(range);
+        }
+        union
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut intersection = Self::new();
+        let mut left = self.iter().peekable();
+        let mut right = other.iter().peekable();
+        while let (Some(l), Some(r)) = (left.peek(), right.peek()) {
+            let start = *l.start().max(r.start());
+            let last = *l.end().min(r.end());
+            if start <= last {
+                intersection.ranges.insert(start, last);
+            }
+            // the range that ends first cannot meet anything else
+            if l.end() < r.end() {
+                left.next();
+            } else {
+                right.next();
+            }
+        }
+        intersection
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut difference = self.clone();
+        for range in other.iter() {
+            difference.remove_inclusive(range);
+        }
+        difference
+    }
+
+    // the ranges of within that are not in the set
+    pub fn gaps(&self, within: RangeInclusive<T>) -> impl Iterator<Item = RangeInclusive<T>> + '_ {
+        // the next value that may be in a gap, None once past within
+        let mut start = (!within.is_empty()).then(|| *within.start());
+        let last = *within.end();
+        self.iter()
+            .map(Some)
+            .chain(Some(None))
+            .filter_map(move |range| {
+                let from = start?;
+                let gap = match range {
+                    Some(r) => {
+                        start = r
+                            .end()
+                            .succ()
+                            .filter(|&next| next <= last)
+                            .map(|next| next.max(from));
+                        let gap_end = *r.start();
+                        (from < gap_end).then(|| from..=(gap_end - T::ONE).min(last))
+                    }
+                    None => {
+                        start = None;
+                        Some(from..=last)
+                    }
+                };
+                gap.filter(|gap| !gap.is_empty())
+            })
+    }
+}
+
+impl<T: Integer> FromIterator<Range<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = Range<T>>>(iter: I) -> Self {
+        let mut set = Self::new();
+        for range in iter {
+            set.insert(range);
+        }
+        set
+    }
+}
+
+impl<T: Integer> FromIterator<RangeInclusive<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = RangeInclusive<T>>>(iter: I) -> Self {
+        let mut set = Self::new();
+        for range in iter {
+            set.insert_inclusive(range);
+        }
+        set
+    }
+}
+
+impl<T: Integer> From<Range<T>> for IntervalSet<T> {
+    fn from(range: Range<T>) -> Self {
+        std::iter::once(range).collect()
+    }
+}
+
+// Piecewise translation: values in the source range of a mapping are
+// moved by the same amount as its start is to its destination; the other
+// values are left unchanged
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RangeMap<T> {
+    // source start -> (source end, destination start)
+    mappings: BTreeMap<T, (T, T)>,
+}
+
+impl<T: Integer> RangeMap<T> {
+    pub fn new() -> Self {
+        Self {
+            mappings: BTreeMap::new(),
+        }
+    }
+
+    // the source ranges must not overlap
+    pub fn add(&mut self, source: Range<T>, destination: T) {
+        if source.is_empty() {
+            return;
+        }
+        assert!(
+            self.mappings
+                .range(..source.end)
+                .next_back()
+                .is_none_or(|(_, &(e, _))| e <= source.start),
+            "mapping {source:?} overlaps another"
+        );
+        self.mappings
+            .insert(source.start, (source.end, destination));
+    }
+
+    pub fn map(&self, value: T) -> T {
+        match self.mappings.range(..=value).next_back() {
+            Some((&s, &(e, d))) if value < e => value - s + d,
+            _ => value,
+        }
+    }
+
+    // the images of the pieces of range, split on the mapping bounds;
+    // nothing for an empty range
+    pub fn map_range(&self, range: Range<T>) -> Vec<Range<T>> {
+        if range.is_empty() {
+            return vec![];
+        }
+        let mut pieces = vec![];
+        let mut start = range.start;
+        // a mapping that starts before the range may still cover its start
+        let first = self
+            .mappings
+            .range(..=start)
+            .next_back()
+            .map(|(&s, _)| s)
+            .unwrap_or(start);
+        for (&s, &(e, d)) in self.mappings.range(first..range.end) {
+            if start >= range.end {
+                break;
+            }
+            if start < s {
+                pieces.push(start..s);
+                start = s;
+            }
+            let end = e.min(range.end);
+            if start < end {
+                pieces.push(start - s + d..end - s + d);
+                start = end;
+            }
+        }
+        if start < range.end {
+            pieces.push(start..range.end);
+        }
+        pieces
+    }
+
+    pub fn apply(&self, set: &IntervalSet<T>) -> IntervalSet<T> {
+        // start..=last as start..last and last, which may be the largest
+        // value of T
+        set.iter()
+            .flat_map(|r| {
+                let (start, last) = r.into_inner();
+                let value = self.map(last);
+                self.map_range(start..last)
+                    .into_iter()
+                    .filter_map(inclusive)
+                    .chain(Some(value..=value))
+            })
+            .collect()
+    }
+}
+
+impl<T: Integer> FromIterator<(Range<T>, T)> for RangeMap<T> {
+    fn from_iter<I: IntoIterator<Item = (Range<T>, T)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        for (source, destination) in iter {
+            map.add(source, destination);
+        }
+        map
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn ranges<T: Integer>(set: &IntervalSet<T>) -> Vec<Range<T>> {
+        set.iter().map(|r| half_open(r).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_insert_remove() {
+        let mut set: IntervalSet<i32> = [5..9, 0..3, 4..8].into_iter().collect();
+        assert_eq!(vec![0..3, 4..9], ranges(&set));
+        // touching ranges are merged
+        set.insert(3..4);
+        assert_eq!(vec![0..9], ranges(&set));
+        set.remove(2..5);
+        set.remove(8..20);
+        assert_eq!(vec![0..2, 5..8], ranges(&set));
+        assert_eq!(5, set.len());
+        assert!(set.contains(0) && set.contains(7) && !set.contains(2) && !set.contains(8));
+        assert!(set.covers(&(5..8)) && !set.covers(&(1..6)));
+
+        set.insert_inclusive(-4..=-2);
+        assert_eq!(vec![-4..-1, 0..2, 5..8], ranges(&set));
+        assert_eq!((Some(-4), Some(7)), (set.first(), set.last()));
+        set.remove(-10..10);
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let a: IntervalSet<u32> = [0..5, 10..15].into_iter().collect();
+        let b: IntervalSet<u32> = [3..12, 14..20].into_iter().collect();
+        assert_eq!(vec![0..20], ranges(&a.union(&b)));
+        assert_eq!(vec![3..5, 10..12, 14..15], ranges(&a.intersection(&b)));
+        assert_eq!(vec![0..3, 12..14], ranges(&a.difference(&b)));
+        assert_eq!(vec![5..10, 15..20], ranges(&b.difference(&a)));
+        assert_eq!(IntervalSet::new(), a.intersection(&IntervalSet::new()));
+    }
+
+    #[test]
+    fn test_gaps() {
+        // 2016 day 20
+        let blocked: IntervalSet<u64> = [5..=8, 0..=2, 4..=7].into_iter().collect();
+        assert_eq!(vec![3..=3, 9..=9], blocked.gaps(0..=9).collect::<Vec<_>>());
+        assert_eq!(vec![3..=3], blocked.gaps(3..=5).collect::<Vec<_>>());
+        assert_eq!(vec![20..=29], blocked.gaps(20..=29).collect::<Vec<_>>());
+        assert_eq!(
+            Vec::<RangeInclusive<u64>>::new(),
+            blocked.gaps(1..=2).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_full_range() {
+        // ranges that end at the largest value do not overflow
+        let mut set: IntervalSet<u32> = [10..=u32::MAX, 0..=4].into_iter().collect();
+        assert_eq!(vec![0..=4, 10..=u32::MAX], set.iter().collect::<Vec<_>>());
+        assert!(set.contains(u32::MAX) && set.covers(&(10..u32::MAX)));
+        assert_eq!(vec![5..=9], set.gaps(0..=u32::MAX).collect::<Vec<_>>());
+        assert_eq!(None, half_open(0..=u32::MAX));
+
+        set.insert_inclusive(5..=9);
+        assert_eq!(vec![0..=u32::MAX], set.iter().collect::<Vec<_>>());
+        assert_eq!(0, set.gaps(0..=u32::MAX).count());
+        set.remove_inclusive(u32::MAX..=u32::MAX);
+        assert_eq!(Some(u32::MAX - 1), set.last());
+        assert_eq!(u32::MAX, set.len());
+
+        let map: RangeMap<u8> = [(0..10, 100)].into_iter().collect();
+        let set: IntervalSet<u8> = [0..=3, 250..=255].into_iter().collect();
+        assert_eq!(
+            vec![100..=103, 250..=255],
+            map.apply(&set).iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_range_map() {
+        // 2023 day 5: seed-to-soil
+        let map: RangeMap<i64> = [(98..100, 50), (50..98, 52)].into_iter().collect();
+        assert_eq!(
+            vec![81, 14, 57, 13],
+            [79, 14, 55, 13].map(|v| map.map(v)).to_vec()
+        );
+        assert_eq!(vec![81..95], map.map_range(79..93));
+        assert_eq!(
+            vec![40..50, 52..100, 50..52, 100..110],
+            map.map_range(40..110)
+        );
+        assert_eq!(vec![51..52], map.map_range(99..100));
+        #[allow(clippy::reversed_empty_ranges)]
+        let inverted = 99..60;
+        assert!(map.map_range(inverted).is_empty());
+        assert!(map.map_range(60..60).is_empty());
+
+        let seeds: IntervalSet<i64> = [79..93, 55..68].into_iter().collect();
+        assert_eq!(vec![57..70, 81..95], ranges(&map.apply(&seeds)));
+    }
+}
@@ -2,9 +2,11 @@ pub use std::fs::File;
 pub use std::io::{BufRead, BufReader};
 
 // should use Path and AsRef(Path) for everything
+// options (such as --visualize) are skipped
 pub fn get_input_path() -> String {
     std::env::args()
-        .nth(1)
+        .skip(1)
+        .find(|a| !a.starts_with("--"))
         .unwrap_or_else(|| "input.txt".to_owned())
 }
 
@@ -14,10 +16,42 @@ pub fn get_input() -> BufReader<File> {
     BufReader::new(input)
 }
 
+pub mod error;
+pub mod parse;
+
 pub mod permutations;
 pub mod ring;
 pub mod union_find;
 
+pub mod graph;
+pub mod min_cut;
+pub mod tour;
+
+pub mod cube_net;
+pub mod cuboid;
+pub mod interval;
+pub mod linalg;
 pub mod num;
+pub mod sequence;
+pub mod spiral;
+pub mod vec3;
+
+pub mod hash;
 
+pub mod automaton;
+pub mod battle;
+pub mod bitgrid;
+pub mod gravity;
 pub mod grid;
+pub mod hex;
+pub mod ocr;
+pub mod render;
+
+pub mod assembunny;
+pub mod elfcode;
+pub mod symbolic;
+
+pub mod alu;
+pub mod chronal;
+
+pub mod assignment;
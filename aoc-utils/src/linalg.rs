@@ -0,0 +1,683 @@
+use std::{
+    cmp::Ordering,
+    fmt::Display,
+    ops::{Add, Div, Mul, Neg, Sub},
+};
+
+// Exact arithmetic for the puzzles where f64 loses precision and i64
+// overflows (hailstones, huge decks of cards): rationals over i128, linear
+// systems solved exactly (over the rationals, or modulo primes for
+// integer solutions too large for the rationals), line intersections,
+// and affine maps x -> ax + b modulo n.
+//
+// Rational operators panic on overflow rather than returning wrong
+// results; the checked_ methods, and intersect, return None instead.
+// Everything is over i128, without a bigint type: the puzzles above fit,
+// and solve_integer covers the systems that do not fit the rationals
+
+fn gcd(mut a: i128, mut b: i128) -> i128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a.abs()
+}
+
+// always reduced, with a positive denominator; neither is i128::MIN, so
+// signs can be flipped without overflow
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Rational {
+    num: i128,
+    den: i128,
+}
+
+impl Rational {
+    pub const ZERO: Rational = Rational { num: 0, den: 1 };
+    pub const ONE: Rational = Rational { num: 1, den: 1 };
+
+    pub fn new(num: i128, den: i128) -> Self {
+        assert!(den != 0, "division by zero");
+        Self::checked_new(num, den).expect("rational overflow")
+    }
+
+    // None on division by zero, or when num or den is i128::MIN
+    pub fn checked_new(num: i128, den: i128) -> Option<Self> {
+        if den == 0 || num == i128::MIN || den == i128::MIN {
+            return None;
+        }
+        let g = gcd(num, den);
+        let sign = den.signum();
+        Some(Self {
+            num: sign * num / g,
+            den: sign * den / g,
+        })
+    }
+
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        let g = gcd(self.den, rhs.den);
+        let num = self
+            .num
+            .checked_mul(rhs.den / g)?
+            .checked_add(rhs.num.checked_mul(self.den / g)?)?;
+        Self::checked_new(num, (self.den / g).checked_mul(rhs.den)?)
+    }
+
+    pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+        self.checked_add(-rhs)
+    }
+
+    pub fn checked_mul(self, rhs: Self) -> Option<Self> {
+        // cross reduce first to keep the products small
+        let g1 = gcd(self.num, rhs.den).max(1);
+        let g2 = gcd(rhs.num, self.den).max(1);
+        Self::checked_new(
+            (self.num / g1).checked_mul(rhs.num / g2)?,
+            (self.den / g2).checked_mul(rhs.den / g1)?,
+        )
+    }
+
+    pub fn checked_div(self, rhs: Self) -> Option<Self> {
+        self.checked_mul(Self::checked_new(rhs.den, rhs.num)?)
+    }
+
+    pub fn numerator(&self) -> i128 {
+        self.num
+    }
+
+    pub fn denominator(&self) -> i128 {
+        self.den
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.num == 0
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.den == 1
+    }
+
+    pub fn to_integer(&self) -> Option<i128> {
+        self.is_integer().then_some(self.num)
+    }
+
+    pub fn floor(&self) -> i128 {
+        self.num.div_euclid(self.den)
+    }
+
+    pub fn abs(&self) -> Self {
+        Self {
+            num: self.num.abs(),
+            den: self.den,
+        }
+    }
+
+    pub fn recip(&self) -> Self {
+        Self::new(self.den, self.num)
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.num as f64 / self.den as f64
+    }
+}
+
+impl From<i64> for Rational {
+    fn from(value: i64) -> Self {
+        Self {
+            num: value as i128,
+            den: 1,
+        }
+    }
+}
+
+impl From<i128> for Rational {
+    fn from(value: i128) -> Self {
+        assert!(value != i128::MIN, "rational overflow");
+        Self { num: value, den: 1 }
+    }
+}
+
+impl Add for Rational {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        self.checked_add(rhs).expect("rational overflow")
+    }
+}
+
+impl Neg for Rational {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self {
+            num: -self.num,
+            den: self.den,
+        }
+    }
+}
+
+impl Sub for Rational {
+    type Output = Self;
+
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn sub(self, rhs: Self) -> Self {
+        self + -rhs
+    }
+}
+
+impl Mul for Rational {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        self.checked_mul(rhs).expect("rational overflow")
+    }
+}
+
+impl Div for Rational {
+    type Output = Self;
+
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn div(self, rhs: Self) -> Self {
+        self * rhs.recip()
+    }
+}
+
+impl Ord for Rational {
+    // compares the integer parts, then the inverses of the fractional
+    // parts (as continued fractions), so nothing can overflow
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (mut a, mut b, mut c, mut d) = (self.num, self.den, other.num, other.den);
+        loop {
+            let (qa, ra) = (a.div_euclid(b), a.rem_euclid(b));
+            let (qc, rc) = (c.div_euclid(d), c.rem_euclid(d));
+            match (qa.cmp(&qc), ra, rc) {
+                (Ordering::Equal, 0, 0) => return Ordering::Equal,
+                (Ordering::Equal, 0, _) => return Ordering::Less,
+                (Ordering::Equal, _, 0) => return Ordering::Greater,
+                // ra/b < rc/d when d/rc < b/ra
+                (Ordering::Equal, _, _) => (a, b, c, d) = (d, rc, b, ra),
+                (ordering, _, _) => return ordering,
+            }
+        }
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Display for Rational {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.den == 1 {
+            write!(f, "{}", self.num)
+        } else {
+            write!(f, "{}/{}", self.num, self.den)
+        }
+    }
+}
+
+// inverse of a modulo n, if a and n are coprime
+pub fn mod_inverse(a: i128, n: i128) -> Option<i128> {
+    let (mut old_r, mut r) = (a.rem_euclid(n), n);
+    let (mut old_s, mut s) = (1i128, 0i128);
+    while r != 0 {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_s, s) = (s, old_s - q * s);
+    }
+    (old_r == 1).then(|| old_s.rem_euclid(n))
+}
+
+// a * b modulo n, for a and b in 0..n, even when the product does not
+// fit in i128
+pub fn mul_mod(a: i128, b: i128, n: i128) -> i128 {
+    if let Some(product) = a.checked_mul(b) {
+        return product % n;
+    }
+    // double and add: values stay below n < 2^127, so sums fit in u128
+    let n = n as u128;
+    let (mut a, mut b) = (a as u128, b as u128);
+    let mut product = 0;
+    while b > 0 {
+        if b & 1 == 1 {
+            product = (product + a) % n;
+        }
+        a = (a + a) % n;
+        b >>= 1;
+    }
+    product as i128
+}
+
+// the operations Gaussian elimination needs
+trait Field: Copy + PartialEq {
+    fn is_zero(&self) -> bool;
+    fn sub(self, rhs: Self) -> Self;
+    fn mul(self, rhs: Self) -> Self;
+    fn div(self, rhs: Self) -> Self;
+}
+
+impl Field for Rational {
+    fn is_zero(&self) -> bool {
+        self.num == 0
+    }
+
+    fn sub(self, rhs: Self) -> Self {
+        self - rhs
+    }
+
+    fn mul(self, rhs: Self) -> Self {
+        self * rhs
+    }
+
+    fn div(self, rhs: Self) -> Self {
+        self / rhs
+    }
+}
+
+// value modulo a prime
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct ModP(i128, i128);
+
+impl Field for ModP {
+    fn is_zero(&self) -> bool {
+        self.0 == 0
+    }
+
+    fn sub(self, rhs: Self) -> Self {
+        ModP((self.0 - rhs.0).rem_euclid(self.1), self.1)
+    }
+
+    fn mul(self, rhs: Self) -> Self {
+        ModP(mul_mod(self.0, rhs.0, self.1), self.1)
+    }
+
+    fn div(self, rhs: Self) -> Self {
+        let inverse = mod_inverse(rhs.0, self.1).expect("division by zero");
+        self.mul(ModP(inverse, self.1))
+    }
+}
+
+// Gauss-Jordan elimination on the augmented matrix; the solution if it is
+// unique
+fn eliminate<F: Field>(mut rows: Vec<Vec<F>>) -> Option<Vec<F>> {
+    let n = rows.len();
+    for col in 0..n {
+        let pivot = (col..n).find(|&r| !rows[r][col].is_zero())?;
+        rows.swap(col, pivot);
+        let p = rows[col][col];
+        for value in rows[col].iter_mut() {
+            *value = value.div(p);
+        }
+        let pivot_row = rows[col].clone();
+        for (r, row) in rows.iter_mut().enumerate() {
+            let factor = row[col];
+            if r != col && !factor.is_zero() {
+                for (value, &p) in row.iter_mut().zip(&pivot_row).skip(col) {
+                    *value = value.sub(factor.mul(p));
+                }
+            }
+        }
+    }
+    Some(rows.into_iter().map(|r| r[n]).collect())
+}
+
+// the solution of matrix * x = rhs for a square matrix, if unique
+pub fn solve(matrix: &[Vec<Rational>], rhs: &[Rational]) -> Option<Vec<Rational>> {
+    assert_eq!(matrix.len(), rhs.len());
+    let rows = matrix
+        .iter()
+        .zip(rhs)
+        .map(|(row, &r)| {
+            assert_eq!(matrix.len(), row.len(), "not a square matrix");
+            row.iter().copied().chain(Some(r)).collect()
+        })
+        .collect();
+    eliminate(rows)
+}
+
+// the solution of matrix * x = rhs modulo the prime p, if unique; p may
+// be any prime that fits in i128
+pub fn solve_mod(matrix: &[Vec<i128>], rhs: &[i128], p: i128) -> Option<Vec<i128>> {
+    assert_eq!(matrix.len(), rhs.len());
+    let rows = matrix
+        .iter()
+        .zip(rhs)
+        .map(|(row, &r)| {
+            assert_eq!(matrix.len(), row.len(), "not a square matrix");
+            row.iter()
+                .chain(Some(&r))
+                .map(|&v| ModP(v.rem_euclid(p), p))
+                .collect()
+        })
+        .collect();
+    eliminate(rows).map(|x| x.into_iter().map(|v| v.0).collect())
+}
+
+const PRIMES: [i128; 3] = [1_000_000_007, 998_244_353, 1_000_000_009];
+
+// The integer solution of matrix * x = rhs, for systems whose
+// elimination would overflow the rationals: solved modulo a few primes,
+// combined with the Chinese remainder theorem, then checked. Solutions
+// must be below 10^26 or so
+pub fn solve_integer(matrix: &[Vec<i128>], rhs: &[i128]) -> Option<Vec<i128>> {
+    let mut solution = vec![0; rhs.len()];
+    let mut modulus = 1;
+    for p in PRIMES {
+        let x = solve_mod(matrix, rhs, p)?;
+        let inverse = mod_inverse(modulus, p).unwrap();
+        for (s, r) in solution.iter_mut().zip(x) {
+            let k = (r - *s).rem_euclid(p) * inverse % p;
+            *s += modulus * k;
+        }
+        modulus *= p;
+    }
+    // back to signed values
+    for s in solution.iter_mut() {
+        if *s > modulus / 2 {
+            *s -= modulus;
+        }
+    }
+    let exact = matrix.iter().zip(rhs).all(|(row, &r)| {
+        row.iter()
+            .zip(&solution)
+            .try_fold(0i128, |acc, (a, x)| acc.checked_add(a.checked_mul(*x)?))
+            == Some(r)
+    });
+    exact.then_some(solution)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Intersection<const N: usize> {
+    // p1 + t v1 = p2 + u v2 = at
+    Point {
+        at: [Rational; N],
+        t: Rational,
+        u: Rational,
+    },
+    // both lines are the same
+    Same,
+    // parallel or skew lines
+    None,
+}
+
+// Intersection of the lines p1 + t v1 and p2 + u v2, in 2 or 3 (or more)
+// dimensions, or None if computing it overflows i128. t and u are solved
+// on the first two axes where the lines are not parallel, then checked on
+// the others
+pub fn intersect<const N: usize>(
+    p1: [i128; N],
+    v1: [i128; N],
+    p2: [i128; N],
+    v2: [i128; N],
+) -> Option<Intersection<N>> {
+    let mut d = [0; N];
+    for ((d, a), b) in d.iter_mut().zip(p2).zip(p1) {
+        *d = a.checked_sub(b)?;
+    }
+    // a b - c e
+    let cross =
+        |a: i128, b: i128, c: i128, e: i128| a.checked_mul(b)?.checked_sub(c.checked_mul(e)?);
+    let on_line = |p: i128, t: Rational, v: i128| {
+        Rational::checked_new(p, 1)?.checked_add(t.checked_mul(Rational::checked_new(v, 1)?)?)
+    };
+    for i in 0..N {
+        for j in i + 1..N {
+            // t v1 - u v2 = d on axes i and j, by Cramer's rule
+            let det = cross(v2[i], v1[j], v1[i], v2[j])?;
+            if det == 0 {
+                continue;
+            }
+            let t = Rational::checked_new(cross(v2[i], d[j], d[i], v2[j])?, det)?;
+            let u = Rational::checked_new(cross(v1[i], d[j], d[i], v1[j])?, det)?;
+            let mut at = [Rational::ZERO; N];
+            for (k, at) in at.iter_mut().enumerate() {
+                *at = on_line(p1[k], t, v1[k])?;
+            }
+            let mut meet = true;
+            for (k, at) in at.iter().enumerate() {
+                meet &= *at == on_line(p2[k], u, v2[k])?;
+            }
+            return Some(if meet {
+                Intersection::Point { at, t, u }
+            } else {
+                Intersection::None
+            });
+        }
+    }
+    // parallel: the same line if d is parallel to them as well
+    let parallel = |v: &[i128; N]| -> Option<bool> {
+        for i in 0..N {
+            for j in i + 1..N {
+                if cross(d[i], v[j], d[j], v[i])? != 0 {
+                    return Some(false);
+                }
+            }
+        }
+        Some(true)
+    };
+    Some(if parallel(&v1)? && parallel(&v2)? {
+        Intersection::Same
+    } else {
+        Intersection::None
+    })
+}
+
+// x -> a x + b modulo n, as the shuffles of 2019 day 22. Values are kept
+// in 0..n; n must be below 2^63 so that products fit in i128
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Affine {
+    pub a: i128,
+    pub b: i128,
+    pub n: i128,
+}
+
+impl Affine {
+    pub fn new(a: i128, b: i128, n: i128) -> Self {
+        Self {
+            a: a.rem_euclid(n),
+            b: b.rem_euclid(n),
+            n,
+        }
+    }
+
+    pub fn identity(n: i128) -> Self {
+        Self::new(1, 0, n)
+    }
+
+    pub fn apply(&self, x: i128) -> i128 {
+        (self.a * x.rem_euclid(self.n) + self.b).rem_euclid(self.n)
+    }
+
+    // self, then other
+    pub fn then(&self, other: &Self) -> Self {
+        assert_eq!(self.n, other.n);
+        Self::new(self.a * other.a, other.a * self.b + other.b, self.n)
+    }
+
+    pub fn inverse(&self) -> Option<Self> {
+        let ai = mod_inverse(self.a, self.n)?;
+        Some(Self::new(ai, -self.b * ai % self.n, self.n))
+    }
+
+    // self applied exp times, by squaring
+    pub fn pow(&self, mut exp: u64) -> Self {
+        let mut result = Self::identity(self.n);
+        let mut base = *self;
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = result.then(&base);
+            }
+            base = base.then(&base);
+            exp >>= 1;
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn r(num: i128, den: i128) -> Rational {
+        Rational::new(num, den)
+    }
+
+    #[test]
+    fn test_rational() {
+        assert_eq!(r(1, 2), r(-3, -6));
+        assert_eq!(r(5, 6), r(1, 2) + r(1, 3));
+        assert_eq!(r(-1, 6), r(1, 3) - r(1, 2));
+        assert_eq!(r(3, 2), r(3, 4) / r(1, 2));
+        assert_eq!("-7/3", (r(7, 9) * r(-3, 1)).to_string());
+        assert_eq!(-3, r(-7, 3).floor());
+        assert!(r(1, 3) < r(1, 2) && r(-1, 2) < r(-1, 3) && r(5, 3) > r(8, 5));
+        assert!(r(355, 113) < r(22, 7));
+        // no overflow when comparing, or multiplying reducible values
+        let big = i128::MAX / 3;
+        assert!(r(big, big - 1) < r(big - 1, big - 2));
+        assert_eq!(Rational::ONE, r(big, 7) * r(7, big));
+        // but overflowing sums and products are reported
+        assert_eq!(None, r(i128::MAX, 1).checked_add(Rational::ONE));
+        assert_eq!(None, r(big, 1).checked_mul(r(big, 1)));
+        assert_eq!(None, r(1, big).checked_sub(r(1, big - 1)));
+        assert_eq!(None, Rational::ONE.checked_div(Rational::ZERO));
+        assert_eq!(None, Rational::checked_new(i128::MIN, 1));
+        assert_eq!(Some(r(5, 6)), r(1, 2).checked_add(r(1, 3)));
+    }
+
+    #[test]
+    fn test_solve() {
+        // x + y + z = 6, 2y + 5z = -4, 2x + 5y - z = 27
+        let m = [[1, 1, 1], [0, 2, 5], [2, 5, -1]];
+        let matrix: Vec<Vec<Rational>> = m
+            .iter()
+            .map(|row| row.iter().map(|&v| Rational::from(v as i64)).collect())
+            .collect();
+        let rhs = [6i64, -4, 27].map(Rational::from);
+        let expected = [5i64, 3, -2].map(Rational::from).to_vec();
+        assert_eq!(Some(expected), solve(&matrix, &rhs));
+        let singular = vec![vec![Rational::ONE, Rational::ONE]; 2];
+        assert_eq!(None, solve(&singular, &[Rational::ONE, Rational::ZERO]));
+
+        let matrix: Vec<Vec<i128>> = m.iter().map(|row| row.to_vec()).collect();
+        assert_eq!(Some(vec![5, 3, 11]), solve_mod(&matrix, &[6, -4, 27], 13));
+        assert_eq!(Some(vec![5, 3, -2]), solve_integer(&matrix, &[6, -4, 27]));
+        // not an integer solution
+        assert_eq!(None, solve_integer(&[vec![2]], &[3]));
+
+        // a prime whose squares overflow i128
+        let p = (1 << 89) - 1;
+        assert_eq!(1, mul_mod(p - 1, p - 1, p));
+        assert_eq!(
+            Some(vec![p - 2, 5]),
+            solve_mod(&[vec![3, 1], vec![1, 2]], &[p - 1, 8], p)
+        );
+    }
+
+    #[test]
+    fn test_hailstones() {
+        // 2023 day 24
+        let hailstones: [([i128; 3], [i128; 3]); 5] = [
+            ([19, 13, 30], [-2, 1, -2]),
+            ([18, 19, 22], [-1, -1, -2]),
+            ([20, 25, 34], [-2, -2, -4]),
+            ([12, 31, 28], [-1, -2, -1]),
+            ([20, 19, 15], [1, -5, -3]),
+        ];
+        let flat = |v: [i128; 3]| [v[0], v[1]];
+        let (a, b) = (hailstones[0], hailstones[1]);
+        match intersect(flat(a.0), flat(a.1), flat(b.0), flat(b.1)).unwrap() {
+            Intersection::Point { at, t, u } => {
+                assert_eq!([r(43, 3), r(46, 3)], at);
+                assert!(t > Rational::ZERO && u > Rational::ZERO);
+            }
+            i => panic!("unexpected {i:?}"),
+        }
+        let (b, c) = (hailstones[1], hailstones[2]);
+        assert_eq!(
+            Some(Intersection::None),
+            intersect(flat(b.0), flat(b.1), flat(c.0), flat(c.1))
+        );
+        // the rock hits all of them
+        let (p, v) = ([24, 13, 10], [-3, 1, 2]);
+        for (h, hv) in hailstones {
+            match intersect(p, v, h, hv).unwrap() {
+                Intersection::Point { t, u, .. } => assert_eq!(t, u),
+                i => panic!("unexpected {i:?}"),
+            }
+        }
+        assert_eq!(
+            Some(Intersection::Same),
+            intersect([0, 0, 0], [1, 2, 3], [2, 4, 6], [-2, -4, -6])
+        );
+        assert_eq!(
+            Some(Intersection::None),
+            intersect([0, 0, 0], [1, 0, 0], [0, 1, 0], [0, 0, 1])
+        );
+    }
+
+    #[test]
+    fn test_input_sized_hailstones() {
+        // coordinates and velocities the size of those of the inputs
+        let (p1, v1) = ([320870677764563, 335750934489987], [-40, -24]);
+        let (p2, v2) = ([219235623600942, 408022798608755], [127, -45]);
+        match intersect(p1, v1, p2, v2).unwrap() {
+            Intersection::Point { at, t, u } => {
+                assert_eq!([r(217472377253928133, 606), r(72426638072697965, 202)], at);
+                assert_eq!(r(-4604949305720591, 4848), t);
+                assert_eq!(r(666264483084703, 606), u);
+            }
+            i => panic!("unexpected {i:?}"),
+        }
+
+        // a rock hitting hailstones at times up to 10^12
+        let (p, v) = (
+            [200000000000024, 300000000000013, 250000000000010],
+            [-3, 1, 2],
+        );
+        let hailstones = [
+            (5, [-170, 41, 310]),
+            (981234567890, [-65, -152, 23]),
+            (123456789, [211, 8, -99]),
+        ];
+        for (time, hv) in hailstones {
+            let h: [i128; 3] = std::array::from_fn(|k| p[k] + (v[k] - hv[k]) * time);
+            match intersect(p, v, h, hv).unwrap() {
+                Intersection::Point { t, u, .. } => {
+                    assert_eq!((Rational::from(time), Rational::from(time)), (t, u))
+                }
+                i => panic!("unexpected {i:?}"),
+            }
+        }
+
+        // differences that do not fit
+        let big = i128::MAX / 2 + 1;
+        assert_eq!(None, intersect([big, 0], [1, 1], [-big, 0], [1, -1]));
+        assert_eq!(None, intersect([0, 0], [big, 1], [1, 0], [1, big]));
+    }
+
+    #[test]
+    fn test_affine() {
+        // 2019 day 22: deal with increment 7, deal into new stack, twice
+        let deck = 10;
+        let increment = Affine::new(7, 0, deck);
+        let new_stack = Affine::new(-1, -1, deck);
+        let shuffle = increment.then(&new_stack).then(&new_stack);
+        let mut out = vec![0; 10];
+        (0..10).for_each(|c| out[shuffle.apply(c) as usize] = c);
+        assert_eq!(vec![0, 3, 6, 9, 2, 5, 8, 1, 4, 7], out);
+
+        let inverse = shuffle.inverse().unwrap();
+        assert_eq!(Affine::identity(deck), shuffle.then(&inverse));
+        assert_eq!(None, Affine::new(2, 0, deck).inverse());
+
+        let big = Affine::new(1234567, 89, 119315717514047);
+        let repeated = (0..1000).fold(Affine::identity(big.n), |a, _| a.then(&big));
+        assert_eq!(repeated, big.pow(1000));
+        let shuffles = big.pow(101741582076661);
+        assert_eq!(
+            2020,
+            shuffles.inverse().unwrap().apply(shuffles.apply(2020))
+        );
+    }
+}
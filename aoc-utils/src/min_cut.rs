@@ -0,0 +1,411 @@
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+
+use crate::graph::Graph;
+use crate::union_find::UnionFind;
+
+// Minimum cuts of a Graph (the wiring of 2023 day 25): the exact global
+// minimum cut with Stoer-Wagner, minimum s-t cuts from Edmonds-Karp max
+// flow, and Karger's randomized contraction. The weight closure gives
+// the capacity of each edge (|_| 1 for unweighted graphs)
+
+// a cut: the nodes on each side, by id, and the edges across, from
+// sides[0] to sides[1]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cut {
+    pub weight: i64,
+    pub edges: Vec<(usize, usize)>,
+    pub sides: [Vec<usize>; 2],
+}
+
+impl Cut {
+    fn from_side<N, E>(graph: &Graph<N, E>, first: &[bool], weight: &impl Fn(&E) -> i64) -> Self
+    where
+        N: Clone + Eq + std::hash::Hash,
+        E: Clone,
+    {
+        let mut cut = Cut {
+            weight: 0,
+            edges: vec![],
+            sides: [vec![], vec![]],
+        };
+        for id in 0..graph.len() {
+            cut.sides[usize::from(!first[id])].push(id);
+            if first[id] {
+                for (to, w) in graph.edges(id).filter(|(to, _)| !first[*to]) {
+                    cut.weight += weight(w);
+                    cut.edges.push((id, to));
+                }
+            }
+        }
+        cut
+    }
+
+    // the sizes of both sides, as the answer of 2023 day 25
+    pub fn sizes(&self) -> (usize, usize) {
+        (self.sides[0].len(), self.sides[1].len())
+    }
+}
+
+// Stoer-Wagner: each phase adds the nodes in maximum adjacency order, the
+// last one added is cut from the rest at the cut of the phase, then merged
+// with the one before. The lightest cut of the phases is a minimum cut.
+// For undirected graphs with at least 2 nodes
+pub fn stoer_wagner<N, E>(graph: &Graph<N, E>, weight: impl Fn(&E) -> i64) -> Cut
+where
+    N: Clone + Eq + std::hash::Hash,
+    E: Clone,
+{
+    assert!(
+        !graph.is_directed(),
+        "Stoer-Wagner needs an undirected graph"
+    );
+    let n = graph.len();
+    assert!(n >= 2, "no cut with fewer than 2 nodes");
+
+    // a disconnected graph is cut for free
+    let components = graph.components();
+    if components.len() > 1 {
+        let mut first = vec![false; n];
+        components[0].iter().for_each(|&id| first[id] = true);
+        return Cut::from_side(graph, &first, &weight);
+    }
+
+    let mut adjacency: Vec<HashMap<usize, i64>> = (0..n)
+        .map(|id| {
+            let mut edges = HashMap::new();
+            for (to, w) in graph.edges(id).filter(|(to, _)| *to != id) {
+                *edges.entry(to).or_insert(0) += weight(w);
+            }
+            edges
+        })
+        .collect();
+    // the original nodes merged into each node
+    let mut members: Vec<Vec<usize>> = (0..n).map(|id| vec![id]).collect();
+    let mut merged = vec![false; n];
+    let mut best: Option<(i64, Vec<usize>)> = None;
+
+    for phase in 0..n - 1 {
+        let start = (0..n).find(|&id| !merged[id]).unwrap();
+        let mut connection = vec![0; n];
+        let mut added = vec![false; n];
+        let mut queue = BinaryHeap::from([(0, start)]);
+        let (mut previous, mut last) = (start, start);
+        let mut count = 0;
+        while let Some((w, id)) = queue.pop() {
+            if added[id] || w != connection[id] {
+                continue;
+            }
+            added[id] = true;
+            (previous, last) = (last, id);
+            count += 1;
+            if count == n - phase {
+                break;
+            }
+            for (&to, &w) in &adjacency[id] {
+                if !added[to] {
+                    connection[to] += w;
+                    queue.push((connection[to], to));
+                }
+            }
+        }
+
+        if best.as_ref().is_none_or(|(w, _)| connection[last] < *w) {
+            best = Some((connection[last], members[last].clone()));
+        }
+
+        // merge last into previous
+        merged[last] = true;
+        let moved = std::mem::take(&mut members[last]);
+        members[previous].extend(moved);
+        for (to, w) in std::mem::take(&mut adjacency[last]) {
+            adjacency[to].remove(&last);
+            if to != previous {
+                *adjacency[previous].entry(to).or_insert(0) += w;
+                *adjacency[to].entry(previous).or_insert(0) += w;
+            }
+        }
+    }
+
+    let mut first = vec![false; n];
+    best.unwrap().1.iter().for_each(|&id| first[id] = true);
+    Cut::from_side(graph, &first, &weight)
+}
+
+// Edmonds-Karp: augmenting along shortest paths until there is none left.
+// The minimum cut separates the nodes still reachable from source in the
+// residual graph. Edges of undirected graphs have their capacity both ways
+pub fn max_flow<N, E>(
+    graph: &Graph<N, E>,
+    source: usize,
+    sink: usize,
+    weight: impl Fn(&E) -> i64,
+) -> Cut
+where
+    N: Clone + Eq + std::hash::Hash,
+    E: Clone,
+{
+    assert_ne!(source, sink);
+    let n = graph.len();
+    let mut residual: Vec<HashMap<usize, i64>> = vec![HashMap::new(); n];
+    for from in 0..n {
+        for (to, w) in graph.edges(from) {
+            *residual[from].entry(to).or_insert(0) += weight(w);
+            residual[to].entry(from).or_insert(0);
+        }
+    }
+
+    loop {
+        // breadth first search for a path with spare capacity
+        let mut parent = vec![None; n];
+        parent[source] = Some(source);
+        let mut queue = VecDeque::from([source]);
+        while let Some(id) = queue.pop_front() {
+            for (&to, &c) in &residual[id] {
+                if c > 0 && parent[to].is_none() {
+                    parent[to] = Some(id);
+                    queue.push_back(to);
+                }
+            }
+        }
+        if parent[sink].is_none() {
+            let first: Vec<bool> = parent.iter().map(Option::is_some).collect();
+            return Cut::from_side(graph, &first, &weight);
+        }
+
+        let mut path = vec![];
+        let mut id = sink;
+        while id != source {
+            let from = parent[id].unwrap();
+            path.push((from, id));
+            id = from;
+        }
+        let bottleneck = path.iter().map(|&(f, t)| residual[f][&t]).min().unwrap();
+        for (f, t) in path {
+            *residual[f].get_mut(&t).unwrap() -= bottleneck;
+            *residual[t].get_mut(&f).unwrap() += bottleneck;
+        }
+    }
+}
+
+// The lightest of the minimum cuts between source and each of sinks. With
+// all the other nodes as sinks, this is a global minimum cut; a sample of
+// them is enough when the sides are known to be large
+pub fn flow_min_cut<N, E>(
+    graph: &Graph<N, E>,
+    source: usize,
+    sinks: impl IntoIterator<Item = usize>,
+    weight: impl Fn(&E) -> i64,
+) -> Option<Cut>
+where
+    N: Clone + Eq + std::hash::Hash,
+    E: Clone,
+{
+    sinks
+        .into_iter()
+        .filter(|&sink| sink != source)
+        .map(|sink| max_flow(graph, source, sink, &weight))
+        .min_by_key(|cut| cut.weight)
+}
+
+// SplitMix64, enough to shuffle edges reproducibly
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    // in 0..n
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    // uniform in (0, 1]
+    pub fn unit(&mut self) -> f64 {
+        ((self.next_u64() >> 11) + 1) as f64 / (1u64 << 53) as f64
+    }
+}
+
+// Karger: contracting edges picked at random until two nodes are left
+// finds a minimum cut with probability at least 2 / n^2, so this keeps the
+// best cut of trials contractions. Edges are picked with a probability
+// proportional to their weight, which must not be negative. For connected
+// undirected graphs
+pub fn karger<N, E>(
+    graph: &Graph<N, E>,
+    trials: usize,
+    seed: u64,
+    weight: impl Fn(&E) -> i64,
+) -> Cut
+where
+    N: Clone + Eq + std::hash::Hash,
+    E: Clone,
+{
+    assert!(!graph.is_directed(), "Karger needs an undirected graph");
+    let n = graph.len();
+    assert!(n >= 2, "no cut with fewer than 2 nodes");
+    let edges: Vec<(usize, usize, i64)> = (0..n)
+        .flat_map(|from| graph.edges(from).map(move |(to, w)| (from, to, w)))
+        .filter(|(from, to, _)| from < to)
+        .map(|(from, to, w)| {
+            let w = weight(w);
+            assert!(w >= 0, "negative weight {w}");
+            (from, to, w)
+        })
+        .collect();
+    let mut rng = Rng::new(seed);
+
+    (0..trials.max(1))
+        .map(|_| {
+            // contracting in the order of exponential clocks, with the
+            // weights as rates, is the same as picking each next edge with
+            // a probability proportional to its weight
+            let mut order: Vec<(f64, usize, usize)> = edges
+                .iter()
+                .map(|&(from, to, w)| {
+                    let clock = if w == 0 {
+                        f64::INFINITY
+                    } else {
+                        -rng.unit().ln() / w as f64
+                    };
+                    (clock, from, to)
+                })
+                .collect();
+            order.sort_by(|a, b| a.0.total_cmp(&b.0));
+            let mut groups = UnionFind::new();
+            groups.ensure_capacity(n - 1);
+            let mut count = n;
+            for &(_, from, to) in &order {
+                if count == 2 {
+                    break;
+                }
+                if groups.leader(from) != groups.leader(to) {
+                    groups.join(from, to);
+                    count -= 1;
+                }
+            }
+            let leader = groups.leader(0);
+            let first: Vec<bool> = (0..n).map(|id| groups.leader(id) == leader).collect();
+            Cut::from_side(graph, &first, &weight)
+        })
+        .min_by_key(|cut| cut.weight)
+        .unwrap()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    static WIRES: &str = "jqt: rhn xhk nvd
+rsh: frs pzl lsr
+xhk: hfx
+cmg: qnr nvd lhk bvb
+rhn: xhk bvb hfx
+bvb: xhk hfx
+pzl: lsr hfx nvd
+qnr: nvd
+ntq: jqt hfx bvb xhk
+nvd: lhk
+lsr: lhk
+rzs: qnr cmg lsr rsh
+frs: qnr lhk lsr";
+
+    fn check(graph: &Graph<&str>, cut: &Cut) {
+        assert_eq!(3, cut.weight);
+        assert_eq!(54, cut.sizes().0 * cut.sizes().1);
+        let mut edges: Vec<_> = cut
+            .edges
+            .iter()
+            .map(|&(f, t)| {
+                let mut e = [*graph.label(f), *graph.label(t)];
+                e.sort();
+                e
+            })
+            .collect();
+        edges.sort();
+        assert_eq!(vec![["bvb", "cmg"], ["hfx", "pzl"], ["jqt", "nvd"]], edges);
+    }
+
+    #[test]
+    fn test_min_cut() {
+        // 2023 day 25
+        let graph = Graph::parse_adjacency(WIRES, ":", " ", false).unwrap();
+        check(&graph, &stoer_wagner(&graph, |_| 1));
+        check(
+            &graph,
+            &flow_min_cut(&graph, 0, 1..graph.len(), |_| 1).unwrap(),
+        );
+        check(&graph, &karger(&graph, 100, 2023, |_| 1));
+
+        // same seed, same cut
+        let cut = karger(&graph, 1, 42, |_| 1);
+        assert_eq!(cut, karger(&graph, 1, 42, |_| 1));
+    }
+
+    #[test]
+    fn test_weighted() {
+        // two triangles joined by a heavy and a light edge
+        let mut graph = Graph::undirected();
+        for (a, b, w) in [
+            ("a", "b", 5),
+            ("b", "c", 5),
+            ("c", "a", 5),
+            ("x", "y", 5),
+            ("y", "z", 5),
+            ("z", "x", 5),
+            ("a", "x", 3),
+            ("b", "y", 1),
+        ] {
+            graph.add_edge(a, b, w);
+        }
+        let cut = stoer_wagner(&graph, |w| *w);
+        assert_eq!((4, (3, 3)), (cut.weight, cut.sizes()));
+        let a = graph.id("a").unwrap();
+        let z = graph.id("z").unwrap();
+        assert_eq!(4, max_flow(&graph, a, z, |w| *w).weight);
+        // directed: only the edges from a can carry flow out of it
+        let chain: Graph<&str, i64> = [("s", "m", 4), ("m", "t", 2), ("t", "s", 9)]
+            .into_iter()
+            .collect();
+        assert_eq!(2, max_flow(&chain, 0, 2, |w| *w).weight);
+
+        // disconnected
+        let mut graph: Graph<&str> = Graph::undirected();
+        graph.add_edge("a", "b", ());
+        graph.add_edge("c", "d", ());
+        assert_eq!(0, stoer_wagner(&graph, |_| 1).weight);
+    }
+
+    #[test]
+    fn test_karger_weighted() {
+        // heavy triangles joined by three light edges: contractions follow
+        // the weights, so a single trial keeps the light edges
+        let mut graph = Graph::undirected();
+        for (a, b, w) in [
+            ("a", "b", 1000),
+            ("b", "c", 1000),
+            ("c", "a", 1000),
+            ("x", "y", 1000),
+            ("y", "z", 1000),
+            ("z", "x", 1000),
+            ("a", "x", 1),
+            ("b", "y", 1),
+            ("c", "z", 1),
+        ] {
+            graph.add_edge(a, b, w);
+        }
+        for seed in 0..20 {
+            let cut = karger(&graph, 1, seed, |w| *w);
+            assert_eq!((3, (3, 3)), (cut.weight, cut.sizes()));
+        }
+    }
+}
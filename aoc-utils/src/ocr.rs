@@ -0,0 +1,294 @@
+use std::collections::BTreeSet;
+use thiserror::Error;
+
+use crate::grid::Grid;
+
+// Reads the block letters that some puzzles draw on a screen (2016 day 8,
+// 2019 days 8 and 11, 2021 day 13, 2022 day 10 with the 4x6 font; 2018
+// day 10 with the 6x10 one). The font is chosen by the height of the lit
+// area; letters are split on blank columns and compared without their
+// blank columns, so the letter pitch and offset do not matter. Glyphs can
+// be wider than the pitch (the small Y is 5 columns wide), and then touch
+// the next letter; such runs of columns are split on the glyphs they start
+// with
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum OcrError {
+    #[error("no font is {0} pixels high")]
+    UnknownFont(usize),
+    #[error("unknown glyph at position {position}:\n{bitmap}")]
+    UnknownGlyph { position: usize, bitmap: String },
+}
+
+type Font = &'static [(char, &'static [&'static str])];
+
+static SMALL: Font = &[
+    ('A', &[".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+    ('B', &["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+    ('C', &[".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+    ('E', &["####", "#...", "###.", "#...", "#...", "####"]),
+    ('F', &["####", "#...", "###.", "#...", "#...", "#..."]),
+    ('G', &[".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+    ('H', &["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+    ('I', &[".###", "..#.", "..#.", "..#.", "..#.", ".###"]),
+    ('J', &["..##", "...#", "...#", "...#", "#..#", ".##."]),
+    ('K', &["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+    ('L', &["#...", "#...", "#...", "#...", "#...", "####"]),
+    ('O', &[".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('P', &["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+    ('R', &["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+    ('S', &[".###", "#...", "#...", ".##.", "...#", "###."]),
+    ('U', &["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('Y', &["#...#", "#...#", ".#.#.", "..#..", "..#..", "..#.."]),
+    ('Z', &["####", "...#", "..#.", ".#..", "#...", "####"]),
+];
+
+#[rustfmt::skip]
+static LARGE: Font = &[
+    ('A', &["..##..", ".#..#.", "#....#", "#....#", "#....#",
+            "######", "#....#", "#....#", "#....#", "#....#"]),
+    ('B', &["#####.", "#....#", "#....#", "#....#", "#####.",
+            "#....#", "#....#", "#....#", "#....#", "#####."]),
+    ('C', &[".####.", "#....#", "#.....", "#.....", "#.....",
+            "#.....", "#.....", "#.....", "#....#", ".####."]),
+    ('E', &["######", "#.....", "#.....", "#.....", "#####.",
+            "#.....", "#.....", "#.....", "#.....", "######"]),
+    ('F', &["######", "#.....", "#.....", "#.....", "#####.",
+            "#.....", "#.....", "#.....", "#.....", "#....."]),
+    ('G', &[".####.", "#....#", "#.....", "#.....", "#.....",
+            "#..###", "#....#", "#....#", "#...##", ".###.#"]),
+    ('H', &["#....#", "#....#", "#....#", "#....#", "######",
+            "#....#", "#....#", "#....#", "#....#", "#....#"]),
+    ('J', &["...###", "....#.", "....#.", "....#.", "....#.",
+            "....#.", "....#.", "#...#.", "#...#.", ".###.."]),
+    ('K', &["#....#", "#...#.", "#..#..", "#.#...", "##....",
+            "##....", "#.#...", "#..#..", "#...#.", "#....#"]),
+    ('L', &["#.....", "#.....", "#.....", "#.....", "#.....",
+            "#.....", "#.....", "#.....", "#.....", "######"]),
+    ('N', &["#....#", "##...#", "##...#", "#.#..#", "#.#..#",
+            "#..#.#", "#..#.#", "#...##", "#...##", "#....#"]),
+    ('P', &["#####.", "#....#", "#....#", "#....#", "#####.",
+            "#.....", "#.....", "#.....", "#.....", "#....."]),
+    ('R', &["#####.", "#....#", "#....#", "#....#", "#####.",
+            "#..#..", "#...#.", "#...#.", "#....#", "#....#"]),
+    ('X', &["#....#", "#....#", ".#..#.", ".#..#.", "..##..",
+            "..##..", ".#..#.", ".#..#.", "#....#", "#....#"]),
+    ('Z', &["######", ".....#", ".....#", "....#.", "...#..",
+            "..#...", ".#....", "#.....", "#.....", "######"]),
+];
+
+// glyph rows without the columns that are blank in every row
+fn trim(rows: &[&str]) -> Vec<String> {
+    let width = rows.iter().map(|r| r.len()).max().unwrap_or(0);
+    let lit: Vec<_> = (0..width)
+        .filter(|&x| rows.iter().any(|r| r.as_bytes().get(x) == Some(&b'#')))
+        .collect();
+    rows.iter()
+        .map(|r| {
+            lit.iter()
+                .map(|&x| {
+                    if r.as_bytes().get(x) == Some(&b'#') {
+                        '#'
+                    } else {
+                        '.'
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+// the letters of a run of lit columns, widest glyphs first; None if the
+// run cannot be split into glyphs
+fn read_run(glyphs: &[(char, Vec<String>)], rows: &[String], start: usize) -> Option<String> {
+    let width = rows[0].len();
+    if start == width {
+        return Some(String::new());
+    }
+    glyphs.iter().find_map(|(c, glyph)| {
+        let end = start + glyph[0].len();
+        let matches = end <= width && rows.iter().zip(glyph).all(|(r, g)| &r[start..end] == g);
+        if matches {
+            read_run(glyphs, rows, end).map(|rest| format!("{c}{rest}"))
+        } else {
+            None
+        }
+    })
+}
+
+pub fn read_points(points: impl IntoIterator<Item = (i64, i64)>) -> Result<String, OcrError> {
+    let points: BTreeSet<_> = points.into_iter().collect();
+    if points.is_empty() {
+        return Ok(String::new());
+    }
+    let x_min = points.iter().map(|p| p.0).min().unwrap();
+    let x_max = points.iter().map(|p| p.0).max().unwrap();
+    let y_min = points.iter().map(|p| p.1).min().unwrap();
+    let y_max = points.iter().map(|p| p.1).max().unwrap();
+    let height = (y_max - y_min + 1) as usize;
+    let font = [SMALL, LARGE]
+        .into_iter()
+        .find(|f| f[0].1.len() == height)
+        .ok_or(OcrError::UnknownFont(height))?;
+    let mut glyphs: Vec<_> = font.iter().map(|(c, glyph)| (*c, trim(glyph))).collect();
+    glyphs.sort_by_key(|(_, glyph)| std::cmp::Reverse(glyph[0].len()));
+
+    // runs of columns with at least one lit pixel
+    let columns: BTreeSet<_> = points.iter().map(|p| p.0).collect();
+    let mut runs = vec![];
+    let mut x = x_min;
+    while x <= x_max {
+        if columns.contains(&x) {
+            let start = x;
+            while columns.contains(&(x + 1)) {
+                x += 1;
+            }
+            runs.push(start..=x);
+        }
+        x += 1;
+    }
+
+    runs.into_iter()
+        .enumerate()
+        .map(|(position, xs)| {
+            let rows: Vec<String> = (y_min..=y_max)
+                .map(|y| {
+                    xs.clone()
+                        .map(|x| if points.contains(&(x, y)) { '#' } else { '.' })
+                        .collect()
+                })
+                .collect();
+            read_run(&glyphs, &rows, 0).ok_or_else(|| OcrError::UnknownGlyph {
+                position,
+                bitmap: rows.join("\n"),
+            })
+        })
+        .collect()
+}
+
+pub fn read_grid<D>(grid: &Grid<bool, D>) -> Result<String, OcrError> {
+    read_points(
+        grid.iter()
+            .enumerate()
+            .filter(|(_, &lit)| lit)
+            .map(|(idx, _)| {
+                let (x, y) = grid.idx_to_pos(idx);
+                (x as i64, y as i64)
+            }),
+    )
+}
+
+// lit pixels are #, anything else is dark
+pub fn read_text(text: &str) -> Result<String, OcrError> {
+    read_points(text.lines().enumerate().flat_map(|(y, l)| {
+        l.char_indices()
+            .filter(|(_, c)| *c == '#')
+            .map(move |(x, _)| (x as i64, y as i64))
+    }))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // the letters of the font side by side, spaced like on the screens
+    fn draw(font: Font, letters: &str, pitch: usize) -> String {
+        let height = font[0].1.len();
+        (0..height)
+            .map(|y| {
+                letters
+                    .chars()
+                    .map(|c| {
+                        let glyph = font.iter().find(|g| g.0 == c).unwrap().1;
+                        format!("{:pitch$}", glyph[y].replace('.', " "))
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn test_small() {
+        let text = "\
+###  #  # #### ###
+#  # #  # #    #  #
+#  # #### ###  #  #
+###  #  # #    ###
+#    #  # #    #
+#    #  # #### #    ";
+        assert_eq!(Ok("PHEP".to_owned()), read_text(text));
+
+        // a 2022 day 10 screen
+        let screen = "\
+###..#..#.###....##.###..###..#.....##..
+#..#.#.#..#..#....#.#..#.#..#.#....#..#.
+#..#.##...#..#....#.###..#..#.#....#..#.
+###..#.#..###.....#.#..#.###..#....####.
+#.#..#.#..#....#..#.#..#.#....#....#..#.
+#..#.#..#.#.....##..###..#....####.#..#.
+";
+        assert_eq!(Ok("RKPJBPLA".to_owned()), read_text(screen));
+
+        // the Y is as wide as the pitch, and touches the Z after it
+        let letters: String = SMALL.iter().map(|g| g.0).collect();
+        assert_eq!(Ok(letters.clone()), read_text(&draw(SMALL, &letters, 5)));
+        assert_eq!(Ok("YIY".to_owned()), read_text(&draw(SMALL, "YIY", 5)));
+    }
+
+    #[test]
+    fn test_large() {
+        let letters: String = LARGE.iter().map(|g| g.0).collect();
+        assert_eq!(Ok(letters.clone()), read_text(&draw(LARGE, &letters, 8)));
+    }
+
+    struct Pixel(u8);
+
+    impl From<u8> for Pixel {
+        fn from(value: u8) -> Self {
+            Pixel(value)
+        }
+    }
+
+    impl From<&Pixel> for bool {
+        fn from(value: &Pixel) -> Self {
+            value.0 == b'#'
+        }
+    }
+
+    #[test]
+    fn test_points_and_grid() {
+        let text = draw(SMALL, "IJ", 5);
+        let points: Vec<_> = text
+            .lines()
+            .enumerate()
+            .flat_map(|(y, l)| {
+                l.char_indices()
+                    .filter(|(_, c)| *c == '#')
+                    .map(move |(x, _)| (x as i64 - 100, y as i64 + 7))
+            })
+            .collect();
+        assert_eq!(Ok("IJ".to_owned()), read_points(points));
+
+        let grid: Grid<Pixel, ()> = Grid::try_from(draw(SMALL, "ZU", 5).as_str()).unwrap();
+        let grid: Grid<bool, ()> = (&grid).into();
+        assert_eq!(Ok("ZU".to_owned()), read_grid(&grid));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(Err(OcrError::UnknownFont(2)), read_text("#\n#"));
+        let text = [
+            " ##  #", "#  # #", "#  # ###", "####   #", "#  #   #", "#  #   #",
+        ]
+        .join("\n");
+        assert_eq!(
+            Err(OcrError::UnknownGlyph {
+                position: 1,
+                bitmap: "#..\n#..\n###\n..#\n..#\n..#".to_owned()
+            }),
+            read_text(&text)
+        );
+        assert_eq!(Ok(String::new()), read_text("...."));
+    }
+}
@@ -0,0 +1,404 @@
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, take_till1, take_while1},
+    character::complete::{
+        char, i64 as integer, line_ending, not_line_ending, satisfy, space0, space1,
+    },
+    combinator::{consumed, map, opt, recognize, verify},
+    error::{ErrorKind, FromExternalError},
+    multi::{many1, separated_list1},
+    sequence::{delimited, pair, preceded, separated_pair, terminated},
+    IResult, Slice,
+};
+use std::{fmt::Display, ops::RangeInclusive};
+
+use crate::{
+    error::{NomError, ParseError, Span},
+    grid::Grid,
+};
+
+// nom combinators for the input shapes that keep coming back. They work on
+// located spans so that failures can be reported with error::final_parse;
+// names and keys are returned as spans as well, to report errors on them
+// later. None of them consumes the line ending after its last line
+
+pub type PResult<'a, O> = IResult<Span<'a>, O, NomError<'a>>;
+
+pub use nom::character::complete::i64;
+
+// a parsed value, with the part of the input it was parsed from, to report
+// errors on values that parse but make no sense
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Spanned<'a, T> {
+    pub span: Span<'a>,
+    pub value: T,
+}
+
+impl<T> Spanned<'_, T> {
+    // error on this value, in src the whole input
+    pub fn error(&self, src: &str, message: impl Into<String>) -> ParseError {
+        ParseError::new(
+            src,
+            self.span.location_offset(),
+            self.span.fragment().len(),
+            message,
+        )
+    }
+}
+
+pub fn spanned<'a, O>(
+    parser: impl FnMut(Span<'a>) -> PResult<'a, O>,
+) -> impl FnMut(Span<'a>) -> PResult<'a, Spanned<'a, O>> {
+    map(consumed(parser), |(span, value)| Spanned { span, value })
+}
+
+// the values, without their spans
+pub fn values<T: Clone>(spanned: &[Spanned<T>]) -> Vec<T> {
+    spanned.iter().map(|s| s.value.clone()).collect()
+}
+
+// separator with optional spaces around it; a blank separator matches
+// any run of spaces
+fn separator<'a>(sep: &'a str) -> impl FnMut(Span<'a>) -> PResult<'a, Span<'a>> {
+    move |i| {
+        if sep.trim().is_empty() {
+            space1(i)
+        } else {
+            delimited(space0, tag(sep), space0)(i)
+        }
+    }
+}
+
+// one item per line
+pub fn lines<'a, O>(
+    item: impl FnMut(Span<'a>) -> PResult<'a, O>,
+) -> impl FnMut(Span<'a>) -> PResult<'a, Vec<O>> {
+    separated_list1(line_ending, item)
+}
+
+// sections separated by blank lines
+pub fn sections<'a, O>(
+    section: impl FnMut(Span<'a>) -> PResult<'a, O>,
+) -> impl FnMut(Span<'a>) -> PResult<'a, Vec<O>> {
+    separated_list1(pair(line_ending, many1(line_ending)), section)
+}
+
+// integers separated by sep, which can be surrounded by spaces; for
+// whitespace separated lists, use " "
+pub fn integers<'a>(sep: &'a str) -> impl FnMut(Span<'a>) -> PResult<'a, Vec<Spanned<'a, i64>>> {
+    separated_list1(separator(sep), spanned(integer))
+}
+
+// every integer of the line, whatever the text around them; the line
+// cannot be empty
+pub fn line_integers(i: Span) -> PResult<Vec<Spanned<i64>>> {
+    let (rest, mut line) = verify(not_line_ending, |l: &Span| !l.is_empty())(i)?;
+    let mut numbers = vec![];
+    while let Some(c) = line.fragment().chars().next() {
+        match spanned(integer::<_, NomError>)(line) {
+            Ok((r, n)) => {
+                numbers.push(n);
+                line = r;
+            }
+            Err(_) => line = line.slice(c.len_utf8()..),
+        }
+    }
+    Ok((rest, numbers))
+}
+
+// N integers separated by sep, as in 3,4 or 1, -2, 5
+pub fn coordinates<'a, const N: usize>(
+    sep: &'a str,
+) -> impl FnMut(Span<'a>) -> PResult<'a, Spanned<'a, [i64; N]>> {
+    spanned(move |mut i| {
+        let mut coordinates = [0; N];
+        for (idx, c) in coordinates.iter_mut().enumerate() {
+            if idx > 0 {
+                (i, _) = separator(sep)(i)?;
+            }
+            (i, *c) = integer(i)?;
+        }
+        Ok((i, coordinates))
+    })
+}
+
+fn bare_range(i: Span) -> PResult<RangeInclusive<i64>> {
+    map(
+        pair(integer, opt(preceded(tag(".."), integer))),
+        |(start, end)| start..=end.unwrap_or(start),
+    )(i)
+}
+
+// a..b, or a single value a as a..=a
+pub fn range(i: Span) -> PResult<Spanned<RangeInclusive<i64>>> {
+    spanned(bare_range)(i)
+}
+
+// an axis and its range, as in x=3..5
+pub type AxisRange<'a> = Spanned<'a, (char, RangeInclusive<i64>)>;
+
+// x=3..5
+pub fn axis_range(i: Span) -> PResult<AxisRange> {
+    spanned(separated_pair(
+        satisfy(|c| c.is_alphabetic()),
+        char('='),
+        bare_range,
+    ))(i)
+}
+
+// x=495, y=2..7 or x=10..12,y=10..12,z=10..12
+pub fn axis_ranges(i: Span) -> PResult<Vec<AxisRange>> {
+    separated_list1(separator(","), axis_range)(i)
+}
+
+// identifier made of letters, digits and underscores
+pub fn name(i: Span) -> PResult<Span> {
+    take_while1(|c: char| c.is_alphanumeric() || c == '_')(i)
+}
+
+// key: value, where the key is anything up to the colon, without the
+// indentation
+pub fn key_value<'a, O>(
+    value: impl FnMut(Span<'a>) -> PResult<'a, O>,
+) -> impl FnMut(Span<'a>) -> PResult<'a, (Span<'a>, O)> {
+    separated_pair(
+        preceded(space0, take_till1(|c| c == ':' || c == '\n')),
+        terminated(char(':'), space0),
+        value,
+    )
+}
+
+// name=value pairs separated by commas, as in x=787,m=2655
+pub fn assignments<'a, O>(
+    value: impl FnMut(Span<'a>) -> PResult<'a, O>,
+) -> impl FnMut(Span<'a>) -> PResult<'a, Vec<(Span<'a>, O)>> {
+    separated_list1(separator(","), separated_pair(name, char('='), value))
+}
+
+// block of key: value lines
+pub fn key_values<'a, O>(
+    value: impl FnMut(Span<'a>) -> PResult<'a, O>,
+) -> impl FnMut(Span<'a>) -> PResult<'a, Vec<(Span<'a>, O)>> {
+    lines(key_value(value))
+}
+
+// a -> b, c (with the given arrow); the neighbours are separated by
+// commas or spaces, as in jqt: rhn xhk
+pub fn adjacency<'a>(
+    arrow: &'a str,
+) -> impl FnMut(Span<'a>) -> PResult<'a, (Span<'a>, Vec<Span<'a>>)> {
+    separated_pair(
+        name,
+        separator(arrow),
+        separated_list1(alt((separator(","), space1)), name),
+    )
+}
+
+// block of non empty lines, converted to a grid. A cell that cannot be
+// converted, or a row of the wrong width, fails the parse at its position
+// with the message of the grid
+pub fn grid<'a, T: TryFrom<u8>, D>(i: Span<'a>) -> PResult<'a, Grid<T, D>>
+where
+    T::Error: Display,
+{
+    let (rest, block) = recognize(separated_list1(
+        line_ending,
+        take_while1(|c| c != '\n' && c != '\r'),
+    ))(i)?;
+    match Grid::try_from(*block.fragment()) {
+        Ok(grid) => Ok((rest, grid)),
+        Err(e) => Err(nom::Err::Failure(NomError::from_external_error(
+            block.slice(e.offset()..),
+            ErrorKind::Verify,
+            e.message(),
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{error::final_parse, grid::Taxicab};
+
+    fn fragments<'a>(spans: &[Span<'a>]) -> Vec<&'a str> {
+        spans.iter().map(|s| *s.fragment()).collect()
+    }
+
+    #[test]
+    fn test_integers() {
+        let numbers = final_parse("0 3   -6", integers(" ")).unwrap();
+        assert_eq!(vec![0, 3, -6], values(&numbers));
+        assert_eq!(6, numbers[2].span.location_offset());
+        assert_eq!(
+            vec![7, 13, 59],
+            values(&final_parse("7,13, 59", integers(",")).unwrap())
+        );
+        let input = "Sensor at x=2, y=18: closest beacon is at x=-2, y=15";
+        let numbers = final_parse(input, line_integers).unwrap();
+        assert_eq!(vec![2, 18, -2, 15], values(&numbers));
+        let lines = final_parse("x=1, y=2\nx=3, y=4\n", lines(line_integers)).unwrap();
+        assert_eq!(2, lines.len());
+
+        // values are located in the input
+        let err = numbers[1].error(input, "too far");
+        assert_eq!((1, 18), (err.line, err.column));
+        assert_eq!("18", *numbers[1].span.fragment());
+    }
+
+    #[test]
+    fn test_coordinates() {
+        let input = "498,4 -> 498,6 -> 496,6";
+        let path = final_parse(input, |i| {
+            separated_list1(separator("->"), coordinates::<2>(","))(i)
+        })
+        .unwrap();
+        assert_eq!(vec![[498, 4], [498, 6], [496, 6]], values(&path));
+        assert_eq!("498,6", *path[1].span.fragment());
+        let err = final_parse("1,2,x", coordinates::<3>(",")).unwrap_err();
+        assert_eq!((1, 5), (err.line, err.column));
+    }
+
+    #[test]
+    fn test_ranges() {
+        let veins = final_parse("x=495, y=2..7\ny=7, x=495..501\n", lines(axis_ranges)).unwrap();
+        assert_eq!(
+            vec![
+                vec![('x', 495..=495), ('y', 2..=7)],
+                vec![('y', 7..=7), ('x', 495..=501)]
+            ],
+            veins.iter().map(|v| values(v)).collect::<Vec<_>>()
+        );
+        // ranges are located in the input
+        assert_eq!("x=495..501", *veins[1][1].span.fragment());
+        assert_eq!(
+            (2, 6),
+            (
+                veins[1][1].span.location_line(),
+                veins[1][1].span.get_column()
+            )
+        );
+
+        let cuboid = final_parse("x=-20..26,y=-36..17,z=-47..7", axis_ranges).unwrap();
+        assert_eq!(
+            vec![('x', -20..=26), ('y', -36..=17), ('z', -47..=7)],
+            values(&cuboid)
+        );
+        let range = final_parse("10..12", range).unwrap();
+        assert_eq!((10..=12, 0), (range.value, range.span.location_offset()));
+        let err = final_parse("x=1..y", axis_range).unwrap_err();
+        assert_eq!((1, 4), (err.line, err.column));
+    }
+
+    #[test]
+    fn test_key_values() {
+        let monkey = "Monkey 0:
+  Starting items: 79, 98
+  Operation: new = old * 19
+  Test: divisible by 23";
+        let (id, items) = final_parse(
+            monkey,
+            separated_pair(
+                delimited(tag("Monkey "), integer, char(':')),
+                line_ending,
+                key_values(not_line_ending),
+            ),
+        )
+        .unwrap();
+        assert_eq!(0, id);
+        assert_eq!(
+            vec![
+                ("Starting items", "79, 98"),
+                ("Operation", "new = old * 19"),
+                ("Test", "divisible by 23")
+            ],
+            items
+                .iter()
+                .map(|(k, v)| (*k.fragment(), *v.fragment()))
+                .collect::<Vec<_>>()
+        );
+        // keys are located in the input
+        assert_eq!(
+            (3, 3),
+            (items[1].0.location_line(), items[1].0.get_column())
+        );
+    }
+
+    #[test]
+    fn test_assignments() {
+        let part = final_parse("x=787,m=2655, a=1222,s=2876", assignments(i64)).unwrap();
+        assert_eq!(
+            vec![("x", 787), ("m", 2655), ("a", 1222), ("s", 2876)],
+            part.iter()
+                .map(|(k, v)| (*k.fragment(), *v))
+                .collect::<Vec<_>>()
+        );
+        let err = final_parse("x=1,m=", assignments(i64)).unwrap_err();
+        assert_eq!((1, 4), (err.line, err.column));
+    }
+
+    #[test]
+    fn test_sections() {
+        let input = "1000\n2000\n\n4000\n\n\n5000\n6000\n";
+        assert_eq!(
+            Ok(vec![vec![1000, 2000], vec![4000], vec![5000, 6000]]),
+            final_parse(input, sections(lines(i64)))
+        );
+    }
+
+    #[test]
+    fn test_adjacency() {
+        let (from, to) = final_parse("broadcaster -> a, b, c", adjacency("->")).unwrap();
+        assert_eq!("broadcaster", *from.fragment());
+        assert_eq!(vec!["a", "b", "c"], fragments(&to));
+
+        let (from, to) = final_parse("jqt: rhn xhk nvd", adjacency(":")).unwrap();
+        assert_eq!("jqt", *from.fragment());
+        assert_eq!(vec!["rhn", "xhk", "nvd"], fragments(&to));
+        assert_eq!(9, to[1].location_offset());
+    }
+
+    #[derive(Debug, PartialEq, Eq)]
+    enum Tile {
+        Wall,
+        Floor,
+    }
+
+    impl TryFrom<u8> for Tile {
+        type Error = String;
+
+        fn try_from(value: u8) -> Result<Self, Self::Error> {
+            match value {
+                b'#' => Ok(Tile::Wall),
+                b'.' => Ok(Tile::Floor),
+                _ => Err(format!("unknown tile {}", value as char)),
+            }
+        }
+    }
+
+    #[test]
+    fn test_grid() {
+        let mut warehouse = separated_pair(
+            grid::<Tile, Taxicab>,
+            pair(line_ending, line_ending),
+            not_line_ending,
+        );
+        let (grid, moves) = final_parse("#..\n.#.\n\n<^>", &mut warehouse).unwrap();
+        assert_eq!((3, 2), (grid.width(), grid.height()));
+        assert_eq!(Tile::Wall, grid[(1, 1)]);
+        assert_eq!("<^>", *moves.fragment());
+
+        let (grid, moves) = final_parse("#..\r\n.#.\r\n\r\n<^>", &mut warehouse).unwrap();
+        assert_eq!((3, 2), (grid.width(), grid.height()));
+        assert_eq!("<^>", *moves.fragment());
+
+        let err = final_parse("#..\n.@.\n\n<^>", &mut warehouse)
+            .err()
+            .unwrap();
+        assert_eq!((2, 2), (err.line, err.column));
+        assert_eq!("unknown tile @", err.message());
+
+        let err = final_parse("#..\n.#\n\n<^>", warehouse).err().unwrap();
+        assert_eq!(2, err.line);
+        assert!(err.message().contains("row"), "{}", err.message());
+    }
+}
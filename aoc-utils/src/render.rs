@@ -0,0 +1,577 @@
+use std::{fmt::Display, io::Write, path::PathBuf, thread::sleep, time::Duration};
+use thiserror::Error;
+
+use crate::grid::Grid;
+
+// Rendering of grids and simulation states, for debugging. A Frame is a
+// rectangle of cells (a symbol and an optional colour), built from a Grid
+// and then overlaid with the entities that move on it (carts, units,
+// robots). A Visualizer shows frames as an ANSI animation in the terminal,
+// or (with the images feature) writes them as PNG files or as an animated
+// GIF. It is set up from the command line:
+//
+//   --visualize             animation in the terminal
+//   --visualize=png:DIR     one PNG file per frame in DIR
+//   --visualize=gif:FILE    animated GIF
+//   --delay=MS              delay between frames (default 100ms)
+//
+// Without --visualize, showing a frame does nothing. GIF frames are
+// written as they are shown; the first one sets the size of the animation.
+// Puzzles with several parts use one Visualizer per part, see part
+
+#[derive(Error, Debug)]
+pub enum RenderError {
+    #[error("unknown visualization {0}")]
+    UnknownOption(String),
+    #[error("invalid delay {0}")]
+    InvalidDelay(String),
+    #[error("{0} output requires the images feature of aoc-utils")]
+    NoImageSupport(&'static str),
+    #[error("cannot write frame: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("cannot encode frame: {0}")]
+    Encoding(String),
+    #[error("frame of {0}x{1} cells is too large")]
+    TooLarge(usize, usize),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    pub const BLACK: Rgb = Rgb(0, 0, 0);
+    pub const WHITE: Rgb = Rgb(255, 255, 255);
+    pub const GREY: Rgb = Rgb(128, 128, 128);
+    pub const RED: Rgb = Rgb(220, 50, 47);
+    pub const GREEN: Rgb = Rgb(80, 200, 80);
+    pub const BLUE: Rgb = Rgb(38, 139, 210);
+    pub const YELLOW: Rgb = Rgb(240, 200, 0);
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Cell {
+    pub symbol: char,
+    pub color: Option<Rgb>,
+}
+
+impl Cell {
+    pub fn new(symbol: char) -> Self {
+        Self {
+            symbol,
+            color: None,
+        }
+    }
+
+    pub fn colored(symbol: char, color: Rgb) -> Self {
+        Self {
+            symbol,
+            color: Some(color),
+        }
+    }
+
+    // colour of the cell in images: its own, or dark for blank cells and
+    // light for anything else
+    pub fn pixel(&self) -> Rgb {
+        match self.color {
+            Some(color) => color,
+            None if self.symbol.is_whitespace() || self.symbol == '.' => Rgb::BLACK,
+            None => Rgb::WHITE,
+        }
+    }
+}
+
+impl From<char> for Cell {
+    fn from(value: char) -> Self {
+        Cell::new(value)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    width: usize,
+    height: usize,
+    cells: Vec<Cell>,
+}
+
+impl Frame {
+    pub fn new(width: usize, height: usize, background: Cell) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![background; width * height],
+        }
+    }
+
+    pub fn from_grid<T, D>(grid: &Grid<T, D>, cell: impl Fn(&T) -> Cell) -> Self {
+        Self {
+            width: grid.width(),
+            height: grid.height(),
+            cells: grid.iter().map(cell).collect(),
+        }
+    }
+
+    // frame of text, one row per line; short lines are padded with spaces
+    pub fn from_text(text: &str) -> Self {
+        let width = text.lines().map(|l| l.chars().count()).max().unwrap_or(0);
+        let mut frame = Frame::new(width, text.lines().count(), Cell::new(' '));
+        for (y, line) in text.lines().enumerate() {
+            for (x, c) in line.chars().enumerate() {
+                frame.draw((x, y), Cell::new(c));
+            }
+        }
+        frame
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, pos: (usize, usize)) -> Option<&Cell> {
+        if pos.0 < self.width && pos.1 < self.height {
+            self.cells.get(pos.0 + pos.1 * self.width)
+        } else {
+            None
+        }
+    }
+
+    // cells outside the frame are ignored
+    pub fn draw(&mut self, pos: (usize, usize), cell: impl Into<Cell>) {
+        if pos.0 < self.width && pos.1 < self.height {
+            self.cells[pos.0 + pos.1 * self.width] = cell.into();
+        }
+    }
+
+    pub fn overlay<C: Into<Cell>>(
+        &mut self,
+        entities: impl IntoIterator<Item = ((usize, usize), C)>,
+    ) {
+        for (pos, cell) in entities {
+            self.draw(pos, cell);
+        }
+    }
+
+    pub fn with_overlay<C: Into<Cell>>(
+        mut self,
+        entities: impl IntoIterator<Item = ((usize, usize), C)>,
+    ) -> Self {
+        self.overlay(entities);
+        self
+    }
+
+    // the frame with 24 bits colour escape codes
+    pub fn to_ansi(&self) -> String {
+        let mut s = String::new();
+        for row in self.cells.chunks(self.width.max(1)) {
+            let mut current = None;
+            for cell in row {
+                if cell.color != current {
+                    match cell.color {
+                        Some(Rgb(r, g, b)) => s += &format!("\x1b[38;2;{r};{g};{b}m"),
+                        None => s += "\x1b[0m",
+                    }
+                    current = cell.color;
+                }
+                s.push(cell.symbol);
+            }
+            if current.is_some() {
+                s += "\x1b[0m";
+            }
+            s.push('\n');
+        }
+        s
+    }
+
+    // RGB pixels, each cell drawn as a scale x scale square
+    pub fn pixels(&self, scale: usize) -> Vec<u8> {
+        let mut data = Vec::with_capacity(self.cells.len() * scale * scale * 3);
+        for row in self.cells.chunks(self.width.max(1)) {
+            for _ in 0..scale {
+                for cell in row {
+                    let Rgb(r, g, b) = cell.pixel();
+                    for _ in 0..scale {
+                        data.extend([r, g, b]);
+                    }
+                }
+            }
+        }
+        data
+    }
+}
+
+impl Display for Frame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in self.cells.chunks(self.width.max(1)) {
+            for cell in row {
+                write!(f, "{}", cell.symbol)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Output {
+    Off,
+    Terminal,
+    Png(PathBuf),
+    Gif(PathBuf),
+}
+
+// size in pixels of a cell in images
+#[cfg(feature = "images")]
+const SCALE: usize = 4;
+
+#[derive(Debug)]
+pub struct Visualizer {
+    output: Output,
+    delay: Duration,
+    count: usize,
+    // created with the first frame of the GIF
+    #[cfg(feature = "images")]
+    gif: Option<GifWriter>,
+}
+
+impl Visualizer {
+    pub fn off() -> Self {
+        Self::with_output(Output::Off)
+    }
+
+    pub fn terminal(delay: Duration) -> Self {
+        Self {
+            delay,
+            ..Self::with_output(Output::Terminal)
+        }
+    }
+
+    fn with_output(output: Output) -> Self {
+        Self {
+            output,
+            delay: Duration::from_millis(100),
+            count: 0,
+            #[cfg(feature = "images")]
+            gif: None,
+        }
+    }
+
+    // the same visualization for one part of a puzzle: PNG files go to the
+    // name subdirectory, and the GIF to FILE-name.gif
+    pub fn part(&self, name: &str) -> Self {
+        let output = match &self.output {
+            Output::Png(dir) => Output::Png(dir.join(name)),
+            Output::Gif(file) => {
+                let stem = file.file_stem().unwrap_or_default().to_string_lossy();
+                Output::Gif(file.with_file_name(format!("{stem}-{name}.gif")))
+            }
+            output => output.clone(),
+        };
+        Self {
+            delay: self.delay,
+            ..Self::with_output(output)
+        }
+    }
+
+    // looks for --visualize and --delay in args; other arguments are
+    // left to the caller
+    pub fn parse<S: AsRef<str>>(args: impl IntoIterator<Item = S>) -> Result<Self, RenderError> {
+        let mut visualizer = Self::off();
+        for arg in args {
+            let arg = arg.as_ref();
+            if arg == "--visualize" {
+                visualizer.output = Output::Terminal;
+            } else if let Some(target) = arg.strip_prefix("--visualize=") {
+                visualizer.output = match target.split_once(':') {
+                    Some(("png", dir)) if cfg!(feature = "images") => Output::Png(dir.into()),
+                    Some(("gif", file)) if cfg!(feature = "images") => Output::Gif(file.into()),
+                    Some(("png", _)) => return Err(RenderError::NoImageSupport("png")),
+                    Some(("gif", _)) => return Err(RenderError::NoImageSupport("gif")),
+                    _ if target == "terminal" => Output::Terminal,
+                    _ => return Err(RenderError::UnknownOption(target.to_owned())),
+                };
+            } else if let Some(ms) = arg.strip_prefix("--delay=") {
+                visualizer.delay = Duration::from_millis(
+                    ms.parse()
+                        .map_err(|_| RenderError::InvalidDelay(ms.to_owned()))?,
+                );
+            }
+        }
+        Ok(visualizer)
+    }
+
+    // from the command line; a bad option is reported and turns the
+    // visualization off
+    pub fn from_args() -> Self {
+        Self::parse(std::env::args().skip(1)).unwrap_or_else(|e| {
+            eprintln!("{e}");
+            Self::off()
+        })
+    }
+
+    // to avoid building frames for nothing
+    pub fn is_enabled(&self) -> bool {
+        self.output != Output::Off
+    }
+
+    pub fn show(&mut self, frame: &Frame) -> Result<(), RenderError> {
+        match &self.output {
+            Output::Off => {}
+            Output::Terminal => {
+                let mut out = std::io::stdout().lock();
+                write!(out, "\x1b[2J\x1b[H{}", frame.to_ansi())?;
+                out.flush()?;
+                sleep(self.delay);
+            }
+            Output::Png(dir) => {
+                std::fs::create_dir_all(dir)?;
+                write_png(&dir.join(format!("frame-{:05}.png", self.count)), frame)?;
+            }
+            Output::Gif(file) => {
+                let file = file.clone();
+                self.write_gif_frame(&file, frame)?;
+            }
+        }
+        self.count += 1;
+        Ok(())
+    }
+
+    #[cfg(feature = "images")]
+    fn write_gif_frame(
+        &mut self,
+        path: &std::path::Path,
+        frame: &Frame,
+    ) -> Result<(), RenderError> {
+        let gif = match &mut self.gif {
+            Some(gif) => gif,
+            None => self.gif.insert(GifWriter::create(path, frame, self.delay)?),
+        };
+        gif.write(frame)
+    }
+
+    #[cfg(not(feature = "images"))]
+    fn write_gif_frame(&mut self, _: &std::path::Path, _: &Frame) -> Result<(), RenderError> {
+        Err(RenderError::NoImageSupport("gif"))
+    }
+
+    // completes the GIF, if any
+    pub fn finish(self) -> Result<(), RenderError> {
+        #[cfg(feature = "images")]
+        if let Some(gif) = self.gif {
+            gif.finish()?;
+        }
+        Ok(())
+    }
+}
+
+// size in pixels of frames of width x height cells
+#[cfg(feature = "images")]
+fn image_size<T: TryFrom<usize>>(frame: &Frame) -> Result<(T, T), RenderError> {
+    let too_large = || RenderError::TooLarge(frame.width, frame.height);
+    let size = |cells: usize| {
+        cells
+            .checked_mul(SCALE)
+            .and_then(|pixels| T::try_from(pixels).ok())
+            .ok_or_else(too_large)
+    };
+    Ok((size(frame.width)?, size(frame.height)?))
+}
+
+#[cfg(feature = "images")]
+fn write_png(path: &std::path::Path, frame: &Frame) -> Result<(), RenderError> {
+    let (width, height) = image_size(frame)?;
+    let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+    let mut encoder = png::Encoder::new(file, width, height);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(&frame.pixels(SCALE)))
+        .map_err(|e| RenderError::Encoding(e.to_string()))
+}
+
+#[cfg(not(feature = "images"))]
+fn write_png(_: &std::path::Path, _: &Frame) -> Result<(), RenderError> {
+    Err(RenderError::NoImageSupport("png"))
+}
+
+#[cfg(feature = "images")]
+struct GifWriter {
+    encoder: gif::Encoder<std::io::BufWriter<std::fs::File>>,
+    width: u16,
+    height: u16,
+    // in hundredths of a second
+    delay: u16,
+}
+
+#[cfg(feature = "images")]
+impl std::fmt::Debug for GifWriter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "GifWriter({}x{})", self.width, self.height)
+    }
+}
+
+#[cfg(feature = "images")]
+fn encoding(e: impl Display) -> RenderError {
+    RenderError::Encoding(e.to_string())
+}
+
+#[cfg(feature = "images")]
+impl GifWriter {
+    // an animation the size of the first frame
+    fn create(path: &std::path::Path, first: &Frame, delay: Duration) -> Result<Self, RenderError> {
+        let (width, height) = image_size(first)?;
+        let delay = u16::try_from(delay.as_millis() / 10)
+            .map_err(|_| RenderError::InvalidDelay(format!("{}ms", delay.as_millis())))?;
+        let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        let mut encoder = gif::Encoder::new(file, width, height, &[]).map_err(encoding)?;
+        encoder
+            .set_repeat(gif::Repeat::Infinite)
+            .map_err(encoding)?;
+        Ok(Self {
+            encoder,
+            width,
+            height,
+            delay,
+        })
+    }
+
+    fn write(&mut self, frame: &Frame) -> Result<(), RenderError> {
+        let (width, height) = image_size(frame)?;
+        if width > self.width || height > self.height {
+            return Err(RenderError::TooLarge(frame.width, frame.height));
+        }
+        let mut image = gif::Frame::from_rgb_speed(width, height, &frame.pixels(SCALE), 10);
+        image.delay = self.delay;
+        self.encoder.write_frame(&image).map_err(encoding)
+    }
+
+    fn finish(self) -> Result<(), RenderError> {
+        let mut file = self.encoder.into_inner()?;
+        file.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Debug)]
+    enum Tile {
+        Wall,
+        Floor,
+    }
+
+    impl From<u8> for Tile {
+        fn from(value: u8) -> Self {
+            match value {
+                b'#' => Tile::Wall,
+                _ => Tile::Floor,
+            }
+        }
+    }
+
+    fn frame() -> Frame {
+        let grid: Grid<Tile, ()> = Grid::try_from("####\n#..#\n####").unwrap();
+        Frame::from_grid(&grid, |t| match t {
+            Tile::Wall => Cell::colored('#', Rgb::GREY),
+            Tile::Floor => Cell::new('.'),
+        })
+    }
+
+    #[test]
+    fn test_overlay() {
+        let frame = frame().with_overlay([((1, 1), '@'), ((7, 1), 'E')]);
+        assert_eq!("####\n#@.#\n####\n", frame.to_string());
+        assert_eq!(Some(&Cell::new('@')), frame.get((1, 1)));
+        assert_eq!(None, frame.get((4, 0)));
+
+        let frame = Frame::from_text("ab\nc");
+        assert_eq!((2, 2), (frame.width(), frame.height()));
+        assert_eq!("ab\nc \n", frame.to_string());
+    }
+
+    #[test]
+    fn test_ansi() {
+        let ansi = frame()
+            .with_overlay([((2, 1), Cell::colored('E', Rgb::RED))])
+            .to_ansi();
+        let lines: Vec<_> = ansi.lines().collect();
+        assert_eq!("\x1b[38;2;128;128;128m####\x1b[0m", lines[0]);
+        assert_eq!(
+            "\x1b[38;2;128;128;128m#\x1b[0m.\x1b[38;2;220;50;47mE\x1b[38;2;128;128;128m#\x1b[0m",
+            lines[1]
+        );
+    }
+
+    #[test]
+    fn test_pixels() {
+        let pixels = Frame::from_text("#.").pixels(2);
+        assert_eq!(2 * 4 * 3, pixels.len());
+        assert_eq!(
+            &[255, 255, 255, 255, 255, 255, 0, 0, 0, 0, 0, 0],
+            &pixels[..12]
+        );
+    }
+
+    #[test]
+    fn test_parse() {
+        let visualizer = Visualizer::parse(["input.txt", "--visualize", "--delay=20"]).unwrap();
+        assert_eq!(Output::Terminal, visualizer.output);
+        assert_eq!(Duration::from_millis(20), visualizer.delay);
+
+        assert!(!Visualizer::parse(["input.txt"]).unwrap().is_enabled());
+        assert!(matches!(
+            Visualizer::parse(["--visualize=mp4:out"]),
+            Err(RenderError::UnknownOption(_))
+        ));
+        assert!(matches!(
+            Visualizer::parse(["--delay=soon"]),
+            Err(RenderError::InvalidDelay(_))
+        ));
+    }
+
+    #[cfg(feature = "images")]
+    #[test]
+    fn test_images() {
+        let dir = std::env::temp_dir().join(format!("aoc-render-{}", std::process::id()));
+        let arg = format!("--visualize=png:{}", dir.display());
+        let mut visualizer = Visualizer::parse([arg]).unwrap();
+        visualizer.show(&frame()).unwrap();
+        visualizer.finish().unwrap();
+        let png = std::fs::read(dir.join("frame-00000.png")).unwrap();
+        assert_eq!(b"\x89PNG", &png[..4]);
+
+        let file = dir.join("frames.gif");
+        let arg = format!("--visualize=gif:{}", file.display());
+        let mut visualizer = Visualizer::parse([&arg]).unwrap();
+        visualizer.show(&frame()).unwrap();
+        visualizer
+            .show(&frame().with_overlay([((1, 1), '@')]))
+            .unwrap();
+        // larger than the first frame
+        assert!(matches!(
+            visualizer.show(&Frame::from_text("#####")),
+            Err(RenderError::TooLarge(5, 1))
+        ));
+        visualizer.finish().unwrap();
+        let gif = std::fs::read(&file).unwrap();
+        assert_eq!(b"GIF89a", &gif[..6]);
+
+        let mut part = Visualizer::parse([&arg]).unwrap().part("part2");
+        part.show(&Frame::from_text("#####")).unwrap();
+        part.finish().unwrap();
+        assert!(dir.join("frames-part2.gif").exists());
+        assert!(matches!(
+            Visualizer::parse([&arg])
+                .unwrap()
+                .show(&Frame::from_text(&"#".repeat(20_000))),
+            Err(RenderError::TooLarge(20_000, 1))
+        ));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,153 @@
+use crate::linalg::Rational;
+
+// Extrapolation of integer sequences: polynomial ones through their
+// difference tables (2023 day 9) or through sampled points (the quadratic
+// growth of 2023 day 21), and simulation outputs whose differences end
+// up periodic (the tower height of 2022 day 17)
+
+// the rows of differences, down to the first row of zeros (not included)
+pub fn difference_table(sequence: &[i128]) -> Vec<Vec<i128>> {
+    let mut table = vec![];
+    let mut row = sequence.to_vec();
+    while row.iter().any(|&n| n != 0) {
+        let next = row.windows(2).map(|w| w[1] - w[0]).collect();
+        table.push(row);
+        row = next;
+    }
+    table
+}
+
+// Value at index n (any n, negative too) of the polynomial that goes
+// through the sequence: Newton's forward formula, the sum of
+// C(n, k) times the first value of each row of differences
+pub fn extrapolate(sequence: &[i128], n: i128) -> i128 {
+    let mut binomial = 1;
+    let mut value = 0;
+    for (k, row) in difference_table(sequence).iter().enumerate() {
+        value += binomial * row[0];
+        // C(n, k + 1) = C(n, k) * (n - k) / (k + 1), always exact
+        binomial = binomial * (n - k as i128) / (k as i128 + 1);
+    }
+    value
+}
+
+pub fn next_value(sequence: &[i128]) -> i128 {
+    extrapolate(sequence, sequence.len() as i128)
+}
+
+pub fn previous_value(sequence: &[i128]) -> i128 {
+    extrapolate(sequence, -1)
+}
+
+// Value at x of the polynomial of lowest degree through the points
+// (which must have distinct x)
+pub fn lagrange(points: &[(i128, i128)], x: i128) -> Rational {
+    points
+        .iter()
+        .enumerate()
+        .map(|(i, &(xi, yi))| {
+            points
+                .iter()
+                .enumerate()
+                .filter(|&(j, _)| j != i)
+                .fold(Rational::from(yi), |acc, (_, &(xj, _))| {
+                    acc * Rational::new(x - xj, xi - xj)
+                })
+        })
+        .fold(Rational::ZERO, |acc, term| acc + term)
+}
+
+// A sequence whose differences are periodic from start on: the values
+// from start + period on are the earlier ones plus a fixed increment
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeriodicDifferences {
+    pub start: usize,
+    pub period: usize,
+    pub increment: i128,
+    values: Vec<i128>,
+}
+
+impl PeriodicDifferences {
+    // Finds the shortest period of the differences of the tail of values.
+    // To avoid patterns repeating by chance, the periodic part must cover
+    // at least half of the values, and two full periods
+    pub fn find(values: &[i128]) -> Option<Self> {
+        let differences: Vec<i128> = values.windows(2).map(|w| w[1] - w[0]).collect();
+        let len = differences.len();
+        (1..=len / 2).find_map(|period| {
+            // the earliest start from which the differences repeat
+            let start = (0..len - period)
+                .rev()
+                .find(|&i| differences[i] != differences[i + period])
+                .map_or(0, |i| i + 1);
+            (len - start >= (2 * period).max(len.div_ceil(2))).then(|| Self {
+                start,
+                period,
+                increment: values[start + period] - values[start],
+                values: values[..=start + period].to_vec(),
+            })
+        })
+    }
+
+    pub fn value_at(&self, n: usize) -> i128 {
+        if n < self.values.len() {
+            return self.values[n];
+        }
+        let cycles = (n - self.start) / self.period;
+        let offset = (n - self.start) % self.period;
+        self.values[self.start + offset] + cycles as i128 * self.increment
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_extrapolate() {
+        // 2023 day 9
+        let sequences = [
+            vec![0, 3, 6, 9, 12, 15],
+            vec![1, 3, 6, 10, 15, 21],
+            vec![10, 13, 16, 21, 30, 45],
+        ];
+        assert_eq!(114, sequences.iter().map(|s| next_value(s)).sum::<i128>());
+        assert_eq!(2, sequences.iter().map(|s| previous_value(s)).sum::<i128>());
+        assert_eq!(3, difference_table(&sequences[1]).len());
+        // squares, far away
+        assert_eq!(1 << 80, extrapolate(&[0, 1, 4, 9], 1 << 40));
+        assert_eq!(0, next_value(&[0, 0, 0]));
+    }
+
+    #[test]
+    fn test_lagrange() {
+        // f(n) = 3n^2 - 2n + 7 sampled at 65, 196 and 327
+        let f = |n: i128| 3 * n * n - 2 * n + 7;
+        let points = [65, 196, 327].map(|x| (x, f(x)));
+        assert_eq!(Rational::from(f(26501365)), lagrange(&points, 26501365));
+        assert_eq!(Rational::from(15i64), lagrange(&[(0, 0), (2, 10)], 3));
+        assert_eq!(Rational::new(1, 2), lagrange(&[(0, 0), (2, 1)], 1));
+    }
+
+    #[test]
+    fn test_periodic() {
+        // a transient of 3, then +1, +3, +2 forever
+        let mut values = vec![0, 5, 6, 10];
+        for i in 0..30 {
+            let last = *values.last().unwrap();
+            values.push(last + [1, 3, 2][i % 3]);
+        }
+        let periodic = PeriodicDifferences::find(&values).unwrap();
+        assert_eq!(
+            (3, 3, 6),
+            (periodic.start, periodic.period, periodic.increment)
+        );
+        for (n, &v) in values.iter().enumerate() {
+            assert_eq!(v, periodic.value_at(n));
+        }
+        assert_eq!(10 + 6 * 1_000_000, periodic.value_at(3 + 3 * 1_000_000));
+
+        // too short to be trusted
+        assert_eq!(None, PeriodicDifferences::find(&[0, 1, 3, 4, 7]));
+    }
+}
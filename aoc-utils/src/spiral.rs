@@ -0,0 +1,173 @@
+use crate::grid::SparseGrid;
+
+// The Ulam spiral of 2017 day 3: 1 at (0, 0), 2 to its right, then
+// counterclockwise outward, with y growing downward (3 is at (1, -1)).
+// Ring k holds the (2k - 1)^2 + 1 ..= (2k + 1)^2 numbers, on the square
+// of the positions at most k away in either direction
+
+// the ring of n, which must be at least 1
+pub fn ring_of(n: u64) -> u64 {
+    assert!(n > 0, "the spiral starts at 1");
+    (n - 1).isqrt().div_ceil(2)
+}
+
+// the numbers on ring k
+pub fn ring_range(k: u64) -> std::ops::RangeInclusive<u64> {
+    if k == 0 {
+        1..=1
+    } else {
+        (2 * k - 1).pow(2) + 1..=(2 * k + 1).pow(2)
+    }
+}
+
+pub fn position(n: u64) -> (i64, i64) {
+    let k = ring_of(n);
+    if k == 0 {
+        return (0, 0);
+    }
+    let offset = n - (2 * k - 1).pow(2) - 1;
+    let (side, j) = (offset / (2 * k), (offset % (2 * k) + 1) as i64);
+    let k = k as i64;
+    match side {
+        0 => (k, k - j),
+        1 => (k - j, -k),
+        2 => (-k, -k + j),
+        _ => (-k + j, k),
+    }
+}
+
+// the number at pos
+pub fn index(pos: (i64, i64)) -> u64 {
+    let (x, y) = pos;
+    let k = x.abs().max(y.abs());
+    if k == 0 {
+        return 1;
+    }
+    let (side, j) = if x == k && y < k {
+        (0, k - y)
+    } else if y == -k && x < k {
+        (1, k - x)
+    } else if x == -k && y > -k {
+        (2, y + k)
+    } else {
+        (3, x + k)
+    };
+    let k = k as u64;
+    (2 * k - 1).pow(2) + side * 2 * k + j as u64
+}
+
+// the positions of ring k, in spiral order
+pub fn ring(k: u64) -> impl Iterator<Item = (i64, i64)> {
+    ring_range(k).map(position)
+}
+
+// all the positions, in spiral order
+pub fn positions() -> impl Iterator<Item = (i64, i64)> {
+    (1..).map(position)
+}
+
+// Walks the spiral outward from first at (0, 0), each next value computed
+// from the values already set around it. Yields the positions and their
+// values, first included
+pub struct Accumulate<T, F> {
+    grid: SparseGrid<T>,
+    first: Option<T>,
+    n: u64,
+    value: F,
+}
+
+pub fn accumulate<T, F>(first: T, value: F) -> Accumulate<T, F>
+where
+    T: Clone,
+    F: FnMut(&mut dyn Iterator<Item = &T>) -> T,
+{
+    Accumulate {
+        grid: SparseGrid::new(),
+        first: Some(first),
+        n: 1,
+        value,
+    }
+}
+
+impl<T, F> Accumulate<T, F> {
+    // the values so far
+    pub fn grid(&self) -> &SparseGrid<T> {
+        &self.grid
+    }
+}
+
+impl<T, F> Iterator for Accumulate<T, F>
+where
+    T: Clone,
+    F: FnMut(&mut dyn Iterator<Item = &T>) -> T,
+{
+    type Item = ((i64, i64), T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let pos = position(self.n);
+        let value = match self.first.take() {
+            Some(first) => first,
+            None => (self.value)(&mut self.grid.neighbours(pos).map(|(_, t)| t)),
+        };
+        self.grid.insert(pos, value.clone());
+        self.n += 1;
+        Some((pos, value))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_positions() {
+        let expected = [
+            (0, 0),
+            (1, 0),
+            (1, -1),
+            (0, -1),
+            (-1, -1),
+            (-1, 0),
+            (-1, 1),
+            (0, 1),
+            (1, 1),
+            (2, 1),
+        ];
+        assert_eq!(expected.to_vec(), positions().take(10).collect::<Vec<_>>());
+        assert_eq!((2, -1), position(12));
+        assert_eq!((0, 2), position(23));
+        for n in 1..2000 {
+            assert_eq!(n, index(position(n)));
+        }
+        let (x, y) = position(1024);
+        assert_eq!(31, x.abs() + y.abs());
+    }
+
+    #[test]
+    fn test_rings() {
+        assert_eq!(vec![(0, 0)], ring(0).collect::<Vec<_>>());
+        assert_eq!(10..=25, ring_range(2));
+        assert_eq!([0, 1, 1, 2], [1, 2, 9, 10].map(ring_of));
+        for k in 1..10 {
+            let ring: Vec<_> = ring(k).collect();
+            assert_eq!(8 * k as usize, ring.len());
+            assert!(ring
+                .iter()
+                .all(|&(x, y)| x.unsigned_abs().max(y.unsigned_abs()) == k));
+        }
+    }
+
+    #[test]
+    fn test_accumulate() {
+        // 2017 day 3
+        let values: Vec<u64> = accumulate(1, |around| around.sum())
+            .map(|(_, v)| v)
+            .take(12)
+            .collect();
+        assert_eq!(vec![1, 1, 2, 4, 5, 10, 11, 23, 25, 26, 54, 57], values);
+
+        let mut walk = accumulate(0, |around| around.count());
+        assert_eq!(Some(((1, -1), 2)), walk.nth(2));
+        assert_eq!(3, walk.grid().len());
+    }
+}
@@ -0,0 +1,408 @@
+use std::{collections::BTreeSet, fmt::Display, rc::Rc};
+
+// Expressions for symbolic execution of the puzzle VMs (2021 day 24 ALU,
+// 2024 day 17 chronospatial computer). Each node carries the range of
+// values it can take, which the constructors use to simplify comparisons,
+// divisions and modulos away.
+
+pub type Expr = Rc<Node>;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Op {
+    Const(i64),
+    Var(String),
+    Input(usize),
+    Add(Expr, Expr),
+    Mul(Expr, Expr),
+    Div(Expr, Expr),
+    Mod(Expr, Expr),
+    Eql(Expr, Expr),
+    Xor(Expr, Expr),
+    Shr(Expr, Expr),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Node {
+    pub op: Op,
+    pub min: i64,
+    pub max: i64,
+}
+
+fn node(op: Op, min: i64, max: i64) -> Expr {
+    Rc::new(Node { op, min, max })
+}
+
+fn bits(n: i64) -> u32 {
+    64 - n.leading_zeros()
+}
+
+// arithmetic shift; amounts outside 0..64 shift every bit out, as dividing
+// by a large enough power of two would
+fn shift_right(x: i64, y: i64) -> i64 {
+    u32::try_from(y)
+        .ok()
+        .and_then(|s| x.checked_shr(s))
+        .unwrap_or(if x < 0 { -1 } else { 0 })
+}
+
+pub fn constant(n: i64) -> Expr {
+    node(Op::Const(n), n, n)
+}
+
+pub fn var(name: &str, min: i64, max: i64) -> Expr {
+    node(Op::Var(name.to_owned()), min, max)
+}
+
+pub fn input(idx: usize, min: i64, max: i64) -> Expr {
+    node(Op::Input(idx), min, max)
+}
+
+impl Node {
+    pub fn as_const(&self) -> Option<i64> {
+        match self.op {
+            Op::Const(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    fn non_negative(&self) -> bool {
+        self.min >= 0
+    }
+
+    // the names of the variables this expression depends on
+    pub fn vars(&self) -> BTreeSet<String> {
+        let mut vars = BTreeSet::new();
+        self.collect_vars(&mut vars);
+        vars
+    }
+
+    fn collect_vars(&self, vars: &mut BTreeSet<String>) {
+        match &self.op {
+            Op::Const(_) | Op::Input(_) => {}
+            Op::Var(name) => {
+                vars.insert(name.clone());
+            }
+            Op::Add(a, b)
+            | Op::Mul(a, b)
+            | Op::Div(a, b)
+            | Op::Mod(a, b)
+            | Op::Eql(a, b)
+            | Op::Xor(a, b)
+            | Op::Shr(a, b) => {
+                a.collect_vars(vars);
+                b.collect_vars(vars);
+            }
+        }
+    }
+
+    // rebuilds the expression with some variables replaced, simplifying
+    // again along the way; None if a divisor becomes a constant zero
+    pub fn substitute(&self, vars: &dyn Fn(&str) -> Option<Expr>) -> Option<Expr> {
+        let s = |e: &Expr| e.substitute(vars);
+        Some(match &self.op {
+            Op::Const(n) => constant(*n),
+            Op::Var(name) => vars(name).unwrap_or_else(|| var(name, self.min, self.max)),
+            Op::Input(idx) => input(*idx, self.min, self.max),
+            Op::Add(a, b) => add(s(a)?, s(b)?),
+            Op::Mul(a, b) => mul(s(a)?, s(b)?),
+            Op::Div(a, b) => div(s(a)?, s(b)?)?,
+            Op::Mod(a, b) => rem(s(a)?, s(b)?)?,
+            Op::Eql(a, b) => eql(s(a)?, s(b)?),
+            Op::Xor(a, b) => xor(s(a)?, s(b)?),
+            Op::Shr(a, b) => shr(s(a)?, s(b)?),
+        })
+    }
+
+    // concrete value, given the values of the variables and inputs
+    pub fn eval(&self, vars: &dyn Fn(&str) -> i64, inputs: &dyn Fn(usize) -> i64) -> i64 {
+        let e = |e: &Expr| e.eval(vars, inputs);
+        match &self.op {
+            Op::Const(n) => *n,
+            Op::Var(name) => vars(name),
+            Op::Input(idx) => inputs(*idx),
+            Op::Add(a, b) => e(a).wrapping_add(e(b)),
+            Op::Mul(a, b) => e(a).wrapping_mul(e(b)),
+            Op::Div(a, b) => e(a).wrapping_div(e(b)),
+            Op::Mod(a, b) => e(a).wrapping_rem(e(b)),
+            Op::Eql(a, b) => (e(a) == e(b)).into(),
+            Op::Xor(a, b) => e(a) ^ e(b),
+            Op::Shr(a, b) => shift_right(e(a), e(b)),
+        }
+    }
+}
+
+pub fn add(a: Expr, b: Expr) -> Expr {
+    match (a.as_const(), b.as_const()) {
+        (Some(x), Some(y)) => constant(x.wrapping_add(y)),
+        (Some(0), _) => b,
+        (_, Some(0)) => a,
+        _ => {
+            let (min, max) = (a.min.saturating_add(b.min), a.max.saturating_add(b.max));
+            node(Op::Add(a, b), min, max)
+        }
+    }
+}
+
+pub fn mul(a: Expr, b: Expr) -> Expr {
+    match (a.as_const(), b.as_const()) {
+        (Some(x), Some(y)) => constant(x.wrapping_mul(y)),
+        (Some(0), _) | (_, Some(0)) => constant(0),
+        (Some(1), _) => b,
+        (_, Some(1)) => a,
+        _ => {
+            let products = [
+                a.min.saturating_mul(b.min),
+                a.min.saturating_mul(b.max),
+                a.max.saturating_mul(b.min),
+                a.max.saturating_mul(b.max),
+            ];
+            let (min, max) = (
+                *products.iter().min().unwrap(),
+                *products.iter().max().unwrap(),
+            );
+            node(Op::Mul(a, b), min, max)
+        }
+    }
+}
+
+// if a is k * q + r with r in 0..k, returns (q, r)
+fn split_multiple(a: &Expr, k: i64) -> Option<(Expr, Expr)> {
+    let Op::Add(x, r) = &a.op else { return None };
+    let Op::Mul(q, m) = &x.op else { return None };
+    (m.as_const() == Some(k) && q.non_negative() && r.non_negative() && r.max < k)
+        .then(|| (q.clone(), r.clone()))
+}
+
+// truncating division, as in the ALU; None for a constant zero divisor,
+// as no evaluation could succeed
+pub fn div(a: Expr, b: Expr) -> Option<Expr> {
+    if b.as_const() == Some(0) {
+        return None;
+    }
+    Some(match (a.as_const(), b.as_const()) {
+        (Some(x), Some(y)) => constant(x.wrapping_div(y)),
+        (Some(0), _) => a,
+        (_, Some(1)) => a,
+        (_, Some(k)) if k > 0 => {
+            if a.non_negative() && a.max < k {
+                constant(0)
+            } else if let Some((q, _)) = split_multiple(&a, k) {
+                q
+            } else {
+                let (min, max) = (a.min / k, a.max / k);
+                node(Op::Div(a, b), min, max)
+            }
+        }
+        _ => {
+            let bound = a.min.saturating_abs().max(a.max.saturating_abs());
+            node(Op::Div(a, b), -bound, bound)
+        }
+    })
+}
+
+// truncating remainder, as in the ALU; None for a constant zero divisor
+pub fn rem(a: Expr, b: Expr) -> Option<Expr> {
+    if b.as_const() == Some(0) {
+        return None;
+    }
+    Some(match (a.as_const(), b.as_const()) {
+        (Some(x), Some(y)) => constant(x.wrapping_rem(y)),
+        (Some(0), _) => a,
+        (_, Some(k)) if k > 0 => {
+            if a.non_negative() && a.max < k {
+                a
+            } else if let Some((_, r)) = split_multiple(&a, k) {
+                r
+            } else if a.non_negative() {
+                node(Op::Mod(a, b), 0, k - 1)
+            } else {
+                node(Op::Mod(a, b), 1 - k, k - 1)
+            }
+        }
+        _ => {
+            let bound = b.min.saturating_abs().max(b.max.saturating_abs());
+            node(Op::Mod(a, b), -bound, bound)
+        }
+    })
+}
+
+pub fn eql(a: Expr, b: Expr) -> Expr {
+    if a == b {
+        constant(1)
+    } else if a.max < b.min || b.max < a.min {
+        constant(0)
+    } else {
+        match (a.as_const(), b.as_const()) {
+            (Some(x), Some(y)) => constant((x == y).into()),
+            _ => node(Op::Eql(a, b), 0, 1),
+        }
+    }
+}
+
+pub fn xor(a: Expr, b: Expr) -> Expr {
+    match (a.as_const(), b.as_const()) {
+        (Some(x), Some(y)) => constant(x ^ y),
+        (Some(0), _) => b,
+        (_, Some(0)) => a,
+        (Some(_), None) => xor(b, a),
+        (None, c2) => {
+            // (x ^ c1) ^ c2 == x ^ (c1 ^ c2)
+            if let (Op::Xor(x, c1), Some(c2)) = (&a.op, c2) {
+                if let Some(c1) = c1.as_const() {
+                    return xor(x.clone(), constant(c1 ^ c2));
+                }
+            }
+            if a.non_negative() && b.non_negative() {
+                let max = match bits(a.max.max(b.max)) {
+                    63.. => i64::MAX,
+                    n => (1 << n) - 1,
+                };
+                node(Op::Xor(a, b), 0, max)
+            } else {
+                node(Op::Xor(a, b), i64::MIN, i64::MAX)
+            }
+        }
+    }
+}
+
+pub fn shr(a: Expr, b: Expr) -> Expr {
+    match (a.as_const(), b.as_const()) {
+        (Some(x), Some(y)) => constant(shift_right(x, y)),
+        (Some(0), _) => a,
+        (_, Some(0)) => a,
+        _ if a.non_negative() && b.non_negative() => {
+            if b.min >= bits(a.max) as i64 {
+                constant(0)
+            } else {
+                let (min, max) = (a.min >> b.max.min(63), a.max >> b.min);
+                node(Op::Shr(a, b), min, max)
+            }
+        }
+        _ => {
+            let (min, max) = (a.min.min(0), a.max.max(0));
+            node(Op::Shr(a, b), min, max)
+        }
+    }
+}
+
+impl Display for Node {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // sub-expressions are parenthesized unless they are atoms
+        let p = |e: &Expr| match e.op {
+            Op::Const(n) if n >= 0 => e.to_string(),
+            Op::Var(_) | Op::Input(_) => e.to_string(),
+            _ => format!("({e})"),
+        };
+        match &self.op {
+            Op::Const(n) => write!(f, "{n}"),
+            Op::Var(name) => write!(f, "{name}"),
+            Op::Input(idx) => write!(f, "d{idx}"),
+            Op::Add(a, b) => match b.as_const() {
+                Some(n) if n < 0 => write!(f, "{} - {}", p(a), -n),
+                _ => write!(f, "{} + {}", p(a), p(b)),
+            },
+            Op::Mul(a, b) => write!(f, "{} * {}", p(a), p(b)),
+            Op::Div(a, b) => write!(f, "{} / {}", p(a), p(b)),
+            Op::Mod(a, b) => write!(f, "{} % {}", p(a), p(b)),
+            Op::Eql(a, b) => match (&a.op, b.as_const()) {
+                (Op::Eql(x, y), Some(0)) => write!(f, "{} != {}", p(x), p(y)),
+                _ => write!(f, "{} == {}", p(a), p(b)),
+            },
+            Op::Xor(a, b) => write!(f, "{} ^ {}", p(a), p(b)),
+            Op::Shr(a, b) => write!(f, "{} >> {}", p(a), p(b)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_folding() {
+        let x = var("x", 0, 100);
+        assert_eq!(constant(7), add(constant(3), constant(4)));
+        assert_eq!(x, add(constant(0), x.clone()));
+        assert_eq!(constant(0), mul(x.clone(), constant(0)));
+        assert_eq!(x, div(x.clone(), constant(1)).unwrap());
+        assert_eq!(constant(0), div(x.clone(), constant(101)).unwrap());
+        assert_eq!(x, rem(x.clone(), constant(101)).unwrap());
+
+        // folding never overflows, and agrees with the evaluator
+        assert_eq!(constant(i64::MIN), add(constant(i64::MAX), constant(1)));
+        assert_eq!(constant(-2), mul(constant(i64::MAX), constant(2)));
+        assert_eq!(constant(0), shr(constant(5), constant(64)));
+        assert_eq!(constant(-1), shr(constant(-5), constant(100)));
+        assert_eq!(constant(0), shr(constant(5), constant(-1)));
+        let e = shr(var("a", 0, i64::MAX), var("b", 0, 100));
+        assert_eq!(0, e.eval(&|v| if v == "a" { 5 } else { 64 }, &|_| 0));
+    }
+
+    #[test]
+    fn test_div_zero() {
+        let x = var("x", 0, 100);
+        assert_eq!(None, div(x.clone(), constant(0)));
+        assert_eq!(None, rem(x.clone(), constant(0)));
+        // a divisor that only becomes zero once substituted
+        let e = add(div(constant(5), var("y", 0, 3)).unwrap(), x);
+        assert_eq!(None, e.substitute(&|v| (v == "y").then(|| constant(0))));
+    }
+
+    #[test]
+    fn test_ranges() {
+        let z = var("z", i64::MIN, i64::MAX);
+        let d = input(0, 1, 9);
+        let x = add(rem(z.clone(), constant(26)).unwrap(), constant(12));
+        assert_eq!((-13, 37), (x.min, x.max));
+        // 12..=37 can never be a digit once z is known to be positive
+        let z = var("z", 0, i64::MAX);
+        let x = add(rem(z.clone(), constant(26)).unwrap(), constant(12));
+        assert_eq!(constant(0), eql(x, d.clone()));
+
+        // push and pop on a base 26 stack
+        let pushed = add(mul(z.clone(), constant(26)), add(d.clone(), constant(4)));
+        assert_eq!(z, div(pushed.clone(), constant(26)).unwrap());
+        assert_eq!(add(d, constant(4)), rem(pushed, constant(26)).unwrap());
+    }
+
+    #[test]
+    fn test_display() {
+        let z = var("z", 0, i64::MAX);
+        let d = input(3, 1, 9);
+        let x = eql(
+            eql(
+                add(rem(z.clone(), constant(26)).unwrap(), constant(-7)),
+                d.clone(),
+            ),
+            constant(0),
+        );
+        assert_eq!("((z % 26) - 7) != d3", x.to_string());
+        let a = var("a", 0, i64::MAX);
+        let b = xor(rem(a.clone(), constant(8)).unwrap(), constant(1));
+        assert_eq!(
+            "(a >> ((a % 8) ^ 1)) % 8",
+            rem(shr(a, b), constant(8)).unwrap().to_string()
+        );
+    }
+
+    #[test]
+    fn test_substitute() {
+        let z = var("z", 0, i64::MAX);
+        let d = input(0, 1, 9);
+        let x = eql(add(rem(z, constant(26)).unwrap(), constant(-7)), d);
+        assert_eq!(
+            constant(0),
+            x.substitute(&|_| Some(constant(26 * 5 + 2))).unwrap()
+        );
+        let x = x.substitute(&|_| Some(constant(26 * 5 + 10))).unwrap();
+        assert_eq!("3 == d0", x.to_string());
+    }
+
+    #[test]
+    fn test_eval() {
+        let a = var("a", 0, i64::MAX);
+        let e = rem(shr(a.clone(), rem(a, constant(8)).unwrap()), constant(8)).unwrap();
+        assert_eq!(2024 % 8, e.eval(&|_| 2024, &|_| 0));
+        assert_eq!((2029 >> 5) % 8, e.eval(&|_| 2029, &|_| 0));
+    }
+}
@@ -0,0 +1,412 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+    ops::{Add, Sub},
+};
+
+// Searches over the visit orders of a few points of interest, once the maze
+// around them is compressed into a distance matrix (Grid::poi_distances,
+// Graph::distances): Held-Karp for the shortest or longest route through
+// all of them (2015 day 9 and 13, 2016 day 24), a Dijkstra over the visited
+// sets when several agents share the work (the keys and doors of 2019
+// day 18), and the best rewards within a time budget (the valves of 2022
+// day 16). Visited sets are bitmasks of the nodes, so at most 63 nodes, and
+// the Held-Karp table grows as 2^n * n, so rather 20
+
+pub trait Weight: Copy + Ord + Default + Add<Output = Self> + Sub<Output = Self> {}
+
+impl<W: Copy + Ord + Default + Add<Output = W> + Sub<Output = W>> Weight for W {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Route<W> {
+    pub length: W,
+    // the nodes in visit order; closed tours do not repeat the first one
+    pub order: Vec<usize>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Tour<W = usize> {
+    // None when there is no way from one node to the other
+    distances: Vec<Vec<Option<W>>>,
+    start: Option<usize>,
+    closed: bool,
+    // for each node, the nodes to visit before it
+    prerequisites: Vec<u64>,
+}
+
+impl<W: Weight> Tour<W> {
+    pub fn new(distances: Vec<Vec<Option<W>>>) -> Self {
+        let n = distances.len();
+        assert!(n < 64, "too many nodes for a bitmask");
+        assert!(
+            distances.iter().all(|row| row.len() == n),
+            "not a square matrix"
+        );
+        Self {
+            distances,
+            start: None,
+            closed: false,
+            prerequisites: vec![0; n],
+        }
+    }
+
+    // for complete graphs
+    pub fn from_matrix(distances: Vec<Vec<W>>) -> Self {
+        Self::new(
+            distances
+                .into_iter()
+                .map(|row| row.into_iter().map(Some).collect())
+                .collect(),
+        )
+    }
+
+    pub fn start_at(mut self, node: usize) -> Self {
+        self.start = Some(node);
+        self
+    }
+
+    // back to the first node at the end
+    pub fn closed(mut self) -> Self {
+        self.closed = true;
+        self
+    }
+
+    // node can only be visited once before has been
+    pub fn requires(mut self, node: usize, before: usize) -> Self {
+        self.prerequisites[node] |= 1 << before;
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.distances.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.distances.is_empty()
+    }
+
+    pub fn distance(&self, from: usize, to: usize) -> Option<W> {
+        self.distances[from][to]
+    }
+
+    fn available(&self, visited: u64, node: usize) -> bool {
+        visited & (1 << node) == 0 && self.prerequisites[node] & !visited == 0
+    }
+
+    pub fn shortest(&self) -> Option<Route<W>> {
+        self.held_karp(|a, b| a < b)
+    }
+
+    pub fn longest(&self) -> Option<Route<W>> {
+        self.held_karp(|a, b| a > b)
+    }
+
+    // table[visited][last]: the best length of the routes through visited
+    // that end at last, and the node before last
+    fn held_karp(&self, better: impl Fn(W, W) -> bool) -> Option<Route<W>> {
+        let n = self.len();
+        if n == 0 {
+            return None;
+        }
+        let full = (1usize << n) - 1;
+        let firsts: Vec<usize> = match self.start {
+            Some(start) => vec![start],
+            // any rotation of a closed tour will do
+            None if self.closed && self.prerequisites.iter().all(|&p| p == 0) => vec![0],
+            None => (0..n).collect(),
+        };
+
+        let mut best: Option<Route<W>> = None;
+        for first in firsts {
+            if self.prerequisites[first] != 0 {
+                continue;
+            }
+            let mut table: Vec<Option<(W, usize)>> = vec![None; (full + 1) * n];
+            table[(1 << first) * n + first] = Some((W::default(), first));
+            for visited in 0..full {
+                for last in 0..n {
+                    let Some((length, _)) = table[visited * n + last] else {
+                        continue;
+                    };
+                    for next in (0..n).filter(|&next| self.available(visited as u64, next)) {
+                        let Some(d) = self.distance(last, next) else {
+                            continue;
+                        };
+                        let entry = &mut table[(visited | 1 << next) * n + next];
+                        if entry.is_none_or(|(l, _)| better(length + d, l)) {
+                            *entry = Some((length + d, last));
+                        }
+                    }
+                }
+            }
+
+            for last in 0..n {
+                let Some((mut length, _)) = table[full * n + last] else {
+                    continue;
+                };
+                if self.closed && n > 1 {
+                    match self.distance(last, first) {
+                        Some(d) => length = length + d,
+                        None => continue,
+                    }
+                }
+                if best.as_ref().is_none_or(|r| better(length, r.length)) {
+                    let mut order = vec![last];
+                    let (mut visited, mut node) = (full, last);
+                    while node != first {
+                        let previous = table[visited * n + node].unwrap().1;
+                        visited ^= 1 << node;
+                        node = previous;
+                        order.push(node);
+                    }
+                    order.reverse();
+                    best = Some(Route { length, order });
+                }
+            }
+        }
+        best
+    }
+
+    // The agents start at the given nodes, and together must visit all the
+    // others; each step moves one agent to a node not visited yet. Dijkstra
+    // over the visited nodes and the positions of the agents, which only
+    // explores the reachable part of the Held-Karp table. Distances must
+    // not be negative
+    pub fn shortest_with_agents(&self, starts: &[usize]) -> Option<W> {
+        let n = self.len();
+        let full: u64 = (1 << n) - 1;
+        let visited: u64 = starts.iter().fold(0, |v, &s| v | 1 << s);
+        let mut best = HashMap::from([((visited, starts.to_vec()), W::default())]);
+        let mut queue = BinaryHeap::from([(Reverse(W::default()), visited, starts.to_vec())]);
+
+        while let Some((Reverse(length), visited, agents)) = queue.pop() {
+            if visited == full {
+                return Some(length);
+            }
+            if best[&(visited, agents.clone())] < length {
+                continue;
+            }
+            for (agent, &from) in agents.iter().enumerate() {
+                for next in (0..n).filter(|&next| self.available(visited, next)) {
+                    let Some(d) = self.distance(from, next) else {
+                        continue;
+                    };
+                    if self.on_the_way(visited, from, next, d) {
+                        continue;
+                    }
+                    let mut moved = agents.clone();
+                    moved[agent] = next;
+                    let state = (visited | 1 << next, moved);
+                    if best.get(&state).is_none_or(|&l| length + d < l) {
+                        best.insert(state.clone(), length + d);
+                        queue.push((Reverse(length + d), state.0, state.1));
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    // Whether another available node is strictly between from and next on
+    // a shortest path: stopping there first costs nothing more
+    fn on_the_way(&self, visited: u64, from: usize, next: usize, d: W) -> bool {
+        let zero = W::default();
+        (0..self.len()).any(|k| {
+            k != next
+                && self.available(visited, k)
+                && match (self.distance(from, k), self.distance(k, next)) {
+                    (Some(a), Some(b)) => a > zero && b > zero && a + b == d,
+                    _ => false,
+                }
+        })
+    }
+
+    // From the start node, moving to a node and visiting it as long as the
+    // budget allows; reward gets the node and the budget left after the
+    // visit. The best total reward for each set of visited nodes (the start
+    // node is not part of the sets)
+    pub fn best_rewards<R: Weight>(
+        &self,
+        budget: W,
+        reward: impl Fn(usize, W) -> R,
+    ) -> HashMap<u64, R> {
+        let start = self.start.expect("best_rewards needs a start node");
+        let mut best = HashMap::new();
+        let mut stack = vec![(start, 0u64, budget, R::default())];
+        while let Some((from, visited, left, total)) = stack.pop() {
+            let entry = best.entry(visited).or_insert(total);
+            *entry = (*entry).max(total);
+            for next in
+                (0..self.len()).filter(|&next| next != start && self.available(visited, next))
+            {
+                match self.distance(from, next) {
+                    Some(d) if d <= left => {
+                        let left = left - d;
+                        stack.push((next, visited | 1 << next, left, total + reward(next, left)));
+                    }
+                    _ => {}
+                }
+            }
+        }
+        best
+    }
+
+    // The best total reward of agents all leaving from the start node, each
+    // with the same budget, none visiting a node another one did (nothing
+    // without agents)
+    pub fn best_reward_with_agents<R: Weight>(
+        &self,
+        agents: usize,
+        budget: W,
+        reward: impl Fn(usize, W) -> R,
+    ) -> R {
+        if agents == 0 {
+            return R::default();
+        }
+        let rewards = self.best_rewards(budget, reward);
+        let size = 1usize << self.len();
+        // within[mask]: the best reward of one agent visiting only nodes in mask
+        let mut within = vec![R::default(); size];
+        for (&visited, &r) in &rewards {
+            within[visited as usize] = within[visited as usize].max(r);
+        }
+        for bit in 0..self.len() {
+            for mask in 0..size {
+                if mask & 1 << bit != 0 {
+                    within[mask] = within[mask].max(within[mask ^ 1 << bit]);
+                }
+            }
+        }
+        // team[mask]: the same with one more agent each round, trying all
+        // the ways to split mask between the new agent and the others
+        let mut team = within.clone();
+        for _ in 2..agents {
+            team = (0..size)
+                .map(|mask| {
+                    let mut best = team[mask];
+                    let mut part = mask;
+                    while part > 0 {
+                        best = best.max(within[part] + team[mask ^ part]);
+                        part = (part - 1) & mask;
+                    }
+                    best
+                })
+                .collect();
+        }
+        if agents < 2 {
+            return within[size - 1];
+        }
+        rewards
+            .iter()
+            .map(|(&visited, &r)| r + team[(size - 1) & !(visited as usize)])
+            .max()
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn cities() -> Tour {
+        // 2015 day 9: London, Dublin, Belfast
+        Tour::from_matrix(vec![
+            vec![0, 464, 518],
+            vec![464, 0, 141],
+            vec![518, 141, 0],
+        ])
+    }
+
+    #[test]
+    fn test_held_karp() {
+        let tour = cities();
+        let shortest = tour.shortest().unwrap();
+        assert_eq!(605, shortest.length);
+        assert_eq!(vec![0, 1, 2], shortest.order);
+        assert_eq!(982, tour.longest().unwrap().length);
+        assert_eq!(1123, tour.clone().closed().shortest().unwrap().length);
+        assert_eq!(982, tour.clone().start_at(1).longest().unwrap().length);
+        assert_eq!(659, tour.clone().start_at(0).longest().unwrap().length);
+
+        // Dublin only after Belfast
+        let tour = tour.requires(1, 2);
+        assert_eq!(vec![2, 1, 0], tour.shortest().unwrap().order);
+        assert_eq!(None, tour.clone().start_at(1).shortest());
+
+        // negative weights, and missing edges
+        let tour: Tour<i64> = Tour::new(vec![
+            vec![None, Some(-5), Some(2)],
+            vec![Some(-5), None, None],
+            vec![Some(2), None, None],
+        ]);
+        let route = tour.shortest().unwrap();
+        assert_eq!(-3, route.length);
+        assert_eq!(vec![1, 0, 2], route.order);
+        assert_eq!(None, tour.closed().longest());
+    }
+
+    #[test]
+    fn test_agents() {
+        // two agents at 0 and 1, a line of nodes on each side
+        //   2 - 0 - 3        4 - 1 - 5
+        let mut distances = vec![vec![None; 6]; 6];
+        for (a, b, d) in [
+            (0, 2, 1),
+            (0, 3, 2),
+            (2, 3, 3),
+            (1, 4, 3),
+            (1, 5, 4),
+            (4, 5, 7),
+        ] {
+            distances[a][b] = Some(d);
+            distances[b][a] = Some(d);
+        }
+        let tour = Tour::new(distances);
+        assert_eq!(Some(1 + 3 + 3 + 7), tour.shortest_with_agents(&[0, 1]));
+        // 5 waits for 3, which does not change anything
+        assert_eq!(
+            Some(14),
+            tour.clone().requires(5, 3).shortest_with_agents(&[0, 1])
+        );
+        // 3 before 2 costs 1 more
+        assert_eq!(
+            Some(15),
+            tour.clone().requires(2, 3).shortest_with_agents(&[0, 1])
+        );
+        assert_eq!(None, tour.shortest_with_agents(&[0]));
+    }
+
+    #[test]
+    fn test_rewards() {
+        // a star around 0, each visit takes one unit of time more
+        let tour = Tour::from_matrix(vec![
+            vec![0, 2, 3, 4],
+            vec![2, 0, 3, 4],
+            vec![3, 3, 0, 5],
+            vec![4, 4, 5, 0],
+        ])
+        .start_at(0);
+        let flow = [0, 10, 1, 7];
+        let rewards = tour.best_rewards(6, |node, left| flow[node] * left);
+        assert_eq!(40, rewards[&0b0010]);
+        // 0 -> 1 -> 2 at time 5, or 0 -> 2 -> 1 at time 6
+        assert_eq!(41, rewards[&0b0110]);
+        assert!(!rewards.contains_key(&0b1110));
+        assert_eq!(
+            0,
+            tour.best_reward_with_agents(0, 6, |node, left| flow[node] * left)
+        );
+        assert_eq!(
+            41,
+            tour.best_reward_with_agents(1, 6, |node, left| flow[node] * left)
+        );
+        assert_eq!(
+            55,
+            tour.best_reward_with_agents(2, 6, |node, left| flow[node] * left)
+        );
+        assert_eq!(
+            57,
+            tour.best_reward_with_agents(3, 6, |node, left| flow[node] * left)
+        );
+    }
+}
@@ -0,0 +1,464 @@
+use anyhow::{anyhow, Error};
+use std::{
+    collections::HashMap,
+    fmt::{Debug, Display},
+    hash::Hash,
+    iter::Sum,
+    ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign},
+    str::FromStr,
+};
+
+// Points and vectors in 3D (moons, lava droplets, beacon scanners), the 24
+// rotations of the axes, and point cloud registration: finding the
+// rotation and translation that make two scans of the same points agree
+// (2021 day 19)
+
+pub trait Signed:
+    Copy
+    + Ord
+    + Hash
+    + Debug
+    + Default
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Neg<Output = Self>
+    + Sum
+{
+    const ONE: Self;
+
+    fn abs(self) -> Self;
+    fn signum(self) -> Self;
+}
+
+macro_rules! signed {
+    ($($t:ty),*) => {
+        $(impl Signed for $t {
+            const ONE: Self = 1;
+
+            fn abs(self) -> Self {
+                <$t>::abs(self)
+            }
+
+            fn signum(self) -> Self {
+                <$t>::signum(self)
+            }
+        })*
+    };
+}
+
+signed!(i8, i16, i32, i64, i128, isize);
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Vec3<T = i64> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+impl<T: Signed> Vec3<T> {
+    pub fn new(x: T, y: T, z: T) -> Self {
+        Self { x, y, z }
+    }
+
+    pub fn zero() -> Self {
+        Self::default()
+    }
+
+    pub fn to_array(self) -> [T; 3] {
+        [self.x, self.y, self.z]
+    }
+
+    // length in Manhattan distance
+    pub fn manhattan(&self) -> T {
+        self.x.abs() + self.y.abs() + self.z.abs()
+    }
+
+    pub fn distance(&self, other: &Self) -> T {
+        (*self - *other).manhattan()
+    }
+
+    // squared euclidean length, unchanged by rotations
+    pub fn norm2(&self) -> T {
+        self.dot(self)
+    }
+
+    pub fn dot(&self, other: &Self) -> T {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    pub fn cross(&self, other: &Self) -> Self {
+        Self::new(
+            self.y * other.z - self.z * other.y,
+            self.z * other.x - self.x * other.z,
+            self.x * other.y - self.y * other.x,
+        )
+    }
+
+    pub fn signum(&self) -> Self {
+        Self::new(self.x.signum(), self.y.signum(), self.z.signum())
+    }
+
+    pub fn abs(&self) -> Self {
+        Self::new(self.x.abs(), self.y.abs(), self.z.abs())
+    }
+
+    // the six points sharing a face with this one
+    pub fn neighbours(self) -> impl Iterator<Item = Self> {
+        let (o, z) = (T::ONE, T::default());
+        [
+            Self::new(-o, z, z),
+            Self::new(o, z, z),
+            Self::new(z, -o, z),
+            Self::new(z, o, z),
+            Self::new(z, z, -o),
+            Self::new(z, z, o),
+        ]
+        .into_iter()
+        .map(move |d| self + d)
+    }
+}
+
+impl<T> From<[T; 3]> for Vec3<T> {
+    fn from([x, y, z]: [T; 3]) -> Self {
+        Self { x, y, z }
+    }
+}
+
+impl<T> From<(T, T, T)> for Vec3<T> {
+    fn from((x, y, z): (T, T, T)) -> Self {
+        Self { x, y, z }
+    }
+}
+
+impl<T: Signed> Add for Vec3<T> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self::new(self.x + rhs.x, self.y + rhs.y, self.z + rhs.z)
+    }
+}
+
+impl<T: Signed> Sub for Vec3<T> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self::new(self.x - rhs.x, self.y - rhs.y, self.z - rhs.z)
+    }
+}
+
+impl<T: Signed> Neg for Vec3<T> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::new(-self.x, -self.y, -self.z)
+    }
+}
+
+impl<T: Signed> Mul<T> for Vec3<T> {
+    type Output = Self;
+
+    fn mul(self, rhs: T) -> Self {
+        Self::new(self.x * rhs, self.y * rhs, self.z * rhs)
+    }
+}
+
+impl<T: Signed> AddAssign for Vec3<T> {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl<T: Signed> SubAssign for Vec3<T> {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl<T: Signed> Sum for Vec3<T> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::zero(), |a, b| a + b)
+    }
+}
+
+impl<T: Display> Display for Vec3<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{},{},{}", self.x, self.y, self.z)
+    }
+}
+
+// x,y,z as in most inputs
+impl<T: Signed + FromStr> FromStr for Vec3<T> {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut coords = s.split(',').map(|c| {
+            c.trim()
+                .parse()
+                .map_err(|_| anyhow!("invalid coordinate {c} in {s}"))
+        });
+        match (coords.next(), coords.next(), coords.next(), coords.next()) {
+            (Some(x), Some(y), Some(z), None) => Ok(Self::new(x?, y?, z?)),
+            _ => Err(anyhow!("expected 3 coordinates in {s}")),
+        }
+    }
+}
+
+// rows of a 3x3 matrix
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Matrix3<T = i64>(pub [[T; 3]; 3]);
+
+impl<T: Signed> Matrix3<T> {
+    pub fn identity() -> Self {
+        let (o, z) = (T::ONE, T::default());
+        Self([[o, z, z], [z, o, z], [z, z, o]])
+    }
+
+    pub fn determinant(&self) -> T {
+        let m = &self.0;
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    }
+
+    // the inverse, for rotations
+    pub fn transpose(&self) -> Self {
+        let m = &self.0;
+        Self(std::array::from_fn(|i| std::array::from_fn(|j| m[j][i])))
+    }
+}
+
+impl<T: Signed> Mul<Vec3<T>> for Matrix3<T> {
+    type Output = Vec3<T>;
+
+    fn mul(self, v: Vec3<T>) -> Vec3<T> {
+        let row = |r: [T; 3]| r[0] * v.x + r[1] * v.y + r[2] * v.z;
+        Vec3::new(row(self.0[0]), row(self.0[1]), row(self.0[2]))
+    }
+}
+
+impl<T: Signed> Mul for Matrix3<T> {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        let (a, b) = (&self.0, &rhs.0);
+        Self(std::array::from_fn(|i| {
+            std::array::from_fn(|j| (0..3).map(|k| a[i][k] * b[k][j]).sum())
+        }))
+    }
+}
+
+// The 24 rotations of the axes: the x axis can be sent to any of the six
+// directions, then rotated in four ways around it
+pub const ROTATIONS: [Matrix3; 24] = [
+    Matrix3([[1, 0, 0], [0, 1, 0], [0, 0, 1]]),
+    Matrix3([[-1, 0, 0], [0, -1, 0], [0, 0, 1]]),
+    Matrix3([[-1, 0, 0], [0, 1, 0], [0, 0, -1]]),
+    Matrix3([[1, 0, 0], [0, -1, 0], [0, 0, -1]]),
+    Matrix3([[-1, 0, 0], [0, 0, -1], [0, -1, 0]]),
+    Matrix3([[-1, 0, 0], [0, 0, 1], [0, 1, 0]]),
+    Matrix3([[1, 0, 0], [0, 0, -1], [0, 1, 0]]),
+    Matrix3([[1, 0, 0], [0, 0, 1], [0, -1, 0]]),
+    Matrix3([[0, -1, 0], [-1, 0, 0], [0, 0, -1]]),
+    Matrix3([[0, -1, 0], [1, 0, 0], [0, 0, 1]]),
+    Matrix3([[0, 1, 0], [-1, 0, 0], [0, 0, 1]]),
+    Matrix3([[0, 1, 0], [1, 0, 0], [0, 0, -1]]),
+    Matrix3([[0, 0, -1], [0, -1, 0], [-1, 0, 0]]),
+    Matrix3([[0, 0, 1], [0, 1, 0], [-1, 0, 0]]),
+    Matrix3([[0, 0, 1], [0, -1, 0], [1, 0, 0]]),
+    Matrix3([[0, 0, -1], [0, 1, 0], [1, 0, 0]]),
+    Matrix3([[0, 0, 1], [1, 0, 0], [0, 1, 0]]),
+    Matrix3([[0, 0, -1], [-1, 0, 0], [0, 1, 0]]),
+    Matrix3([[0, 0, -1], [1, 0, 0], [0, -1, 0]]),
+    Matrix3([[0, 0, 1], [-1, 0, 0], [0, -1, 0]]),
+    Matrix3([[0, 1, 0], [0, 0, 1], [1, 0, 0]]),
+    Matrix3([[0, -1, 0], [0, 0, -1], [1, 0, 0]]),
+    Matrix3([[0, -1, 0], [0, 0, 1], [-1, 0, 0]]),
+    Matrix3([[0, 1, 0], [0, 0, -1], [-1, 0, 0]]),
+];
+
+// rotation then translation: sends points of a scan into the frame of the
+// reference scan; the translation is the position of the scanner
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Alignment {
+    pub rotation: Matrix3,
+    pub translation: Vec3,
+}
+
+impl Alignment {
+    pub fn identity() -> Self {
+        Self {
+            rotation: Matrix3::identity(),
+            translation: Vec3::zero(),
+        }
+    }
+
+    pub fn apply(&self, p: Vec3) -> Vec3 {
+        self.rotation * p + self.translation
+    }
+
+    // self after other: sends points through other first
+    pub fn compose(&self, other: &Self) -> Self {
+        Self {
+            rotation: self.rotation * other.rotation,
+            translation: self.apply(other.translation),
+        }
+    }
+}
+
+// A scan with its fingerprint: the squared distances between all pairs of
+// points, with how often each occurs. Distances do not depend on where the
+// scanner is or how it is turned, so two scans with k points in common
+// share at least k(k-1)/2 of them, counting repeats; scans that do not are
+// not worth trying to align
+#[derive(Debug, Clone)]
+pub struct PointCloud {
+    pub points: Vec<Vec3>,
+    fingerprint: HashMap<i64, usize>,
+}
+
+impl PointCloud {
+    pub fn new(points: Vec<Vec3>) -> Self {
+        let mut fingerprint = HashMap::new();
+        for (i, p1) in points.iter().enumerate() {
+            for p2 in &points[i + 1..] {
+                *fingerprint.entry((*p1 - *p2).norm2()).or_default() += 1;
+            }
+        }
+        Self {
+            points,
+            fingerprint,
+        }
+    }
+
+    pub fn may_overlap(&self, other: &Self, k: usize) -> bool {
+        let shared: usize = self
+            .fingerprint
+            .iter()
+            .filter_map(|(d, c1)| other.fingerprint.get(d).map(|c2| *c1.min(c2)))
+            .sum();
+        shared >= k * k.saturating_sub(1) / 2
+    }
+
+    // The alignment sending at least k points of other onto points of
+    // this cloud. For each rotation, every pair of points votes for the
+    // translation that would match them
+    pub fn align(&self, other: &Self, k: usize) -> Option<Alignment> {
+        if !self.may_overlap(other, k) {
+            return None;
+        }
+        for rotation in ROTATIONS {
+            let mut votes: HashMap<Vec3, usize> = HashMap::new();
+            for p2 in &other.points {
+                let rotated = rotation * *p2;
+                for p1 in &self.points {
+                    let count = votes.entry(*p1 - rotated).or_default();
+                    *count += 1;
+                    if *count >= k {
+                        return Some(Alignment {
+                            rotation,
+                            translation: *p1 - rotated,
+                        });
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+// the alignment of points onto reference, with at least k points in
+// common
+pub fn register(reference: &[Vec3], points: &[Vec3], k: usize) -> Option<Alignment> {
+    PointCloud::new(reference.to_vec()).align(&PointCloud::new(points.to_vec()), k)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_vec3() {
+        let a: Vec3 = "1,-2,3".parse().unwrap();
+        let b = Vec3::new(-4, 0, 2);
+        assert_eq!(Vec3::new(-3, -2, 5), a + b);
+        assert_eq!(8, a.distance(&b));
+        assert_eq!(Vec3::new(1, -1, 1), a.signum());
+        assert_eq!(Vec3::new(-4, -14, -8), a.cross(&b));
+        assert_eq!(6, a.neighbours().count());
+        assert!(a.neighbours().all(|n| n.distance(&a) == 1));
+        assert!("1,2".parse::<Vec3>().is_err() && "1,a,2".parse::<Vec3>().is_err());
+        assert_eq!(Vec3::<i32>::new(1, 2, 3), [1, 2, 3].into());
+    }
+
+    #[test]
+    fn test_rotations() {
+        let rotations: HashSet<_> = ROTATIONS.iter().collect();
+        assert_eq!(24, rotations.len());
+        for r1 in ROTATIONS {
+            assert_eq!(1, r1.determinant());
+            assert_eq!(Matrix3::identity(), r1 * r1.transpose());
+            // a group: closed under composition
+            for r2 in ROTATIONS {
+                assert!(rotations.contains(&(r1 * r2)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_register() {
+        let reference: Vec<Vec3> = (0..12)
+            .map(|i| Vec3::new(i * i, 3 * i - 7, 100 - i * 5 + (i % 3)))
+            .collect();
+        let expected = Alignment {
+            rotation: ROTATIONS[17],
+            translation: Vec3::new(25, -40, 3),
+        };
+        // scan of the reference from another scanner, with points of its own
+        let inverse = Alignment {
+            rotation: expected.rotation.transpose(),
+            translation: expected.rotation.transpose() * -expected.translation,
+        };
+        let mut points: Vec<Vec3> = reference.iter().map(|&p| inverse.apply(p)).collect();
+        points.extend([Vec3::new(500, 500, 500), Vec3::new(-300, 20, 7)]);
+
+        assert_eq!(Some(expected), register(&reference, &points, 12));
+        assert_eq!(None, register(&reference, &points, 13));
+        assert_eq!(Alignment::identity(), expected.compose(&inverse));
+
+        // fewer than two points in common need no shared distance
+        let (a, b) = (PointCloud::new(reference), PointCloud::new(vec![]));
+        assert!(a.may_overlap(&b, 0) && a.may_overlap(&b, 1) && !a.may_overlap(&b, 2));
+    }
+
+    #[test]
+    fn test_register_lattice() {
+        // evenly spaced points: 27 points but only 9 distinct distances
+        let reference: Vec<Vec3> = (0..27)
+            .map(|i| Vec3::new(i % 3, i / 3 % 3, i / 9) * 10)
+            .collect();
+        let alignment = Alignment {
+            rotation: ROTATIONS[5],
+            translation: Vec3::new(-7, 12, 31),
+        };
+        let inverse = Alignment {
+            rotation: alignment.rotation.transpose(),
+            translation: alignment.rotation.transpose() * -alignment.translation,
+        };
+        let points: Vec<Vec3> = reference.iter().map(|&p| inverse.apply(p)).collect();
+
+        let (a, b) = (
+            PointCloud::new(reference.clone()),
+            PointCloud::new(points.clone()),
+        );
+        assert!(a.may_overlap(&b, 27) && !a.may_overlap(&b, 28));
+        let found = register(&reference, &points, 27).unwrap();
+        // the lattice is symmetric, so any alignment matching all points will do
+        let mut aligned: Vec<Vec3> = points.iter().map(|&p| found.apply(p)).collect();
+        let mut expected = reference;
+        aligned.sort_by_key(|p| (p.x, p.y, p.z));
+        expected.sort_by_key(|p| (p.x, p.y, p.z));
+        assert_eq!(expected, aligned);
+    }
+}